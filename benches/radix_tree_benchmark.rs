@@ -0,0 +1,200 @@
+//! Char tree vs token tree for cache-aware routing
+//!
+//! Both trees are filled with the same chat-like workload: a few long shared system
+//! prompts, each followed by a unique user turn. Token ids are derived from the words of
+//! the text so the benchmark needs no tokenizer download; encode costs are covered by
+//! `tokenizer_benchmark`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use vllm_router_rs::token_tree::TokenTree;
+use vllm_router_rs::tree::Tree;
+
+const WORKERS: [&str; 4] = [
+    "http://w1:8000",
+    "http://w2:8000",
+    "http://w3:8000",
+    "http://w4:8000",
+];
+const SYSTEM_PROMPTS: usize = 8;
+const BLOCK_SIZE: usize = 16;
+
+fn system_prompt(idx: usize, words: usize) -> String {
+    let topics = [
+        "mathematics",
+        "physics",
+        "chemistry",
+        "biology",
+        "software",
+        "medicine",
+        "law",
+        "economics",
+    ];
+    let mut prompt = format!("<|system|> You are assistant {} and an expert in", idx);
+    for i in 0..words {
+        prompt.push(' ');
+        prompt.push_str(topics[(idx + i) % topics.len()]);
+    }
+    prompt
+}
+
+fn requests(count: usize, prompt_words: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            format!(
+                "{} <|user|> question {} about item {} and item {} <|assistant|>",
+                system_prompt(i % SYSTEM_PROMPTS, prompt_words),
+                i,
+                i * 7,
+                i * 13
+            )
+        })
+        .collect()
+}
+
+/// One token id per word
+fn tokenize(text: &str) -> Vec<u32> {
+    text.split_whitespace()
+        .map(|word| {
+            let mut hasher = DefaultHasher::new();
+            word.hash(&mut hasher);
+            hasher.finish() as u32
+        })
+        .collect()
+}
+
+fn char_tree(texts: &[String]) -> Tree {
+    let tree = Tree::new();
+    for (i, text) in texts.iter().enumerate() {
+        tree.insert(text, WORKERS[i % WORKERS.len()]);
+    }
+    tree
+}
+
+fn token_tree(tokens: &[Vec<u32>]) -> TokenTree {
+    let mut tree = TokenTree::new(BLOCK_SIZE);
+    for (i, ids) in tokens.iter().enumerate() {
+        tree.insert(ids, WORKERS[i % WORKERS.len()]);
+    }
+    tree
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radix_tree_insert");
+
+    for prompt_words in [64, 512] {
+        let texts = requests(256, prompt_words);
+        let tokens: Vec<Vec<u32>> = texts.iter().map(|text| tokenize(text)).collect();
+        group.throughput(Throughput::Elements(texts.len() as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("char", prompt_words),
+            &texts,
+            |b, texts| {
+                b.iter(|| black_box(char_tree(texts)));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("token", prompt_words),
+            &tokens,
+            |b, tokens| {
+                b.iter(|| black_box(token_tree(tokens)));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_prefix_match(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radix_tree_prefix_match");
+
+    for prompt_words in [64, 512] {
+        let texts = requests(256, prompt_words);
+        let tokens: Vec<Vec<u32>> = texts.iter().map(|text| tokenize(text)).collect();
+        // Queries share a system prompt with the cached requests but not the user turn
+        let queries = requests(512, prompt_words).split_off(256);
+        let query_tokens: Vec<Vec<u32>> = queries.iter().map(|text| tokenize(text)).collect();
+        group.throughput(Throughput::Elements(queries.len() as u64));
+
+        let tree = char_tree(&texts);
+        group.bench_with_input(
+            BenchmarkId::new("char", prompt_words),
+            &queries,
+            |b, queries| {
+                b.iter(|| {
+                    for query in queries {
+                        black_box(tree.prefix_match(query));
+                    }
+                });
+            },
+        );
+
+        let mut tree = token_tree(&tokens);
+        group.bench_with_input(
+            BenchmarkId::new("token", prompt_words),
+            &query_tokens,
+            |b, queries| {
+                b.iter(|| {
+                    for query in queries {
+                        black_box(tree.prefix_match(query));
+                    }
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_eviction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radix_tree_eviction");
+
+    let texts = requests(256, 512);
+    let tokens: Vec<Vec<u32>> = texts.iter().map(|text| tokenize(text)).collect();
+
+    group.bench_function("char", |b| {
+        b.iter_batched(
+            || char_tree(&texts),
+            |tree| tree.evict_tenant_by_size(1000),
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("token", |b| {
+        b.iter_batched(
+            || token_tree(&tokens),
+            |mut tree| tree.evict_tenant_by_size(200),
+            criterion::BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+fn benchmark_summary(_c: &mut Criterion) {
+    let texts = requests(256, 512);
+    let tokens: Vec<Vec<u32>> = texts.iter().map(|text| tokenize(text)).collect();
+    let chars = char_tree(&texts).get_used_size_per_tenant();
+    let ids = token_tree(&tokens).get_used_size_per_tenant();
+
+    println!("\nRadix tree size per worker (256 requests, 8 system prompts):");
+    for worker in WORKERS {
+        println!(
+            "  {}: {} chars, {} tokens",
+            worker,
+            chars.get(worker).copied().unwrap_or(0),
+            ids.get(worker).copied().unwrap_or(0)
+        );
+    }
+}
+
+criterion_group!(
+    benches,
+    benchmark_summary,
+    bench_insert,
+    bench_prefix_match,
+    bench_eviction
+);
+criterion_main!(benches);
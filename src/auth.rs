@@ -0,0 +1,425 @@
+//! Inbound API key authentication
+//!
+//! Clients authenticate with `Authorization: Bearer <key>`, or with `X-Api-Key: <key>` as
+//! sent by Anthropic SDKs. Keys come from the router
+//! configuration and, optionally, from a key file that is re-read whenever it changes so
+//! keys can be rotated without a restart.
+//!
+//! Each key has a scope: inference keys may call the generation routes, admin keys may
+//! additionally manage workers. Failures are reported as OpenAI-style error bodies.
+
+use crate::config::AuthConfig;
+use crate::protocols::spec::ErrorResponse;
+use crate::server::AppState;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+/// Header used by Anthropic clients to carry the API key
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+
+/// Access level granted by an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiKeyScope {
+    /// Generation routes such as `/v1/chat/completions`
+    Inference,
+    /// All routes, including worker management
+    Admin,
+}
+
+impl std::str::FromStr for ApiKeyScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inference" => Ok(ApiKeyScope::Inference),
+            "admin" => Ok(ApiKeyScope::Admin),
+            _ => Err(format!(
+                "unknown API key scope '{}', expected 'inference' or 'admin'",
+                s
+            )),
+        }
+    }
+}
+
+/// Keys loaded from the key file, with the modification time they were read at
+#[derive(Debug, Default)]
+struct FileKeys {
+    keys: HashMap<String, ApiKeyScope>,
+    modified: Option<SystemTime>,
+}
+
+/// Set of API keys accepted by the router
+#[derive(Debug, Default)]
+pub struct ApiKeyStore {
+    static_keys: HashMap<String, ApiKeyScope>,
+    key_file: Option<String>,
+    file_keys: RwLock<FileKeys>,
+}
+
+impl ApiKeyStore {
+    /// Build the key set from configuration, reading the key file if one is set
+    pub fn from_config(config: &AuthConfig) -> Result<Self, String> {
+        let mut static_keys = HashMap::new();
+        for key in &config.inference_keys {
+            static_keys.insert(key.clone(), ApiKeyScope::Inference);
+        }
+        // Admin wins when a key is listed under both scopes
+        for key in &config.admin_keys {
+            static_keys.insert(key.clone(), ApiKeyScope::Admin);
+        }
+
+        let store = Self {
+            static_keys,
+            key_file: config.key_file.clone(),
+            file_keys: RwLock::new(FileKeys::default()),
+        };
+        if store.key_file.is_some() {
+            store.reload()?;
+        }
+        Ok(store)
+    }
+
+    /// Whether requests must carry an API key
+    pub fn is_enabled(&self) -> bool {
+        !self.static_keys.is_empty() || self.key_file.is_some()
+    }
+
+    /// Scope of `key`, if it is a known key
+    pub fn scope(&self, key: &str) -> Option<ApiKeyScope> {
+        let file_scope = self.file_keys.read().unwrap().keys.get(key).copied();
+        let static_scope = self.static_keys.get(key).copied();
+        file_scope.max(static_scope)
+    }
+
+    /// Re-read the key file, returning the number of keys it contains.
+    ///
+    /// On error the previously loaded keys stay in effect.
+    pub fn reload(&self) -> Result<usize, String> {
+        let Some(path) = &self.key_file else {
+            return Ok(0);
+        };
+
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("Failed to read API key file {}: {}", path, e))?;
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read API key file {}: {}", path, e))?;
+        let keys = parse_key_file(&content)
+            .map_err(|e| format!("Invalid API key file {}: {}", path, e))?;
+
+        let count = keys.len();
+        *self.file_keys.write().unwrap() = FileKeys {
+            keys,
+            modified: Some(modified),
+        };
+        Ok(count)
+    }
+
+    /// Reload the key file if it changed since it was last read
+    fn reload_if_modified(&self) {
+        let Some(path) = &self.key_file else {
+            return;
+        };
+
+        let modified = match std::fs::metadata(path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(e) => {
+                warn!("Failed to check API key file {}: {}", path, e);
+                return;
+            }
+        };
+        if self.file_keys.read().unwrap().modified == Some(modified) {
+            return;
+        }
+
+        match self.reload() {
+            Ok(count) => info!("Reloaded {} API keys from {}", count, path),
+            Err(e) => warn!("{}; keeping previously loaded keys", e),
+        }
+    }
+
+    /// Watch the key file for changes in the background
+    pub fn start_reloader(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        self.key_file.as_ref()?;
+
+        let store = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                store.reload_if_modified();
+            }
+        }))
+    }
+
+    /// Check the request's bearer token against `required`
+    fn authorize(&self, headers: &HeaderMap, required: ApiKeyScope) -> Result<(), AuthError> {
+        let key = request_key(headers).ok_or(AuthError::MissingKey)?;
+        match self.scope(key) {
+            None => Err(AuthError::InvalidKey),
+            Some(scope) if scope < required => Err(AuthError::InsufficientScope),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// Reasons a request is rejected by the auth middleware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuthError {
+    MissingKey,
+    InvalidKey,
+    InsufficientScope,
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthError::MissingKey => unauthorized(
+                "You didn't provide an API key. You need to provide your API key in an \
+                 Authorization header using Bearer auth (i.e. Authorization: Bearer YOUR_KEY).",
+                None,
+            ),
+            AuthError::InvalidKey => {
+                unauthorized("Incorrect API key provided.", Some("invalid_api_key"))
+            }
+            AuthError::InsufficientScope => error_response(
+                StatusCode::FORBIDDEN,
+                "You have insufficient permissions for this operation.",
+                "permission_error",
+                Some("insufficient_permissions"),
+            ),
+        }
+    }
+}
+
+/// Parse a key file: one `<key> [inference|admin]` per line, `#` starts a comment
+fn parse_key_file(content: &str) -> Result<HashMap<String, ApiKeyScope>, String> {
+    let mut keys = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let key = parts.next().unwrap_or_default();
+        let scope = match parts.next() {
+            Some(scope) => scope
+                .parse()
+                .map_err(|e| format!("line {}: {}", index + 1, e))?,
+            None => ApiKeyScope::Inference,
+        };
+        if parts.next().is_some() {
+            return Err(format!("line {}: unexpected trailing fields", index + 1));
+        }
+
+        let entry = keys.entry(key.to_string()).or_insert(scope);
+        *entry = (*entry).max(scope);
+    }
+    Ok(keys)
+}
+
+/// Extract the token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Extract the API key a request authenticates with: its bearer token, else its
+/// `X-Api-Key` header
+pub fn request_key(headers: &HeaderMap) -> Option<&str> {
+    bearer_token(headers).or_else(|| api_key_header(headers))
+}
+
+/// Extract the key from an `X-Api-Key` header
+fn api_key_header(headers: &HeaderMap) -> Option<&str> {
+    let key = headers.get(API_KEY_HEADER)?.to_str().ok()?.trim();
+    (!key.is_empty()).then_some(key)
+}
+
+fn error_response(
+    status: StatusCode,
+    message: &str,
+    error_type: &str,
+    code: Option<&str>,
+) -> Response {
+    (status, Json(ErrorResponse::new(message, error_type, code))).into_response()
+}
+
+fn unauthorized(message: &str, code: Option<&str>) -> Response {
+    let mut response = error_response(
+        StatusCode::UNAUTHORIZED,
+        message,
+        "invalid_request_error",
+        code,
+    );
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+async fn require_scope(
+    app_state: &AppState,
+    required: ApiKeyScope,
+    request: Request,
+    next: Next,
+) -> Response {
+    let store = &app_state.context.api_keys;
+    if store.is_enabled() {
+        if let Err(e) = store.authorize(request.headers(), required) {
+            debug!("Rejected request to {}: {:?}", request.uri().path(), e);
+            return e.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Middleware for inference routes: accepts inference and admin keys
+pub async fn inference_auth_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    require_scope(&app_state, ApiKeyScope::Inference, request, next).await
+}
+
+/// Middleware for admin and worker management routes: accepts admin keys only
+pub async fn admin_auth_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    require_scope(&app_state, ApiKeyScope::Admin, request, next).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers_with(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(authorization).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_parse_key_file() {
+        let keys = parse_key_file(
+            "# rotated 2024-01-01\n\
+             sk-user\n\
+             sk-ops admin  # on-call\n\
+             \n\
+             sk-both inference\n\
+             sk-both admin\n",
+        )
+        .unwrap();
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys["sk-user"], ApiKeyScope::Inference);
+        assert_eq!(keys["sk-ops"], ApiKeyScope::Admin);
+        assert_eq!(keys["sk-both"], ApiKeyScope::Admin);
+
+        assert!(parse_key_file("sk-user superuser").is_err());
+    }
+
+    #[test]
+    fn test_authorize_scopes() {
+        let store = ApiKeyStore::from_config(&AuthConfig {
+            inference_keys: vec!["sk-user".to_string()],
+            admin_keys: vec!["sk-admin".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let user = headers_with("Bearer sk-user");
+        let admin = headers_with("bearer sk-admin");
+        assert!(store.authorize(&user, ApiKeyScope::Inference).is_ok());
+        assert!(store.authorize(&admin, ApiKeyScope::Inference).is_ok());
+        assert!(store.authorize(&admin, ApiKeyScope::Admin).is_ok());
+
+        assert_eq!(
+            store.authorize(&user, ApiKeyScope::Admin),
+            Err(AuthError::InsufficientScope)
+        );
+        assert_eq!(
+            store.authorize(&headers_with("Bearer sk-other"), ApiKeyScope::Inference),
+            Err(AuthError::InvalidKey)
+        );
+        assert_eq!(
+            store.authorize(&headers_with("Basic sk-user"), ApiKeyScope::Inference),
+            Err(AuthError::MissingKey)
+        );
+
+        let mut api_key = HeaderMap::new();
+        api_key.insert(API_KEY_HEADER, HeaderValue::from_static("sk-user"));
+        assert!(store.authorize(&api_key, ApiKeyScope::Inference).is_ok());
+
+        let missing = store
+            .authorize(&HeaderMap::new(), ApiKeyScope::Inference)
+            .unwrap_err()
+            .into_response();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(missing.headers()[header::WWW_AUTHENTICATE], "Bearer");
+    }
+
+    #[test]
+    fn test_request_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_key(&headers), None);
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("sk-anthropic"));
+        assert_eq!(request_key(&headers), Some("sk-anthropic"));
+
+        // A bearer token takes precedence
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer sk-openai"),
+        );
+        assert_eq!(request_key(&headers), Some("sk-openai"));
+    }
+
+    #[test]
+    fn test_key_file_reload() {
+        let path = std::env::temp_dir().join(format!("router-keys-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "sk-old\n").unwrap();
+
+        let store = ApiKeyStore::from_config(&AuthConfig {
+            key_file: Some(path.to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(store.is_enabled());
+        assert_eq!(store.scope("sk-old"), Some(ApiKeyScope::Inference));
+
+        std::fs::write(&path, "sk-new admin\n").unwrap();
+        assert_eq!(store.reload().unwrap(), 1);
+        assert_eq!(store.scope("sk-old"), None);
+        assert_eq!(store.scope("sk-new"), Some(ApiKeyScope::Admin));
+
+        // A broken file keeps the last good key set
+        std::fs::write(&path, "sk-new root\n").unwrap();
+        assert!(store.reload().is_err());
+        assert_eq!(store.scope("sk-new"), Some(ApiKeyScope::Admin));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -28,6 +28,9 @@ pub struct RouterConfig {
     pub dp_aware: bool,
     /// The api key used for the authorization with the worker
     pub api_key: Option<String>,
+    /// API keys accepted from clients (empty = no authentication)
+    #[serde(default)]
+    pub auth: AuthConfig,
     /// Service discovery configuration (optional)
     pub discovery: Option<DiscoveryConfig>,
     /// Metrics configuration (optional)
@@ -44,8 +47,17 @@ pub struct RouterConfig {
     pub queue_size: usize,
     /// Maximum time (in seconds) a request can wait in queue before timing out
     pub queue_timeout_secs: u64,
+    /// Priority classes of the admission queue
+    #[serde(default)]
+    pub admission: AdmissionConfig,
+    /// Worker removal and router shutdown draining
+    #[serde(default)]
+    pub drain: DrainConfig,
     /// Token bucket refill rate (tokens per second). If not set, defaults to max_concurrent_requests
     pub rate_limit_tokens_per_second: Option<usize>,
+    /// Per-tenant request and token rate limits
+    #[serde(default)]
+    pub tenant_rate_limits: TenantRateLimitConfig,
     /// CORS allowed origins
     pub cors_allowed_origins: Vec<String>,
     /// Retry configuration
@@ -67,25 +79,206 @@ pub struct RouterConfig {
     pub model_path: Option<String>,
     /// Explicit tokenizer path (overrides model_path tokenizer if provided)
     pub tokenizer_path: Option<String>,
-    /// History backend configuration (memory or none, default: memory)
+    /// History backend configuration (memory, file or none, default: memory)
     #[serde(default = "default_history_backend")]
     pub history_backend: HistoryBackend,
+    /// Location and expiry of stored responses for persistent history backends
+    #[serde(default)]
+    pub history_storage: HistoryStorageConfig,
+    /// MCP servers available to the Responses API tool loop
+    #[serde(default)]
+    pub mcp: McpToolsConfig,
+    /// The router's own MCP server endpoint
+    #[serde(default)]
+    pub mcp_server: McpEndpointConfig,
+    /// Inference engine of the configured workers; workers added later can override it
+    /// with a `backend` label
+    #[serde(default)]
+    pub backend: WorkerBackend,
+    /// OpenAI mode providers with their own credentials, model names and weight, in
+    /// addition to the bare `worker_urls`
+    #[serde(default)]
+    pub openai_upstreams: Vec<OpenAIUpstream>,
+    /// Public model names mapped to backend model ids or worker labels
+    #[serde(default)]
+    pub model_aliases: Vec<ModelAlias>,
+    /// Interval in seconds between queries of each worker's `/v1/models` for the model
+    /// catalog
+    #[serde(default = "default_model_refresh_interval_secs")]
+    pub model_refresh_interval_secs: u64,
+    /// Unit of the worker load that load-balancing policies compare
+    #[serde(default)]
+    pub load_unit: LoadUnit,
 }
 
 fn default_history_backend() -> HistoryBackend {
     HistoryBackend::Memory
 }
 
+fn default_model_refresh_interval_secs() -> u64 {
+    30
+}
+
 /// History backend configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum HistoryBackend {
     /// In-memory storage (default)
     Memory,
+    /// Append-only JSONL log in `history_storage.path`, kept across restarts
+    File,
     /// No history storage
     None,
 }
 
+/// Inference engine running on a worker
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkerBackend {
+    /// vLLM OpenAI-compatible server (default)
+    #[default]
+    Vllm,
+    /// TensorRT-LLM `trtllm-serve`
+    Trtllm,
+}
+
+impl WorkerBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkerBackend::Vllm => "vllm",
+            WorkerBackend::Trtllm => "trtllm",
+        }
+    }
+}
+
+impl std::str::FromStr for WorkerBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vllm" => Ok(WorkerBackend::Vllm),
+            "trtllm" => Ok(WorkerBackend::Trtllm),
+            other => Err(format!(
+                "unknown worker backend '{}' (expected vllm or trtllm)",
+                other
+            )),
+        }
+    }
+}
+
+/// An OpenAI-compatible provider behind the OpenAI router
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenAIUpstream {
+    /// Base URL of the provider's OpenAI-compatible API
+    pub url: String,
+    /// API key sent as `Authorization: Bearer`, replacing the client's credentials
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Client model names mapped to the names this provider knows them by
+    #[serde(default)]
+    pub model_map: HashMap<String, String>,
+    /// Share of first attempts this provider receives; 0 makes it failover-only
+    #[serde(default = "default_upstream_weight")]
+    pub weight: u32,
+}
+
+fn default_upstream_weight() -> u32 {
+    1
+}
+
+impl OpenAIUpstream {
+    /// A provider without credentials or model mapping, at the default weight
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            api_key: None,
+            model_map: HashMap::new(),
+            weight: default_upstream_weight(),
+        }
+    }
+}
+
+/// A public model name served by a backend model or a subset of workers
+///
+/// Chat and completion requests for `name` have their `model` field rewritten to
+/// `model_id` and the parameter policy applied before routing; responses report `name`
+/// again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelAlias {
+    /// Model name clients send
+    pub name: String,
+    /// Model id the workers serve (None = forward `name` unchanged)
+    #[serde(default)]
+    pub model_id: Option<String>,
+    /// Labels a worker must carry to serve this alias
+    #[serde(default)]
+    pub worker_selector: HashMap<String, String>,
+    /// Request parameter defaults, caps and removals
+    #[serde(default)]
+    pub parameters: ParameterPolicy,
+}
+
+/// Request parameter rules of a model alias, applied in field order
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ParameterPolicy {
+    /// Fields removed from requests
+    #[serde(default)]
+    pub strip: Vec<String>,
+    /// Values of fields the request leaves unset
+    #[serde(default)]
+    pub defaults: HashMap<String, serde_json::Value>,
+    /// Upper bounds of numeric fields, e.g. `max_tokens`
+    #[serde(default)]
+    pub max: HashMap<String, serde_json::Number>,
+}
+
+/// Storage settings for persistent history backends
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct HistoryStorageConfig {
+    /// Directory holding the stored responses (required for the file backend)
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Responses older than this many seconds expire (None = keep forever)
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// MCP tool execution for `/v1/responses` requests that declare `mcp` tools
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpToolsConfig {
+    /// YAML file listing the MCP servers requests can reference by name (None = disabled)
+    #[serde(default)]
+    pub config_path: Option<String>,
+    /// Maximum tool-calling rounds per request before the response is marked incomplete
+    #[serde(default = "default_mcp_max_iterations")]
+    pub max_iterations: usize,
+}
+
+fn default_mcp_max_iterations() -> usize {
+    10
+}
+
+impl Default for McpToolsConfig {
+    fn default() -> Self {
+        Self {
+            config_path: None,
+            max_iterations: default_mcp_max_iterations(),
+        }
+    }
+}
+
+/// `/mcp` endpoint exposing the router's own capabilities as MCP tools
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct McpEndpointConfig {
+    /// Serve the streamable-HTTP MCP endpoint at `/mcp`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Key unlocking the admin tools when sent as `X-Mcp-Admin-Key`; admin API keys
+    /// unlock them as well
+    #[serde(default)]
+    pub admin_key: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[serde(tag = "type")]
 pub enum ConnectionMode {
@@ -219,6 +412,12 @@ pub enum PolicyConfig {
         eviction_interval_secs: u64,
         /// Maximum cache tree size per tenant
         max_tree_size: usize,
+        /// Whether the cache trees match on characters or on token blocks
+        #[serde(default)]
+        tree_type: CacheTreeType,
+        /// Tokens per KV cache block for token-level matching
+        #[serde(default = "default_cache_block_size")]
+        block_size: usize,
     },
 
     #[serde(rename = "power_of_two")]
@@ -231,6 +430,36 @@ pub enum PolicyConfig {
     ConsistentHash {
         /// Number of virtual nodes per worker for better distribution
         virtual_nodes: u32,
+        /// Ordered sources of the affinity key; without any, session and user ids are
+        /// scanned from the request text
+        #[serde(default)]
+        hash_key_sources: Vec<HashKeySource>,
+        /// Bound on a worker's load relative to the average, above which requests spill
+        /// to the next worker on the ring (None = unbounded)
+        #[serde(default)]
+        capacity_factor: Option<f32>,
+    },
+
+    #[serde(rename = "kv_pressure")]
+    KvPressure {
+        /// Cost of a waiting request relative to a running one
+        queue_weight: f32,
+        /// Cost of a full KV cache, in running requests
+        kv_cache_weight: f32,
+        /// KV cache usage (0.0-1.0) above which workers are avoided
+        max_kv_cache_usage: f32,
+        /// Interval for scraping worker metrics (seconds)
+        load_check_interval_secs: u64,
+    },
+
+    #[serde(rename = "latency")]
+    Latency {
+        /// Weight of the newest sample in the latency and error rate averages
+        ewma_alpha: f32,
+        /// Age after which a worker's latency stats count half as much (seconds)
+        decay_half_life_secs: u64,
+        /// Output tokens assumed when predicting a request's completion time
+        expected_output_tokens: u32,
     },
 }
 
@@ -242,6 +471,152 @@ impl PolicyConfig {
             PolicyConfig::CacheAware { .. } => "cache_aware",
             PolicyConfig::PowerOfTwo { .. } => "power_of_two",
             PolicyConfig::ConsistentHash { .. } => "consistent_hash",
+            PolicyConfig::KvPressure { .. } => "kv_pressure",
+            PolicyConfig::Latency { .. } => "latency",
+        }
+    }
+
+    /// Interval at which the policy needs worker loads, None if it does not use them
+    pub fn load_check_interval_secs(&self) -> Option<u64> {
+        match self {
+            PolicyConfig::PowerOfTwo {
+                load_check_interval_secs,
+            }
+            | PolicyConfig::KvPressure {
+                load_check_interval_secs,
+                ..
+            } => Some(*load_check_interval_secs),
+            _ => None,
+        }
+    }
+
+    /// Whether the policy matches requests on token ids and so needs a tokenizer
+    pub fn uses_token_tree(&self) -> bool {
+        matches!(
+            self,
+            PolicyConfig::CacheAware {
+                tree_type: CacheTreeType::Token,
+                ..
+            }
+        )
+    }
+}
+
+fn default_cache_block_size() -> usize {
+    16
+}
+
+/// Load scraping interval for policies created from worker hints, which carry no interval
+pub const HINTED_POLICY_LOAD_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Unit the cache-aware policy's prefix trees match requests on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheTreeType {
+    /// Characters of the request text (default)
+    #[default]
+    Char,
+    /// Token ids of the rendered prompt, in blocks of `block_size` tokens
+    Token,
+}
+
+impl CacheTreeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheTreeType::Char => "char",
+            CacheTreeType::Token => "token",
+        }
+    }
+}
+
+/// Unit in which the policies measure and balance worker load
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadUnit {
+    /// Requests in flight (default)
+    #[default]
+    Requests,
+    /// Estimated tokens outstanding: prompt tokens plus remaining decode budget
+    Tokens,
+}
+
+impl LoadUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadUnit::Requests => "requests",
+            LoadUnit::Tokens => "tokens",
+        }
+    }
+}
+
+impl std::str::FromStr for LoadUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "requests" => Ok(LoadUnit::Requests),
+            "tokens" => Ok(LoadUnit::Tokens),
+            other => Err(format!(
+                "unknown load unit '{}' (expected requests or tokens)",
+                other
+            )),
+        }
+    }
+}
+
+/// Source of the affinity key consistent hashing routes a request on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HashKeySource {
+    /// Value of a request header, e.g. `x-session-id`
+    Header(String),
+    /// String or number at a JSON pointer into the request body, e.g.
+    /// `/session_params/session_id`
+    JsonPointer(String),
+    /// OpenAI `prompt_cache_key` of the request body
+    PromptCacheKey,
+}
+
+impl std::fmt::Display for HashKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashKeySource::Header(name) => write!(f, "header:{}", name),
+            HashKeySource::JsonPointer(pointer) => write!(f, "json:{}", pointer),
+            HashKeySource::PromptCacheKey => write!(f, "prompt_cache_key"),
+        }
+    }
+}
+
+impl std::str::FromStr for HashKeySource {
+    type Err = String;
+
+    /// Parse `header:<name>`, `json:<pointer>` or `prompt_cache_key`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "prompt_cache_key" {
+            return Ok(HashKeySource::PromptCacheKey);
+        }
+        match s.split_once(':') {
+            Some(("header", name)) => Ok(HashKeySource::Header(name.to_ascii_lowercase())),
+            Some(("json", pointer)) => Ok(HashKeySource::JsonPointer(pointer.to_string())),
+            _ => Err(format!(
+                "unknown hash key source '{}' (expected header:<name>, json:<pointer> or prompt_cache_key)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::str::FromStr for CacheTreeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "char" => Ok(CacheTreeType::Char),
+            "token" => Ok(CacheTreeType::Token),
+            other => Err(format!(
+                "unknown cache tree type '{}' (expected char or token)",
+                other
+            )),
         }
     }
 }
@@ -282,6 +657,216 @@ impl Default for DiscoveryConfig {
     }
 }
 
+/// Inbound API key authentication
+///
+/// Clients authenticate with `Authorization: Bearer <key>`. Inference keys grant access to
+/// the generation routes; admin keys additionally grant access to worker management.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthConfig {
+    /// Keys accepted on inference routes
+    #[serde(default)]
+    pub inference_keys: Vec<String>,
+    /// Keys accepted on all routes, including admin and worker management routes
+    #[serde(default)]
+    pub admin_keys: Vec<String>,
+    /// File with additional keys, one `<key> [inference|admin]` per line
+    #[serde(default)]
+    pub key_file: Option<String>,
+    /// Interval between checks of the key file for changes (in seconds)
+    #[serde(default = "default_key_file_reload_interval_secs")]
+    pub key_file_reload_interval_secs: u64,
+}
+
+impl AuthConfig {
+    /// Whether any key source is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.inference_keys.is_empty() || !self.admin_keys.is_empty() || self.key_file.is_some()
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            inference_keys: vec![],
+            admin_keys: vec![],
+            key_file: None,
+            key_file_reload_interval_secs: default_key_file_reload_interval_secs(),
+        }
+    }
+}
+
+fn default_key_file_reload_interval_secs() -> u64 {
+    10
+}
+
+/// How requests are attributed to a tenant for rate limiting
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantSource {
+    /// The bearer token of the request
+    #[default]
+    ApiKey,
+    /// The `user` field of the request body
+    User,
+}
+
+/// Rate limits for one tenant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TenantRateLimit {
+    /// Tenant name, used as the metrics label
+    pub name: String,
+    /// API keys or `user` values (depending on the tenant source) belonging to this tenant
+    pub ids: Vec<String>,
+    /// Maximum requests per minute (None = unlimited)
+    #[serde(default)]
+    pub requests_per_minute: Option<u64>,
+    /// Maximum estimated prompt + completion tokens per minute (None = unlimited)
+    #[serde(default)]
+    pub tokens_per_minute: Option<u64>,
+}
+
+/// Per-tenant rate limiting configuration
+///
+/// Every tenant id gets its own buckets. Ids not listed in `tenants` use the default
+/// limits and are reported as the `default` tenant in metrics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TenantRateLimitConfig {
+    /// How the tenant id of a request is determined
+    #[serde(default)]
+    pub tenant_source: TenantSource,
+    /// Requests per minute for tenants without an explicit entry (None = unlimited)
+    #[serde(default)]
+    pub default_requests_per_minute: Option<u64>,
+    /// Tokens per minute for tenants without an explicit entry (None = unlimited)
+    #[serde(default)]
+    pub default_tokens_per_minute: Option<u64>,
+    /// Tenants with explicit limits
+    #[serde(default)]
+    pub tenants: Vec<TenantRateLimit>,
+}
+
+impl TenantRateLimitConfig {
+    /// Whether any tenant is rate limited
+    pub fn is_enabled(&self) -> bool {
+        self.default_requests_per_minute.is_some()
+            || self.default_tokens_per_minute.is_some()
+            || !self.tenants.is_empty()
+    }
+}
+
+/// A priority class of the admission queue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriorityClass {
+    /// Class name, matched case-insensitively against the priority header
+    pub name: String,
+    /// Queued requests of higher priority classes are admitted first
+    pub priority: u32,
+    /// Maximum queued requests of this class (None = `queue_size`)
+    #[serde(default)]
+    pub queue_size: Option<usize>,
+    /// Maximum time in seconds a request of this class waits in the queue (None = `queue_timeout_secs`)
+    #[serde(default)]
+    pub queue_timeout_secs: Option<u64>,
+    /// API keys whose requests always get this class, regardless of the priority header
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+}
+
+impl PriorityClass {
+    fn new(name: &str, priority: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            priority,
+            queue_size: None,
+            queue_timeout_secs: None,
+            api_keys: vec![],
+        }
+    }
+}
+
+/// Admission queue configuration
+///
+/// Requests that cannot be served right away because `max_concurrent_requests` is reached
+/// wait in the queue of their priority class. An empty class list gives a single FIFO queue.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AdmissionConfig {
+    /// Header carrying the priority class name of a request
+    #[serde(default = "default_priority_header")]
+    pub priority_header: String,
+    /// Class of requests without a (known) priority
+    #[serde(default = "default_priority_class")]
+    pub default_class: String,
+    /// Queued requests gain one priority level per interval waited, so lower classes are
+    /// not starved (0 = no aging)
+    #[serde(default = "default_aging_interval_secs")]
+    pub aging_interval_secs: u64,
+    /// Priority classes
+    #[serde(default = "default_priority_classes")]
+    pub classes: Vec<PriorityClass>,
+}
+
+fn default_priority_header() -> String {
+    "x-request-priority".to_string()
+}
+
+fn default_priority_class() -> String {
+    "normal".to_string()
+}
+
+fn default_aging_interval_secs() -> u64 {
+    10
+}
+
+fn default_priority_classes() -> Vec<PriorityClass> {
+    vec![
+        PriorityClass::new("high", 2),
+        PriorityClass::new("normal", 1),
+        PriorityClass::new("low", 0),
+    ]
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            priority_header: default_priority_header(),
+            default_class: default_priority_class(),
+            aging_interval_secs: default_aging_interval_secs(),
+            classes: default_priority_classes(),
+        }
+    }
+}
+
+/// Draining of removed workers and of the router itself on shutdown
+///
+/// A draining worker or router takes no new requests, but in-flight requests (including
+/// streams) are given time to finish before it goes away.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DrainConfig {
+    /// Maximum time in seconds a removed worker stays registered while requests are in flight
+    #[serde(default = "default_worker_drain_timeout_secs")]
+    pub worker_timeout_secs: u64,
+    /// Maximum time in seconds the router waits for in-flight requests on SIGTERM
+    #[serde(default = "default_shutdown_grace_period_secs")]
+    pub shutdown_grace_period_secs: u64,
+}
+
+fn default_worker_drain_timeout_secs() -> u64 {
+    30
+}
+
+fn default_shutdown_grace_period_secs() -> u64 {
+    30
+}
+
+impl Default for DrainConfig {
+    fn default() -> Self {
+        Self {
+            worker_timeout_secs: default_worker_drain_timeout_secs(),
+            shutdown_grace_period_secs: default_shutdown_grace_period_secs(),
+        }
+    }
+}
+
 /// Retry configuration for request handling
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryConfig {
@@ -399,6 +984,7 @@ impl Default for RouterConfig {
             worker_startup_check_interval_secs: 30,
             dp_aware: false,
             api_key: None,
+            auth: AuthConfig::default(),
             discovery: None,
             metrics: None,
             log_dir: None,
@@ -407,7 +993,10 @@ impl Default for RouterConfig {
             max_concurrent_requests: 256,
             queue_size: 100,
             queue_timeout_secs: 60,
+            admission: AdmissionConfig::default(),
+            drain: DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: TenantRateLimitConfig::default(),
             cors_allowed_origins: vec![],
             retry: RetryConfig::default(),
             circuit_breaker: CircuitBreakerConfig::default(),
@@ -419,6 +1008,14 @@ impl Default for RouterConfig {
             model_path: None,
             tokenizer_path: None,
             history_backend: default_history_backend(),
+            history_storage: HistoryStorageConfig::default(),
+            mcp: McpToolsConfig::default(),
+            mcp_server: McpEndpointConfig::default(),
+            backend: WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: default_model_refresh_interval_secs(),
+            load_unit: LoadUnit::Requests,
         }
     }
 }
@@ -458,6 +1055,33 @@ impl RouterConfig {
         self.metrics.is_some()
     }
 
+    /// Check if any policy matches requests on token ids, which requires a tokenizer
+    pub fn uses_token_tree(&self) -> bool {
+        self.policy.uses_token_tree()
+            || self.mode.get_prefill_policy(&self.policy).uses_token_tree()
+            || self.mode.get_decode_policy(&self.policy).uses_token_tree()
+    }
+
+    /// Interval in seconds for scraping worker loads: the shortest any policy asks for,
+    /// None if no policy uses loads
+    ///
+    /// In IGW mode workers may be added with a hint for a load-aware policy, so loads
+    /// are always scraped, by default every [`HINTED_POLICY_LOAD_CHECK_INTERVAL_SECS`].
+    pub fn load_check_interval_secs(&self) -> Option<u64> {
+        let hinted = self
+            .enable_igw
+            .then_some(HINTED_POLICY_LOAD_CHECK_INTERVAL_SECS);
+        [
+            &self.policy,
+            self.mode.get_prefill_policy(&self.policy),
+            self.mode.get_decode_policy(&self.policy),
+        ]
+        .iter()
+        .filter_map(|policy| policy.load_check_interval_secs())
+        .chain(hinted)
+        .min()
+    }
+
     /// Compute the effective retry config considering disable flag
     pub fn effective_retry_config(&self) -> RetryConfig {
         let mut cfg = self.retry.clone();
@@ -645,6 +1269,8 @@ mod tests {
             balance_rel_threshold: 1.5,
             eviction_interval_secs: 300,
             max_tree_size: 1000,
+            tree_type: CacheTreeType::Char,
+            block_size: 16,
         };
         assert_eq!(cache_aware.name(), "cache_aware");
 
@@ -668,11 +1294,25 @@ mod tests {
             balance_rel_threshold: 1.5,
             eviction_interval_secs: 300,
             max_tree_size: 1000,
+            tree_type: CacheTreeType::Char,
+            block_size: 16,
         };
         let json = serde_json::to_string(&cache_aware).unwrap();
         assert!(json.contains("\"type\":\"cache_aware\""));
         assert!(json.contains("\"cache_threshold\":0.8"));
         assert!(json.contains("\"balance_abs_threshold\":10"));
+        assert!(json.contains("\"tree_type\":\"char\""));
+
+        // The tree options are optional
+        let cache_aware: PolicyConfig = serde_json::from_str(
+            r#"{"type":"cache_aware","cache_threshold":0.5,"balance_abs_threshold":32,"balance_rel_threshold":1.1,"eviction_interval_secs":30,"max_tree_size":100}"#,
+        )
+        .unwrap();
+        assert!(!cache_aware.uses_token_tree());
+        match cache_aware {
+            PolicyConfig::CacheAware { block_size, .. } => assert_eq!(block_size, 16),
+            _ => panic!("Expected CacheAware"),
+        }
 
         // Test PowerOfTwo
         let power_of_two = PolicyConfig::PowerOfTwo {
@@ -691,6 +1331,8 @@ mod tests {
             balance_rel_threshold: 2.0,
             eviction_interval_secs: 600,
             max_tree_size: 5000,
+            tree_type: CacheTreeType::Token,
+            block_size: 32,
         };
 
         match cache_aware {
@@ -700,12 +1342,16 @@ mod tests {
                 balance_rel_threshold,
                 eviction_interval_secs,
                 max_tree_size,
+                tree_type,
+                block_size,
             } => {
                 assert!((cache_threshold - 0.75).abs() < 0.0001);
                 assert_eq!(balance_abs_threshold, 20);
                 assert!((balance_rel_threshold - 2.0).abs() < 0.0001);
                 assert_eq!(eviction_interval_secs, 600);
                 assert_eq!(max_tree_size, 5000);
+                assert_eq!(tree_type, CacheTreeType::Token);
+                assert_eq!(block_size, 32);
             }
             _ => panic!("Expected CacheAware"),
         }
@@ -727,6 +1373,127 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_check_interval() {
+        let kv_pressure = PolicyConfig::KvPressure {
+            queue_weight: 2.0,
+            kv_cache_weight: 16.0,
+            max_kv_cache_usage: 0.95,
+            load_check_interval_secs: 2,
+        };
+        let json = serde_json::to_string(&kv_pressure).unwrap();
+        assert!(json.contains("\"type\":\"kv_pressure\""));
+        assert_eq!(kv_pressure.load_check_interval_secs(), Some(2));
+        assert_eq!(PolicyConfig::Random.load_check_interval_secs(), None);
+
+        let mut config = RouterConfig {
+            policy: PolicyConfig::RoundRobin,
+            ..Default::default()
+        };
+        assert_eq!(config.load_check_interval_secs(), None);
+
+        config.mode = RoutingMode::PrefillDecode {
+            prefill_urls: vec![("http://prefill".to_string(), None)],
+            decode_urls: vec!["http://decode".to_string()],
+            prefill_policy: Some(PolicyConfig::PowerOfTwo {
+                load_check_interval_secs: 5,
+            }),
+            decode_policy: Some(kv_pressure),
+        };
+        assert_eq!(config.load_check_interval_secs(), Some(2));
+
+        // Workers may bring a load-aware policy hint in IGW mode
+        let config = RouterConfig {
+            policy: PolicyConfig::RoundRobin,
+            enable_igw: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.load_check_interval_secs(),
+            Some(HINTED_POLICY_LOAD_CHECK_INTERVAL_SECS)
+        );
+    }
+
+    #[test]
+    fn test_latency_policy_serialization() {
+        let json = r#"{"type":"latency","ewma_alpha":0.3,"decay_half_life_secs":30,"expected_output_tokens":128}"#;
+        let policy: PolicyConfig = serde_json::from_str(json).unwrap();
+        match &policy {
+            PolicyConfig::Latency {
+                ewma_alpha,
+                decay_half_life_secs,
+                expected_output_tokens,
+            } => {
+                assert_eq!(*ewma_alpha, 0.3);
+                assert_eq!(*decay_half_life_secs, 30);
+                assert_eq!(*expected_output_tokens, 128);
+            }
+            _ => panic!("Expected Latency"),
+        }
+        assert_eq!(policy.name(), "latency");
+        assert_eq!(policy.load_check_interval_secs(), None);
+    }
+
+    #[test]
+    fn test_load_unit() {
+        assert_eq!(RouterConfig::default().load_unit, LoadUnit::Requests);
+        assert_eq!(
+            serde_json::to_string(&LoadUnit::Tokens).unwrap(),
+            r#""tokens""#
+        );
+        assert_eq!("tokens".parse::<LoadUnit>(), Ok(LoadUnit::Tokens));
+        assert_eq!(LoadUnit::Requests.as_str(), "requests");
+        assert!("bytes".parse::<LoadUnit>().is_err());
+    }
+
+    #[test]
+    fn test_consistent_hash_serialization() {
+        // Configs without key sources or a capacity factor keep the legacy behavior
+        let policy: PolicyConfig =
+            serde_json::from_str(r#"{"type":"consistent_hash","virtual_nodes":160}"#).unwrap();
+        match &policy {
+            PolicyConfig::ConsistentHash {
+                hash_key_sources,
+                capacity_factor,
+                ..
+            } => {
+                assert!(hash_key_sources.is_empty());
+                assert_eq!(*capacity_factor, None);
+            }
+            _ => panic!("Expected ConsistentHash"),
+        }
+
+        let json = r#"{"type":"consistent_hash","virtual_nodes":80,"hash_key_sources":[{"header":"x-session-id"},{"json_pointer":"/metadata/conversation"},"prompt_cache_key"],"capacity_factor":1.25}"#;
+        let policy: PolicyConfig = serde_json::from_str(json).unwrap();
+        let sources = vec![
+            HashKeySource::Header("x-session-id".to_string()),
+            HashKeySource::JsonPointer("/metadata/conversation".to_string()),
+            HashKeySource::PromptCacheKey,
+        ];
+        match &policy {
+            PolicyConfig::ConsistentHash {
+                virtual_nodes,
+                hash_key_sources,
+                capacity_factor,
+            } => {
+                assert_eq!(*virtual_nodes, 80);
+                assert_eq!(*hash_key_sources, sources);
+                assert_eq!(*capacity_factor, Some(1.25));
+            }
+            _ => panic!("Expected ConsistentHash"),
+        }
+
+        // The CLI form round-trips through Display
+        for source in sources {
+            assert_eq!(source.to_string().parse::<HashKeySource>(), Ok(source));
+        }
+        assert_eq!(
+            "header:X-Session-Id".parse::<HashKeySource>(),
+            Ok(HashKeySource::Header("x-session-id".to_string()))
+        );
+        assert!("cookie:session".parse::<HashKeySource>().is_err());
+    }
+
     // ============= DiscoveryConfig Tests =============
 
     #[test]
@@ -960,6 +1727,7 @@ mod tests {
             worker_startup_check_interval_secs: 5,
             dp_aware: false,
             api_key: None,
+            auth: AuthConfig::default(),
             discovery: Some(DiscoveryConfig {
                 enabled: true,
                 namespace: Some("sglang".to_string()),
@@ -982,11 +1750,22 @@ mod tests {
             enable_igw: false,
             queue_size: 100,
             queue_timeout_secs: 60,
+            admission: AdmissionConfig::default(),
+            drain: DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: TenantRateLimitConfig::default(),
             connection_mode: ConnectionMode::Http,
             model_path: None,
             tokenizer_path: None,
             history_backend: default_history_backend(),
+            history_storage: HistoryStorageConfig::default(),
+            mcp: McpToolsConfig::default(),
+            mcp_server: McpEndpointConfig::default(),
+            backend: WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         assert!(config.mode.is_pd_mode());
@@ -1015,6 +1794,8 @@ mod tests {
                 balance_rel_threshold: 1.2,
                 eviction_interval_secs: 600,
                 max_tree_size: 10000,
+                tree_type: CacheTreeType::Char,
+                block_size: 16,
             },
             host: "0.0.0.0".to_string(),
             port: 3001,
@@ -1024,6 +1805,7 @@ mod tests {
             worker_startup_check_interval_secs: 15,
             dp_aware: false,
             api_key: None,
+            auth: AuthConfig::default(),
             discovery: Some(DiscoveryConfig {
                 enabled: true,
                 namespace: None,
@@ -1046,11 +1828,22 @@ mod tests {
             enable_igw: false,
             queue_size: 100,
             queue_timeout_secs: 60,
+            admission: AdmissionConfig::default(),
+            drain: DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: TenantRateLimitConfig::default(),
             connection_mode: ConnectionMode::Http,
             model_path: None,
             tokenizer_path: None,
             history_backend: default_history_backend(),
+            history_storage: HistoryStorageConfig::default(),
+            mcp: McpToolsConfig::default(),
+            mcp_server: McpEndpointConfig::default(),
+            backend: WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         assert!(!config.mode.is_pd_mode());
@@ -1079,6 +1872,7 @@ mod tests {
             worker_startup_check_interval_secs: 20,
             dp_aware: false,
             api_key: None,
+            auth: AuthConfig::default(),
             discovery: Some(DiscoveryConfig {
                 enabled: true,
                 namespace: Some("production".to_string()),
@@ -1106,11 +1900,22 @@ mod tests {
             enable_igw: false,
             queue_size: 100,
             queue_timeout_secs: 60,
+            admission: AdmissionConfig::default(),
+            drain: DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: TenantRateLimitConfig::default(),
             connection_mode: ConnectionMode::Http,
             model_path: None,
             tokenizer_path: None,
             history_backend: default_history_backend(),
+            history_storage: HistoryStorageConfig::default(),
+            mcp: McpToolsConfig::default(),
+            mcp_server: McpEndpointConfig::default(),
+            backend: WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         assert!(config.has_service_discovery());
@@ -1143,6 +1948,8 @@ mod tests {
                 balance_rel_threshold: 1.1,
                 eviction_interval_secs: 60,
                 max_tree_size: 1000,
+                tree_type: CacheTreeType::Char,
+                block_size: 16,
             }),
             decode_policy: Some(PolicyConfig::PowerOfTwo {
                 load_check_interval_secs: 60,
@@ -1175,6 +1982,8 @@ mod tests {
                 balance_rel_threshold: 1.1,
                 eviction_interval_secs: 60,
                 max_tree_size: 1000,
+                tree_type: CacheTreeType::Char,
+                block_size: 16,
             }),
             decode_policy: None,
         };
@@ -1237,6 +2046,8 @@ mod tests {
             balance_rel_threshold: 1.5,
             eviction_interval_secs: 300,
             max_tree_size: 2000,
+            tree_type: CacheTreeType::Char,
+            block_size: 16,
         };
 
         // Both should fall back to main policy
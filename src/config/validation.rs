@@ -1,5 +1,10 @@
 use super::*;
 
+/// Smallest cache-aware `balance_abs_threshold` accepted when loads are counted in tokens;
+/// a request-scale threshold would treat workers a few requests' worth of tokens apart as
+/// imbalanced and switch cache affinity off
+const MIN_TOKEN_BALANCE_ABS_THRESHOLD: usize = 1024;
+
 /// Configuration validator
 pub struct ConfigValidator;
 
@@ -11,8 +16,16 @@ impl ConfigValidator {
             || matches!(&config.mode, RoutingMode::VllmPrefillDecode { discovery_address: Some(_), .. });
 
         Self::validate_mode(&config.mode, has_service_discovery)?;
+        Self::validate_openai_upstreams(config)?;
         Self::validate_policy(&config.policy)?;
         Self::validate_server_settings(config)?;
+        Self::validate_auth(&config.auth)?;
+        Self::validate_tenant_rate_limits(&config.tenant_rate_limits)?;
+        Self::validate_admission(&config.admission)?;
+        Self::validate_model_aliases(&config.model_aliases)?;
+        Self::validate_history(&config.history_backend, &config.history_storage)?;
+        Self::validate_mcp(&config.mcp)?;
+        Self::validate_mcp_server(&config.mcp_server)?;
 
         if let Some(discovery) = &config.discovery {
             Self::validate_discovery(discovery, &config.mode)?;
@@ -149,23 +162,48 @@ impl ConfigValidator {
                 }
             }
             RoutingMode::OpenAI { worker_urls } => {
-                // Require exactly one worker URL for OpenAI router
-                if worker_urls.len() != 1 {
-                    return Err(ConfigError::ValidationFailed {
-                        reason: "OpenAI mode requires exactly one --worker-urls entry".to_string(),
-                    });
-                }
-                // Validate URL format
-                if let Err(e) = url::Url::parse(&worker_urls[0]) {
-                    return Err(ConfigError::ValidationFailed {
-                        reason: format!("Invalid OpenAI worker URL '{}': {}", &worker_urls[0], e),
-                    });
+                // Providers may also come from `openai_upstreams`, checked separately
+                for url in worker_urls {
+                    if let Err(e) = url::Url::parse(url) {
+                        return Err(ConfigError::ValidationFailed {
+                            reason: format!("Invalid OpenAI worker URL '{}': {}", url, e),
+                        });
+                    }
                 }
             }
         }
         Ok(())
     }
 
+    /// Validate the providers of OpenAI mode
+    fn validate_openai_upstreams(config: &RouterConfig) -> ConfigResult<()> {
+        let RoutingMode::OpenAI { worker_urls } = &config.mode else {
+            return Ok(());
+        };
+        if worker_urls.is_empty() && config.openai_upstreams.is_empty() {
+            return Err(ConfigError::ValidationFailed {
+                reason: "OpenAI mode requires --worker-urls or --openai-upstreams-file".to_string(),
+            });
+        }
+        for upstream in &config.openai_upstreams {
+            if let Err(e) = url::Url::parse(&upstream.url) {
+                return Err(ConfigError::InvalidValue {
+                    field: "openai_upstreams.url".to_string(),
+                    value: upstream.url.clone(),
+                    reason: e.to_string(),
+                });
+            }
+            if upstream.api_key.as_deref() == Some("") {
+                return Err(ConfigError::InvalidValue {
+                    field: "openai_upstreams.api_key".to_string(),
+                    value: upstream.url.clone(),
+                    reason: "API key must not be empty".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Validate policy configuration
     fn validate_policy(policy: &PolicyConfig) -> ConfigResult<()> {
         match policy {
@@ -178,6 +216,8 @@ impl ConfigValidator {
                 balance_rel_threshold,
                 eviction_interval_secs,
                 max_tree_size,
+                tree_type: _,
+                block_size,
             } => {
                 if !(0.0..=1.0).contains(cache_threshold) {
                     return Err(ConfigError::InvalidValue {
@@ -210,6 +250,14 @@ impl ConfigValidator {
                         reason: "Must be > 0".to_string(),
                     });
                 }
+
+                if *block_size == 0 {
+                    return Err(ConfigError::InvalidValue {
+                        field: "block_size".to_string(),
+                        value: block_size.to_string(),
+                        reason: "Must be > 0".to_string(),
+                    });
+                }
             }
             PolicyConfig::PowerOfTwo {
                 load_check_interval_secs,
@@ -222,7 +270,11 @@ impl ConfigValidator {
                     });
                 }
             }
-            PolicyConfig::ConsistentHash { virtual_nodes } => {
+            PolicyConfig::ConsistentHash {
+                virtual_nodes,
+                hash_key_sources,
+                capacity_factor,
+            } => {
                 if *virtual_nodes == 0 {
                     return Err(ConfigError::InvalidValue {
                         field: "virtual_nodes".to_string(),
@@ -230,6 +282,95 @@ impl ConfigValidator {
                         reason: "Must be > 0".to_string(),
                     });
                 }
+                for source in hash_key_sources {
+                    let valid = match source {
+                        HashKeySource::Header(name) => !name.is_empty(),
+                        HashKeySource::JsonPointer(pointer) => pointer.starts_with('/'),
+                        HashKeySource::PromptCacheKey => true,
+                    };
+                    if !valid {
+                        return Err(ConfigError::InvalidValue {
+                            field: "hash_key_sources".to_string(),
+                            value: source.to_string(),
+                            reason: "Headers need a name and JSON pointers must start with '/'"
+                                .to_string(),
+                        });
+                    }
+                }
+                if let Some(factor) = capacity_factor {
+                    // A bound below the average load would leave no worker with capacity
+                    if factor.is_nan() || *factor < 1.0 {
+                        return Err(ConfigError::InvalidValue {
+                            field: "capacity_factor".to_string(),
+                            value: factor.to_string(),
+                            reason: "Must be >= 1.0".to_string(),
+                        });
+                    }
+                }
+            }
+            PolicyConfig::KvPressure {
+                queue_weight,
+                kv_cache_weight,
+                max_kv_cache_usage,
+                load_check_interval_secs,
+            } => {
+                for (field, weight) in [
+                    ("queue_weight", queue_weight),
+                    ("kv_cache_weight", kv_cache_weight),
+                ] {
+                    if weight.is_nan() || *weight < 0.0 {
+                        return Err(ConfigError::InvalidValue {
+                            field: field.to_string(),
+                            value: weight.to_string(),
+                            reason: "Must be >= 0".to_string(),
+                        });
+                    }
+                }
+
+                if !(0.0..=1.0).contains(max_kv_cache_usage) || *max_kv_cache_usage == 0.0 {
+                    return Err(ConfigError::InvalidValue {
+                        field: "max_kv_cache_usage".to_string(),
+                        value: max_kv_cache_usage.to_string(),
+                        reason: "Must be between 0.0 (exclusive) and 1.0".to_string(),
+                    });
+                }
+
+                if *load_check_interval_secs == 0 {
+                    return Err(ConfigError::InvalidValue {
+                        field: "load_check_interval_secs".to_string(),
+                        value: load_check_interval_secs.to_string(),
+                        reason: "Must be > 0".to_string(),
+                    });
+                }
+            }
+            PolicyConfig::Latency {
+                ewma_alpha,
+                decay_half_life_secs,
+                expected_output_tokens,
+            } => {
+                if !(0.0..=1.0).contains(ewma_alpha) || *ewma_alpha == 0.0 {
+                    return Err(ConfigError::InvalidValue {
+                        field: "ewma_alpha".to_string(),
+                        value: ewma_alpha.to_string(),
+                        reason: "Must be between 0.0 (exclusive) and 1.0".to_string(),
+                    });
+                }
+
+                if *decay_half_life_secs == 0 {
+                    return Err(ConfigError::InvalidValue {
+                        field: "decay_half_life_secs".to_string(),
+                        value: decay_half_life_secs.to_string(),
+                        reason: "Must be > 0".to_string(),
+                    });
+                }
+
+                if *expected_output_tokens == 0 {
+                    return Err(ConfigError::InvalidValue {
+                        field: "expected_output_tokens".to_string(),
+                        value: expected_output_tokens.to_string(),
+                        reason: "Must be > 0".to_string(),
+                    });
+                }
             }
         }
         Ok(())
@@ -277,6 +418,237 @@ impl ConfigValidator {
             });
         }
 
+        if config.model_refresh_interval_secs == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "model_refresh_interval_secs".to_string(),
+                value: config.model_refresh_interval_secs.to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate inbound authentication configuration
+    fn validate_auth(auth: &AuthConfig) -> ConfigResult<()> {
+        if auth
+            .inference_keys
+            .iter()
+            .chain(&auth.admin_keys)
+            .any(|key| key.trim().is_empty())
+        {
+            return Err(ConfigError::InvalidValue {
+                field: "auth".to_string(),
+                value: String::new(),
+                reason: "API keys cannot be empty".to_string(),
+            });
+        }
+
+        if auth.key_file.is_some() && auth.key_file_reload_interval_secs == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "auth.key_file_reload_interval_secs".to_string(),
+                value: auth.key_file_reload_interval_secs.to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate per-tenant rate limits
+    fn validate_tenant_rate_limits(limits: &TenantRateLimitConfig) -> ConfigResult<()> {
+        let zero_limit = |field: &str| ConfigError::InvalidValue {
+            field: field.to_string(),
+            value: "0".to_string(),
+            reason: "Must be > 0".to_string(),
+        };
+
+        if limits.default_requests_per_minute == Some(0) {
+            return Err(zero_limit("tenant_rate_limits.default_requests_per_minute"));
+        }
+        if limits.default_tokens_per_minute == Some(0) {
+            return Err(zero_limit("tenant_rate_limits.default_tokens_per_minute"));
+        }
+
+        let mut seen_ids = std::collections::HashSet::new();
+        for tenant in &limits.tenants {
+            if tenant.name.is_empty() {
+                return Err(ConfigError::MissingRequired {
+                    field: "tenant_rate_limits.tenants.name".to_string(),
+                });
+            }
+            if tenant.requests_per_minute == Some(0) {
+                return Err(zero_limit(&format!(
+                    "tenant_rate_limits.tenants[{}].requests_per_minute",
+                    tenant.name
+                )));
+            }
+            if tenant.tokens_per_minute == Some(0) {
+                return Err(zero_limit(&format!(
+                    "tenant_rate_limits.tenants[{}].tokens_per_minute",
+                    tenant.name
+                )));
+            }
+            // Ids may be API keys, so only the tenant name goes into the error
+            if tenant.ids.iter().any(|id| !seen_ids.insert(id.as_str())) {
+                return Err(ConfigError::ValidationFailed {
+                    reason: format!(
+                        "Tenant '{}' reuses an id that is already assigned to a tenant",
+                        tenant.name
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate admission queue priority classes
+    fn validate_admission(admission: &AdmissionConfig) -> ConfigResult<()> {
+        if admission.priority_header.is_empty() {
+            return Err(ConfigError::MissingRequired {
+                field: "admission.priority_header".to_string(),
+            });
+        }
+
+        let mut seen_names = std::collections::HashSet::new();
+        let mut seen_keys = std::collections::HashSet::new();
+        for class in &admission.classes {
+            if class.name.is_empty() {
+                return Err(ConfigError::MissingRequired {
+                    field: "admission.classes.name".to_string(),
+                });
+            }
+            if !seen_names.insert(class.name.to_lowercase()) {
+                return Err(ConfigError::ValidationFailed {
+                    reason: format!("Duplicate priority class '{}'", class.name),
+                });
+            }
+            if class.queue_timeout_secs == Some(0) {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("admission.classes[{}].queue_timeout_secs", class.name),
+                    value: "0".to_string(),
+                    reason: "Must be > 0".to_string(),
+                });
+            }
+            // Don't echo the key itself into the error
+            if class
+                .api_keys
+                .iter()
+                .any(|key| !seen_keys.insert(key.as_str()))
+            {
+                return Err(ConfigError::ValidationFailed {
+                    reason: format!(
+                        "Priority class '{}' reuses an API key that is already assigned to a class",
+                        class.name
+                    ),
+                });
+            }
+        }
+
+        if !admission.classes.is_empty()
+            && !seen_names.contains(&admission.default_class.to_lowercase())
+        {
+            return Err(ConfigError::InvalidValue {
+                field: "admission.default_class".to_string(),
+                value: admission.default_class.clone(),
+                reason: "Must name one of the configured priority classes".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate model aliases
+    fn validate_model_aliases(aliases: &[ModelAlias]) -> ConfigResult<()> {
+        let mut seen_names = std::collections::HashSet::new();
+        for alias in aliases {
+            if alias.name.is_empty() {
+                return Err(ConfigError::MissingRequired {
+                    field: "model_aliases.name".to_string(),
+                });
+            }
+            if !seen_names.insert(alias.name.as_str()) {
+                return Err(ConfigError::ValidationFailed {
+                    reason: format!("Model alias '{}' is defined more than once", alias.name),
+                });
+            }
+            if alias.model_id.as_deref() == Some("") {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("model_aliases[{}].model_id", alias.name),
+                    value: String::new(),
+                    reason: "Must not be empty".to_string(),
+                });
+            }
+            // The model field is owned by `model_id`
+            let params = &alias.parameters;
+            if params.strip.iter().any(|f| f == "model")
+                || params.defaults.contains_key("model")
+                || params.max.contains_key("model")
+            {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("model_aliases[{}].parameters", alias.name),
+                    value: "model".to_string(),
+                    reason: "Use model_id to change the model".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate history storage settings
+    fn validate_history(
+        backend: &HistoryBackend,
+        storage: &HistoryStorageConfig,
+    ) -> ConfigResult<()> {
+        if *backend == HistoryBackend::File
+            && storage.path.as_deref().is_none_or(|path| path.is_empty())
+        {
+            return Err(ConfigError::MissingRequired {
+                field: "history_storage.path".to_string(),
+            });
+        }
+        if storage.ttl_secs == Some(0) {
+            return Err(ConfigError::InvalidValue {
+                field: "history_storage.ttl_secs".to_string(),
+                value: "0".to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate MCP tool loop settings
+    fn validate_mcp(mcp: &McpToolsConfig) -> ConfigResult<()> {
+        if mcp.max_iterations == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "mcp.max_iterations".to_string(),
+                value: "0".to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+        if mcp.config_path.as_deref() == Some("") {
+            return Err(ConfigError::InvalidValue {
+                field: "mcp.config_path".to_string(),
+                value: String::new(),
+                reason: "Must not be empty".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate the router's MCP server endpoint settings
+    fn validate_mcp_server(mcp_server: &McpEndpointConfig) -> ConfigResult<()> {
+        if mcp_server.admin_key.as_deref() == Some("") {
+            return Err(ConfigError::InvalidValue {
+                field: "mcp_server.admin_key".to_string(),
+                value: String::new(),
+                reason: "Must not be empty".to_string(),
+            });
+        }
+
         Ok(())
     }
 
@@ -448,6 +820,53 @@ impl ConfigValidator {
             });
         }
 
+        // Token-level cache trees tokenize requests with the router's tokenizer
+        if config.uses_token_tree()
+            && config.tokenizer_path.is_none()
+            && config.model_path.is_none()
+        {
+            return Err(ConfigError::ValidationFailed {
+                reason: "The token cache tree requires either --tokenizer-path or --model-path to be specified".to_string(),
+            });
+        }
+
+        // Cache-aware balance thresholds are counted in the load unit
+        if config.load_unit == LoadUnit::Tokens {
+            for policy in [
+                &config.policy,
+                config.mode.get_prefill_policy(&config.policy),
+                config.mode.get_decode_policy(&config.policy),
+            ] {
+                if let PolicyConfig::CacheAware {
+                    balance_abs_threshold,
+                    ..
+                } = policy
+                {
+                    if *balance_abs_threshold < MIN_TOKEN_BALANCE_ABS_THRESHOLD {
+                        return Err(ConfigError::IncompatibleConfig {
+                            reason: format!(
+                                "With load unit 'tokens' the cache-aware balance_abs_threshold is a number of tokens; {} looks like a request count, use at least {}",
+                                balance_abs_threshold, MIN_TOKEN_BALANCE_ABS_THRESHOLD
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Backend profiles are only consulted by the regular HTTP router
+        if config.backend != WorkerBackend::Vllm
+            && (config.connection_mode == ConnectionMode::Grpc
+                || !matches!(config.mode, RoutingMode::Regular { .. }))
+        {
+            return Err(ConfigError::IncompatibleConfig {
+                reason: format!(
+                    "The {} backend is only supported in regular HTTP routing mode",
+                    config.backend.as_str()
+                ),
+            });
+        }
+
         // All policies are now supported for both router types thanks to the unified trait design
         // No mode/policy restrictions needed anymore
 
@@ -632,6 +1051,8 @@ mod tests {
                 balance_rel_threshold: 1.1,
                 eviction_interval_secs: 60,
                 max_tree_size: 1000,
+                tree_type: CacheTreeType::Char,
+                block_size: 16,
             },
         );
 
@@ -651,6 +1072,8 @@ mod tests {
                 balance_rel_threshold: 1.1,
                 eviction_interval_secs: 60,
                 max_tree_size: 1000,
+                tree_type: CacheTreeType::Char,
+                block_size: 16,
             },
         );
 
@@ -705,6 +1128,8 @@ mod tests {
                 balance_rel_threshold: 1.1,
                 eviction_interval_secs: 60,
                 max_tree_size: 1000,
+                tree_type: CacheTreeType::Char,
+                block_size: 16,
             },
         );
 
@@ -750,6 +1175,8 @@ mod tests {
                     balance_rel_threshold: 1.1,
                     eviction_interval_secs: 60,
                     max_tree_size: 1000,
+                    tree_type: CacheTreeType::Char,
+                    block_size: 16,
                 }),
                 decode_policy: Some(PolicyConfig::PowerOfTwo {
                     load_check_interval_secs: 60,
@@ -842,4 +1269,314 @@ mod tests {
         let result = ConfigValidator::validate(&config);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_validate_token_tree_requires_tokenizer() {
+        let policy = PolicyConfig::CacheAware {
+            cache_threshold: 0.5,
+            balance_abs_threshold: 32,
+            balance_rel_threshold: 1.1,
+            eviction_interval_secs: 30,
+            max_tree_size: 1000,
+            tree_type: CacheTreeType::Token,
+            block_size: 16,
+        };
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            policy,
+        );
+
+        let result = ConfigValidator::validate(&config);
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert!(e.to_string().contains("token cache tree requires"));
+        }
+
+        config.model_path = Some("meta-llama/Llama-3-8B".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_token_load_balance_threshold() {
+        let cache_aware = |balance_abs_threshold| {
+            let mut config = RouterConfig::new(
+                RoutingMode::Regular {
+                    worker_urls: vec!["http://worker:8000".to_string()],
+                },
+                PolicyConfig::CacheAware {
+                    cache_threshold: 0.5,
+                    balance_abs_threshold,
+                    balance_rel_threshold: 1.1,
+                    eviction_interval_secs: 30,
+                    max_tree_size: 1000,
+                    tree_type: CacheTreeType::Char,
+                    block_size: 16,
+                },
+            );
+            config.load_unit = LoadUnit::Tokens;
+            config
+        };
+
+        let result = ConfigValidator::validate(&cache_aware(64));
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert!(e.to_string().contains("number of tokens"));
+        }
+        assert!(ConfigValidator::validate(&cache_aware(4096)).is_ok());
+
+        // Request counts keep the request-scale threshold
+        let mut config = cache_aware(64);
+        config.load_unit = LoadUnit::Requests;
+        assert!(ConfigValidator::validate(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_kv_pressure() {
+        let kv_pressure = |queue_weight: f32, max_kv_cache_usage: f32| {
+            RouterConfig::new(
+                RoutingMode::Regular {
+                    worker_urls: vec!["http://worker:8000".to_string()],
+                },
+                PolicyConfig::KvPressure {
+                    queue_weight,
+                    kv_cache_weight: 16.0,
+                    max_kv_cache_usage,
+                    load_check_interval_secs: 5,
+                },
+            )
+        };
+
+        assert!(ConfigValidator::validate(&kv_pressure(2.0, 0.95)).is_ok());
+        assert!(ConfigValidator::validate(&kv_pressure(-1.0, 0.95)).is_err());
+        assert!(ConfigValidator::validate(&kv_pressure(2.0, 0.0)).is_err());
+        assert!(ConfigValidator::validate(&kv_pressure(2.0, 1.5)).is_err());
+    }
+
+    #[test]
+    fn test_validate_latency() {
+        let latency = |ewma_alpha: f32, decay_half_life_secs: u64| {
+            RouterConfig::new(
+                RoutingMode::Regular {
+                    worker_urls: vec!["http://worker:8000".to_string()],
+                },
+                PolicyConfig::Latency {
+                    ewma_alpha,
+                    decay_half_life_secs,
+                    expected_output_tokens: 256,
+                },
+            )
+        };
+
+        assert!(ConfigValidator::validate(&latency(0.2, 60)).is_ok());
+        assert!(ConfigValidator::validate(&latency(1.0, 60)).is_ok());
+        assert!(ConfigValidator::validate(&latency(0.0, 60)).is_err());
+        assert!(ConfigValidator::validate(&latency(1.5, 60)).is_err());
+        assert!(ConfigValidator::validate(&latency(0.2, 0)).is_err());
+    }
+
+    #[test]
+    fn test_validate_consistent_hash() {
+        let consistent_hash = |hash_key_sources: Vec<HashKeySource>, capacity_factor| {
+            RouterConfig::new(
+                RoutingMode::Regular {
+                    worker_urls: vec!["http://worker:8000".to_string()],
+                },
+                PolicyConfig::ConsistentHash {
+                    virtual_nodes: 160,
+                    hash_key_sources,
+                    capacity_factor,
+                },
+            )
+        };
+
+        let sources = vec![
+            HashKeySource::Header("x-session-id".to_string()),
+            HashKeySource::JsonPointer("/session_params/session_id".to_string()),
+            HashKeySource::PromptCacheKey,
+        ];
+        assert!(ConfigValidator::validate(&consistent_hash(vec![], None)).is_ok());
+        assert!(ConfigValidator::validate(&consistent_hash(sources, Some(1.25))).is_ok());
+        assert!(ConfigValidator::validate(&consistent_hash(vec![], Some(0.5))).is_err());
+        assert!(ConfigValidator::validate(&consistent_hash(
+            vec![HashKeySource::JsonPointer("session_id".to_string())],
+            None
+        ))
+        .is_err());
+        assert!(ConfigValidator::validate(&consistent_hash(
+            vec![HashKeySource::Header(String::new())],
+            None
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_auth_rejects_empty_key() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+
+        config.auth.inference_keys = vec!["sk-valid".to_string(), " ".to_string()];
+
+        let result = ConfigValidator::validate(&config);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("API keys cannot be empty"));
+    }
+
+    #[test]
+    fn test_validate_tenant_rate_limits() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+
+        let tenant = |name: &str, id: &str| TenantRateLimit {
+            name: name.to_string(),
+            ids: vec![id.to_string()],
+            requests_per_minute: Some(60),
+            tokens_per_minute: None,
+        };
+        config.tenant_rate_limits.tenants = vec![tenant("a", "sk-a"), tenant("b", "sk-b")];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.tenant_rate_limits.tenants = vec![tenant("a", "sk-a"), tenant("b", "sk-a")];
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.tenant_rate_limits.tenants = vec![];
+        config.tenant_rate_limits.default_tokens_per_minute = Some(0);
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_admission_classes() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.admission.default_class = "urgent".to_string();
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        // Without classes the queue is a single FIFO and the default class is unused
+        config.admission.classes = vec![];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.admission = AdmissionConfig::default();
+        let mut duplicate = config.admission.classes[0].clone();
+        duplicate.name = duplicate.name.to_uppercase();
+        config.admission.classes.push(duplicate);
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_history_storage() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+
+        config.history_backend = HistoryBackend::File;
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.history_storage.path = Some("/var/lib/vllm-router/history".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.history_storage.ttl_secs = Some(0);
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_trtllm_backend_requires_regular_mode() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        config.backend = WorkerBackend::Trtllm;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.mode = RoutingMode::PrefillDecode {
+            prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+            decode_urls: vec!["http://decode:8000".to_string()],
+            prefill_policy: None,
+            decode_policy: None,
+        };
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_model_aliases() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        let alias = ModelAlias {
+            name: "gpt-4o-mini".to_string(),
+            model_id: Some("llama-3-8b".to_string()),
+            worker_selector: std::collections::HashMap::new(),
+            parameters: ParameterPolicy::default(),
+        };
+        config.model_aliases = vec![alias.clone()];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.model_aliases = vec![alias.clone(), alias.clone()];
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        let mut rewrites_model = alias;
+        rewrites_model
+            .parameters
+            .defaults
+            .insert("model".to_string(), serde_json::json!("other"));
+        config.model_aliases = vec![rewrites_model];
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_openai_upstreams() {
+        let mut config = RouterConfig::new(
+            RoutingMode::OpenAI {
+                worker_urls: vec![
+                    "http://vllm:8000".to_string(),
+                    "http://vllm-2:8000".to_string(),
+                ],
+            },
+            PolicyConfig::Random,
+        );
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        // Configured upstreams alone are enough
+        config.mode = RoutingMode::OpenAI {
+            worker_urls: vec![],
+        };
+        assert!(ConfigValidator::validate(&config).is_err());
+        let mut hosted = OpenAIUpstream::new("https://api.example.com");
+        hosted.api_key = Some("sk-test".to_string());
+        hosted.weight = 0;
+        config.openai_upstreams = vec![hosted];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.openai_upstreams[0].api_key = Some(String::new());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.openai_upstreams[0].api_key = None;
+        config.openai_upstreams[0].url = "not a url".to_string();
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
 }
@@ -0,0 +1,165 @@
+//! Per-backend worker behaviour
+//!
+//! vLLM and TensorRT-LLM both serve the OpenAI API, but differ in the endpoints the
+//! router uses to manage workers and in the request fields they accept. A
+//! [`BackendProfile`] describes those differences so vLLM and TRT-LLM workers can sit
+//! behind the same router.
+
+use crate::config::WorkerBackend;
+use crate::core::worker_metrics::{parse_prometheus, WorkerLoad};
+use serde_json::Value;
+
+/// Endpoints and request rewrites of one inference backend
+#[derive(Debug)]
+pub struct BackendProfile {
+    /// Health check path, or None to use the configured `health_check.endpoint`
+    pub health_endpoint: Option<&'static str>,
+    /// Path reporting the worker's current load
+    pub load_endpoint: &'static str,
+    /// Path answering the router's `/get_model_info`
+    pub model_info_endpoint: &'static str,
+    /// Path reporting `dp_size`, or None when the backend does not expose DP ranks
+    pub server_info_endpoint: Option<&'static str>,
+    /// Request fields the backend rejects, removed from chat and completion requests
+    pub unsupported_fields: &'static [&'static str],
+    parse_load: fn(&str) -> Option<WorkerLoad>,
+}
+
+static VLLM: BackendProfile = BackendProfile {
+    health_endpoint: None,
+    load_endpoint: "/metrics",
+    model_info_endpoint: "/get_model_info",
+    server_info_endpoint: Some("/get_server_info"),
+    unsupported_fields: &[],
+    parse_load: parse_prometheus,
+};
+
+// trtllm-serve validates requests strictly, so vLLM sampling extensions it does not
+// know must not reach it
+static TRTLLM: BackendProfile = BackendProfile {
+    health_endpoint: Some("/health"),
+    load_endpoint: "/metrics",
+    model_info_endpoint: "/v1/models",
+    server_info_endpoint: None,
+    unsupported_fields: &[
+        "regex",
+        "ebnf",
+        "json_schema",
+        "no_stop_trim",
+        "continue_final_message",
+        "separate_reasoning",
+        "stream_reasoning",
+        "lora_path",
+        "session_params",
+        "return_hidden_states",
+        "rid",
+    ],
+    parse_load: trtllm_load,
+};
+
+impl BackendProfile {
+    /// Profile of the given backend
+    pub fn of(backend: WorkerBackend) -> &'static BackendProfile {
+        match backend {
+            WorkerBackend::Vllm => &VLLM,
+            WorkerBackend::Trtllm => &TRTLLM,
+        }
+    }
+
+    /// Extract the load from the load endpoint's response body
+    pub fn parse_load(&self, body: &str) -> Option<WorkerLoad> {
+        (self.parse_load)(body)
+    }
+
+    /// Rewrite a request body sent to `route` into the form the backend accepts
+    pub fn rewrite_request(&self, route: &str, body: &mut Value) {
+        if !matches!(route, "/v1/chat/completions" | "/v1/completions") {
+            return;
+        }
+        if let Some(fields) = body.as_object_mut() {
+            for field in self.unsupported_fields {
+                fields.remove(*field);
+            }
+        }
+    }
+}
+
+/// `/metrics` returns the iteration stats recorded since the previous call, oldest
+/// first, as JSON; no stats means no iteration ran, i.e. the worker is idle
+fn trtllm_load(body: &str) -> Option<WorkerLoad> {
+    let body: Value = serde_json::from_str(body).ok()?;
+    let stats = body.as_array()?;
+    let Some(latest) = stats.last() else {
+        return Some(WorkerLoad::default());
+    };
+    let count = |value: &Value, field: &str| value.get(field).and_then(Value::as_u64);
+    let kv_cache_usage = latest.get("kvCacheStats").and_then(|kv| {
+        let used = count(kv, "usedNumBlocks")?;
+        let max = count(kv, "maxNumBlocks").filter(|&max| max > 0)?;
+        Some(used as f64 / max as f64)
+    });
+    Some(WorkerLoad {
+        running: count(latest, "numActiveRequests")?,
+        waiting: count(latest, "numQueuedRequests").unwrap_or(0),
+        kv_cache_usage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_load() {
+        let requests = |load: Option<WorkerLoad>| load.map(|load| load.requests());
+
+        let vllm = BackendProfile::of(WorkerBackend::Vllm);
+        let metrics = "vllm:num_requests_running 2\nvllm:num_requests_waiting 1\n";
+        assert_eq!(requests(vllm.parse_load(metrics)), Some(3));
+        assert_eq!(vllm.parse_load(r#"{"load": 3}"#), None);
+
+        let trtllm = BackendProfile::of(WorkerBackend::Trtllm);
+        let stats = json!([
+            {"iter": 1, "numActiveRequests": 8, "numQueuedRequests": 0},
+            {
+                "iter": 2,
+                "numActiveRequests": 4,
+                "numQueuedRequests": 2,
+                "kvCacheStats": {"usedNumBlocks": 30, "maxNumBlocks": 120}
+            }
+        ])
+        .to_string();
+        let load = trtllm.parse_load(&stats).unwrap();
+        assert_eq!(load.requests(), 6);
+        assert_eq!(load.kv_cache_usage, Some(0.25));
+        assert_eq!(requests(trtllm.parse_load("[]")), Some(0));
+        assert_eq!(trtllm.parse_load(r#"{"load": 3}"#), None);
+    }
+
+    #[test]
+    fn test_trtllm_drops_unsupported_fields() {
+        let trtllm = BackendProfile::of(WorkerBackend::Trtllm);
+        let request = json!({
+            "model": "m",
+            "messages": [],
+            "top_k": 20,
+            "regex": "[0-9]+",
+            "separate_reasoning": true,
+            "no_stop_trim": false
+        });
+
+        let mut chat = request.clone();
+        trtllm.rewrite_request("/v1/chat/completions", &mut chat);
+        assert_eq!(chat, json!({"model": "m", "messages": [], "top_k": 20}));
+
+        // Only chat and completion requests are rewritten
+        let mut generate = request.clone();
+        trtllm.rewrite_request("/generate", &mut generate);
+        assert_eq!(generate, request);
+
+        let mut vllm = request.clone();
+        BackendProfile::of(WorkerBackend::Vllm).rewrite_request("/v1/chat/completions", &mut vllm);
+        assert_eq!(vllm, request);
+    }
+}
@@ -0,0 +1,126 @@
+//! Propagation of client disconnects to workers
+//!
+//! When a streaming client goes away, dropping the response stream on the router side
+//! does not stop the worker: it keeps generating until `max_tokens`. The routers use
+//! [`RequestAborter`] to tell the worker(s) serving the request to stop, via the gRPC
+//! `Abort` RPC for gRPC workers and `POST /abort_request` for HTTP workers.
+
+use super::Worker;
+use crate::metrics::RouterMetrics;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Reason reported to gRPC workers when a request is aborted
+const CLIENT_DISCONNECTED: &str = "client disconnected";
+
+/// Sends abort requests to workers on behalf of disconnected clients
+#[derive(Debug, Clone)]
+pub struct RequestAborter {
+    client: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl RequestAborter {
+    /// Create an aborter that uses `client` (and `api_key`, if set) for HTTP workers
+    pub fn new(client: reqwest::Client, api_key: Option<String>) -> Self {
+        Self { client, api_key }
+    }
+
+    /// Abort `request_id` on every given worker.
+    ///
+    /// The abort calls run in the background so callers can release their resources
+    /// right away; failures are only logged since the client is already gone.
+    pub fn abort(&self, route: &str, request_id: &str, workers: Vec<Arc<dyn Worker>>) {
+        RouterMetrics::record_request_aborted(route);
+
+        for worker in workers {
+            let aborter = self.clone();
+            let request_id = request_id.to_string();
+            tokio::spawn(async move {
+                match aborter.abort_on_worker(worker.as_ref(), &request_id).await {
+                    Ok(()) => debug!("Aborted request {} on worker {}", request_id, worker.url()),
+                    Err(e) => warn!(
+                        "Failed to abort request {} on worker {}: {}",
+                        request_id,
+                        worker.url(),
+                        e
+                    ),
+                }
+            });
+        }
+    }
+
+    async fn abort_on_worker(&self, worker: &dyn Worker, request_id: &str) -> Result<(), String> {
+        if let Some(client) = worker.grpc_client() {
+            let mut client = client.lock().await.clone();
+            return client
+                .abort_request(request_id.to_string(), CLIENT_DISCONNECTED.to_string())
+                .await
+                .map_err(|e| e.to_string());
+        }
+
+        let mut request = self
+            .client
+            .post(format!("{}/abort_request", worker.base_url()))
+            .json(&json!({ "rid": request_id }));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("worker returned status {}", response.status()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+    use axum::{extract::State, routing::post, Json, Router};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_abort_posts_rid_to_http_worker() {
+        let received: Arc<Mutex<Vec<serde_json::Value>>> = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/abort_request",
+                post(
+                    |State(received): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                     Json(body): Json<serde_json::Value>| async move {
+                        received.lock().unwrap().push(body);
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let worker: Arc<dyn Worker> = Arc::new(BasicWorker::new(
+            format!("http://{}", addr),
+            WorkerType::Regular,
+        ));
+        let aborter = RequestAborter::new(reqwest::Client::new(), None);
+        aborter
+            .abort_on_worker(worker.as_ref(), "req-1")
+            .await
+            .unwrap();
+
+        assert_eq!(*received.lock().unwrap(), vec![json!({"rid": "req-1"})]);
+    }
+
+    #[tokio::test]
+    async fn test_abort_reports_unreachable_worker() {
+        let worker = BasicWorker::new("http://127.0.0.1:1".to_string(), WorkerType::Regular);
+        let aborter = RequestAborter::new(reqwest::Client::new(), None);
+        assert!(aborter.abort_on_worker(&worker, "req-1").await.is_err());
+    }
+}
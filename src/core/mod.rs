@@ -2,25 +2,44 @@
 //!
 //! This module contains the fundamental types and traits used throughout the router:
 //! - Worker trait and implementations
+//! - Per-backend (vLLM, TensorRT-LLM) worker endpoints and request rewrites
 //! - Error types
+//! - Model catalog aggregated from the workers' `/v1/models`
+//! - Worker load snapshots scraped from the workers' metrics
+//! - Token-weighted worker load estimated from the requests
 //! - Circuit breaker for reliability
+//! - Request cancellation on client disconnect
+//! - Per-tenant request and token rate limiting
 //! - Common utilities
 
+pub mod backend;
+pub mod cancellation;
 pub mod circuit_breaker;
 pub mod error;
+pub mod model_catalog;
 pub mod retry;
+pub mod tenant_limiter;
 pub mod token_bucket;
+pub mod token_load;
 pub mod worker;
+pub mod worker_metrics;
 pub mod worker_registry;
 
 // Re-export commonly used types at the module level
+pub use backend::BackendProfile;
+pub use cancellation::RequestAborter;
 pub use circuit_breaker::{
     CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, CircuitState,
 };
 pub use error::{WorkerError, WorkerResult};
+pub use model_catalog::ModelCatalog;
 pub use retry::{is_retryable_status, BackoffCalculator, RetryError, RetryExecutor};
+pub use tenant_limiter::{RateLimitExceeded, RateLimitKind, TenantRateLimiter, TenantUsage};
+pub use token_load::{TokenEstimate, TokenLoadGuard};
 pub use worker::{
-    start_health_checker, BasicWorker, ConnectionMode, DPAwareWorker, HealthChecker, HealthConfig,
-    Worker, WorkerCollection, WorkerFactory, WorkerLoadGuard, WorkerType,
+    discover_served_models, start_health_checker, BasicWorker, ConnectionMode, DPAwareWorker,
+    HealthChecker, HealthConfig, OwnedWorkerLoadGuard, Worker, WorkerCollection, WorkerFactory,
+    WorkerLoadGuard, WorkerType, UNKNOWN_MODEL_ID,
 };
+pub use worker_metrics::{WorkerLoad, WorkerMetrics};
 pub use worker_registry::{WorkerId, WorkerRegistry, WorkerRegistryStats};
@@ -0,0 +1,328 @@
+//! Aggregated model catalog
+//!
+//! A worker only reports the models it serves itself, so proxying `/v1/models` to a
+//! single worker shows clients a partial list in multi-model deployments. The
+//! [`ModelCatalog`] periodically queries the `/v1/models` of every registered worker and
+//! merges the answers with the model ids the workers were registered under. Each model
+//! is listed once, with its context length, owner, the number of workers serving it and
+//! its LoRA adapters.
+
+use crate::core::{ConnectionMode, WorkerRegistry, UNKNOWN_MODEL_ID};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// Timeout of one worker's `/v1/models` query
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An entry of a worker's `/v1/models` response
+#[derive(Debug, Clone, Default, Deserialize)]
+struct WorkerModel {
+    id: String,
+    #[serde(default)]
+    created: Option<i64>,
+    #[serde(default)]
+    owned_by: Option<String>,
+    #[serde(default)]
+    root: Option<String>,
+    /// Base model of a LoRA adapter
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    max_model_len: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    data: Vec<WorkerModel>,
+}
+
+/// The models a worker reported in its last answer
+#[derive(Debug)]
+struct WorkerModels {
+    models: Vec<WorkerModel>,
+}
+
+/// A model merged across the workers serving it
+#[derive(Debug, Default)]
+struct CatalogEntry {
+    created: Option<i64>,
+    owned_by: Option<String>,
+    root: Option<String>,
+    parent: Option<String>,
+    max_model_len: Option<u64>,
+    workers: usize,
+    available_workers: usize,
+    lora_adapters: BTreeSet<String>,
+}
+
+impl CatalogEntry {
+    fn add(&mut self, model: WorkerModel, healthy: bool) {
+        self.created = self.created.or(model.created);
+        self.owned_by = self.owned_by.take().or(model.owned_by);
+        self.root = self.root.take().or(model.root);
+        self.parent = self.parent.take().or(model.parent);
+        // Requests must fit on every worker the model may be routed to
+        self.max_model_len = match (self.max_model_len, model.max_model_len) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.workers += 1;
+        if healthy {
+            self.available_workers += 1;
+        }
+    }
+
+    fn to_json(&self, id: &str) -> Value {
+        json!({
+            "id": id,
+            "object": "model",
+            "created": self.created.unwrap_or(0),
+            "owned_by": self.owned_by.as_deref().unwrap_or("vllm-router"),
+            "root": self.root.as_deref().unwrap_or(id),
+            "parent": self.parent,
+            "max_model_len": self.max_model_len,
+            "workers": self.workers,
+            "available_workers": self.available_workers,
+            "lora_adapters": self.lora_adapters,
+        })
+    }
+}
+
+/// The models served by the registered workers
+///
+/// Worker answers are cached and re-queried every refresh interval by the refresher;
+/// reads only query workers added since the last refresh.
+#[derive(Debug)]
+pub struct ModelCatalog {
+    worker_registry: Arc<WorkerRegistry>,
+    client: reqwest::Client,
+    api_key: Option<String>,
+    refresh_interval: Duration,
+    /// Reported models by worker base URL
+    workers: DashMap<String, WorkerModels>,
+    /// Held while querying workers, so concurrent reads share one round of queries
+    refresh_lock: tokio::sync::Mutex<()>,
+}
+
+impl ModelCatalog {
+    pub fn new(
+        worker_registry: Arc<WorkerRegistry>,
+        client: reqwest::Client,
+        api_key: Option<String>,
+        refresh_interval: Duration,
+    ) -> Self {
+        Self {
+            worker_registry,
+            client,
+            api_key,
+            refresh_interval,
+            workers: DashMap::new(),
+            refresh_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Spawn a task re-querying all workers every refresh interval
+    pub fn start_refresher(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let catalog = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(catalog.refresh_interval);
+            loop {
+                ticker.tick().await;
+                catalog.refresh(true).await;
+            }
+        })
+    }
+
+    /// The catalog's models as `/v1/models` entries, sorted by id
+    pub async fn list(&self) -> Vec<Value> {
+        self.refresh(false).await;
+        self.entries()
+            .iter()
+            .map(|(id, entry)| entry.to_json(id))
+            .collect()
+    }
+
+    /// `/v1/models` response listing the catalog
+    pub async fn models_response(&self) -> Response {
+        let data = self.list().await;
+        if data.is_empty() {
+            return (StatusCode::SERVICE_UNAVAILABLE, "No models available").into_response();
+        }
+        Json(json!({"object": "list", "data": data})).into_response()
+    }
+
+    /// Query the HTTP workers that never answered a query, or all of them with `force`,
+    /// and forget removed workers
+    async fn refresh(&self, force: bool) {
+        let _refreshing = self.refresh_lock.lock().await;
+        let base_urls: HashSet<String> = self
+            .worker_registry
+            .get_all()
+            .iter()
+            .filter(|worker| worker.connection_mode() == ConnectionMode::Http)
+            .map(|worker| worker.base_url().to_string())
+            .collect();
+        self.workers.retain(|url, _| base_urls.contains(url));
+
+        let stale: Vec<String> = base_urls
+            .into_iter()
+            .filter(|url| force || !self.workers.contains_key(url))
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+
+        let results = futures::future::join_all(stale.iter().map(|url| self.fetch(url))).await;
+        for (url, result) in stale.into_iter().zip(results) {
+            let models = match result {
+                Ok(models) => models,
+                Err(e) => {
+                    // Keep the last answer; the worker is retried after the interval
+                    debug!("Failed to query models of worker {}: {}", url, e);
+                    self.workers
+                        .get(&url)
+                        .map(|previous| previous.models.clone())
+                        .unwrap_or_default()
+                }
+            };
+            self.workers.insert(url, WorkerModels { models });
+        }
+    }
+
+    async fn fetch(&self, base_url: &str) -> Result<Vec<WorkerModel>, String> {
+        let mut request = self
+            .client
+            .get(format!("{}/v1/models", base_url))
+            .timeout(FETCH_TIMEOUT);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("status {}", response.status()));
+        }
+        let list: ModelList = response.json().await.map_err(|e| e.to_string())?;
+        Ok(list.data)
+    }
+
+    /// Merge the models of all registered workers
+    fn entries(&self) -> BTreeMap<String, CatalogEntry> {
+        let mut entries: BTreeMap<String, CatalogEntry> = BTreeMap::new();
+        for worker in self.worker_registry.get_all() {
+            let mut models = self
+                .workers
+                .get(worker.base_url())
+                .map(|reported| reported.models.clone())
+                .unwrap_or_default();
+            // Workers are routed to by the model ids they are registered under, even
+            // before they answered
+            for model_id in worker.model_ids() {
+                if model_id != UNKNOWN_MODEL_ID && !models.iter().any(|model| model.id == model_id)
+                {
+                    models.push(WorkerModel {
+                        id: model_id.to_string(),
+                        ..Default::default()
+                    });
+                }
+            }
+
+            let healthy = worker.is_healthy();
+            for model in models {
+                if let Some(parent) = &model.parent {
+                    entries
+                        .entry(parent.clone())
+                        .or_default()
+                        .lora_adapters
+                        .insert(model.id.clone());
+                }
+                entries
+                    .entry(model.id.clone())
+                    .or_default()
+                    .add(model, healthy);
+            }
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, CircuitBreakerConfig, Worker, WorkerFactory, WorkerType};
+    use std::collections::HashMap;
+
+    fn worker_models(models: Value) -> WorkerModels {
+        WorkerModels {
+            models: serde_json::from_value(models).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_merges_workers() {
+        let registry = Arc::new(WorkerRegistry::new());
+        let mut labels = HashMap::new();
+        labels.insert("model_id".to_string(), "llama-3".to_string());
+        for url in ["http://w1:8000", "http://w2:8000"] {
+            registry.register(Arc::from(WorkerFactory::create_regular_with_labels(
+                url.to_string(),
+                labels.clone(),
+                CircuitBreakerConfig::default(),
+            )));
+        }
+        let labeled_only =
+            BasicWorker::new("http://w3:8000".to_string(), WorkerType::Regular).with_labels(
+                HashMap::from([("model_id".to_string(), "qwen".to_string())]),
+            );
+        labeled_only.set_healthy(false);
+        registry.register(Arc::new(labeled_only));
+
+        let catalog = ModelCatalog::new(
+            registry,
+            reqwest::Client::new(),
+            None,
+            Duration::from_secs(60),
+        );
+        catalog.workers.insert(
+            "http://w1:8000".to_string(),
+            worker_models(json!([
+                {"id": "llama-3", "owned_by": "vllm", "max_model_len": 8192},
+                {"id": "sql-lora", "root": "/adapters/sql", "parent": "llama-3"}
+            ])),
+        );
+        catalog.workers.insert(
+            "http://w2:8000".to_string(),
+            worker_models(json!([{"id": "llama-3", "max_model_len": 4096}])),
+        );
+        catalog
+            .workers
+            .insert("http://w3:8000".to_string(), worker_models(json!([])));
+
+        let models = catalog.list().await;
+        let ids: Vec<_> = models.iter().map(|m| m["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["llama-3", "qwen", "sql-lora"]);
+
+        assert_eq!(models[0]["owned_by"], "vllm");
+        assert_eq!(models[0]["max_model_len"], 4096);
+        assert_eq!(models[0]["workers"], 2);
+        assert_eq!(models[0]["available_workers"], 2);
+        assert_eq!(models[0]["lora_adapters"], json!(["sql-lora"]));
+
+        assert_eq!(models[1]["workers"], 1);
+        assert_eq!(models[1]["available_workers"], 0);
+        assert_eq!(models[1]["max_model_len"], Value::Null);
+
+        assert_eq!(models[2]["parent"], "llama-3");
+        assert_eq!(models[2]["root"], "/adapters/sql");
+        assert_eq!(models[2]["workers"], 1);
+    }
+}
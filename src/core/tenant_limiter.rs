@@ -0,0 +1,458 @@
+//! Per-tenant request and token rate limiting
+//!
+//! Each tenant gets a request bucket and a token bucket refilled per minute. Token usage
+//! is estimated up front from the prompt length and the requested completion length, so a
+//! request is charged before it reaches a worker.
+
+use super::token_bucket::TokenBucket;
+use crate::config::{TenantRateLimitConfig, TenantSource};
+use dashmap::DashMap;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Metrics label for tenants without explicit limits
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Number of tenant ids kept after an eviction pass; the least recently used go first
+const MAX_TRACKED_TENANTS: usize = 10_000;
+
+/// Buckets unused for this long are dropped on the next eviction pass
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(600);
+
+/// Interval between eviction passes over the tracked buckets
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rough number of characters per token for prompt length estimates
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// The limit a request ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKind {
+    Requests,
+    Tokens,
+}
+
+impl RateLimitKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitKind::Requests => "requests",
+            RateLimitKind::Tokens => "tokens",
+        }
+    }
+}
+
+/// State of one bucket after a request was checked against it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketStatus {
+    /// Bucket capacity (the per-minute limit)
+    pub limit: u64,
+    /// Whole units left in the bucket
+    pub remaining: u64,
+    /// Time until the bucket is full again
+    pub reset: Duration,
+}
+
+/// Rate limit state of a tenant, reported back to the client
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenantUsage {
+    /// Tenant name used for metrics
+    pub tenant: String,
+    pub requests: Option<BucketStatus>,
+    pub tokens: Option<BucketStatus>,
+}
+
+/// A request rejected by the tenant rate limiter
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitExceeded {
+    pub kind: RateLimitKind,
+    pub usage: TenantUsage,
+    /// The request needs more than the bucket holds when full, so retrying cannot help
+    pub exceeds_capacity: bool,
+}
+
+impl RateLimitExceeded {
+    /// Time until the exhausted bucket is full again
+    pub fn retry_after(&self) -> Duration {
+        let status = match self.kind {
+            RateLimitKind::Requests => self.usage.requests,
+            RateLimitKind::Tokens => self.usage.tokens,
+        };
+        status.map(|s| s.reset).unwrap_or_default()
+    }
+}
+
+struct TenantBuckets {
+    tenant: String,
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+    last_used: Mutex<Instant>,
+}
+
+impl TenantBuckets {
+    fn new(
+        tenant: String,
+        requests_per_minute: Option<u64>,
+        tokens_per_minute: Option<u64>,
+    ) -> Self {
+        let per_minute = |limit: u64| TokenBucket::with_rate(limit as f64, limit as f64 / 60.0);
+        Self {
+            tenant,
+            requests: requests_per_minute.map(per_minute),
+            tokens: tokens_per_minute.map(per_minute),
+            last_used: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn usage(&self) -> TenantUsage {
+        TenantUsage {
+            tenant: self.tenant.clone(),
+            requests: bucket_status(self.requests.as_ref()).await,
+            tokens: bucket_status(self.tokens.as_ref()).await,
+        }
+    }
+}
+
+async fn bucket_status(bucket: Option<&TokenBucket>) -> Option<BucketStatus> {
+    let bucket = bucket?;
+    let available = bucket.available_tokens().await;
+    Some(BucketStatus {
+        limit: bucket.capacity() as u64,
+        remaining: available.floor() as u64,
+        reset: bucket.time_to_full(available),
+    })
+}
+
+/// Rate limiter keeping separate buckets per tenant
+pub struct TenantRateLimiter {
+    config: TenantRateLimitConfig,
+    /// Tenant id -> index of the tenant in `config.tenants`
+    tenant_index: HashMap<String, usize>,
+    buckets: Arc<DashMap<String, Arc<TenantBuckets>>>,
+}
+
+impl std::fmt::Debug for TenantRateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TenantRateLimiter")
+            .field("tenants", &self.config.tenants.len())
+            .field("tracked", &self.buckets.len())
+            .finish()
+    }
+}
+
+impl TenantRateLimiter {
+    pub fn new(config: &TenantRateLimitConfig) -> Self {
+        let tenant_index = config
+            .tenants
+            .iter()
+            .enumerate()
+            .flat_map(|(index, tenant)| tenant.ids.iter().map(move |id| (id.clone(), index)))
+            .collect();
+
+        let buckets = Arc::new(DashMap::new());
+        if config.is_enabled() {
+            // The thread holds a weak reference and exits once the limiter is dropped
+            let buckets = Arc::downgrade(&buckets);
+            thread::spawn(move || evict_buckets_periodically(buckets));
+        }
+
+        Self {
+            config: config.clone(),
+            tenant_index,
+            buckets,
+        }
+    }
+
+    /// Whether any tenant is rate limited
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_enabled()
+    }
+
+    /// How the tenant id of a request is determined
+    pub fn tenant_source(&self) -> TenantSource {
+        self.config.tenant_source
+    }
+
+    /// Charge one request and `estimated_tokens` tokens to the tenant identified by `tenant_id`.
+    ///
+    /// Nothing is charged when the request is rejected. A request estimated at more tokens
+    /// than the tenant's per-minute limit is rejected with `exceeds_capacity` set.
+    pub async fn check(
+        &self,
+        tenant_id: Option<&str>,
+        estimated_tokens: u64,
+    ) -> Result<TenantUsage, RateLimitExceeded> {
+        let buckets = self.buckets_for(tenant_id.unwrap_or_default());
+
+        if let Some(tokens) = &buckets.tokens {
+            if estimated_tokens as f64 > tokens.capacity() {
+                return Err(RateLimitExceeded {
+                    kind: RateLimitKind::Tokens,
+                    usage: buckets.usage().await,
+                    exceeds_capacity: true,
+                });
+            }
+        }
+
+        if let Some(requests) = &buckets.requests {
+            if requests.try_acquire(1.0).await.is_err() {
+                return Err(RateLimitExceeded {
+                    kind: RateLimitKind::Requests,
+                    usage: buckets.usage().await,
+                    exceeds_capacity: false,
+                });
+            }
+        }
+
+        if let Some(tokens) = &buckets.tokens {
+            if tokens.try_acquire(estimated_tokens as f64).await.is_err() {
+                if let Some(requests) = &buckets.requests {
+                    requests.return_tokens(1.0).await;
+                }
+                return Err(RateLimitExceeded {
+                    kind: RateLimitKind::Tokens,
+                    usage: buckets.usage().await,
+                    exceeds_capacity: false,
+                });
+            }
+        }
+
+        Ok(buckets.usage().await)
+    }
+
+    /// Charge `estimated_tokens` tokens to the tenant without counting a request, for
+    /// requests admitted by `check` before their token estimate was known
+    pub async fn check_tokens(
+        &self,
+        tenant_id: Option<&str>,
+        estimated_tokens: u64,
+    ) -> Result<TenantUsage, RateLimitExceeded> {
+        let buckets = self.buckets_for(tenant_id.unwrap_or_default());
+
+        if let Some(tokens) = &buckets.tokens {
+            let exceeds_capacity = estimated_tokens as f64 > tokens.capacity();
+            if exceeds_capacity || tokens.try_acquire(estimated_tokens as f64).await.is_err() {
+                return Err(RateLimitExceeded {
+                    kind: RateLimitKind::Tokens,
+                    usage: buckets.usage().await,
+                    exceeds_capacity,
+                });
+            }
+        }
+
+        Ok(buckets.usage().await)
+    }
+
+    fn buckets_for(&self, tenant_id: &str) -> Arc<TenantBuckets> {
+        // Configured tenants share buckets across their ids; everyone else gets their own
+        let (key, tenant, requests_per_minute, tokens_per_minute) =
+            match self.tenant_index.get(tenant_id) {
+                Some(&index) => {
+                    let tenant = &self.config.tenants[index];
+                    (
+                        format!("tenant:{}", tenant.name),
+                        tenant.name.clone(),
+                        tenant.requests_per_minute,
+                        tenant.tokens_per_minute,
+                    )
+                }
+                None => (
+                    format!("id:{}", tenant_id),
+                    DEFAULT_TENANT.to_string(),
+                    self.config.default_requests_per_minute,
+                    self.config.default_tokens_per_minute,
+                ),
+            };
+
+        if let Some(buckets) = self.buckets.get(&key) {
+            *buckets.last_used.lock().unwrap() = Instant::now();
+            return Arc::clone(&buckets);
+        }
+
+        let buckets = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| {
+                Arc::new(TenantBuckets::new(
+                    tenant,
+                    requests_per_minute,
+                    tokens_per_minute,
+                ))
+            })
+            .clone();
+        buckets
+    }
+}
+
+fn evict_buckets_periodically(buckets: Weak<DashMap<String, Arc<TenantBuckets>>>) {
+    loop {
+        thread::sleep(EVICTION_INTERVAL);
+        match buckets.upgrade() {
+            Some(buckets) => evict_buckets(&buckets),
+            None => return,
+        }
+    }
+}
+
+/// Drop idle buckets, then the least recently used ones beyond `MAX_TRACKED_TENANTS`
+fn evict_buckets(buckets: &DashMap<String, Arc<TenantBuckets>>) {
+    buckets.retain(|_, b| b.last_used.lock().unwrap().elapsed() < IDLE_BUCKET_TTL);
+
+    let excess = buckets.len().saturating_sub(MAX_TRACKED_TENANTS);
+    if excess == 0 {
+        return;
+    }
+    let mut by_last_use: Vec<(String, Instant)> = buckets
+        .iter()
+        .map(|entry| (entry.key().clone(), *entry.last_used.lock().unwrap()))
+        .collect();
+    by_last_use.sort_unstable_by_key(|(_, last_used)| *last_used);
+    for (key, _) in by_last_use.into_iter().take(excess) {
+        buckets.remove(&key);
+    }
+}
+
+/// Estimate the tokens a request will consume: prompt length plus requested completion length
+pub fn estimate_request_tokens(body: &Value) -> u64 {
+    let prompt_chars: u64 = ["messages", "prompt", "text", "input", "system"]
+        .iter()
+        .filter_map(|field| body.get(field))
+        .map(text_chars)
+        .sum();
+    let prompt_tokens = prompt_chars.div_ceil(CHARS_PER_TOKEN);
+
+    let max_tokens = ["max_tokens", "max_completion_tokens", "max_output_tokens"]
+        .iter()
+        .find_map(|field| body.get(field).and_then(Value::as_u64))
+        .or_else(|| {
+            body.pointer("/sampling_params/max_new_tokens")
+                .and_then(Value::as_u64)
+        })
+        .unwrap_or(0);
+
+    prompt_tokens + max_tokens
+}
+
+/// Total length of the strings in a JSON value
+fn text_chars(value: &Value) -> u64 {
+    match value {
+        Value::String(s) => s.chars().count() as u64,
+        Value::Array(items) => items.iter().map(text_chars).sum(),
+        Value::Object(map) => map.values().map(text_chars).sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TenantRateLimit;
+    use serde_json::json;
+
+    fn limiter() -> TenantRateLimiter {
+        TenantRateLimiter::new(&TenantRateLimitConfig {
+            tenant_source: TenantSource::ApiKey,
+            default_requests_per_minute: Some(2),
+            default_tokens_per_minute: None,
+            tenants: vec![TenantRateLimit {
+                name: "acme".to_string(),
+                ids: vec!["sk-acme-1".to_string(), "sk-acme-2".to_string()],
+                requests_per_minute: None,
+                tokens_per_minute: Some(100),
+            }],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_default_limits_are_per_id() {
+        let limiter = limiter();
+
+        let usage = limiter.check(Some("sk-a"), 0).await.unwrap();
+        assert_eq!(usage.tenant, DEFAULT_TENANT);
+        assert_eq!(usage.requests.unwrap().limit, 2);
+        assert_eq!(usage.requests.unwrap().remaining, 1);
+        assert!(usage.tokens.is_none());
+
+        assert!(limiter.check(Some("sk-a"), 0).await.is_ok());
+        let exceeded = limiter.check(Some("sk-a"), 0).await.unwrap_err();
+        assert_eq!(exceeded.kind, RateLimitKind::Requests);
+        assert!(exceeded.retry_after() > Duration::ZERO);
+
+        // Another id has its own bucket
+        assert!(limiter.check(Some("sk-b"), 0).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_configured_tenant_shares_token_bucket() {
+        let limiter = limiter();
+
+        let usage = limiter.check(Some("sk-acme-1"), 60).await.unwrap();
+        assert_eq!(usage.tenant, "acme");
+        assert_eq!(usage.tokens.unwrap().remaining, 40);
+        assert!(usage.requests.is_none());
+
+        let exceeded = limiter.check(Some("sk-acme-2"), 60).await.unwrap_err();
+        assert_eq!(exceeded.kind, RateLimitKind::Tokens);
+        assert_eq!(exceeded.usage.tokens.unwrap().remaining, 40);
+
+        assert!(limiter.check(Some("sk-acme-2"), 40).await.is_ok());
+
+        // A request larger than the whole bucket can never be admitted
+        let exceeded = limiter.check(Some("sk-acme-1"), 101).await.unwrap_err();
+        assert_eq!(exceeded.kind, RateLimitKind::Tokens);
+        assert!(exceeded.exceeds_capacity);
+    }
+
+    #[tokio::test]
+    async fn test_check_tokens_charges_no_request() {
+        let limiter = limiter();
+
+        let usage = limiter.check_tokens(Some("sk-acme-1"), 60).await.unwrap();
+        assert_eq!(usage.tokens.unwrap().remaining, 40);
+        let exceeded = limiter.check_tokens(Some("sk-acme-2"), 60).await.unwrap_err();
+        assert!(!exceeded.exceeds_capacity);
+
+        // Default tenants have no token limit, and their request budget is untouched
+        assert!(limiter.check_tokens(Some("sk-a"), 1000).await.is_ok());
+        let usage = limiter.check(Some("sk-a"), 0).await.unwrap();
+        assert_eq!(usage.requests.unwrap().remaining, 1);
+    }
+
+    #[test]
+    fn test_evict_buckets_keeps_most_recently_used() {
+        let buckets = DashMap::new();
+        let start = Instant::now();
+        for i in 0..MAX_TRACKED_TENANTS + 5 {
+            let bucket = TenantBuckets::new(DEFAULT_TENANT.to_string(), Some(1), None);
+            *bucket.last_used.lock().unwrap() = start + Duration::from_micros(i as u64);
+            buckets.insert(format!("id:{}", i), Arc::new(bucket));
+        }
+
+        evict_buckets(&buckets);
+
+        assert_eq!(buckets.len(), MAX_TRACKED_TENANTS);
+        assert!((0..5).all(|i| !buckets.contains_key(&format!("id:{}", i))));
+        assert!(buckets.contains_key(&format!("id:{}", MAX_TRACKED_TENANTS + 4)));
+    }
+
+    #[test]
+    fn test_estimate_request_tokens() {
+        let chat = json!({
+            "model": "m",
+            "messages": [
+                {"role": "user", "content": "12345678"},
+                {"role": "user", "content": [{"type": "text", "text": "1234"}]}
+            ],
+            "max_tokens": 10
+        });
+        // "user" role strings count too: (4 + 8 + 4 + 4 + 4) chars -> 6 tokens
+        assert_eq!(estimate_request_tokens(&chat), 16);
+
+        let generate = json!({"text": "1234", "sampling_params": {"max_new_tokens": 5}});
+        assert_eq!(estimate_request_tokens(&generate), 6);
+
+        assert_eq!(estimate_request_tokens(&json!({})), 0);
+    }
+}
@@ -29,9 +29,11 @@ impl TokenBucket {
     /// * `capacity` - Maximum number of tokens (burst capacity)
     /// * `refill_rate` - Tokens added per second
     pub fn new(capacity: usize, refill_rate: usize) -> Self {
-        let capacity = capacity as f64;
-        let refill_rate = refill_rate as f64;
+        Self::with_rate(capacity as f64, refill_rate as f64)
+    }
 
+    /// Create a token bucket with a fractional refill rate, e.g. for per-minute limits
+    pub fn with_rate(capacity: f64, refill_rate: f64) -> Self {
         // Ensure refill_rate is not zero to prevent division by zero
         let refill_rate = if refill_rate > 0.0 {
             refill_rate
@@ -140,6 +142,16 @@ impl TokenBucket {
         );
     }
 
+    /// Maximum number of tokens the bucket holds
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    /// Seconds until the bucket is full again, given `available` tokens
+    pub fn time_to_full(&self, available: f64) -> Duration {
+        Duration::from_secs_f64(((self.capacity - available) / self.refill_rate).max(0.0))
+    }
+
     /// Get current available tokens (for monitoring)
     pub async fn available_tokens(&self) -> f64 {
         let mut inner = self.inner.lock().await;
@@ -0,0 +1,180 @@
+//! Token-weighted worker load
+//!
+//! A worker's request count weighs a 100k-token prompt like a 10-token one. The router
+//! therefore also keeps an estimate of the tokens outstanding on every worker: the
+//! prompt tokens of its requests, which hold KV cache until the request ends, plus their
+//! decode budget (`max_tokens`), which drains as generated tokens stream back.
+
+use super::Worker;
+use crate::metrics::RouterMetrics;
+use crate::tokenizer::traits::Tokenizer;
+use std::sync::Arc;
+
+/// Bytes per token assumed when no tokenizer is available
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Decode budget assumed for requests that do not limit their output tokens
+pub const DEFAULT_DECODE_BUDGET: usize = 256;
+
+/// Tokens a request is expected to cost a worker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenEstimate {
+    /// Tokens of the prompt
+    pub prompt_tokens: usize,
+    /// Tokens the request may generate
+    pub decode_budget: usize,
+}
+
+impl TokenEstimate {
+    /// Estimate a request from its prompt, counting tokens with the tokenizer if there
+    /// is one and by bytes otherwise
+    pub fn new(
+        prompt: &str,
+        max_output_tokens: Option<u32>,
+        tokenizer: Option<&dyn Tokenizer>,
+    ) -> Self {
+        let prompt_tokens = tokenizer
+            .and_then(|tokenizer| tokenizer.encode(prompt).ok())
+            .map(|encoding| encoding.token_ids().len())
+            .unwrap_or_else(|| prompt.len().div_ceil(BYTES_PER_TOKEN));
+        Self {
+            prompt_tokens,
+            decode_budget: max_output_tokens.map_or(DEFAULT_DECODE_BUDGET, |max| max as usize),
+        }
+    }
+
+    /// Estimate a request like `new`, tokenizing on the blocking pool so long prompts
+    /// don't stall the async runtime
+    pub async fn estimate(
+        prompt: String,
+        max_output_tokens: Option<u32>,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+    ) -> Self {
+        let by_bytes = Self::new(&prompt, max_output_tokens, None);
+        let Some(tokenizer) = tokenizer else {
+            return by_bytes;
+        };
+        tokio::task::spawn_blocking(move || {
+            Self::new(&prompt, max_output_tokens, Some(tokenizer.as_ref()))
+        })
+        .await
+        .unwrap_or(by_bytes)
+    }
+
+    pub fn total(&self) -> usize {
+        self.prompt_tokens + self.decode_budget
+    }
+}
+
+/// Tokens a request holds on a worker, released when dropped
+#[derive(Debug)]
+pub struct TokenLoadGuard {
+    worker: Arc<dyn Worker>,
+    /// Tokens still counted on the worker
+    held: usize,
+    /// Part of `held` that generated tokens release
+    decode_budget: usize,
+}
+
+impl TokenLoadGuard {
+    /// Add a request's tokens to the worker's load
+    pub fn new(worker: Arc<dyn Worker>, estimate: TokenEstimate) -> Self {
+        worker.add_token_load(estimate.total());
+        RouterMetrics::set_worker_token_load(worker.url(), worker.token_load());
+        Self {
+            worker,
+            held: estimate.total(),
+            decode_budget: estimate.decode_budget,
+        }
+    }
+
+    /// Release the decode budget of tokens the worker generated
+    pub fn drain(&mut self, tokens: usize) {
+        let tokens = tokens.min(self.decode_budget);
+        if tokens == 0 {
+            return;
+        }
+        self.decode_budget -= tokens;
+        self.release(tokens);
+    }
+
+    fn release(&mut self, tokens: usize) {
+        self.held -= tokens;
+        self.worker.remove_token_load(tokens);
+        RouterMetrics::set_worker_token_load(self.worker.url(), self.worker.token_load());
+    }
+}
+
+impl Drop for TokenLoadGuard {
+    fn drop(&mut self) {
+        if self.held > 0 {
+            self.release(self.held);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+
+    #[test]
+    fn test_token_estimate() {
+        let estimate = TokenEstimate::new(&"x".repeat(4001), Some(100), None);
+        assert_eq!(
+            estimate,
+            TokenEstimate {
+                prompt_tokens: 1001,
+                decode_budget: 100,
+            }
+        );
+        assert_eq!(estimate.total(), 1101);
+
+        let estimate = TokenEstimate::new("", None, None);
+        assert_eq!(estimate.total(), DEFAULT_DECODE_BUDGET);
+    }
+
+    #[tokio::test]
+    async fn test_token_estimate_off_runtime() {
+        let by_bytes = TokenEstimate::estimate("Hello world".to_string(), Some(8), None).await;
+        assert_eq!(by_bytes.prompt_tokens, 3);
+
+        let tokenizer: Arc<dyn Tokenizer> = Arc::new(crate::tokenizer::mock::MockTokenizer::new());
+        let tokenized =
+            TokenEstimate::estimate("Hello world".to_string(), Some(8), Some(tokenizer)).await;
+        assert_eq!(
+            tokenized,
+            TokenEstimate {
+                prompt_tokens: 2,
+                decode_budget: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn test_token_load_guard() {
+        let worker: Arc<dyn Worker> = Arc::new(BasicWorker::new(
+            "http://w1:8000".to_string(),
+            WorkerType::Regular,
+        ));
+        let estimate = TokenEstimate {
+            prompt_tokens: 1000,
+            decode_budget: 10,
+        };
+
+        let mut first = TokenLoadGuard::new(worker.clone(), estimate);
+        let second = TokenLoadGuard::new(worker.clone(), estimate);
+        assert_eq!(worker.token_load(), 2020);
+
+        // Generated tokens drain the decode budget but not the prompt
+        first.drain(4);
+        assert_eq!(worker.token_load(), 2016);
+        first.drain(100);
+        assert_eq!(worker.token_load(), 2010);
+
+        drop(first);
+        assert_eq!(worker.token_load(), 1010);
+        drop(second);
+        assert_eq!(worker.token_load(), 0);
+    }
+}
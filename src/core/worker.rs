@@ -1,4 +1,5 @@
-use super::{CircuitBreaker, CircuitBreakerConfig, WorkerError, WorkerResult};
+use super::{BackendProfile, CircuitBreaker, CircuitBreakerConfig, WorkerError, WorkerResult};
+use crate::config::{LoadUnit, WorkerBackend};
 use crate::grpc::VllmSchedulerClient;
 use crate::metrics::RouterMetrics;
 use async_trait::async_trait;
@@ -17,6 +18,12 @@ static WORKER_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
         .expect("Failed to create worker HTTP client")
 });
 
+/// Model ID of workers whose model is neither labeled nor discovered
+pub const UNKNOWN_MODEL_ID: &str = "unknown";
+
+/// Timeout of each request asking a worker for its served models
+const MODEL_DISCOVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Core worker abstraction that represents a backend service
 #[async_trait]
 pub trait Worker: Send + Sync + fmt::Debug {
@@ -66,6 +73,31 @@ pub trait Worker: Send + Sync + fmt::Debug {
         // Workers that track load should override this
     }
 
+    /// Get the estimated tokens outstanding on the worker: the prompt tokens of its
+    /// requests plus their remaining decode budget
+    fn token_load(&self) -> usize {
+        0
+    }
+
+    /// Add estimated tokens to the worker's token load
+    fn add_token_load(&self, _tokens: usize) {
+        // Default implementation - does nothing
+        // Workers that track token load should override this
+    }
+
+    /// Remove estimated tokens from the worker's token load
+    fn remove_token_load(&self, _tokens: usize) {
+        // Default implementation - does nothing
+    }
+
+    /// Get the current load in the given unit
+    fn load_in(&self, unit: LoadUnit) -> usize {
+        match unit {
+            LoadUnit::Requests => self.load(),
+            LoadUnit::Tokens => self.token_load(),
+        }
+    }
+
     /// Get the number of processed requests
     fn processed_requests(&self) -> usize;
 
@@ -78,11 +110,40 @@ pub trait Worker: Send + Sync + fmt::Debug {
     /// Get the circuit breaker for this worker
     fn circuit_breaker(&self) -> &CircuitBreaker;
 
-    /// Check if the worker is available (healthy + circuit closed/half-open)
+    /// Get the gRPC client for gRPC workers
+    fn grpc_client(&self) -> Option<Arc<Mutex<VllmSchedulerClient>>> {
+        None
+    }
+
+    /// Check if the worker is available (healthy + not draining + circuit closed/half-open)
     fn is_available(&self) -> bool {
-        self.is_healthy() && self.circuit_breaker().can_execute()
+        self.is_healthy() && !self.is_draining() && self.circuit_breaker().can_execute()
     }
 
+    /// Check if the worker is draining: it finishes in-flight requests but gets no new ones
+    fn is_draining(&self) -> bool {
+        false
+    }
+
+    /// Start or stop draining the worker
+    fn set_draining(&self, _draining: bool) {
+        // Default implementation - does nothing
+        // Workers that support draining should override this
+    }
+
+    /// Create a copy of this worker with different labels (priority, cost, ...).
+    ///
+    /// The copy shares load, health, draining and circuit breaker state with this worker,
+    /// so it can replace it in the registry while requests are in flight.
+    fn clone_with_labels(
+        &self,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Arc<dyn Worker>;
+
+    /// Create a copy of this worker serving the given models, sharing its state like
+    /// [`Worker::clone_with_labels`]
+    fn clone_with_served_models(&self, models: Vec<String>) -> Arc<dyn Worker>;
+
     /// Record the outcome of a request to this worker
     fn record_outcome(&self, success: bool) {
         // Record outcome-level metric with worker label
@@ -170,13 +231,35 @@ pub trait Worker: Send + Sync + fmt::Debug {
     // This way service discovery just calls router.add_worker() and the worker
     // handles its own metadata discovery internally.
 
-    /// Get the model ID this worker serves
+    /// Get the model ID this worker serves: its `model_id` label, else the first model
+    /// it reported serving
     fn model_id(&self) -> &str {
         self.metadata()
             .labels
             .get("model_id")
+            .or_else(|| self.metadata().served_models.first())
             .map(|s| s.as_str())
-            .unwrap_or("unknown")
+            .unwrap_or(UNKNOWN_MODEL_ID)
+    }
+
+    /// Get all model IDs this worker serves, starting with [`Worker::model_id`]
+    fn model_ids(&self) -> Vec<&str> {
+        let mut ids = vec![self.model_id()];
+        for model in &self.metadata().served_models {
+            if !ids.contains(&model.as_str()) {
+                ids.push(model);
+            }
+        }
+        ids
+    }
+
+    /// Get the inference backend this worker runs (vLLM unless labeled otherwise)
+    fn backend(&self) -> WorkerBackend {
+        self.metadata()
+            .labels
+            .get("backend")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default()
     }
 
     /// Get the priority of this worker (higher value = higher priority)
@@ -319,6 +402,9 @@ pub struct WorkerMetadata {
     pub connection_mode: ConnectionMode,
     /// Additional labels/tags
     pub labels: std::collections::HashMap<String, String>,
+    /// Models the worker reported serving (served model names and LoRA adapters), empty
+    /// until discovered
+    pub served_models: Vec<String>,
     /// Health check configuration
     pub health_config: HealthConfig,
 }
@@ -328,10 +414,12 @@ pub struct WorkerMetadata {
 pub struct BasicWorker {
     metadata: WorkerMetadata,
     load_counter: Arc<AtomicUsize>,
+    token_counter: Arc<AtomicUsize>,
     processed_counter: Arc<AtomicUsize>,
     healthy: Arc<AtomicBool>,
     consecutive_failures: Arc<AtomicUsize>,
     consecutive_successes: Arc<AtomicUsize>,
+    draining: Arc<AtomicBool>,
     circuit_breaker: CircuitBreaker,
     /// Optional gRPC client for gRPC workers
     grpc_client: Option<Arc<Mutex<VllmSchedulerClient>>>,
@@ -342,6 +430,7 @@ impl fmt::Debug for BasicWorker {
         f.debug_struct("BasicWorker")
             .field("metadata", &self.metadata)
             .field("healthy", &self.healthy.load(Ordering::Relaxed))
+            .field("draining", &self.draining.load(Ordering::Relaxed))
             .field("circuit_breaker", &self.circuit_breaker)
             .field("has_grpc_client", &self.grpc_client.is_some())
             .finish()
@@ -363,16 +452,19 @@ impl BasicWorker {
             worker_type,
             connection_mode,
             labels: std::collections::HashMap::new(),
+            served_models: Vec::new(),
             health_config: HealthConfig::default(),
         };
 
         Self {
             metadata,
             load_counter: Arc::new(AtomicUsize::new(0)),
+            token_counter: Arc::new(AtomicUsize::new(0)),
             processed_counter: Arc::new(AtomicUsize::new(0)),
             healthy: Arc::new(AtomicBool::new(true)),
             consecutive_failures: Arc::new(AtomicUsize::new(0)),
             consecutive_successes: Arc::new(AtomicUsize::new(0)),
+            draining: Arc::new(AtomicBool::new(false)),
             circuit_breaker: CircuitBreaker::new(),
             grpc_client: None,
         }
@@ -383,6 +475,11 @@ impl BasicWorker {
         self
     }
 
+    pub fn with_served_models(mut self, models: Vec<String>) -> Self {
+        self.metadata.served_models = models;
+        self
+    }
+
     pub fn with_health_config(mut self, config: HealthConfig) -> Self {
         self.metadata.health_config = config;
         self
@@ -451,7 +548,10 @@ impl Worker for BasicWorker {
             ConnectionMode::Http => {
                 // Perform HTTP health check
                 let url = self.normalised_url()?;
-                let health_url = format!("{}{}", url, self.metadata.health_config.endpoint);
+                let endpoint = BackendProfile::of(self.backend())
+                    .health_endpoint
+                    .unwrap_or(self.metadata.health_config.endpoint.as_str());
+                let health_url = format!("{}{}", url, endpoint);
                 let timeout = Duration::from_secs(self.metadata.health_config.timeout_secs);
 
                 // Use the shared client with a custom timeout for this request
@@ -542,6 +642,22 @@ impl Worker for BasicWorker {
         self.load_counter.store(0, Ordering::Relaxed);
     }
 
+    fn token_load(&self) -> usize {
+        self.token_counter.load(Ordering::Relaxed)
+    }
+
+    fn add_token_load(&self, tokens: usize) {
+        self.token_counter.fetch_add(tokens, Ordering::Relaxed);
+    }
+
+    fn remove_token_load(&self, tokens: usize) {
+        self.token_counter
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(tokens))
+            })
+            .ok();
+    }
+
     fn processed_requests(&self) -> usize {
         self.processed_counter.load(Ordering::Relaxed)
     }
@@ -557,6 +673,29 @@ impl Worker for BasicWorker {
     fn circuit_breaker(&self) -> &CircuitBreaker {
         &self.circuit_breaker
     }
+
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    fn set_draining(&self, draining: bool) {
+        self.draining.store(draining, Ordering::Release);
+    }
+
+    fn clone_with_labels(
+        &self,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Arc<dyn Worker> {
+        Arc::new(self.clone().with_labels(labels))
+    }
+
+    fn clone_with_served_models(&self, models: Vec<String>) -> Arc<dyn Worker> {
+        Arc::new(self.clone().with_served_models(models))
+    }
+
+    fn grpc_client(&self) -> Option<Arc<Mutex<VllmSchedulerClient>>> {
+        self.grpc_client.clone()
+    }
 }
 
 /// A DP-aware worker that handles data-parallel routing
@@ -631,6 +770,18 @@ impl Worker for DPAwareWorker {
         self.base_worker.reset_load();
     }
 
+    fn token_load(&self) -> usize {
+        self.base_worker.token_load()
+    }
+
+    fn add_token_load(&self, tokens: usize) {
+        self.base_worker.add_token_load(tokens);
+    }
+
+    fn remove_token_load(&self, tokens: usize) {
+        self.base_worker.remove_token_load(tokens);
+    }
+
     fn processed_requests(&self) -> usize {
         self.base_worker.processed_requests()
     }
@@ -647,6 +798,39 @@ impl Worker for DPAwareWorker {
         self.base_worker.circuit_breaker()
     }
 
+    fn is_draining(&self) -> bool {
+        self.base_worker.is_draining()
+    }
+
+    fn set_draining(&self, draining: bool) {
+        self.base_worker.set_draining(draining);
+    }
+
+    fn clone_with_labels(
+        &self,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Arc<dyn Worker> {
+        Arc::new(Self {
+            base_worker: self.base_worker.clone().with_labels(labels),
+            dp_rank: self.dp_rank,
+            dp_size: self.dp_size,
+            base_url: self.base_url.clone(),
+        })
+    }
+
+    fn clone_with_served_models(&self, models: Vec<String>) -> Arc<dyn Worker> {
+        Arc::new(Self {
+            base_worker: self.base_worker.clone().with_served_models(models),
+            dp_rank: self.dp_rank,
+            dp_size: self.dp_size,
+            base_url: self.base_url.clone(),
+        })
+    }
+
+    fn grpc_client(&self) -> Option<Arc<Mutex<VllmSchedulerClient>>> {
+        self.base_worker.grpc_client()
+    }
+
     // DP-aware specific implementations
 
     fn is_dp_aware(&self) -> bool {
@@ -1028,6 +1212,29 @@ impl<'a> Drop for WorkerLoadGuard<'a> {
     }
 }
 
+/// RAII guard for worker load that owns its worker, for requests outliving the handler
+/// that selected the worker, such as streams and background tasks
+#[derive(Debug)]
+pub struct OwnedWorkerLoadGuard {
+    worker: Arc<dyn Worker>,
+}
+
+impl OwnedWorkerLoadGuard {
+    /// Count a request in the worker's load until the guard is dropped
+    pub fn new(worker: Arc<dyn Worker>) -> Self {
+        worker.increment_load();
+        RouterMetrics::set_running_requests(worker.url(), worker.load());
+        Self { worker }
+    }
+}
+
+impl Drop for OwnedWorkerLoadGuard {
+    fn drop(&mut self) {
+        self.worker.decrement_load();
+        RouterMetrics::set_running_requests(self.worker.url(), self.worker.load());
+    }
+}
+
 /// Health checker handle with graceful shutdown
 pub struct HealthChecker {
     handle: tokio::task::JoinHandle<()>,
@@ -1055,6 +1262,59 @@ impl HealthChecker {
     }
 }
 
+/// Ask the HTTP worker at `url` which models it serves
+///
+/// Every id listed by `/v1/models` is returned, which covers all of the worker's served
+/// model names and loaded LoRA adapters. Workers without that endpoint are asked for the
+/// `model_path` of `/get_model_info` instead. A DP rank suffix (`url@rank`) is ignored.
+pub async fn discover_served_models(url: &str, api_key: Option<&str>) -> WorkerResult<Vec<String>> {
+    let base_url = match url.rsplit_once('@') {
+        Some((base, rank)) if rank.parse::<usize>().is_ok() => base,
+        _ => url,
+    };
+    let get = |path: &str| {
+        let mut request = WORKER_CLIENT
+            .get(format!("{}{}", base_url, path))
+            .timeout(MODEL_DISCOVERY_TIMEOUT);
+        if let Some(key) = api_key {
+            request = request.bearer_auth(key);
+        }
+        request.send()
+    };
+
+    let models: Vec<String> = match get("/v1/models").await.and_then(|r| r.error_for_status()) {
+        Ok(response) => response
+            .json::<serde_json::Value>()
+            .await
+            .map(|body| {
+                body["data"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|model| model["id"].as_str())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    if !models.is_empty() {
+        return Ok(models);
+    }
+
+    let info: serde_json::Value = get("/get_model_info")
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    match info.get("model_path").and_then(|path| path.as_str()) {
+        Some(path) => Ok(vec![path.to_string()]),
+        None => Err(WorkerError::InvalidConfiguration {
+            message: format!("worker {} reported no served models", base_url),
+        }),
+    }
+}
+
 /// Start an async background health checker for a collection of workers
 pub fn start_health_checker(
     workers: std::sync::Arc<std::sync::RwLock<Vec<std::sync::Arc<dyn Worker>>>>,
@@ -1247,6 +1507,17 @@ mod tests {
         assert_eq!(worker.metadata().labels, labels);
     }
 
+    #[test]
+    fn test_worker_backend_from_label() {
+        let worker = BasicWorker::new("http://test:8080".to_string(), WorkerType::Regular);
+        assert_eq!(worker.backend(), WorkerBackend::Vllm);
+
+        let mut labels = std::collections::HashMap::new();
+        labels.insert("backend".to_string(), "trtllm".to_string());
+        let worker = worker.with_labels(labels);
+        assert_eq!(worker.backend(), WorkerBackend::Trtllm);
+    }
+
     #[test]
     fn test_worker_with_health_config() {
         let custom_config = HealthConfig {
@@ -1340,6 +1611,27 @@ mod tests {
         assert_eq!(worker.load(), 0);
     }
 
+    #[test]
+    fn test_token_load_operations() {
+        let worker = BasicWorker::new("http://test:8080".to_string(), WorkerType::Regular);
+        assert_eq!(worker.token_load(), 0);
+
+        worker.add_token_load(1000);
+        worker.add_token_load(24);
+        worker.increment_load();
+        assert_eq!(worker.load_in(LoadUnit::Tokens), 1024);
+        assert_eq!(worker.load_in(LoadUnit::Requests), 1);
+
+        // Copies share the token load
+        let copy = worker.clone_with_labels(std::collections::HashMap::new());
+        copy.remove_token_load(1000);
+        assert_eq!(worker.token_load(), 24);
+
+        // Removing more than is outstanding stops at 0
+        worker.remove_token_load(100);
+        assert_eq!(worker.token_load(), 0);
+    }
+
     #[test]
     fn test_processed_counter() {
         let worker = BasicWorker::new("http://test:8080".to_string(), WorkerType::Regular);
@@ -0,0 +1,293 @@
+//! Worker load scraping
+//!
+//! vLLM workers do not serve a load endpoint; they report their scheduler state on the
+//! Prometheus `/metrics` endpoint instead: running and waiting requests and the
+//! fraction of KV cache blocks in use. The [`WorkerMetrics`] scraper periodically reads
+//! the load endpoint of every HTTP worker, keeps a [`WorkerLoad`] snapshot per worker
+//! and hands the snapshots to the load-aware policies.
+
+use crate::config::WorkerBackend;
+use crate::core::{BackendProfile, ConnectionMode, Worker, WorkerRegistry};
+use crate::policies::PolicyRegistry;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// Timeout of one worker's metrics scrape
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const RUNNING_METRIC: &str = "vllm:num_requests_running";
+const WAITING_METRIC: &str = "vllm:num_requests_waiting";
+/// vLLM V0 name of the KV cache usage gauge; V1 renamed it
+const GPU_CACHE_USAGE_METRIC: &str = "vllm:gpu_cache_usage_perc";
+const KV_CACHE_USAGE_METRIC: &str = "vllm:kv_cache_usage_perc";
+
+/// Scheduler state a worker reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct WorkerLoad {
+    /// Requests being processed
+    pub running: u64,
+    /// Requests queued for a free batch slot or KV cache blocks
+    pub waiting: u64,
+    /// Fraction (0.0-1.0) of KV cache blocks in use, if the backend reports it
+    pub kv_cache_usage: Option<f64>,
+}
+
+impl WorkerLoad {
+    /// Requests on the worker, running or waiting
+    pub fn requests(&self) -> isize {
+        (self.running + self.waiting) as isize
+    }
+}
+
+/// Parse vLLM's Prometheus text exposition
+///
+/// A worker running several engines (data parallel ranks, models) reports one sample
+/// per engine: request counts are summed and the KV cache usage is that of the fullest
+/// engine. Returns None when the request counts are missing.
+pub fn parse_prometheus(text: &str) -> Option<WorkerLoad> {
+    let mut running = None;
+    let mut waiting = None;
+    let mut kv_cache_usage: Option<f64> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, value)) = parse_sample(line) else {
+            continue;
+        };
+        if !value.is_finite() {
+            continue;
+        }
+        match name {
+            RUNNING_METRIC => *running.get_or_insert(0.0) += value,
+            WAITING_METRIC => *waiting.get_or_insert(0.0) += value,
+            GPU_CACHE_USAGE_METRIC | KV_CACHE_USAGE_METRIC => {
+                kv_cache_usage = Some(kv_cache_usage.map_or(value, |usage| usage.max(value)));
+            }
+            _ => {}
+        }
+    }
+
+    if running.is_none() && waiting.is_none() {
+        return None;
+    }
+    Some(WorkerLoad {
+        running: running.unwrap_or(0.0).max(0.0) as u64,
+        waiting: waiting.unwrap_or(0.0).max(0.0) as u64,
+        kv_cache_usage: kv_cache_usage.map(|usage| usage.clamp(0.0, 1.0)),
+    })
+}
+
+/// Split a sample line `name{labels} value [timestamp]` into its name and value
+fn parse_sample(line: &str) -> Option<(&str, f64)> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .unwrap_or(line.len());
+    let name = &line[..name_end];
+    let rest = &line[name_end..];
+    // Label values may contain spaces, so the value starts after the closing brace
+    let rest = if rest.starts_with('{') {
+        &rest[rest.rfind('}')? + 1..]
+    } else {
+        rest
+    };
+    let value = rest.split_whitespace().next()?.parse().ok()?;
+    Some((name, value))
+}
+
+/// Load snapshots of the registered workers
+#[derive(Debug)]
+pub struct WorkerMetrics {
+    worker_registry: Arc<WorkerRegistry>,
+    policy_registry: Arc<PolicyRegistry>,
+    client: reqwest::Client,
+    api_key: Option<String>,
+    /// Snapshots by worker base URL
+    snapshots: DashMap<String, WorkerLoad>,
+}
+
+impl WorkerMetrics {
+    pub fn new(
+        worker_registry: Arc<WorkerRegistry>,
+        policy_registry: Arc<PolicyRegistry>,
+        client: reqwest::Client,
+        api_key: Option<String>,
+    ) -> Self {
+        Self {
+            worker_registry,
+            policy_registry,
+            client,
+            api_key,
+            snapshots: DashMap::new(),
+        }
+    }
+
+    /// Spawn a task scraping all workers every `interval`
+    pub fn start_scraper(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let metrics = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                metrics.scrape().await;
+            }
+        })
+    }
+
+    /// Scrape every HTTP worker and pass the loads to the policies
+    ///
+    /// A worker that fails to answer loses its snapshot, so policies fall back to the
+    /// router's own request counts for it.
+    pub async fn scrape(&self) {
+        let mut targets = Vec::new();
+        let mut seen = HashSet::new();
+        for worker in self.worker_registry.get_all() {
+            if worker.connection_mode() == ConnectionMode::Http
+                && seen.insert(worker.base_url().to_string())
+            {
+                targets.push(worker);
+            }
+        }
+        self.snapshots.retain(|url, _| seen.contains(url));
+
+        let results =
+            futures::future::join_all(targets.iter().map(|worker| self.fetch(worker.as_ref())))
+                .await;
+        for (worker, result) in targets.iter().zip(results) {
+            match result {
+                Ok(load) => {
+                    self.snapshots.insert(worker.base_url().to_string(), load);
+                }
+                Err(e) => {
+                    debug!(
+                        "Failed to scrape load of worker {}: {}",
+                        worker.base_url(),
+                        e
+                    );
+                    self.snapshots.remove(worker.base_url());
+                }
+            }
+        }
+
+        self.policy_registry.update_worker_loads(&self.loads());
+    }
+
+    /// Last scraped load of every registered worker, by worker URL
+    ///
+    /// DP-aware workers share the snapshot of their server.
+    pub fn loads(&self) -> HashMap<String, WorkerLoad> {
+        self.worker_registry
+            .get_all()
+            .iter()
+            .filter_map(|worker| {
+                let snapshot = self.snapshots.get(worker.base_url())?;
+                Some((worker.url().to_string(), *snapshot))
+            })
+            .collect()
+    }
+
+    /// Scrape one worker now, without updating its snapshot
+    pub async fn fetch_load(&self, worker_url: &str) -> Option<WorkerLoad> {
+        let result = match self.worker_registry.get_by_url(worker_url) {
+            Some(worker) => self.fetch(worker.as_ref()).await,
+            None => {
+                let profile = BackendProfile::of(WorkerBackend::default());
+                self.fetch_with_profile(worker_url, profile).await
+            }
+        };
+        result
+            .map_err(|e| debug!("Failed to get load from {}: {}", worker_url, e))
+            .ok()
+    }
+
+    async fn fetch(&self, worker: &dyn Worker) -> Result<WorkerLoad, String> {
+        self.fetch_with_profile(worker.base_url(), BackendProfile::of(worker.backend()))
+            .await
+    }
+
+    async fn fetch_with_profile(
+        &self,
+        base_url: &str,
+        profile: &BackendProfile,
+    ) -> Result<WorkerLoad, String> {
+        let mut request = self
+            .client
+            .get(format!("{}{}", base_url, profile.load_endpoint))
+            .timeout(SCRAPE_TIMEOUT);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("status {}", response.status()));
+        }
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        profile
+            .parse_load(&body)
+            .ok_or_else(|| format!("no load in {} response", profile.load_endpoint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: &str = r#"# HELP vllm:num_requests_running Number of requests in model execution batches.
+# TYPE vllm:num_requests_running gauge
+vllm:num_requests_running{engine="0",model_name="llama"} 3.0
+vllm:num_requests_running{engine="1",model_name="llama"} 4.0
+# HELP vllm:num_requests_waiting Number of requests waiting to be processed.
+# TYPE vllm:num_requests_waiting gauge
+vllm:num_requests_waiting{engine="0",model_name="llama"} 2.0
+vllm:num_requests_waiting{engine="1",model_name="llama"} 0.0
+# HELP vllm:gpu_cache_usage_perc GPU KV-cache usage. 1 means 100 percent usage.
+# TYPE vllm:gpu_cache_usage_perc gauge
+vllm:gpu_cache_usage_perc{engine="0",model_name="llama"} 0.25
+vllm:gpu_cache_usage_perc{engine="1",model_name="llama"} 0.8125
+vllm:prompt_tokens_total{model_name="llama"} 12345.0 1700000000000
+"#;
+
+    #[test]
+    fn test_parse_prometheus() {
+        let load = parse_prometheus(METRICS).unwrap();
+        assert_eq!(
+            load,
+            WorkerLoad {
+                running: 7,
+                waiting: 2,
+                kv_cache_usage: Some(0.8125),
+            }
+        );
+        assert_eq!(load.requests(), 9);
+    }
+
+    #[test]
+    fn test_parse_prometheus_variants() {
+        // V1 metric name, unlabeled samples, label values with spaces
+        let text = "vllm:num_requests_running 1\n\
+                    vllm:num_requests_waiting{model_name=\"a b}\"} 5 1700000000000\n\
+                    vllm:kv_cache_usage_perc 0.5\n";
+        assert_eq!(
+            parse_prometheus(text),
+            Some(WorkerLoad {
+                running: 1,
+                waiting: 5,
+                kv_cache_usage: Some(0.5),
+            })
+        );
+
+        // Without the KV cache gauge
+        let load = parse_prometheus("vllm:num_requests_running 2").unwrap();
+        assert_eq!(load.kv_cache_usage, None);
+
+        assert_eq!(parse_prometheus(""), None);
+        assert_eq!(parse_prometheus("vllm:gpu_cache_usage_perc 0.5"), None);
+        assert_eq!(parse_prometheus("vllm:num_requests_running NaN"), None);
+    }
+}
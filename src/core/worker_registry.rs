@@ -2,11 +2,18 @@
 //!
 //! Provides centralized registry for workers with model-based indexing
 
-use crate::core::{ConnectionMode, Worker, WorkerType};
-use dashmap::DashMap;
+use crate::core::{discover_served_models, ConnectionMode, Worker, WorkerType, UNKNOWN_MODEL_ID};
+use crate::policies::PolicyRegistry;
+use dashmap::{DashMap, DashSet};
+use std::future::Future;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// How often a draining worker's in-flight requests are checked
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Unique identifier for a worker
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct WorkerId(String);
@@ -57,6 +64,9 @@ pub struct WorkerRegistry {
 
     /// URL to worker ID mapping (for backward compatibility)
     url_to_id: Arc<DashMap<String, WorkerId>>,
+
+    /// URLs of workers that could not be asked which models they serve yet
+    undiscovered: Arc<DashSet<String>>,
 }
 
 impl WorkerRegistry {
@@ -69,6 +79,7 @@ impl WorkerRegistry {
             type_workers: Arc::new(DashMap::new()),
             connection_workers: Arc::new(DashMap::new()),
             url_to_id: Arc::new(DashMap::new()),
+            undiscovered: Arc::new(DashSet::new()),
         }
     }
 
@@ -88,20 +99,10 @@ impl WorkerRegistry {
         self.url_to_id
             .insert(worker.url().to_string(), worker_id.clone());
 
-        // Update model index (both ID-based and optimized)
-        let model_id = worker.model_id().to_string();
-        self.model_workers
-            .entry(model_id.clone())
-            .or_default()
-            .push(worker_id.clone());
-
-        // Update optimized model index for O(1) lookups
-        self.model_index
-            .entry(model_id)
-            .or_insert_with(|| Arc::new(RwLock::new(Vec::new())))
-            .write()
-            .expect("RwLock for model_index is poisoned")
-            .push(worker.clone());
+        // Update model index (both ID-based and optimized) for every served model
+        for model_id in worker.model_ids() {
+            self.index_model(model_id, &worker_id, &worker);
+        }
 
         // Update type index
         self.type_workers
@@ -123,19 +124,11 @@ impl WorkerRegistry {
         if let Some((_, worker)) = self.workers.remove(worker_id) {
             // Remove from URL mapping
             self.url_to_id.remove(worker.url());
+            self.undiscovered.remove(worker.url());
 
             // Remove from model index (both ID-based and optimized)
-            if let Some(mut model_workers) = self.model_workers.get_mut(worker.model_id()) {
-                model_workers.retain(|id| id != worker_id);
-            }
-
-            // Remove from optimized model index
-            if let Some(model_index_entry) = self.model_index.get(worker.model_id()) {
-                let worker_url = worker.url();
-                model_index_entry
-                    .write()
-                    .expect("RwLock for model_index is poisoned")
-                    .retain(|w| w.url() != worker_url);
+            for model_id in worker.model_ids() {
+                self.unindex_model(model_id, worker_id, worker.url());
             }
 
             // Remove from type index
@@ -156,6 +149,142 @@ impl WorkerRegistry {
         }
     }
 
+    /// Replace the worker registered under `url` with a copy carrying new labels.
+    ///
+    /// The worker keeps its ID, runtime state and position in the type and connection
+    /// indexes; only the model index is updated if the `model_id` label changed.
+    pub fn update_labels(
+        &self,
+        url: &str,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Option<Arc<dyn Worker>> {
+        self.replace(url, |worker| worker.clone_with_labels(labels))
+    }
+
+    /// Replace the worker registered under `url` with a copy serving `models`, keeping
+    /// its ID and runtime state like [`WorkerRegistry::update_labels`]
+    pub fn update_served_models(&self, url: &str, models: Vec<String>) -> Option<Arc<dyn Worker>> {
+        self.replace(url, |worker| worker.clone_with_served_models(models))
+    }
+
+    /// Ask the HTTP worker registered under `url` which models it serves and re-index it
+    /// under them
+    ///
+    /// Returns the worker as registered afterwards; it is left unchanged if the worker
+    /// cannot be asked, and asked again by the health checker.
+    pub async fn discover_served_models(
+        &self,
+        url: &str,
+        api_key: Option<&str>,
+    ) -> Option<Arc<dyn Worker>> {
+        let worker = self.get_by_url(url)?;
+        if worker.connection_mode() != ConnectionMode::Http {
+            return Some(worker);
+        }
+        let models = match discover_served_models(url, api_key).await {
+            Ok(models) => models,
+            Err(e) => {
+                warn!("Failed to discover the models served by {}: {}", url, e);
+                self.undiscovered.insert(url.to_string());
+                return Some(worker);
+            }
+        };
+        self.undiscovered.remove(url);
+        if models == worker.metadata().served_models {
+            return Some(worker);
+        }
+        info!("Worker {} serves models {:?}", url, models);
+        self.update_served_models(url, models)
+    }
+
+    /// Discover the models served by a registered worker again, moving its policy
+    /// registration if its model changed
+    async fn rediscover_served_models(
+        &self,
+        worker: &Arc<dyn Worker>,
+        policy_registry: &PolicyRegistry,
+        api_key: Option<&str>,
+    ) {
+        let Some(updated) = self.discover_served_models(worker.url(), api_key).await else {
+            return;
+        };
+        if updated.model_id() != worker.model_id() {
+            policy_registry.on_worker_removed(worker.model_id());
+            let policy_hint = updated.metadata().labels.get("policy").map(String::as_str);
+            policy_registry.on_worker_added(updated.model_id(), policy_hint);
+        }
+    }
+
+    /// Swap the worker registered under `url` for `replacement(worker)` and move it to the
+    /// model indexes of the models the replacement serves
+    ///
+    /// The worker's entry stays locked until the indexes are updated, so concurrent
+    /// replacements of a worker apply one after the other.
+    fn replace<F>(&self, url: &str, replacement: F) -> Option<Arc<dyn Worker>>
+    where
+        F: FnOnce(&dyn Worker) -> Arc<dyn Worker>,
+    {
+        let worker_id = self.url_to_id.get(url)?.clone();
+        let mut entry = self.workers.get_mut(&worker_id)?;
+        let old = entry.clone();
+        let worker = replacement(old.as_ref());
+        *entry = worker.clone();
+
+        let old_models = old.model_ids();
+        let new_models = worker.model_ids();
+        for model_id in &old_models {
+            if !new_models.contains(model_id) {
+                self.unindex_model(model_id, &worker_id, url);
+            }
+        }
+        for model_id in new_models {
+            if !old_models.contains(&model_id) {
+                self.index_model(model_id, &worker_id, &worker);
+            } else if let Some(model_index_entry) = self.model_index.get(model_id) {
+                // Keep the worker's position among the model's workers
+                let mut workers = model_index_entry
+                    .write()
+                    .expect("RwLock for model_index is poisoned");
+                if let Some(slot) = workers.iter_mut().find(|w| w.url() == url) {
+                    *slot = worker.clone();
+                }
+            }
+        }
+        drop(entry);
+
+        Some(worker)
+    }
+
+    /// Add a worker to the indexes of `model_id`
+    fn index_model(&self, model_id: &str, worker_id: &WorkerId, worker: &Arc<dyn Worker>) {
+        self.model_workers
+            .entry(model_id.to_string())
+            .or_default()
+            .push(worker_id.clone());
+
+        // Update optimized model index for O(1) lookups
+        self.model_index
+            .entry(model_id.to_string())
+            .or_insert_with(|| Arc::new(RwLock::new(Vec::new())))
+            .write()
+            .expect("RwLock for model_index is poisoned")
+            .push(worker.clone());
+    }
+
+    /// Remove a worker from the indexes of `model_id`
+    fn unindex_model(&self, model_id: &str, worker_id: &WorkerId, url: &str) {
+        if let Some(mut model_workers) = self.model_workers.get_mut(model_id) {
+            model_workers.retain(|id| id != worker_id);
+        }
+
+        if let Some(model_index_entry) = self.model_index.get(model_id) {
+            model_index_entry
+                .write()
+                .expect("RwLock for model_index is poisoned")
+                .retain(|w| w.url() != url);
+        }
+    }
+
     /// Remove a worker by URL
     pub fn remove_by_url(&self, url: &str) -> Option<Arc<dyn Worker>> {
         if let Some((_, worker_id)) = self.url_to_id.remove(url) {
@@ -165,6 +294,57 @@ impl WorkerRegistry {
         }
     }
 
+    /// Remove the worker at `url` without cutting off its in-flight requests.
+    ///
+    /// The worker (or, for a DP-aware worker, each of its `url@rank` workers) is marked
+    /// draining so it gets no new requests. If it is idle, `remove` runs before this returns;
+    /// otherwise it runs in a background task once the worker is idle or `timeout` has
+    /// passed. Returns whether the removal was deferred.
+    pub async fn remove_after_drain<F>(&self, url: &str, timeout: Duration, remove: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let dp_prefix = format!("{}@", url);
+        let workers: Vec<Arc<dyn Worker>> = self
+            .get_all()
+            .into_iter()
+            .filter(|w| w.url() == url || w.url().starts_with(&dp_prefix))
+            .collect();
+        for worker in &workers {
+            worker.set_draining(true);
+        }
+
+        let in_flight = move || workers.iter().map(|w| w.load()).sum::<usize>();
+        let load = in_flight();
+        if load == 0 {
+            remove.await;
+            return false;
+        }
+
+        info!("Draining worker {} ({} requests in flight)", url, load);
+        let url = url.to_string();
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + timeout;
+            loop {
+                let load = in_flight();
+                if load == 0 {
+                    info!("Worker {} drained", url);
+                    break;
+                }
+                if tokio::time::Instant::now() >= deadline {
+                    warn!(
+                        "Worker {} still has {} requests in flight after {:?}, removing it anyway",
+                        url, load, timeout
+                    );
+                    break;
+                }
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+            remove.await;
+        });
+        true
+    }
+
     /// Get a worker by ID
     pub fn get(&self, worker_id: &WorkerId) -> Option<Arc<dyn Worker>> {
         self.workers.get(worker_id).map(|entry| entry.clone())
@@ -197,6 +377,17 @@ impl WorkerRegistry {
             .unwrap_or_default()
     }
 
+    /// Get the workers serving a model, or, when no worker is known to serve it, the
+    /// workers whose models are unknown
+    pub fn get_serving_model(&self, model_id: &str) -> Vec<Arc<dyn Worker>> {
+        let workers = self.get_by_model_fast(model_id);
+        if workers.is_empty() {
+            self.get_by_model_fast(UNKNOWN_MODEL_ID)
+        } else {
+            workers
+        }
+    }
+
     /// Get all workers by worker type
     pub fn get_by_type(&self, worker_type: &WorkerType) -> Vec<Arc<dyn Worker>> {
         self.type_workers
@@ -352,13 +543,22 @@ impl WorkerRegistry {
 
     /// Start a health checker for all workers in the registry
     /// This should be called once after the registry is populated with workers
-    pub fn start_health_checker(&self, check_interval_secs: u64) -> crate::core::HealthChecker {
+    ///
+    /// Workers that recover are asked again which models they serve, authenticating with
+    /// `api_key`, as they may have been restarted with different models; so are healthy
+    /// workers that could not be asked before. Workers whose model changed are moved to
+    /// the policy of their new model in `policy_registry`.
+    pub fn start_health_checker(
+        self: &Arc<Self>,
+        check_interval_secs: u64,
+        api_key: Option<String>,
+        policy_registry: Arc<PolicyRegistry>,
+    ) -> crate::core::HealthChecker {
         use std::sync::atomic::{AtomicBool, Ordering};
-        use std::sync::Arc;
 
         let shutdown = Arc::new(AtomicBool::new(false));
         let shutdown_clone = shutdown.clone();
-        let workers_ref = self.workers.clone();
+        let registry = Arc::clone(self);
 
         let handle = tokio::spawn(async move {
             let mut interval =
@@ -378,14 +578,19 @@ impl WorkerRegistry {
                 }
 
                 // Get all workers from registry
-                let workers: Vec<Arc<dyn crate::core::Worker>> = workers_ref
-                    .iter()
-                    .map(|entry| entry.value().clone())
-                    .collect();
+                let workers = registry.get_all();
 
                 // Perform health checks
                 for worker in &workers {
+                    let was_healthy = worker.is_healthy();
                     let _ = worker.check_health_async().await; // Use async version directly
+                    if worker.is_healthy()
+                        && (!was_healthy || registry.undiscovered.contains(worker.url()))
+                    {
+                        registry
+                            .rediscover_served_models(worker, &policy_registry, api_key.as_deref())
+                            .await;
+                    }
                 }
 
                 // Reset loads periodically
@@ -523,4 +728,175 @@ mod tests {
         assert_eq!(llama_workers_after.len(), 1);
         assert_eq!(llama_workers_after[0].url(), "http://worker2:8080");
     }
+
+    #[test]
+    fn test_update_labels_keeps_runtime_state() {
+        let registry = WorkerRegistry::new();
+
+        let mut labels = HashMap::new();
+        labels.insert("model_id".to_string(), "llama-3".to_string());
+        labels.insert("priority".to_string(), "50".to_string());
+        let worker: Arc<dyn Worker> = Arc::from(WorkerFactory::create_regular_with_labels(
+            "http://worker1:8080".to_string(),
+            labels.clone(),
+            CircuitBreakerConfig::default(),
+        ));
+        let worker_id = registry.register(worker.clone());
+        worker.increment_load();
+
+        labels.insert("priority".to_string(), "90".to_string());
+        let updated = registry
+            .update_labels("http://worker1:8080", labels.clone())
+            .unwrap();
+        assert_eq!(updated.priority(), 90);
+        assert_eq!(registry.get(&worker_id).unwrap().priority(), 90);
+        assert_eq!(registry.get_by_model_fast("llama-3")[0].priority(), 90);
+
+        // Load and draining state are shared with the instance still serving requests
+        assert_eq!(updated.load(), 1);
+        updated.set_draining(true);
+        assert!(worker.is_draining());
+        assert!(!worker.is_available());
+        worker.decrement_load();
+        assert_eq!(updated.load(), 0);
+
+        // Changing the model moves the worker in the model index
+        labels.insert("model_id".to_string(), "llama-3.1".to_string());
+        registry
+            .update_labels("http://worker1:8080", labels)
+            .unwrap();
+        assert!(registry.get_by_model_fast("llama-3").is_empty());
+        assert!(registry.get_by_model("llama-3").is_empty());
+        assert_eq!(registry.get_by_model_fast("llama-3.1").len(), 1);
+        assert_eq!(registry.get_by_model("llama-3.1").len(), 1);
+
+        assert!(registry
+            .update_labels("http://unknown:8080", HashMap::new())
+            .is_none());
+    }
+
+    #[test]
+    fn test_update_served_models() {
+        let registry = WorkerRegistry::new();
+        let worker: Arc<dyn Worker> = Arc::from(WorkerFactory::create_regular(
+            "http://worker1:8080".to_string(),
+        ));
+        registry.register(worker.clone());
+        assert_eq!(worker.model_id(), UNKNOWN_MODEL_ID);
+
+        // Requests for any model fall back to workers whose models are unknown
+        assert_eq!(registry.get_serving_model("llama-3").len(), 1);
+
+        let updated = registry
+            .update_served_models(
+                "http://worker1:8080",
+                vec!["llama-3".to_string(), "sql-lora".to_string()],
+            )
+            .unwrap();
+        assert_eq!(updated.model_id(), "llama-3");
+        assert_eq!(updated.model_ids(), vec!["llama-3", "sql-lora"]);
+        assert!(registry.get_by_model_fast(UNKNOWN_MODEL_ID).is_empty());
+        assert_eq!(registry.get_by_model_fast("llama-3").len(), 1);
+        assert_eq!(registry.get_by_model("sql-lora").len(), 1);
+        assert_eq!(registry.get_serving_model("sql-lora").len(), 1);
+        assert!(registry.get_serving_model("qwen").is_empty());
+
+        // A model_id label takes precedence but the served models stay indexed
+        let mut labels = HashMap::new();
+        labels.insert("model_id".to_string(), "llama".to_string());
+        registry.update_labels("http://worker1:8080", labels);
+        assert_eq!(registry.get_by_model_fast("llama").len(), 1);
+        assert_eq!(registry.get_by_model_fast("llama-3").len(), 1);
+
+        registry.remove_by_url("http://worker1:8080");
+        for model in ["llama", "llama-3", "sql-lora"] {
+            assert!(registry.get_by_model_fast(model).is_empty());
+            assert!(registry.get_by_model(model).is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_discovery_is_retried() {
+        let registry = WorkerRegistry::new();
+        // Nothing listens on port 1, so the worker cannot be asked
+        let url = "http://127.0.0.1:1";
+        let worker: Arc<dyn Worker> = Arc::from(WorkerFactory::create_regular(url.to_string()));
+        registry.register(worker);
+
+        let unchanged = registry.discover_served_models(url, None).await.unwrap();
+        assert_eq!(unchanged.model_id(), UNKNOWN_MODEL_ID);
+        assert!(registry.undiscovered.contains(url));
+
+        registry.remove_by_url(url);
+        assert!(!registry.undiscovered.contains(url));
+    }
+
+    #[tokio::test]
+    async fn test_remove_after_drain_waits_for_in_flight_requests() {
+        let registry = Arc::new(WorkerRegistry::new());
+        let worker: Arc<dyn Worker> = Arc::from(WorkerFactory::create_regular(
+            "http://worker1:8080".to_string(),
+        ));
+        registry.register(worker.clone());
+
+        let remove = |registry: &Arc<WorkerRegistry>| {
+            let registry = registry.clone();
+            async move {
+                registry.remove_by_url("http://worker1:8080");
+            }
+        };
+
+        // A busy worker stops taking requests but stays registered until it is idle
+        worker.increment_load();
+        let deferred = registry
+            .remove_after_drain(
+                "http://worker1:8080",
+                Duration::from_secs(5),
+                remove(&registry),
+            )
+            .await;
+        assert!(deferred);
+        assert!(worker.is_draining());
+        assert!(!worker.is_available());
+        assert!(registry.get_by_url("http://worker1:8080").is_some());
+
+        worker.decrement_load();
+        tokio::time::sleep(DRAIN_POLL_INTERVAL * 3).await;
+        assert!(registry.get_by_url("http://worker1:8080").is_none());
+
+        // An idle worker is removed right away
+        registry.register(worker.clone());
+        let deferred = registry
+            .remove_after_drain(
+                "http://worker1:8080",
+                Duration::from_secs(5),
+                remove(&registry),
+            )
+            .await;
+        assert!(!deferred);
+        assert!(registry.get_by_url("http://worker1:8080").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remove_after_drain_gives_up_at_deadline() {
+        let registry = Arc::new(WorkerRegistry::new());
+        let worker: Arc<dyn Worker> = Arc::from(WorkerFactory::create_regular(
+            "http://worker1:8080".to_string(),
+        ));
+        registry.register(worker.clone());
+        worker.increment_load();
+
+        let registry_clone = registry.clone();
+        registry
+            .remove_after_drain(
+                "http://worker1:8080",
+                Duration::from_millis(200),
+                async move {
+                    registry_clone.remove_by_url("http://worker1:8080");
+                },
+            )
+            .await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(registry.get_by_url("http://worker1:8080").is_none());
+    }
 }
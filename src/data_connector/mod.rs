@@ -1,8 +1,10 @@
 // Data connector module for response storage
+pub mod response_file_store;
 pub mod response_memory_store;
 pub mod response_noop_store;
 pub mod responses;
 
+pub use response_file_store::FileResponseStorage;
 pub use response_memory_store::MemoryResponseStorage;
 pub use response_noop_store::NoOpResponseStorage;
 pub use responses::{
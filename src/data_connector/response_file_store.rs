@@ -0,0 +1,935 @@
+//! Append-only JSONL file implementation of response storage
+//!
+//! Every stored or deleted response is appended as one JSON line to `responses.jsonl` in the
+//! storage directory, so responses survive router restarts. An in-memory index, rebuilt by
+//! replaying the log on startup, maps response IDs to the position of their line and keeps
+//! the chain links and per-user lists; reads only touch the lines they return. Responses
+//! older than the TTL (if any) are treated as gone, and the log is compacted once deleted and
+//! expired entries make up most of it.
+//!
+//! All file I/O runs on the blocking thread pool. Compaction runs as its own background task
+//! that copies the live lines without holding the store lock, then briefly takes the lock to
+//! carry over whatever was appended meanwhile and swap the logs.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::responses::{
+    ResponseChain, ResponseId, ResponseStorage, ResponseStorageError, Result, StoredResponse,
+};
+
+/// Name of the log file inside the storage directory
+const LOG_FILE: &str = "responses.jsonl";
+
+/// Compaction only runs once at least this many log lines are garbage
+const COMPACT_MIN_DEAD: usize = 1000;
+
+/// Expired responses are purged from the index every this many appends
+const PURGE_INTERVAL: usize = 1000;
+
+/// One line of the log
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum LogRecord {
+    Store { response: Box<StoredResponse> },
+    Delete { id: ResponseId },
+}
+
+/// Where a stored response lives in the log, plus what is needed to query it without reading it
+struct IndexEntry {
+    offset: u64,
+    len: usize,
+    previous_response_id: Option<ResponseId>,
+    user: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl IndexEntry {
+    fn new(response: &StoredResponse, offset: u64, len: usize) -> Self {
+        Self {
+            offset,
+            len,
+            previous_response_id: response.previous_response_id.clone(),
+            user: response.user.clone(),
+            created_at: response.created_at,
+        }
+    }
+}
+
+struct InnerStore {
+    file: File,
+    /// Size of the log, i.e. the offset of the next appended line
+    end: u64,
+    entries: HashMap<ResponseId, IndexEntry>,
+    user_index: HashMap<String, Vec<ResponseId>>,
+    /// Log lines that no longer back a live response
+    dead: usize,
+    appends_since_purge: usize,
+}
+
+/// The live lines of the log at the start of a compaction
+struct CompactionSnapshot {
+    /// (id, offset, len) of each live line, in log order
+    lines: Vec<(ResponseId, u64, usize)>,
+    /// Size of the log when the snapshot was taken
+    end: u64,
+}
+
+/// The compacted copy of a snapshot, not yet swapped in
+struct CompactedLog {
+    tmp: File,
+    /// Old offset and new offset of each copied line
+    moved: HashMap<ResponseId, (u64, u64)>,
+    end: u64,
+}
+
+/// State shared between the storage handle and its blocking tasks
+struct FileStore {
+    path: PathBuf,
+    ttl: Option<chrono::Duration>,
+    store: Mutex<InnerStore>,
+    /// Set while a compaction is running, so at most one runs at a time
+    compacting: AtomicBool,
+}
+
+/// Durable response storage backed by an append-only JSONL log
+pub struct FileResponseStorage {
+    inner: Arc<FileStore>,
+}
+
+fn io_error(context: &str, path: &Path, e: std::io::Error) -> ResponseStorageError {
+    ResponseStorageError::StorageError(format!("{} {}: {}", context, path.display(), e))
+}
+
+impl FileResponseStorage {
+    /// Open (or create) the storage in `dir`, replaying its log.
+    ///
+    /// Responses older than `ttl` are treated as expired and dropped when the log is compacted.
+    pub fn open(dir: impl AsRef<Path>, ttl: Option<Duration>) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| io_error("Failed to create", dir, e))?;
+        let path = dir.join(LOG_FILE);
+
+        let ttl = ttl
+            .map(chrono::Duration::from_std)
+            .transpose()
+            .map_err(|e| ResponseStorageError::StorageError(format!("Invalid TTL: {}", e)))?;
+        let store = FileStore::replay(&path)?;
+        let inner = FileStore {
+            path,
+            ttl,
+            store: Mutex::new(store),
+            compacting: AtomicBool::new(false),
+        };
+
+        {
+            let mut store = inner.store.lock();
+            inner.purge_expired_locked(&mut store);
+            info!(
+                "Loaded {} stored responses from {}",
+                store.entries.len(),
+                inner.path.display()
+            );
+            if store.dead > 0 {
+                let snapshot = FileStore::snapshot(&store);
+                let compacted = inner.write_compacted(&snapshot)?;
+                inner.finish_compaction(&mut store, &snapshot, compacted)?;
+            }
+        }
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Purge expired responses and compact the log now.
+    ///
+    /// Does nothing if a background compaction is already running.
+    pub fn compact(&self) -> Result<()> {
+        if self.inner.compacting.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let result = self.inner.compact();
+        self.inner.compacting.store(false, Ordering::Release);
+        result
+    }
+
+    /// Get statistics about the store
+    pub fn stats(&self) -> FileStoreStats {
+        let store = self.inner.store.lock();
+        FileStoreStats {
+            response_count: store.entries.len(),
+            user_count: store.user_index.len(),
+            dead_lines: store.dead,
+            log_bytes: store.end,
+        }
+    }
+
+    /// Run `f` against the store on the blocking thread pool
+    async fn run_blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Arc<FileStore>) -> Result<T> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || f(&inner))
+            .await
+            .map_err(|e| {
+                ResponseStorageError::StorageError(format!("Storage task failed: {}", e))
+            })?
+    }
+}
+
+impl FileStore {
+    /// Build the index from the log at `path`
+    fn replay(path: &Path) -> Result<InnerStore> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| io_error("Failed to open", path, e))?;
+
+        let mut inner = InnerStore {
+            file: file
+                .try_clone()
+                .map_err(|e| io_error("Failed to open", path, e))?,
+            end: 0,
+            entries: HashMap::new(),
+            user_index: HashMap::new(),
+            dead: 0,
+            appends_since_purge: 0,
+        };
+
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| io_error("Failed to read", path, e))?;
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| io_error("Failed to read", path, e))?;
+            if read == 0 {
+                break;
+            }
+            if line.last() != Some(&b'\n') {
+                // A write cut short by a crash; the next append overwrites it
+                warn!(
+                    "Discarding incomplete last line of {} at offset {}",
+                    path.display(),
+                    inner.end
+                );
+                inner
+                    .file
+                    .set_len(inner.end)
+                    .map_err(|e| io_error("Failed to truncate", path, e))?;
+                break;
+            }
+
+            let offset = inner.end;
+            inner.end += read as u64;
+            match serde_json::from_slice::<LogRecord>(&line) {
+                Ok(LogRecord::Store { response }) => {
+                    if inner.remove(&response.id) {
+                        inner.dead += 1;
+                    }
+                    inner.insert(
+                        response.id.clone(),
+                        IndexEntry::new(&response, offset, read),
+                    );
+                }
+                Ok(LogRecord::Delete { id }) => {
+                    if inner.remove(&id) {
+                        inner.dead += 1;
+                    }
+                    inner.dead += 1;
+                }
+                Err(e) => {
+                    warn!(
+                        "Skipping malformed line of {} at offset {}: {}",
+                        path.display(),
+                        offset,
+                        e
+                    );
+                    inner.dead += 1;
+                }
+            }
+        }
+
+        Ok(inner)
+    }
+
+    fn is_expired(&self, entry: &IndexEntry, now: DateTime<Utc>) -> bool {
+        self.ttl.is_some_and(|ttl| now - entry.created_at > ttl)
+    }
+
+    fn append(&self, store: &mut InnerStore, record: &LogRecord) -> Result<(u64, usize)> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        store
+            .file
+            .write_all(&line)
+            .map_err(|e| io_error("Failed to write", &self.path, e))?;
+
+        let offset = store.end;
+        store.end += line.len() as u64;
+        store.appends_since_purge += 1;
+        Ok((offset, line.len()))
+    }
+
+    fn read_entry(
+        &self,
+        store: &mut InnerStore,
+        id: &ResponseId,
+    ) -> Result<Option<StoredResponse>> {
+        let Some(entry) = store.entries.get(id) else {
+            return Ok(None);
+        };
+        let mut line = vec![0; entry.len];
+        store
+            .file
+            .seek(SeekFrom::Start(entry.offset))
+            .and_then(|_| store.file.read_exact(&mut line))
+            .map_err(|e| io_error("Failed to read", &self.path, e))?;
+
+        match serde_json::from_slice(&line)? {
+            LogRecord::Store { response } => Ok(Some(*response)),
+            LogRecord::Delete { .. } => Err(ResponseStorageError::StorageError(format!(
+                "Index of {} points at a delete record for {}",
+                self.path.display(),
+                id.0
+            ))),
+        }
+    }
+
+    /// Drop expired responses from the index; returns how many were dropped
+    fn purge_expired_locked(&self, store: &mut InnerStore) -> usize {
+        store.appends_since_purge = 0;
+        if self.ttl.is_none() {
+            return 0;
+        }
+
+        let now = Utc::now();
+        let expired: Vec<ResponseId> = store
+            .entries
+            .iter()
+            .filter(|(_, entry)| self.is_expired(entry, now))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &expired {
+            store.remove(id);
+        }
+        store.dead += expired.len();
+        if !expired.is_empty() {
+            debug!("Expired {} stored responses", expired.len());
+        }
+        expired.len()
+    }
+
+    /// Purge expired responses, and start a background compaction if most of the log is
+    /// garbage
+    fn maintain_locked(self: &Arc<Self>, store: &mut InnerStore) {
+        if store.appends_since_purge >= PURGE_INTERVAL {
+            self.purge_expired_locked(store);
+        }
+        if store.dead < COMPACT_MIN_DEAD || store.dead <= store.entries.len() {
+            return;
+        }
+        if self.compacting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = this.compact() {
+                warn!("Failed to compact {}: {}", this.path.display(), e);
+            }
+            this.compacting.store(false, Ordering::Release);
+        });
+    }
+
+    /// Purge expired responses and rewrite the log with only the live responses.
+    ///
+    /// The live lines are copied without holding the store lock; callers make sure only one
+    /// compaction runs at a time.
+    fn compact(&self) -> Result<()> {
+        let snapshot = {
+            let mut store = self.store.lock();
+            self.purge_expired_locked(&mut store);
+            Self::snapshot(&store)
+        };
+        let compacted = self.write_compacted(&snapshot)?;
+        let mut store = self.store.lock();
+        self.finish_compaction(&mut store, &snapshot, compacted)
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        self.path.with_extension("jsonl.tmp")
+    }
+
+    fn snapshot(store: &InnerStore) -> CompactionSnapshot {
+        let mut lines: Vec<_> = store
+            .entries
+            .iter()
+            .map(|(id, entry)| (id.clone(), entry.offset, entry.len))
+            .collect();
+        lines.sort_by_key(|(_, offset, _)| *offset);
+        CompactionSnapshot {
+            lines,
+            end: store.end,
+        }
+    }
+
+    /// Copy the snapshotted lines into a temporary log, reading through a separate handle
+    fn write_compacted(&self, snapshot: &CompactionSnapshot) -> Result<CompactedLog> {
+        let tmp_path = self.tmp_path();
+        let write_error = |e| io_error("Failed to compact", &tmp_path, e);
+
+        let mut log =
+            File::open(&self.path).map_err(|e| io_error("Failed to open", &self.path, e))?;
+        let mut tmp = File::create(&tmp_path).map_err(write_error)?;
+        let mut moved = HashMap::with_capacity(snapshot.lines.len());
+        let mut end = 0u64;
+        for (id, offset, len) in &snapshot.lines {
+            let mut line = vec![0; *len];
+            log.seek(SeekFrom::Start(*offset))
+                .and_then(|_| log.read_exact(&mut line))
+                .map_err(|e| io_error("Failed to read", &self.path, e))?;
+            tmp.write_all(&line).map_err(write_error)?;
+            moved.insert(id.clone(), (*offset, end));
+            end += *len as u64;
+        }
+
+        Ok(CompactedLog { tmp, moved, end })
+    }
+
+    /// Append the lines written since the snapshot to the compacted log and swap it in
+    fn finish_compaction(
+        &self,
+        store: &mut InnerStore,
+        snapshot: &CompactionSnapshot,
+        compacted: CompactedLog,
+    ) -> Result<()> {
+        let tmp_path = self.tmp_path();
+        let write_error = |e| io_error("Failed to compact", &tmp_path, e);
+        let CompactedLog {
+            mut tmp,
+            moved,
+            end: copied_end,
+        } = compacted;
+
+        let mut tail = Vec::new();
+        store
+            .file
+            .seek(SeekFrom::Start(snapshot.end))
+            .and_then(|_| {
+                (&store.file)
+                    .take(store.end - snapshot.end)
+                    .read_to_end(&mut tail)
+            })
+            .map_err(|e| io_error("Failed to read", &self.path, e))?;
+        tmp.write_all(&tail).map_err(write_error)?;
+        tmp.sync_all().map_err(write_error)?;
+        drop(tmp);
+
+        // Responses stored before the snapshot kept their line; later ones moved with the tail
+        let mut new_offsets = Vec::with_capacity(store.entries.len());
+        for (id, entry) in &store.entries {
+            let offset = if entry.offset >= snapshot.end {
+                entry.offset - snapshot.end + copied_end
+            } else {
+                match moved.get(id) {
+                    Some(&(old, new)) if old == entry.offset => new,
+                    _ => {
+                        return Err(ResponseStorageError::StorageError(format!(
+                            "Response {} changed during compaction of {}",
+                            id.0,
+                            self.path.display()
+                        )))
+                    }
+                }
+            };
+            new_offsets.push((id.clone(), offset));
+        }
+
+        fs::rename(&tmp_path, &self.path).map_err(write_error)?;
+        store.file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| io_error("Failed to open", &self.path, e))?;
+        for (id, offset) in new_offsets {
+            if let Some(entry) = store.entries.get_mut(&id) {
+                entry.offset = offset;
+            }
+        }
+
+        let lines = snapshot.lines.len() + tail.iter().filter(|&&b| b == b'\n').count();
+        info!(
+            "Compacted {}: dropped {} dead lines, kept {} responses",
+            self.path.display(),
+            (store.entries.len() + store.dead).saturating_sub(lines),
+            store.entries.len()
+        );
+        store.end = copied_end + tail.len() as u64;
+        store.dead = lines.saturating_sub(store.entries.len());
+        Ok(())
+    }
+}
+
+impl InnerStore {
+    fn insert(&mut self, id: ResponseId, entry: IndexEntry) {
+        if let Some(ref user) = entry.user {
+            self.user_index
+                .entry(user.clone())
+                .or_default()
+                .push(id.clone());
+        }
+        self.entries.insert(id, entry);
+    }
+
+    /// Remove a response from the index; returns whether it was there
+    fn remove(&mut self, id: &ResponseId) -> bool {
+        let Some(entry) = self.entries.remove(id) else {
+            return false;
+        };
+        if let Some(user) = entry.user {
+            if let Some(user_responses) = self.user_index.get_mut(&user) {
+                user_responses.retain(|user_id| user_id != id);
+                if user_responses.is_empty() {
+                    self.user_index.remove(&user);
+                }
+            }
+        }
+        true
+    }
+}
+
+#[async_trait]
+impl ResponseStorage for FileResponseStorage {
+    async fn store_response(&self, mut response: StoredResponse) -> Result<ResponseId> {
+        // Generate ID if not set
+        if response.id.0.is_empty() {
+            response.id = ResponseId::new();
+        }
+
+        self.run_blocking(move |inner| {
+            let response_id = response.id.clone();
+            let mut entry = IndexEntry::new(&response, 0, 0);
+            let mut store = inner.store.lock();
+            (entry.offset, entry.len) = inner.append(
+                &mut store,
+                &LogRecord::Store {
+                    response: Box::new(response),
+                },
+            )?;
+            if store.remove(&response_id) {
+                store.dead += 1;
+            }
+            store.insert(response_id.clone(), entry);
+            inner.maintain_locked(&mut store);
+
+            Ok(response_id)
+        })
+        .await
+    }
+
+    async fn get_response(&self, response_id: &ResponseId) -> Result<Option<StoredResponse>> {
+        let response_id = response_id.clone();
+        self.run_blocking(move |inner| {
+            let mut store = inner.store.lock();
+            let live = store
+                .entries
+                .get(&response_id)
+                .is_some_and(|entry| !inner.is_expired(entry, Utc::now()));
+            if !live {
+                return Ok(None);
+            }
+            inner.read_entry(&mut store, &response_id)
+        })
+        .await
+    }
+
+    async fn delete_response(&self, response_id: &ResponseId) -> Result<()> {
+        let response_id = response_id.clone();
+        self.run_blocking(move |inner| {
+            let mut store = inner.store.lock();
+            if !store.entries.contains_key(&response_id) {
+                return Ok(());
+            }
+
+            inner.append(
+                &mut store,
+                &LogRecord::Delete {
+                    id: response_id.clone(),
+                },
+            )?;
+            store.remove(&response_id);
+            store.dead += 2;
+            inner.maintain_locked(&mut store);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_response_chain(
+        &self,
+        response_id: &ResponseId,
+        max_depth: Option<usize>,
+    ) -> Result<ResponseChain> {
+        let max_depth = max_depth.unwrap_or(100); // Default max depth to prevent infinite loops
+        let response_id = response_id.clone();
+        self.run_blocking(move |inner| {
+            let now = Utc::now();
+            let mut store = inner.store.lock();
+
+            // Walk the links in the index, then read only the responses in the chain
+            let mut response_ids = Vec::new();
+            let mut current_id = Some(response_id);
+            while let Some(id) = current_id {
+                if response_ids.len() >= max_depth {
+                    break;
+                }
+                match store.entries.get(&id) {
+                    Some(entry) if !inner.is_expired(entry, now) => {
+                        current_id = entry.previous_response_id.clone();
+                        response_ids.push(id);
+                    }
+                    _ => break,
+                }
+            }
+
+            // Reverse to get chronological order (oldest first)
+            response_ids.reverse();
+
+            let mut chain = ResponseChain::new();
+            for id in &response_ids {
+                if let Some(response) = inner.read_entry(&mut store, id)? {
+                    chain.add_response(response);
+                }
+            }
+            Ok(chain)
+        })
+        .await
+    }
+
+    async fn list_user_responses(
+        &self,
+        user: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<StoredResponse>> {
+        let user = user.to_string();
+        self.run_blocking(move |inner| {
+            let now = Utc::now();
+            let mut store = inner.store.lock();
+
+            let Some(user_response_ids) = store.user_index.get(&user) else {
+                return Ok(Vec::new());
+            };
+
+            // Sort by creation time (newest first) using the index only
+            let mut responses_with_time: Vec<_> = user_response_ids
+                .iter()
+                .filter_map(|id| {
+                    store
+                        .entries
+                        .get(id)
+                        .filter(|entry| !inner.is_expired(entry, now))
+                        .map(|entry| (entry.created_at, id.clone()))
+                })
+                .collect();
+            responses_with_time.sort_by_key(|(created_at, _)| Reverse(*created_at));
+            responses_with_time.truncate(limit.unwrap_or(responses_with_time.len()));
+
+            let mut user_responses = Vec::with_capacity(responses_with_time.len());
+            for (_, id) in &responses_with_time {
+                if let Some(response) = inner.read_entry(&mut store, id)? {
+                    user_responses.push(response);
+                }
+            }
+            Ok(user_responses)
+        })
+        .await
+    }
+
+    async fn delete_user_responses(&self, user: &str) -> Result<usize> {
+        let user = user.to_string();
+        self.run_blocking(move |inner| {
+            let mut store = inner.store.lock();
+            let Some(user_response_ids) = store.user_index.get(&user).cloned() else {
+                return Ok(0);
+            };
+
+            let now = Utc::now();
+            let mut count = 0;
+            for id in user_response_ids {
+                let live = store
+                    .entries
+                    .get(&id)
+                    .is_some_and(|entry| !inner.is_expired(entry, now));
+                inner.append(&mut store, &LogRecord::Delete { id: id.clone() })?;
+                store.remove(&id);
+                store.dead += 2;
+                if live {
+                    count += 1;
+                }
+            }
+            inner.maintain_locked(&mut store);
+
+            Ok(count)
+        })
+        .await
+    }
+}
+
+/// Statistics for the file store
+#[derive(Debug, Clone)]
+pub struct FileStoreStats {
+    pub response_count: usize,
+    pub user_count: usize,
+    /// Log lines that no longer back a live response, dropped on the next compaction
+    pub dead_lines: usize,
+    pub log_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_response(input: &str, user: &str) -> StoredResponse {
+        let mut response = StoredResponse::new(input.to_string(), format!("Re: {}", input), None);
+        response.user = Some(user.to_string());
+        response
+    }
+
+    #[tokio::test]
+    async fn test_file_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (id1, id3) = {
+            let store = FileResponseStorage::open(dir.path(), None).unwrap();
+            let id1 = store
+                .store_response(StoredResponse::new(
+                    "First".to_string(),
+                    "First response".to_string(),
+                    None,
+                ))
+                .await
+                .unwrap();
+            let id2 = store
+                .store_response(StoredResponse::new(
+                    "Second".to_string(),
+                    "Second response".to_string(),
+                    Some(id1.clone()),
+                ))
+                .await
+                .unwrap();
+            let id3 = store
+                .store_response(StoredResponse::new(
+                    "Third".to_string(),
+                    "Third response".to_string(),
+                    Some(id2),
+                ))
+                .await
+                .unwrap();
+            (id1, id3)
+        };
+
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+        let chain = store.get_response_chain(&id3, None).await.unwrap();
+        let inputs: Vec<_> = chain.responses.iter().map(|r| r.input.as_str()).collect();
+        assert_eq!(inputs, vec!["First", "Second", "Third"]);
+
+        let limited_chain = store.get_response_chain(&id3, Some(2)).await.unwrap();
+        assert_eq!(limited_chain.responses.len(), 2);
+        assert_eq!(limited_chain.responses[0].input, "Second");
+
+        // Deletes are persisted as well
+        store.delete_response(&id1).await.unwrap();
+        drop(store);
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+        assert!(store.get_response(&id1).await.unwrap().is_none());
+        assert_eq!(
+            store
+                .get_response_chain(&id3, None)
+                .await
+                .unwrap()
+                .responses
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_store_user_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+
+        store
+            .store_response(user_response("one", "user1"))
+            .await
+            .unwrap();
+        store
+            .store_response(user_response("two", "user1"))
+            .await
+            .unwrap();
+        store
+            .store_response(user_response("three", "user2"))
+            .await
+            .unwrap();
+
+        let user1_responses = store.list_user_responses("user1", None).await.unwrap();
+        assert_eq!(user1_responses.len(), 2);
+        assert_eq!(
+            store
+                .list_user_responses("user1", Some(1))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert_eq!(store.delete_user_responses("user1").await.unwrap(), 2);
+        drop(store);
+
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+        assert!(store
+            .list_user_responses("user1", None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store
+                .list_user_responses("user2", None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        // Reopening compacted the deleted responses away
+        assert_eq!(store.stats().dead_lines, 0);
+        assert_eq!(store.stats().response_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_file_store_ttl_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileResponseStorage::open(dir.path(), Some(Duration::from_secs(3600))).unwrap();
+
+        let mut old = user_response("old", "user1");
+        old.created_at = Utc::now() - chrono::Duration::hours(2);
+        let old_id = store.store_response(old).await.unwrap();
+        let mut reply = user_response("new", "user1");
+        reply.previous_response_id = Some(old_id.clone());
+        let new_id = store.store_response(reply).await.unwrap();
+
+        assert!(store.get_response(&old_id).await.unwrap().is_none());
+        assert_eq!(
+            store
+                .get_response_chain(&new_id, None)
+                .await
+                .unwrap()
+                .responses
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .list_user_responses("user1", None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        store.compact().unwrap();
+        assert_eq!(store.stats().response_count, 1);
+        assert_eq!(store.stats().dead_lines, 0);
+    }
+
+    #[tokio::test]
+    async fn test_file_store_compacts_in_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+
+        for i in 0..COMPACT_MIN_DEAD {
+            store
+                .store_response(user_response(&format!("gone {}", i), "user1"))
+                .await
+                .unwrap();
+        }
+        let kept = store
+            .store_response(user_response("kept", "user2"))
+            .await
+            .unwrap();
+        let bytes_before = store.stats().log_bytes;
+
+        // The deletes leave most of the log dead, which hands compaction to a background task
+        store.delete_user_responses("user1").await.unwrap();
+        for _ in 0..500 {
+            if !store.inner.compacting.load(Ordering::Acquire) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(store.stats().dead_lines, 0);
+        assert!(store.stats().log_bytes < bytes_before);
+        assert_eq!(
+            store.get_response(&kept).await.unwrap().unwrap().input,
+            "kept"
+        );
+        drop(store);
+
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+        assert_eq!(store.stats().response_count, 1);
+        assert!(store.get_response(&kept).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_ignores_incomplete_last_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = {
+            let store = FileResponseStorage::open(dir.path(), None).unwrap();
+            store
+                .store_response(user_response("kept", "user1"))
+                .await
+                .unwrap()
+        };
+
+        // Simulate a crash in the middle of an append
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(LOG_FILE))
+            .unwrap();
+        file.write_all(b"{\"op\":\"store\",\"resp").unwrap();
+        drop(file);
+
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+        assert_eq!(
+            store.get_response(&id).await.unwrap().unwrap().input,
+            "kept"
+        );
+        let next = store
+            .store_response(user_response("next", "user1"))
+            .await
+            .unwrap();
+        drop(store);
+
+        let store = FileResponseStorage::open(dir.path(), None).unwrap();
+        assert!(store.get_response(&next).await.unwrap().is_some());
+        assert_eq!(store.stats().response_count, 2);
+    }
+}
@@ -63,8 +63,9 @@ impl ResponseStorage for MemoryResponseStorage {
         // Single lock acquisition for atomic update
         let mut store = self.store.write();
 
-        // Update user index if user is specified
-        if let Some(ref user) = response.user {
+        // Update user index if user is specified (re-storing an id updates it in place)
+        let is_new = !store.responses.contains_key(&response_id);
+        if let Some(user) = response.user.as_ref().filter(|_| is_new) {
             store
                 .user_index
                 .entry(user.clone())
@@ -35,6 +35,10 @@ pub struct StoredResponse {
     /// The user input for this response
     pub input: String,
 
+    /// The structured input items as submitted, for listing them back
+    #[serde(default)]
+    pub input_items: Vec<serde_json::Value>,
+
     /// System instructions used
     pub instructions: Option<String>,
 
@@ -55,6 +59,10 @@ pub struct StoredResponse {
 
     /// Model used for generation
     pub model: Option<String>,
+
+    /// The full response object as returned to the client
+    #[serde(default)]
+    pub raw_response: Option<serde_json::Value>,
 }
 
 impl StoredResponse {
@@ -63,6 +71,7 @@ impl StoredResponse {
             id: ResponseId::new(),
             previous_response_id,
             input,
+            input_items: Vec::new(),
             instructions: None,
             output,
             tool_calls: Vec::new(),
@@ -70,6 +79,7 @@ impl StoredResponse {
             created_at: chrono::Utc::now(),
             user: None,
             model: None,
+            raw_response: None,
         }
     }
 }
@@ -11,6 +11,7 @@ pub mod proto {
 // package vllm.grpc.scheduler; generates a nested module structure
 
 /// gRPC client for VLLM scheduler
+#[derive(Clone)]
 pub struct VllmSchedulerClient {
     client: proto::vllm_scheduler_client::VllmSchedulerClient<Channel>,
 }
@@ -1,4 +1,5 @@
 use pyo3::prelude::*;
+pub mod auth;
 pub mod config;
 pub mod logging;
 use std::collections::HashMap;
@@ -16,6 +17,7 @@ pub mod reasoning_parser;
 pub mod routers;
 pub mod server;
 pub mod service_discovery;
+pub mod token_tree;
 pub mod tokenizer;
 pub mod tool_parser;
 pub mod tree;
@@ -129,6 +131,8 @@ impl Router {
                     balance_rel_threshold: self.balance_rel_threshold,
                     eviction_interval_secs: self.eviction_interval_secs,
                     max_tree_size: self.max_tree_size,
+                    tree_type: config::CacheTreeType::Char,
+                    block_size: 16,
                 },
                 PolicyType::PowerOfTwo => ConfigPolicyConfig::PowerOfTwo {
                     load_check_interval_secs: 5, // Default value
@@ -195,6 +199,7 @@ impl Router {
             worker_startup_check_interval_secs: self.worker_startup_check_interval,
             dp_aware: self.dp_aware,
             api_key: self.api_key.clone(),
+            auth: config::AuthConfig::default(),
             discovery,
             metrics,
             log_dir: self.log_dir.clone(),
@@ -203,7 +208,10 @@ impl Router {
             max_concurrent_requests: self.max_concurrent_requests,
             queue_size: self.queue_size,
             queue_timeout_secs: self.queue_timeout_secs,
+            admission: config::AdmissionConfig::default(),
+            drain: config::DrainConfig::default(),
             rate_limit_tokens_per_second: self.rate_limit_tokens_per_second,
+            tenant_rate_limits: config::TenantRateLimitConfig::default(),
             cors_allowed_origins: self.cors_allowed_origins.clone(),
             retry: config::RetryConfig {
                 max_retries: self.retry_max_retries,
@@ -231,6 +239,14 @@ impl Router {
             model_path: self.model_path.clone(),
             tokenizer_path: self.tokenizer_path.clone(),
             history_backend: config::HistoryBackend::Memory,
+            history_storage: config::HistoryStorageConfig::default(),
+            mcp: config::McpToolsConfig::default(),
+            mcp_server: config::McpEndpointConfig::default(),
+            backend: config::WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: config::LoadUnit::Requests,
         })
     }
 }
@@ -1,8 +1,9 @@
 use clap::{ArgAction, Parser, ValueEnum};
 use vllm_router_rs::config::{
-    CircuitBreakerConfig, ConfigError, ConfigResult, ConnectionMode, DiscoveryConfig,
-    HealthCheckConfig, HistoryBackend, MetricsConfig, PolicyConfig, RetryConfig, RouterConfig,
-    RoutingMode,
+    AuthConfig, CacheTreeType, CircuitBreakerConfig, ConfigError, ConfigResult, ConnectionMode, DiscoveryConfig,
+    DrainConfig, HashKeySource, HealthCheckConfig, HistoryBackend, HistoryStorageConfig, LoadUnit, McpEndpointConfig, McpToolsConfig, MetricsConfig, PolicyConfig, RetryConfig,
+    RouterConfig, AdmissionConfig, RoutingMode, TenantRateLimitConfig, TenantSource,
+    WorkerBackend,
 };
 use vllm_router_rs::metrics::PrometheusConfig;
 use vllm_router_rs::server::{self, ServerConfig};
@@ -141,7 +142,7 @@ struct CliArgs {
     worker_urls: Vec<String>,
 
     /// Load balancing policy to use
-    #[arg(long, default_value = "cache_aware", value_parser = ["random", "round_robin", "cache_aware", "power_of_two", "consistent_hash"])]
+    #[arg(long, default_value = "cache_aware", value_parser = ["random", "round_robin", "cache_aware", "power_of_two", "consistent_hash", "kv_pressure", "latency"])]
     policy: String,
 
     /// Enable PD (Prefill-Decode) disaggregated mode
@@ -162,11 +163,11 @@ struct CliArgs {
     decode: Vec<String>,
 
     /// Specific policy for prefill nodes in PD mode
-    #[arg(long, value_parser = ["random", "round_robin", "cache_aware", "power_of_two", "consistent_hash"])]
+    #[arg(long, value_parser = ["random", "round_robin", "cache_aware", "power_of_two", "consistent_hash", "kv_pressure", "latency"])]
     prefill_policy: Option<String>,
 
     /// Specific policy for decode nodes in PD mode
-    #[arg(long, value_parser = ["random", "round_robin", "cache_aware", "power_of_two", "consistent_hash"])]
+    #[arg(long, value_parser = ["random", "round_robin", "cache_aware", "power_of_two", "consistent_hash", "kv_pressure", "latency"])]
     decode_policy: Option<String>,
 
     /// Timeout in seconds for worker startup
@@ -181,9 +182,10 @@ struct CliArgs {
     #[arg(long, default_value_t = 0.3)]
     cache_threshold: f32,
 
-    /// Absolute threshold for load balancing
-    #[arg(long, default_value_t = 64)]
-    balance_abs_threshold: usize,
+    /// Absolute threshold for load balancing, counted in the load unit
+    /// [default: 64 requests, or 4096 tokens with --load-unit tokens]
+    #[arg(long)]
+    balance_abs_threshold: Option<usize>,
 
     /// Relative threshold for load balancing
     #[arg(long, default_value_t = 1.5)]
@@ -197,6 +199,60 @@ struct CliArgs {
     #[arg(long, default_value_t = 67108864)] // 2^26
     max_tree_size: usize,
 
+    /// Match cache-aware routing on characters of the request text or on token ids of the
+    /// rendered prompt (token requires --tokenizer-path or --model-path)
+    #[arg(long, default_value = "char", value_parser = ["char", "token"])]
+    cache_tree_type: String,
+
+    /// Tokens per KV cache block; the token cache tree only matches whole blocks
+    #[arg(long, default_value_t = 16)]
+    cache_block_size: usize,
+
+    /// Ordered sources of the consistent_hash affinity key, first present wins:
+    /// header:<name>, json:<pointer> (e.g. json:/metadata/session) or prompt_cache_key.
+    /// Without any, session and user ids are taken from the request text
+    #[arg(long, num_args = 0..)]
+    hash_key_sources: Vec<HashKeySource>,
+
+    /// Spill consistent_hash requests to the next worker on the ring once a worker's
+    /// load would exceed this factor (>= 1.0) times the average load
+    #[arg(long)]
+    hash_capacity_factor: Option<f32>,
+
+    /// Cost of a waiting request relative to a running one for kv_pressure routing
+    #[arg(long, default_value_t = 2.0)]
+    kv_queue_weight: f32,
+
+    /// Cost of a full KV cache, in running requests, for kv_pressure routing
+    #[arg(long, default_value_t = 16.0)]
+    kv_cache_weight: f32,
+
+    /// KV cache usage (0.0-1.0) above which kv_pressure routing avoids a worker
+    #[arg(long, default_value_t = 0.95)]
+    max_kv_cache_usage: f32,
+
+    /// Weight of the newest sample in latency routing's moving averages (0.0-1.0]
+    #[arg(long, default_value_t = 0.2)]
+    latency_ewma_alpha: f32,
+
+    /// Seconds after which latency routing weighs a worker's stats half as much
+    #[arg(long, default_value_t = 60)]
+    latency_decay_half_life: u64,
+
+    /// Output tokens latency routing assumes when predicting completion times
+    #[arg(long, default_value_t = 256)]
+    latency_expected_output_tokens: u32,
+
+    /// Unit of the worker load that policies balance: requests in flight, or estimated
+    /// outstanding tokens (prompt tokens plus remaining max_tokens). With tokens,
+    /// --balance-abs-threshold counts tokens and must be at least 1024
+    #[arg(long, default_value = "requests", value_parser = ["requests", "tokens"])]
+    load_unit: String,
+
+    /// Interval in seconds between scrapes of the workers' load metrics
+    #[arg(long, default_value_t = 5)]
+    load_check_interval: u64,
+
     /// Maximum payload size in bytes
     #[arg(long, default_value_t = 536870912)] // 512MB
     max_payload_size: usize,
@@ -209,10 +265,38 @@ struct CliArgs {
     #[arg(long)]
     api_key: Option<String>,
 
+    /// API keys clients may use on inference routes (enables authentication)
+    #[arg(long, num_args = 0..)]
+    inference_api_keys: Vec<String>,
+
+    /// API keys clients may use on all routes, including worker management
+    #[arg(long, num_args = 0..)]
+    admin_api_keys: Vec<String>,
+
+    /// File with client API keys, one "<key> [inference|admin]" per line; reloaded on change
+    #[arg(long)]
+    api_key_file: Option<String>,
+
+    /// Interval in seconds between checks of the API key file for changes
+    #[arg(long, default_value_t = 10)]
+    api_key_file_reload_interval_secs: u64,
+
     /// Backend to route requests to (vllm, trtllm, openai, anthropic)
     #[arg(long, value_enum, default_value_t = Backend::Vllm, alias = "runtime")]
     backend: Backend,
 
+    /// JSON or YAML file with a list of OpenAI mode providers (url, api_key, model_map, weight)
+    #[arg(long)]
+    openai_upstreams_file: Option<String>,
+
+    /// JSON or YAML file with a list of model aliases (name, model_id, worker_selector, parameters)
+    #[arg(long)]
+    model_aliases_file: Option<String>,
+
+    /// Interval in seconds between queries of each worker's /v1/models for the model list
+    #[arg(long, default_value_t = 30)]
+    model_refresh_interval_secs: u64,
+
     /// Directory to store log files
     #[arg(long)]
     log_dir: Option<String>,
@@ -265,6 +349,46 @@ struct CliArgs {
     #[arg(long, default_value_t = 256)]
     max_concurrent_requests: usize,
 
+    /// How requests are attributed to tenants for rate limiting
+    #[arg(long, default_value = "api_key", value_parser = ["api_key", "user"])]
+    tenant_source: String,
+
+    /// Requests per minute allowed for each tenant without explicit limits
+    #[arg(long)]
+    tenant_requests_per_minute: Option<u64>,
+
+    /// Estimated tokens per minute allowed for each tenant without explicit limits
+    #[arg(long)]
+    tenant_tokens_per_minute: Option<u64>,
+
+    /// JSON or YAML file with a list of per-tenant limits (name, ids, requests_per_minute, tokens_per_minute)
+    #[arg(long)]
+    tenant_rate_limits_file: Option<String>,
+
+    /// Header carrying the priority class of a request for the admission queue
+    #[arg(long, default_value = "x-request-priority")]
+    priority_header: String,
+
+    /// Priority class of requests without a (known) priority header
+    #[arg(long, default_value = "normal")]
+    default_priority_class: String,
+
+    /// Seconds after which a queued request is promoted by one priority level (0 = no aging)
+    #[arg(long, default_value_t = 10)]
+    queue_aging_interval_secs: u64,
+
+    /// JSON or YAML file with a list of priority classes (name, priority, queue_size, queue_timeout_secs, api_keys)
+    #[arg(long)]
+    priority_classes_file: Option<String>,
+
+    /// Seconds a removed worker keeps serving its in-flight requests before it is dropped
+    #[arg(long, default_value_t = 30)]
+    worker_drain_timeout_secs: u64,
+
+    /// Seconds the router waits for in-flight requests to finish after SIGTERM
+    #[arg(long, default_value_t = 30)]
+    shutdown_grace_period_secs: u64,
+
     /// CORS allowed origins
     #[arg(long, num_args = 0..)]
     cors_allowed_origins: Vec<String>,
@@ -350,9 +474,33 @@ struct CliArgs {
     #[arg(long)]
     tokenizer_path: Option<String>,
 
-    /// History backend configuration (memory or none)
-    #[arg(long, default_value = "memory", value_parser = ["memory", "none"])]
+    /// History backend configuration (memory, file or none)
+    #[arg(long, default_value = "memory", value_parser = ["memory", "file", "none"])]
     history_backend: String,
+
+    /// Directory for the file history backend
+    #[arg(long)]
+    history_path: Option<String>,
+
+    /// Expire stored responses after this many seconds (unset keeps them forever)
+    #[arg(long)]
+    history_ttl_secs: Option<u64>,
+
+    /// YAML file listing MCP servers that /v1/responses requests can use as tools
+    #[arg(long)]
+    mcp_config_path: Option<String>,
+
+    /// Maximum tool-calling rounds per request in the MCP tool loop
+    #[arg(long, default_value_t = 10)]
+    mcp_max_iterations: usize,
+
+    /// Serve the router's own capabilities as MCP tools at /mcp
+    #[arg(long, default_value_t = false)]
+    enable_mcp_server: bool,
+
+    /// Key unlocking the admin tools of the /mcp endpoint (sent as X-Mcp-Admin-Key)
+    #[arg(long)]
+    mcp_server_admin_key: Option<String>,
 }
 
 impl CliArgs {
@@ -388,21 +536,56 @@ impl CliArgs {
             "round_robin" => PolicyConfig::RoundRobin,
             "cache_aware" => PolicyConfig::CacheAware {
                 cache_threshold: self.cache_threshold,
-                balance_abs_threshold: self.balance_abs_threshold,
+                balance_abs_threshold: self.balance_abs_threshold.unwrap_or(
+                    match self.load_unit.parse().unwrap_or(LoadUnit::Requests) {
+                        LoadUnit::Requests => 64,
+                        LoadUnit::Tokens => 4096,
+                    },
+                ),
                 balance_rel_threshold: self.balance_rel_threshold,
                 eviction_interval_secs: self.eviction_interval,
                 max_tree_size: self.max_tree_size,
+                tree_type: self.cache_tree_type.parse().unwrap_or(CacheTreeType::Char),
+                block_size: self.cache_block_size,
             },
             "power_of_two" => PolicyConfig::PowerOfTwo {
-                load_check_interval_secs: 5, // Default value
+                load_check_interval_secs: self.load_check_interval,
             },
             "consistent_hash" => PolicyConfig::ConsistentHash {
                 virtual_nodes: 160, // Default value
+                hash_key_sources: self.hash_key_sources.clone(),
+                capacity_factor: self.hash_capacity_factor,
+            },
+            "kv_pressure" => PolicyConfig::KvPressure {
+                queue_weight: self.kv_queue_weight,
+                kv_cache_weight: self.kv_cache_weight,
+                max_kv_cache_usage: self.max_kv_cache_usage,
+                load_check_interval_secs: self.load_check_interval,
+            },
+            "latency" => PolicyConfig::Latency {
+                ewma_alpha: self.latency_ewma_alpha,
+                decay_half_life_secs: self.latency_decay_half_life,
+                expected_output_tokens: self.latency_expected_output_tokens,
             },
             _ => PolicyConfig::RoundRobin, // Fallback
         }
     }
 
+    /// Load a list of config entries (tenants, classes, upstreams, aliases) from JSON or YAML
+    fn load_config_file<T: serde::de::DeserializeOwned>(
+        field: &str,
+        path: &str,
+    ) -> ConfigResult<Vec<T>> {
+        let invalid = |reason: String| ConfigError::InvalidValue {
+            field: field.to_string(),
+            value: path.to_string(),
+            reason,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        // YAML is a superset of JSON, so one parser handles both formats
+        serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))
+    }
+
     /// Convert CLI arguments to RouterConfig
     fn to_router_config(
         &self,
@@ -556,6 +739,29 @@ impl CliArgs {
             _ => Self::determine_connection_mode(&all_urls),
         };
 
+        let tenant_rate_limits = TenantRateLimitConfig {
+            tenant_source: match self.tenant_source.as_str() {
+                "user" => TenantSource::User,
+                _ => TenantSource::ApiKey,
+            },
+            default_requests_per_minute: self.tenant_requests_per_minute,
+            default_tokens_per_minute: self.tenant_tokens_per_minute,
+            tenants: match &self.tenant_rate_limits_file {
+                Some(path) => Self::load_config_file("tenant_rate_limits_file", path)?,
+                None => vec![],
+            },
+        };
+
+        let admission = AdmissionConfig {
+            priority_header: self.priority_header.clone(),
+            default_class: self.default_priority_class.clone(),
+            aging_interval_secs: self.queue_aging_interval_secs,
+            classes: match &self.priority_classes_file {
+                Some(path) => Self::load_config_file("priority_classes_file", path)?,
+                None => AdmissionConfig::default().classes,
+            },
+        };
+
         // Build RouterConfig
         Ok(RouterConfig {
             mode,
@@ -569,6 +775,12 @@ impl CliArgs {
             worker_startup_check_interval_secs: self.worker_startup_check_interval,
             dp_aware: self.dp_aware,
             api_key: self.api_key.clone(),
+            auth: AuthConfig {
+                inference_keys: self.inference_api_keys.clone(),
+                admin_keys: self.admin_api_keys.clone(),
+                key_file: self.api_key_file.clone(),
+                key_file_reload_interval_secs: self.api_key_file_reload_interval_secs,
+            },
             discovery,
             metrics,
             log_dir: self.log_dir.clone(),
@@ -581,6 +793,11 @@ impl CliArgs {
             max_concurrent_requests: self.max_concurrent_requests,
             queue_size: 100,        // Default queue size
             queue_timeout_secs: 60, // Default timeout
+            admission,
+            drain: DrainConfig {
+                worker_timeout_secs: self.worker_drain_timeout_secs,
+                shutdown_grace_period_secs: self.shutdown_grace_period_secs,
+            },
             cors_allowed_origins: self.cors_allowed_origins.clone(),
             retry: RetryConfig {
                 max_retries: self.retry_max_retries,
@@ -606,12 +823,40 @@ impl CliArgs {
             },
             enable_igw: self.enable_igw,
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits,
             model_path: self.model_path.clone(),
             tokenizer_path: self.tokenizer_path.clone(),
             history_backend: match self.history_backend.as_str() {
                 "none" => HistoryBackend::None,
+                "file" => HistoryBackend::File,
                 _ => HistoryBackend::Memory,
             },
+            history_storage: HistoryStorageConfig {
+                path: self.history_path.clone(),
+                ttl_secs: self.history_ttl_secs,
+            },
+            mcp: McpToolsConfig {
+                config_path: self.mcp_config_path.clone(),
+                max_iterations: self.mcp_max_iterations,
+            },
+            mcp_server: McpEndpointConfig {
+                enabled: self.enable_mcp_server,
+                admin_key: self.mcp_server_admin_key.clone(),
+            },
+            backend: match self.backend {
+                Backend::Trtllm => WorkerBackend::Trtllm,
+                Backend::Vllm | Backend::Openai | Backend::Anthropic => WorkerBackend::Vllm,
+            },
+            openai_upstreams: match &self.openai_upstreams_file {
+                Some(path) => Self::load_config_file("openai_upstreams_file", path)?,
+                None => vec![],
+            },
+            model_aliases: match &self.model_aliases_file {
+                Some(path) => Self::load_config_file("model_aliases_file", path)?,
+                None => vec![],
+            },
+            model_refresh_interval_secs: self.model_refresh_interval_secs,
+            load_unit: self.load_unit.parse().unwrap_or(LoadUnit::Requests),
         })
     }
 
@@ -718,14 +963,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Warn for runtimes that are parsed but not yet implemented
     match cli_args.backend {
-        Backend::Trtllm | Backend::Anthropic => {
+        Backend::Anthropic => {
             println!(
                 "WARNING: runtime '{}' not implemented yet; falling back to regular routing. \
 Provide --worker-urls or PD flags as usual.",
                 cli_args.backend
             );
         }
-        Backend::Vllm | Backend::Openai => {}
+        Backend::Vllm | Backend::Trtllm | Backend::Openai => {}
     }
 
     if !cli_args.enable_igw {
@@ -54,18 +54,30 @@ pub struct McpClientManager {
     clients: HashMap<String, RunningService<RoleClient, ()>>,
     /// Map of tool_name -> (server_name, tool_definition)
     tools: DashMap<String, (String, McpTool)>,
+    /// Map of server_name -> tools of that server, including those shadowed in `tools`
+    server_tools: DashMap<String, Vec<McpTool>>,
     /// Map of prompt_name -> (server_name, prompt_definition)
     prompts: DashMap<String, (String, Prompt)>,
     /// Map of resource_uri -> (server_name, resource_definition)
     resources: DashMap<String, (String, Resource)>,
 }
 
+impl std::fmt::Debug for McpClientManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClientManager")
+            .field("servers", &self.list_servers())
+            .field("tools", &self.tools.len())
+            .finish()
+    }
+}
+
 impl McpClientManager {
     /// Create a new manager and connect to all configured servers
     pub async fn new(config: McpConfig) -> McpResult<Self> {
         let mut mgr = Self {
             clients: HashMap::new(),
             tools: DashMap::new(),
+            server_tools: DashMap::new(),
             prompts: DashMap::new(),
             resources: DashMap::new(),
         };
@@ -105,6 +117,8 @@ impl McpClientManager {
         match client.peer().list_all_tools().await {
             Ok(ts) => {
                 tracing::info!("Discovered {} tools from '{}'", ts.len(), server_name);
+                self.server_tools
+                    .insert(server_name.to_string(), ts.clone());
                 for t in ts {
                     if self.tools.contains_key(t.name.as_ref()) {
                         tracing::warn!(
@@ -268,7 +282,8 @@ impl McpClientManager {
             McpTransport::Streamable { url, token } => {
                 let transport = if let Some(tok) = token {
                     let mut cfg = StreamableHttpClientTransportConfig::with_uri(url.as_str());
-                    cfg.auth_header = Some(format!("Bearer {}", tok));
+                    // The transport sends this as a bearer token itself
+                    cfg.auth_header = Some(tok.clone());
                     StreamableHttpClientTransport::from_config(cfg)
                 } else {
                     StreamableHttpClientTransport::from_uri(url.as_str())
@@ -326,7 +341,19 @@ impl McpClientManager {
         arguments: Option<serde_json::Map<String, serde_json::Value>>,
     ) -> McpResult<rmcp::model::CallToolResult> {
         let (server_name, _tool) = self.tool_entry(tool_name)?;
-        let client = self.client_for(&server_name)?;
+        self.call_server_tool(&server_name, tool_name, arguments)
+            .await
+    }
+
+    /// Call a tool on a specific server, even if another server's tool of the same name
+    /// shadows it in the tool index
+    pub async fn call_server_tool(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> McpResult<rmcp::model::CallToolResult> {
+        let client = self.client_for(server_name)?;
 
         tracing::debug!("Calling tool '{}' on '{}'", tool_name, server_name);
 
@@ -357,6 +384,24 @@ impl McpClientManager {
             .collect()
     }
 
+    /// Get the tools of one server
+    pub fn list_server_tools(&self, server_name: &str) -> Vec<ToolInfo> {
+        self.server_tools
+            .get(server_name)
+            .map(|tools| {
+                tools
+                    .iter()
+                    .map(|tool| ToolInfo {
+                        name: tool.name.to_string(),
+                        description: tool.description.as_deref().unwrap_or_default().to_string(),
+                        server: server_name.to_string(),
+                        parameters: Some(serde_json::Value::Object((*tool.input_schema).clone())),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get a specific tool by name
     pub fn get_tool(&self, name: &str) -> Option<ToolInfo> {
         self.tools.get(name).map(|entry| {
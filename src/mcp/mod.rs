@@ -6,13 +6,18 @@
 // - Prompts: Reusable templates for LLM interactions
 // - Resources: File/data access with subscription support
 // - OAuth: Secure authentication for remote servers
+//
+// It also contains the router's own MCP server, which exposes routing, model and
+// worker information as tools over streamable HTTP.
 
 pub mod client_manager;
 pub mod config;
 pub mod error;
 pub mod oauth;
+pub mod router_server;
 
 // Re-export the main types for convenience
 pub use client_manager::{McpClientManager, PromptInfo, ResourceInfo, ToolInfo};
 pub use config::{McpConfig, McpServerConfig, McpTransport};
 pub use error::{McpError, McpResult};
+pub use router_server::RouterMcpServer;
@@ -0,0 +1,346 @@
+//! The router's own MCP server
+//!
+//! When `mcp_server.enabled` is set, the router serves a streamable-HTTP MCP endpoint at
+//! `/mcp` that exposes its capabilities as tools, so agent frameworks can use the
+//! cluster directly as a tool provider. The endpoint sits behind the inference API key
+//! check like the generation routes. It is stateless: there are no sessions or
+//! server-initiated streams, so every message is authenticated on its own. `chat` and `list_models` are available to every
+//! client; the admin tools `list_workers` and `get_worker_loads` are only listed and
+//! callable when the request carries `mcp_server.admin_key` in `X-Mcp-Admin-Key`, or
+//! authenticates with an admin API key.
+
+use crate::auth::{self, ApiKeyScope};
+use crate::core::tenant_limiter::estimate_request_tokens;
+use crate::metrics::RouterMetrics;
+use crate::middleware;
+use crate::protocols::spec::ChatCompletionRequest;
+use crate::routers::model_alias;
+use crate::server::{self, AppState};
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{request::Parts, HeaderMap},
+    response::Response,
+};
+use rmcp::{
+    model::{
+        CallToolRequestParam, CallToolResult, Content, Implementation, JsonObject, ListToolsResult,
+        PaginatedRequestParam, ProtocolVersion, ServerCapabilities, ServerInfo, Tool,
+    },
+    service::RequestContext,
+    transport::streamable_http_server::{
+        session::local::LocalSessionManager, StreamableHttpServerConfig, StreamableHttpService,
+    },
+    ErrorData, RoleServer, ServerHandler,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Header carrying the key that unlocks the admin tools
+pub const MCP_ADMIN_KEY_HEADER: &str = "x-mcp-admin-key";
+
+const CHAT_TOOL: &str = "chat";
+const LIST_MODELS_TOOL: &str = "list_models";
+const LIST_WORKERS_TOOL: &str = "list_workers";
+const GET_WORKER_LOADS_TOOL: &str = "get_worker_loads";
+
+/// MCP server handler exposing the router's capabilities as tools
+#[derive(Clone)]
+pub struct RouterMcpServer {
+    state: Arc<AppState>,
+}
+
+impl RouterMcpServer {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Stateless streamable-HTTP service creating a handler per MCP request
+    pub fn service(state: Arc<AppState>) -> StreamableHttpService<Self, LocalSessionManager> {
+        StreamableHttpService::new(
+            move || Ok(Self::new(state.clone())),
+            LocalSessionManager::default().into(),
+            StreamableHttpServerConfig {
+                stateful_mode: false,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Whether the request behind an MCP message may use the admin tools
+    fn is_admin(&self, context: &RequestContext<RoleServer>) -> bool {
+        let Some(parts) = context.extensions.get::<Parts>() else {
+            return false;
+        };
+        let headers = &parts.headers;
+
+        let admin_key = self
+            .state
+            .context
+            .router_config
+            .mcp_server
+            .admin_key
+            .as_deref();
+        if admin_key.is_some()
+            && headers
+                .get(MCP_ADMIN_KEY_HEADER)
+                .and_then(|v| v.to_str().ok())
+                == admin_key
+        {
+            return true;
+        }
+
+        let api_keys = &self.state.context.api_keys;
+        api_keys.is_enabled()
+            && auth::request_key(headers).and_then(|key| api_keys.scope(key))
+                == Some(ApiKeyScope::Admin)
+    }
+
+    async fn chat(
+        &self,
+        headers: Option<&HeaderMap>,
+        arguments: JsonObject,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut arguments = Value::Object(arguments);
+        if let Err(response) = self.charge_tokens(headers, &arguments).await {
+            return tool_result(response).await;
+        }
+        // Tool results are returned whole, so the model response is never streamed
+        arguments["stream"] = Value::Bool(false);
+        let request: ChatCompletionRequest = serde_json::from_value(arguments)
+            .map_err(|e| ErrorData::invalid_params(format!("Invalid chat request: {}", e), None))?;
+
+        let router = &self.state.router;
+        let response =
+            model_alias::route_with_alias(
+                &self.state.context.model_aliases,
+                headers.cloned().unwrap_or_default(),
+                request,
+                |headers, request| async move {
+                    router.route_chat(Some(&headers), &request, None).await
+                },
+            )
+            .await;
+        tool_result(response).await
+    }
+
+    /// Charge the tokens of a chat call to its tenant. The rate limit middleware only
+    /// sees the JSON-RPC envelope, which counts the request but not its tokens.
+    async fn charge_tokens(
+        &self,
+        headers: Option<&HeaderMap>,
+        arguments: &Value,
+    ) -> Result<(), Response> {
+        let limiter = &self.state.context.tenant_rate_limiter;
+        if !limiter.is_enabled() {
+            return Ok(());
+        }
+        let headers = headers.cloned().unwrap_or_default();
+        let tenant_id = middleware::tenant_id(limiter.tenant_source(), &headers, Some(arguments));
+        let estimated_tokens = estimate_request_tokens(arguments);
+        match limiter
+            .check_tokens(tenant_id.as_deref(), estimated_tokens)
+            .await
+        {
+            Ok(usage) => {
+                RouterMetrics::record_tenant_tokens(&usage.tenant, estimated_tokens);
+                Ok(())
+            }
+            Err(exceeded) => Err(middleware::rate_limited_response(
+                exceeded,
+                estimated_tokens,
+            )),
+        }
+    }
+}
+
+impl ServerHandler for RouterMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "vllm-router".to_string(),
+                title: None,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                icons: None,
+                website_url: None,
+            },
+            instructions: Some(
+                "Routes chat requests across the cluster's model workers and reports on \
+                 the models and workers it manages."
+                    .to_string(),
+            ),
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(router_tools(
+            self.is_admin(&context),
+        )))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let name = request.name.as_ref();
+        let is_admin_tool = matches!(name, LIST_WORKERS_TOOL | GET_WORKER_LOADS_TOOL);
+        if is_admin_tool && !self.is_admin(&context) {
+            debug!("Rejected MCP call to admin tool {} without admin key", name);
+            return Err(ErrorData::invalid_request(
+                format!("Tool '{}' requires the MCP admin key", name),
+                None,
+            ));
+        }
+
+        let headers = context
+            .extensions
+            .get::<Parts>()
+            .map(|parts| &parts.headers);
+        match name {
+            CHAT_TOOL => {
+                self.chat(headers, request.arguments.unwrap_or_default())
+                    .await
+            }
+            LIST_MODELS_TOOL => {
+                tool_result(
+                    server::v1_models(State(self.state.clone()), Request::new(Body::empty())).await,
+                )
+                .await
+            }
+            LIST_WORKERS_TOOL => {
+                tool_result(server::list_workers_rest(State(self.state.clone())).await).await
+            }
+            GET_WORKER_LOADS_TOOL => tool_result(self.state.router.get_worker_loads().await).await,
+            _ => Err(ErrorData::invalid_params(
+                format!("Unknown tool '{}'", name),
+                None,
+            )),
+        }
+    }
+}
+
+/// Tools offered to a client, including the admin tools if it may use them
+fn router_tools(admin: bool) -> Vec<Tool> {
+    let schema = |value: Value| match value {
+        Value::Object(object) => Arc::new(object),
+        _ => Arc::new(JsonObject::new()),
+    };
+    let no_arguments = || schema(json!({"type": "object", "properties": {}}));
+
+    let mut tools = vec![
+        Tool::new(
+            CHAT_TOOL,
+            "Create a chat completion, routed to one of the cluster's workers. Accepts \
+             OpenAI chat completion parameters.",
+            schema(json!({
+                "type": "object",
+                "properties": {
+                    "model": {"type": "string", "description": "Model to use"},
+                    "messages": {
+                        "type": "array",
+                        "description": "Conversation as OpenAI chat messages",
+                        "items": {"type": "object"}
+                    },
+                    "max_tokens": {"type": "integer"},
+                    "temperature": {"type": "number"}
+                },
+                "required": ["model", "messages"]
+            })),
+        ),
+        Tool::new(
+            LIST_MODELS_TOOL,
+            "List the models served by the cluster",
+            no_arguments(),
+        ),
+    ];
+    if admin {
+        tools.push(Tool::new(
+            LIST_WORKERS_TOOL,
+            "List the router's workers with their health, load and metadata",
+            no_arguments(),
+        ));
+        tools.push(Tool::new(
+            GET_WORKER_LOADS_TOOL,
+            "Get the current load of every worker",
+            no_arguments(),
+        ));
+    }
+    tools
+}
+
+/// Turn a router response into a tool result, marking non-2xx responses as errors
+async fn tool_result(response: Response) -> Result<CallToolResult, ErrorData> {
+    let is_success = response.status().is_success();
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .map_err(|e| ErrorData::internal_error(format!("Failed to read response: {}", e), None))?;
+
+    Ok(match serde_json::from_slice::<Value>(&bytes) {
+        Ok(body @ Value::Object(_)) if is_success => CallToolResult::structured(body),
+        Ok(body @ Value::Object(_)) => CallToolResult::structured_error(body),
+        _ => {
+            let content = vec![Content::text(String::from_utf8_lossy(&bytes).into_owned())];
+            if is_success {
+                CallToolResult::success(content)
+            } else {
+                CallToolResult::error(content)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    #[test]
+    fn test_admin_tools_are_only_offered_to_admins() {
+        let names = |admin| {
+            router_tools(admin)
+                .into_iter()
+                .map(|tool| tool.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(false), vec![CHAT_TOOL, LIST_MODELS_TOOL]);
+        assert_eq!(
+            names(true),
+            vec![
+                CHAT_TOOL,
+                LIST_MODELS_TOOL,
+                LIST_WORKERS_TOOL,
+                GET_WORKER_LOADS_TOOL
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_result_from_router_response() {
+        let ok = tool_result(axum::Json(json!({"data": []})).into_response())
+            .await
+            .unwrap();
+        assert_ne!(ok.is_error, Some(true));
+        assert_eq!(ok.structured_content, Some(json!({"data": []})));
+
+        let failed = tool_result((StatusCode::SERVICE_UNAVAILABLE, "No workers").into_response())
+            .await
+            .unwrap();
+        assert_eq!(failed.is_error, Some(true));
+        assert_eq!(
+            failed.content[0]
+                .raw
+                .as_text()
+                .map(|text| text.text.as_str()),
+            Some("No workers")
+        );
+    }
+}
@@ -44,6 +44,22 @@ pub fn init_metrics() {
         "sgl_router_retries_exhausted_total",
         "Total number of requests that exhausted retries by route"
     );
+    describe_counter!(
+        "sgl_router_requests_aborted_total",
+        "Total number of requests aborted on workers after the client disconnected, by route"
+    );
+    describe_counter!(
+        "sgl_router_tenant_requests_total",
+        "Total number of requests admitted by the tenant rate limiter, by tenant"
+    );
+    describe_counter!(
+        "sgl_router_tenant_tokens_total",
+        "Total number of estimated tokens charged to tenants, by tenant"
+    );
+    describe_counter!(
+        "sgl_router_tenant_rate_limited_total",
+        "Total number of requests rejected by the tenant rate limiter, by tenant and limit"
+    );
 
     // Circuit breaker metrics
     describe_gauge!(
@@ -155,12 +171,35 @@ pub fn init_metrics() {
     );
     describe_gauge!("sgl_router_embeddings_queue_size", "Embedding queue size");
 
+    // Admission queue metrics
+    describe_gauge!(
+        "sgl_router_queue_depth",
+        "Number of requests waiting in the admission queue by priority class"
+    );
+    describe_histogram!(
+        "sgl_router_queue_wait_duration_seconds",
+        "Time requests waited in the admission queue before being admitted, by priority class"
+    );
+    describe_counter!(
+        "sgl_router_queue_timeouts_total",
+        "Total number of requests that timed out in the admission queue by priority class"
+    );
+    describe_counter!(
+        "sgl_router_queue_rejected_total",
+        "Total number of requests rejected because their admission queue was full, by priority class"
+    );
+
     // Running requests gauge for cache-aware policy
     describe_gauge!(
         "sgl_router_running_requests",
         "Number of running requests per worker"
     );
 
+    describe_gauge!(
+        "sgl_router_worker_token_load",
+        "Estimated tokens outstanding per worker (prompt tokens plus remaining decode budget)"
+    );
+
     // Tokenizer metrics
     describe_histogram!(
         "sgl_tokenizer_encode_duration_seconds",
@@ -324,6 +363,37 @@ impl RouterMetrics {
         .increment(1);
     }
 
+    pub fn record_request_aborted(route: &str) {
+        counter!("sgl_router_requests_aborted_total",
+            "route" => route.to_string()
+        )
+        .increment(1);
+    }
+
+    // Tenant rate limit metrics
+    pub fn record_tenant_request(tenant: &str, estimated_tokens: u64) {
+        counter!("sgl_router_tenant_requests_total",
+            "tenant" => tenant.to_string()
+        )
+        .increment(1);
+        Self::record_tenant_tokens(tenant, estimated_tokens);
+    }
+
+    pub fn record_tenant_tokens(tenant: &str, estimated_tokens: u64) {
+        counter!("sgl_router_tenant_tokens_total",
+            "tenant" => tenant.to_string()
+        )
+        .increment(estimated_tokens);
+    }
+
+    pub fn record_tenant_rate_limited(tenant: &str, limit: &str) {
+        counter!("sgl_router_tenant_rate_limited_total",
+            "tenant" => tenant.to_string(),
+            "limit" => limit.to_string()
+        )
+        .increment(1);
+    }
+
     // Worker metrics
     pub fn set_active_workers(count: usize) {
         gauge!("sgl_router_active_workers").set(count as f64);
@@ -473,6 +543,35 @@ impl RouterMetrics {
         gauge!("sgl_router_embeddings_queue_size").set(size as f64);
     }
 
+    // Admission queue metrics
+    pub fn set_queue_depth(class: &str, depth: usize) {
+        gauge!("sgl_router_queue_depth",
+            "class" => class.to_string()
+        )
+        .set(depth as f64);
+    }
+
+    pub fn record_queue_wait(class: &str, duration: Duration) {
+        histogram!("sgl_router_queue_wait_duration_seconds",
+            "class" => class.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    pub fn record_queue_timeout(class: &str) {
+        counter!("sgl_router_queue_timeouts_total",
+            "class" => class.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn record_queue_rejected(class: &str) {
+        counter!("sgl_router_queue_rejected_total",
+            "class" => class.to_string()
+        )
+        .increment(1);
+    }
+
     // Running requests for cache-aware policy
     pub fn set_running_requests(worker: &str, count: usize) {
         gauge!("sgl_router_running_requests",
@@ -481,6 +580,13 @@ impl RouterMetrics {
         .set(count as f64);
     }
 
+    pub fn set_worker_token_load(worker: &str, tokens: usize) {
+        gauge!("sgl_router_worker_token_load",
+            "worker" => worker.to_string()
+        )
+        .set(tokens as f64);
+    }
+
     // Circuit breaker metrics
     pub fn set_cb_state(worker: &str, state_code: u8) {
         gauge!("sgl_router_cb_state",
@@ -883,6 +989,7 @@ mod tests {
         RouterMetrics::record_discovery_update(3, 1);
         RouterMetrics::record_generate_duration(Duration::from_secs(2));
         RouterMetrics::set_running_requests("http://worker1", 15);
+        RouterMetrics::set_worker_token_load("http://worker1", 4096);
     }
 
     #[test]
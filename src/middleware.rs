@@ -1,20 +1,32 @@
 use axum::{
-    extract::Request, extract::State, http::HeaderValue, http::StatusCode, middleware::Next,
-    response::IntoResponse, response::Response,
+    body::{Body, HttpBody},
+    extract::Request,
+    extract::State,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::IntoResponse,
+    response::Response,
+    Json,
 };
+use futures_util::StreamExt;
 use rand::Rng;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::time::Instant;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{oneshot, Notify};
 use tower::{Layer, Service};
 use tower_http::trace::{MakeSpan, OnRequest, OnResponse, TraceLayer};
 use tracing::{debug, error, field::Empty, info, info_span, warn, Span};
 
 pub use crate::core::token_bucket::TokenBucket;
 
+use crate::config::{AdmissionConfig, TenantSource};
+use crate::core::tenant_limiter::estimate_request_tokens;
+use crate::core::{RateLimitExceeded, RateLimitKind, TenantUsage};
 use crate::metrics::RouterMetrics;
+use crate::protocols::spec::ErrorResponse;
 use crate::server::AppState;
 
 /// Generate OpenAI-compatible request ID based on endpoint
@@ -292,14 +304,14 @@ pub fn log_request(entry: RequestLogEntry) {
     }
 }
 
-// ============ Concurrency Limiting with Queue Support ============
+// ============ Concurrency Limiting with Priority Queue Support ============
 
 /// Request queue entry
-pub struct QueuedRequest {
+struct QueuedRequest {
     /// Time when the request was queued
     queued_at: Instant,
-    /// Channel to send the permit back when acquired
-    permit_tx: oneshot::Sender<Result<(), StatusCode>>,
+    /// Channel to send the permit through once a token was acquired for the request
+    permit_tx: oneshot::Sender<()>,
 }
 
 /// Queue metrics for monitoring
@@ -311,97 +323,249 @@ pub struct QueueMetrics {
     pub total_rejected: std::sync::atomic::AtomicU64,
 }
 
-/// Queue processor that handles queued requests
-pub struct QueueProcessor {
+/// Waiting requests of one priority class
+struct ClassQueue {
+    name: String,
+    priority: u32,
+    max_size: usize,
+    timeout: Duration,
+    waiting: VecDeque<QueuedRequest>,
+}
+
+impl ClassQueue {
+    /// Drop requests whose client stopped waiting (timed out or disconnected)
+    fn prune(&mut self) {
+        self.waiting.retain(|queued| !queued.permit_tx.is_closed());
+        RouterMetrics::set_queue_depth(&self.name, self.waiting.len());
+    }
+}
+
+/// Admission queue for requests that could not get a concurrency token right away.
+///
+/// Requests wait in the queue of their priority class. Whenever a token frees up it goes
+/// to the oldest request of the highest priority class. With aging enabled, a request
+/// gains one priority level per `aging_interval` it waits, so lower classes are not
+/// starved under sustained load.
+pub struct AdmissionQueue {
     token_bucket: Arc<TokenBucket>,
-    queue_rx: mpsc::Receiver<QueuedRequest>,
-    queue_timeout: Duration,
+    classes: Mutex<Vec<ClassQueue>>,
+    /// Lowercase class name -> index in `classes`
+    class_index: HashMap<String, usize>,
+    /// API key -> index in `classes`
+    api_key_classes: HashMap<String, usize>,
+    default_class: usize,
+    priority_header: String,
+    aging_interval: Option<Duration>,
+    /// Wakes the dispatcher when a request is queued
+    queued: Notify,
 }
 
-impl QueueProcessor {
+impl AdmissionQueue {
+    /// Create the admission queue, or `None` if no priority class may queue requests.
+    ///
+    /// Classes without an explicit depth or timeout use `queue_size` and `queue_timeout`.
+    /// Without configured classes all requests share a single FIFO queue.
     pub fn new(
         token_bucket: Arc<TokenBucket>,
-        queue_rx: mpsc::Receiver<QueuedRequest>,
+        config: &AdmissionConfig,
+        queue_size: usize,
         queue_timeout: Duration,
-    ) -> Self {
-        Self {
+    ) -> Option<Self> {
+        let class_queue = |name: &str, priority, max_size, timeout| ClassQueue {
+            name: name.to_string(),
+            priority,
+            max_size,
+            timeout,
+            waiting: VecDeque::new(),
+        };
+        let classes: Vec<ClassQueue> = if config.classes.is_empty() {
+            vec![class_queue("default", 0, queue_size, queue_timeout)]
+        } else {
+            config
+                .classes
+                .iter()
+                .map(|class| {
+                    class_queue(
+                        &class.name,
+                        class.priority,
+                        class.queue_size.unwrap_or(queue_size),
+                        class
+                            .queue_timeout_secs
+                            .map(Duration::from_secs)
+                            .unwrap_or(queue_timeout),
+                    )
+                })
+                .collect()
+        };
+        if classes.iter().all(|class| class.max_size == 0) {
+            return None;
+        }
+
+        let class_index: HashMap<String, usize> = classes
+            .iter()
+            .enumerate()
+            .map(|(index, class)| (class.name.to_lowercase(), index))
+            .collect();
+        let api_key_classes = config
+            .classes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, class)| class.api_keys.iter().map(move |key| (key.clone(), index)))
+            .collect();
+        let default_class = class_index
+            .get(&config.default_class.to_lowercase())
+            .copied()
+            .unwrap_or_default();
+
+        Some(Self {
             token_bucket,
-            queue_rx,
-            queue_timeout,
+            classes: Mutex::new(classes),
+            class_index,
+            api_key_classes,
+            default_class,
+            priority_header: config.priority_header.clone(),
+            aging_interval: (config.aging_interval_secs > 0)
+                .then(|| Duration::from_secs(config.aging_interval_secs)),
+            queued: Notify::new(),
+        })
+    }
+
+    /// Priority class of a request: the class of its API key, else the class named in the
+    /// priority header, else the default class
+    pub fn classify(&self, headers: &HeaderMap) -> usize {
+        if let Some(&class) =
+            crate::auth::request_key(headers).and_then(|key| self.api_key_classes.get(key))
+        {
+            return class;
         }
+
+        headers
+            .get(self.priority_header.as_str())
+            .and_then(|value| value.to_str().ok())
+            .and_then(|name| self.class_index.get(&name.trim().to_lowercase()))
+            .copied()
+            .unwrap_or(self.default_class)
     }
 
-    pub async fn run(mut self) {
-        info!("Starting concurrency queue processor");
+    /// Whether no request is waiting
+    pub fn is_empty(&self) -> bool {
+        self.classes.lock().unwrap().iter().all(|class| {
+            class
+                .waiting
+                .iter()
+                .all(|queued| queued.permit_tx.is_closed())
+        })
+    }
 
-        // Process requests in a single task to reduce overhead
-        while let Some(queued) = self.queue_rx.recv().await {
-            // Check timeout immediately
-            let elapsed = queued.queued_at.elapsed();
-            if elapsed >= self.queue_timeout {
-                warn!("Request already timed out in queue");
-                let _ = queued.permit_tx.send(Err(StatusCode::REQUEST_TIMEOUT));
-                continue;
+    /// Queue a request of the given class and wait until it is admitted.
+    ///
+    /// On success the caller holds one token of the concurrency bucket and must return it
+    /// when done. Fails with 429 if the class queue is full and 408 on timeout.
+    pub async fn admit(&self, class: usize) -> Result<(), StatusCode> {
+        let (permit_tx, mut permit_rx) = oneshot::channel();
+        let queued_at = Instant::now();
+
+        let (name, timeout) = {
+            let mut classes = self.classes.lock().unwrap();
+            let queue = &mut classes[class];
+            queue.prune();
+            if queue.waiting.len() >= queue.max_size {
+                RouterMetrics::record_queue_rejected(&queue.name);
+                return Err(StatusCode::TOO_MANY_REQUESTS);
             }
+            queue.waiting.push_back(QueuedRequest {
+                queued_at,
+                permit_tx,
+            });
+            RouterMetrics::set_queue_depth(&queue.name, queue.waiting.len());
+            (queue.name.clone(), queue.timeout)
+        };
+        self.queued.notify_one();
 
-            let remaining_timeout = self.queue_timeout - elapsed;
-
-            // Try to acquire token for this request
-            if self.token_bucket.try_acquire(1.0).await.is_ok() {
-                // Got token immediately
-                debug!("Queue: acquired token immediately for queued request");
-                let _ = queued.permit_tx.send(Ok(()));
-            } else {
-                // Need to wait for token
-                let token_bucket = self.token_bucket.clone();
-
-                // Spawn task only when we actually need to wait
-                tokio::spawn(async move {
-                    if token_bucket
-                        .acquire_timeout(1.0, remaining_timeout)
-                        .await
-                        .is_ok()
-                    {
-                        debug!("Queue: acquired token after waiting");
-                        let _ = queued.permit_tx.send(Ok(()));
-                    } else {
-                        warn!("Queue: request timed out waiting for token");
-                        let _ = queued.permit_tx.send(Err(StatusCode::REQUEST_TIMEOUT));
-                    }
-                });
+        let admitted = tokio::select! {
+            result = &mut permit_rx => result.is_ok(),
+            _ = tokio::time::sleep(timeout) => {
+                // The dispatcher may have admitted the request just as the timeout fired
+                permit_rx.close();
+                permit_rx.try_recv().is_ok()
             }
-        }
+        };
 
-        warn!("Concurrency queue processor shutting down");
+        if admitted {
+            RouterMetrics::record_queue_wait(&name, queued_at.elapsed());
+            Ok(())
+        } else {
+            RouterMetrics::record_queue_timeout(&name);
+            self.classes.lock().unwrap()[class].prune();
+            Err(StatusCode::REQUEST_TIMEOUT)
+        }
     }
-}
 
-/// State for the concurrency limiter
-pub struct ConcurrencyLimiter {
-    pub queue_tx: Option<mpsc::Sender<QueuedRequest>>,
-}
+    /// Hand out concurrency tokens to queued requests, highest priority first
+    pub async fn run(self: Arc<Self>) {
+        info!("Starting admission queue dispatcher");
 
-impl ConcurrencyLimiter {
-    /// Create new concurrency limiter with optional queue
-    pub fn new(
-        token_bucket: Arc<TokenBucket>,
-        queue_size: usize,
-        queue_timeout: Duration,
-    ) -> (Self, Option<QueueProcessor>) {
-        if queue_size > 0 {
-            let (queue_tx, queue_rx) = mpsc::channel(queue_size);
-            let processor = QueueProcessor::new(token_bucket, queue_rx, queue_timeout);
-
-            (
-                Self {
-                    queue_tx: Some(queue_tx),
-                },
-                Some(processor),
-            )
-        } else {
-            (Self { queue_tx: None }, None)
+        loop {
+            if self.is_empty() {
+                self.queued.notified().await;
+                continue;
+            }
+
+            // Retry until a token frees up; acquire() gives up after the estimated refill time
+            if self.token_bucket.acquire(1.0).await.is_err() {
+                continue;
+            }
+
+            let mut admitted = false;
+            while let Some(queued) = self.pop_next() {
+                if queued.permit_tx.send(()).is_ok() {
+                    admitted = true;
+                    break;
+                }
+            }
+            if !admitted {
+                // Everyone waiting timed out or went away in the meantime
+                self.token_bucket.return_tokens(1.0).await;
+            }
         }
     }
+
+    /// Remove the next request to admit: the oldest of the highest effective priority
+    fn pop_next(&self) -> Option<QueuedRequest> {
+        let now = Instant::now();
+        let mut classes = self.classes.lock().unwrap();
+
+        let (index, _, _) = classes
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, class)| {
+                while class
+                    .waiting
+                    .front()
+                    .is_some_and(|queued| queued.permit_tx.is_closed())
+                {
+                    class.waiting.pop_front();
+                }
+                let queued_at = class.waiting.front()?.queued_at;
+                let priority = self.effective_priority(class.priority, now - queued_at);
+                Some((index, priority, queued_at))
+            })
+            // Highest priority wins; among equals the request queued first
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)))?;
+
+        let class = &mut classes[index];
+        let queued = class.waiting.pop_front();
+        RouterMetrics::set_queue_depth(&class.name, class.waiting.len());
+        queued
+    }
+
+    fn effective_priority(&self, priority: u32, waited: Duration) -> u64 {
+        let aging_levels = self
+            .aging_interval
+            .map(|interval| (waited.as_secs_f64() / interval.as_secs_f64()) as u64)
+            .unwrap_or(0);
+        priority as u64 + aging_levels
+    }
 }
 
 /// Middleware function for concurrency limiting with optional queuing
@@ -416,86 +580,470 @@ pub async fn concurrency_limit_middleware(
     // Identify if this is an embeddings request based on path
     let is_embeddings = request.uri().path().contains("/v1/embeddings");
     let token_bucket = app_state.context.rate_limiter.clone();
+    let queue = app_state.admission_queue.as_ref();
 
-    // Try to acquire token immediately
-    if token_bucket.try_acquire(1.0).await.is_ok() {
+    // Try to acquire token immediately, unless queued requests are waiting for one
+    if queue.is_none_or(|queue| queue.is_empty()) && token_bucket.try_acquire(1.0).await.is_ok() {
         debug!("Acquired token immediately");
         let response = next.run(request).await;
 
         // Return the token to the bucket
         token_bucket.return_tokens(1.0).await;
 
-        response
-    } else {
-        // No tokens available, try to queue if enabled
-        if let Some(queue_tx) = &app_state.concurrency_queue_tx {
-            debug!("No tokens available, attempting to queue request");
+        return response;
+    }
 
-            // Create a channel for the token response
-            let (permit_tx, permit_rx) = oneshot::channel();
+    // No tokens available, try to queue if enabled
+    let Some(queue) = queue else {
+        warn!("No tokens available and queuing is disabled, returning 429");
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    };
 
-            let queued = QueuedRequest {
-                queued_at: Instant::now(),
-                permit_tx,
-            };
-
-            // Try to send to queue
-            match queue_tx.try_send(queued) {
-                Ok(_) => {
-                    // On successful enqueue, update embeddings queue gauge if applicable
-                    if is_embeddings {
-                        let new_val = EMBEDDINGS_QUEUE_SIZE.fetch_add(1, Ordering::Relaxed) + 1;
-                        RouterMetrics::set_embeddings_queue_size(new_val as usize);
-                    }
-
-                    // Wait for token from queue processor
-                    match permit_rx.await {
-                        Ok(Ok(())) => {
-                            debug!("Acquired token from queue");
-                            // Dequeue for embeddings
-                            if is_embeddings {
-                                let new_val =
-                                    EMBEDDINGS_QUEUE_SIZE.fetch_sub(1, Ordering::Relaxed) - 1;
-                                RouterMetrics::set_embeddings_queue_size(new_val as usize);
-                            }
-
-                            let response = next.run(request).await;
-
-                            // Return the token to the bucket
-                            token_bucket.return_tokens(1.0).await;
-
-                            response
-                        }
-                        Ok(Err(status)) => {
-                            warn!("Queue returned error status: {}", status);
-                            // Dequeue for embeddings on error
-                            if is_embeddings {
-                                let new_val =
-                                    EMBEDDINGS_QUEUE_SIZE.fetch_sub(1, Ordering::Relaxed) - 1;
-                                RouterMetrics::set_embeddings_queue_size(new_val as usize);
-                            }
-                            status.into_response()
-                        }
-                        Err(_) => {
-                            error!("Queue response channel closed");
-                            // Dequeue for embeddings on channel error
-                            if is_embeddings {
-                                let new_val =
-                                    EMBEDDINGS_QUEUE_SIZE.fetch_sub(1, Ordering::Relaxed) - 1;
-                                RouterMetrics::set_embeddings_queue_size(new_val as usize);
-                            }
-                            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-                        }
-                    }
-                }
-                Err(_) => {
-                    warn!("Request queue is full, returning 429");
-                    StatusCode::TOO_MANY_REQUESTS.into_response()
+    let class = queue.classify(request.headers());
+    debug!("No tokens available, queueing request");
+
+    if is_embeddings {
+        let new_val = EMBEDDINGS_QUEUE_SIZE.fetch_add(1, Ordering::Relaxed) + 1;
+        RouterMetrics::set_embeddings_queue_size(new_val as usize);
+    }
+    let admitted = queue.admit(class).await;
+    if is_embeddings {
+        let new_val = EMBEDDINGS_QUEUE_SIZE.fetch_sub(1, Ordering::Relaxed) - 1;
+        RouterMetrics::set_embeddings_queue_size(new_val as usize);
+    }
+
+    match admitted {
+        Ok(()) => {
+            debug!("Acquired token from queue");
+            let response = next.run(request).await;
+
+            // Return the token to the bucket
+            token_bucket.return_tokens(1.0).await;
+
+            response
+        }
+        Err(status) => {
+            warn!("Request was not admitted from the queue: {}", status);
+            status.into_response()
+        }
+    }
+}
+
+/// Draining state of the router for graceful shutdown
+///
+/// Counts in-flight inference requests until their response body (e.g. a stream) has been
+/// fully sent. Once draining starts, new inference requests are rejected and shutdown waits
+/// for the in-flight ones.
+#[derive(Debug, Default)]
+pub struct ShutdownState {
+    in_flight: AtomicUsize,
+    draining_since: Mutex<Option<Instant>>,
+    idle: Notify,
+}
+
+impl ShutdownState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop accepting new inference requests
+    pub fn begin_drain(&self) {
+        let mut draining_since = self.draining_since.lock().unwrap();
+        if draining_since.is_none() {
+            *draining_since = Some(Instant::now());
+        }
+    }
+
+    /// Whether the router is draining for shutdown
+    pub fn is_draining(&self) -> bool {
+        self.draining_since.lock().unwrap().is_some()
+    }
+
+    /// Time since draining started, if it has
+    pub fn draining_for(&self) -> Option<Duration> {
+        self.draining_since
+            .lock()
+            .unwrap()
+            .map(|since| since.elapsed())
+    }
+
+    /// Number of inference requests being served
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Wait until no requests are in flight or `timeout` passes; returns whether it went idle
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                // Register before checking so a request finishing in between is not missed
+                let idle = self.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
                 }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    fn track(self: &Arc<Self>) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard(self.clone())
+    }
+}
+
+/// Counts a request as in flight until dropped
+struct InFlightGuard(Arc<ShutdownState>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// Middleware rejecting new inference requests while the router drains for shutdown, and
+/// counting accepted ones as in flight until their response has been sent
+pub async fn shutdown_drain_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let shutdown = &app_state.shutdown;
+    if shutdown.is_draining() {
+        let error = ErrorResponse::new(
+            "Router is shutting down",
+            "service_unavailable",
+            Some("shutting_down"),
+        );
+        let mut response = (StatusCode::SERVICE_UNAVAILABLE, Json(error)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONNECTION, HeaderValue::from_static("close"));
+        return response;
+    }
+
+    let guard = shutdown.track();
+    let response = next.run(request).await;
+
+    // Buffered bodies are sent right away; streams keep the request in flight until they end
+    // or the client goes away
+    if response.body().size_hint().exact().is_some() {
+        return response;
+    }
+    response.map(|body| {
+        Body::from_stream(body.into_data_stream().map(move |chunk| {
+            let _in_flight = &guard;
+            chunk
+        }))
+    })
+}
+
+/// Middleware enforcing per-tenant request and token limits.
+///
+/// The tenant is the caller's API key or the `user` field of the request body, depending
+/// on `tenant_rate_limits.tenant_source`. Responses carry `x-ratelimit-*` headers describing
+/// the tenant's remaining budget.
+pub async fn tenant_rate_limit_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let limiter = &app_state.context.tenant_rate_limiter;
+    if !limiter.is_enabled() {
+        return next.run(request).await;
+    }
+
+    // The body is needed for the tenant id and the token estimate
+    let (parts, body) = request.into_parts();
+    let max_payload_size = app_state.context.router_config.max_payload_size;
+    let bytes = match axum::body::to_bytes(body, max_payload_size).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to read request body: {}", e),
+            )
+                .into_response()
+        }
+    };
+    let json: Option<serde_json::Value> = serde_json::from_slice(&bytes).ok();
+
+    let tenant_id = tenant_id(limiter.tenant_source(), &parts.headers, json.as_ref());
+    let estimated_tokens = json
+        .as_ref()
+        .map(estimate_request_tokens)
+        .unwrap_or_default();
+
+    match limiter.check(tenant_id.as_deref(), estimated_tokens).await {
+        Ok(usage) => {
+            RouterMetrics::record_tenant_request(&usage.tenant, estimated_tokens);
+            let request = Request::from_parts(parts, axum::body::Body::from(bytes));
+            let mut response = next.run(request).await;
+            insert_rate_limit_headers(response.headers_mut(), &usage);
+            response
+        }
+        Err(exceeded) => rate_limited_response(exceeded, estimated_tokens),
+    }
+}
+
+/// Tenant id of a request with the given headers and JSON body
+pub(crate) fn tenant_id(
+    source: TenantSource,
+    headers: &HeaderMap,
+    body: Option<&serde_json::Value>,
+) -> Option<String> {
+    match source {
+        TenantSource::ApiKey => crate::auth::request_key(headers).map(str::to_string),
+        TenantSource::User => body
+            .and_then(|body| body.get("user"))
+            .and_then(|user| user.as_str())
+            .map(str::to_string),
+    }
+}
+
+/// Error response for a request the tenant rate limiter rejected
+pub(crate) fn rate_limited_response(
+    exceeded: RateLimitExceeded,
+    estimated_tokens: u64,
+) -> Response {
+    if exceeded.exceeds_capacity {
+        let limit = exceeded.usage.tokens.map(|t| t.limit).unwrap_or_default();
+        debug!(
+            "Request of tenant {} needs ~{} tokens, above its limit of {} per minute",
+            exceeded.usage.tenant, estimated_tokens, limit
+        );
+        RouterMetrics::record_tenant_rate_limited(&exceeded.usage.tenant, exceeded.kind.as_str());
+
+        let error = ErrorResponse::new(
+            format!(
+                "Request needs about {} tokens, more than the limit of {} tokens per minute",
+                estimated_tokens, limit
+            ),
+            "invalid_request_error",
+            Some("request_too_large"),
+        );
+        let mut response = (StatusCode::PAYLOAD_TOO_LARGE, Json(error)).into_response();
+        insert_rate_limit_headers(response.headers_mut(), &exceeded.usage);
+        return response;
+    }
+
+    let limit = exceeded.kind.as_str();
+    debug!(
+        "Tenant {} exceeded its {} per minute limit",
+        exceeded.usage.tenant, limit
+    );
+    RouterMetrics::record_tenant_rate_limited(&exceeded.usage.tenant, limit);
+
+    let error = ErrorResponse::new(
+        format!("Rate limit exceeded: too many {} per minute", limit),
+        limit,
+        Some("rate_limit_exceeded"),
+    );
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(error)).into_response();
+    let retry_after = exceeded.retry_after().as_secs_f64().ceil().max(1.0) as u64;
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    insert_rate_limit_headers(response.headers_mut(), &exceeded.usage);
+    response
+}
+
+/// Add OpenAI-style `x-ratelimit-*` headers for the tenant's buckets
+fn insert_rate_limit_headers(headers: &mut HeaderMap, usage: &TenantUsage) {
+    let buckets = [
+        (RateLimitKind::Requests, usage.requests),
+        (RateLimitKind::Tokens, usage.tokens),
+    ];
+    for (kind, status) in buckets {
+        let Some(status) = status else { continue };
+        let kind = kind.as_str();
+        let values = [
+            ("limit", status.limit.to_string()),
+            ("remaining", status.remaining.to_string()),
+            ("reset", format_reset(status.reset)),
+        ];
+        for (field, value) in values {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::try_from(format!("x-ratelimit-{}-{}", field, kind)),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.insert(name, value);
             }
-        } else {
-            warn!("No tokens available and queuing is disabled, returning 429");
-            StatusCode::TOO_MANY_REQUESTS.into_response()
         }
     }
 }
+
+/// Format a reset duration like OpenAI does, e.g. `1.5s` or `120ms`
+fn format_reset(reset: Duration) -> String {
+    if reset < Duration::from_secs(1) {
+        format!("{}ms", reset.as_millis())
+    } else {
+        format!("{}s", (reset.as_secs_f64() * 10.0).ceil() / 10.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PriorityClass;
+
+    fn admission_config() -> AdmissionConfig {
+        let mut config = AdmissionConfig::default();
+        config.classes[0].api_keys = vec!["sk-vip".to_string()];
+        config.classes[2].queue_size = Some(1);
+        config.classes[2].queue_timeout_secs = Some(1);
+        config
+    }
+
+    /// A queue whose single token is already taken and does not refill on its own
+    async fn busy_queue(config: &AdmissionConfig) -> (Arc<AdmissionQueue>, Arc<TokenBucket>) {
+        let bucket = Arc::new(TokenBucket::with_rate(1.0, 0.001));
+        bucket.try_acquire(1.0).await.unwrap();
+        let queue = AdmissionQueue::new(bucket.clone(), config, 10, Duration::from_secs(5))
+            .map(Arc::new)
+            .unwrap();
+        tokio::spawn(queue.clone().run());
+        (queue, bucket)
+    }
+
+    #[test]
+    fn test_classify_request() {
+        let bucket = Arc::new(TokenBucket::new(1, 1));
+        let queue =
+            AdmissionQueue::new(bucket, &admission_config(), 10, Duration::from_secs(5)).unwrap();
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(queue.classify(&headers), 1);
+
+        headers.insert("x-request-priority", HeaderValue::from_static("LOW"));
+        assert_eq!(queue.classify(&headers), 2);
+
+        // API key classes take precedence over the header
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer sk-vip"),
+        );
+        assert_eq!(queue.classify(&headers), 0);
+
+        headers.insert("x-request-priority", HeaderValue::from_static("unknown"));
+        headers.remove(header::AUTHORIZATION);
+        assert_eq!(queue.classify(&headers), 1);
+    }
+
+    #[test]
+    fn test_no_queue_without_capacity() {
+        let bucket = Arc::new(TokenBucket::new(1, 1));
+        let config = AdmissionConfig::default();
+        assert!(AdmissionQueue::new(bucket.clone(), &config, 0, Duration::from_secs(5)).is_none());
+
+        let mut config = AdmissionConfig::default();
+        config.classes.push(PriorityClass {
+            name: "batch".to_string(),
+            priority: 0,
+            queue_size: Some(5),
+            queue_timeout_secs: None,
+            api_keys: vec![],
+        });
+        assert!(AdmissionQueue::new(bucket, &config, 0, Duration::from_secs(5)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_is_admitted_first() {
+        let (queue, bucket) = busy_queue(&admission_config()).await;
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut tasks = Vec::new();
+        for class in [2, 1, 0] {
+            let queue = queue.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                queue.admit(class).await.unwrap();
+                order.lock().unwrap().push(class);
+            }));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        // Free one token per admitted request
+        for _ in 0..3 {
+            bucket.return_tokens(1.0).await;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aging_promotes_waiting_requests() {
+        let mut config = admission_config();
+        config.classes[2].queue_timeout_secs = None;
+        config.aging_interval_secs = 1;
+        let (queue, bucket) = busy_queue(&config).await;
+
+        let low = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.admit(2).await.map(|_| Instant::now()) }
+        });
+        // After 2.1s the low request has aged past the normal class
+        tokio::time::sleep(Duration::from_millis(2100)).await;
+        let normal = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.admit(1).await.map(|_| Instant::now()) }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        bucket.return_tokens(1.0).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        bucket.return_tokens(1.0).await;
+
+        let low = low.await.unwrap().unwrap();
+        let normal = normal.await.unwrap().unwrap();
+        assert!(low < normal);
+    }
+
+    #[tokio::test]
+    async fn test_class_queue_limits() {
+        let (queue, _bucket) = busy_queue(&admission_config()).await;
+
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.admit(2).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // The low class holds a single request
+        assert_eq!(queue.admit(2).await, Err(StatusCode::TOO_MANY_REQUESTS));
+
+        // ...which times out after its class timeout
+        assert_eq!(waiting.await.unwrap(), Err(StatusCode::REQUEST_TIMEOUT));
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_state_waits_for_in_flight_requests() {
+        let shutdown = Arc::new(ShutdownState::new());
+        assert!(!shutdown.is_draining());
+
+        let guard = shutdown.track();
+        shutdown.begin_drain();
+        assert!(shutdown.is_draining());
+        assert_eq!(shutdown.in_flight(), 1);
+        assert!(!shutdown.wait_idle(Duration::from_millis(20)).await);
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait_idle(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(guard);
+        assert!(waiter.await.unwrap());
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+}
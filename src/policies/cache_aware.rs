@@ -19,8 +19,10 @@
     1. Cache-Aware Routing (Approximate Tree)
     -------------------------------------------
     This strategy maintains an approximate radix tree for each worker based on request history,
-    eliminating the need for direct cache state queries. The tree stores raw text characters
-    instead of token IDs to avoid tokenization overhead.
+    eliminating the need for direct cache state queries. By default the tree stores raw text
+    characters instead of token IDs to avoid tokenization overhead; the token tree instead
+    matches the token ids of the rendered prompt in whole KV cache blocks, like the workers'
+    prefix caches do.
 
     Process:
     a. For each request, find the worker with the highest prefix match
@@ -57,37 +59,152 @@
     5. max_tree_size: (integer)
     Maximum nodes per tree. When exceeded, LRU leaf nodes are evicted
     during the next eviction cycle.
+
+    6. tree_type: (char | token)
+    Match on characters of the request text, or on token ids. The token tree needs the
+    router's tokenizer and falls back to the char tree without one.
+
+    7. block_size: (integer)
+    Tokens per KV cache block; the token tree only matches whole blocks.
+
+    8. load_unit: (requests | tokens)
+    Unit of the worker loads the balance thresholds compare: outstanding requests, or
+    estimated outstanding tokens, in which case abs_threshold is a number of tokens.
 */
 
-use super::{get_healthy_worker_indices, CacheAwareConfig, LoadBalancingPolicy};
+use super::{get_healthy_worker_indices, CacheAwareConfig, LoadBalancingPolicy, RequestTiming};
+use crate::config::CacheTreeType;
 use crate::core::Worker;
 use crate::metrics::RouterMetrics;
+use crate::token_tree::TokenTree;
+use crate::tokenizer::traits::{TokenIdType, Tokenizer};
 use crate::tree::Tree;
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Approximate prefix cache of one model's workers
+#[derive(Debug)]
+enum PrefixTree {
+    Char(Tree),
+    Token(TokenTree),
+}
+
+/// A request's routing key in the unit of the policy's trees
+enum RoutingKey<'a> {
+    Text(&'a str),
+    Tokens(Vec<TokenIdType>),
+}
+
+impl RoutingKey<'_> {
+    fn len(&self) -> usize {
+        match self {
+            RoutingKey::Text(text) => text.chars().count(),
+            RoutingKey::Tokens(tokens) => tokens.len(),
+        }
+    }
+}
+
+impl PrefixTree {
+    // A policy derives both its trees and its keys from whether it has a tokenizer, so
+    // the kinds always agree
+
+    fn insert(&mut self, key: &RoutingKey, tenant: &str) {
+        match (self, key) {
+            (PrefixTree::Char(tree), RoutingKey::Text(text)) => tree.insert(text, tenant),
+            (PrefixTree::Token(tree), RoutingKey::Tokens(tokens)) => tree.insert(tokens, tenant),
+            _ => unreachable!("routing key does not match the tree type"),
+        }
+    }
+
+    /// Matched length of the key and the worker holding the match
+    fn prefix_match(&mut self, key: &RoutingKey) -> (usize, String) {
+        match (self, key) {
+            (PrefixTree::Char(tree), RoutingKey::Text(text)) => {
+                let (matched_text, tenant) = tree.prefix_match(text);
+                (matched_text.chars().count(), tenant)
+            }
+            (PrefixTree::Token(tree), RoutingKey::Tokens(tokens)) => tree.prefix_match(tokens),
+            _ => unreachable!("routing key does not match the tree type"),
+        }
+    }
+
+    fn add_tenant(&mut self, tenant: &str) {
+        match self {
+            PrefixTree::Char(tree) => tree.insert("", tenant),
+            PrefixTree::Token(tree) => tree.insert(&[], tenant),
+        }
+    }
+
+    fn remove_tenant(&mut self, tenant: &str) {
+        match self {
+            PrefixTree::Char(tree) => tree.remove_tenant(tenant),
+            PrefixTree::Token(tree) => tree.remove_tenant(tenant),
+        }
+    }
+
+    fn evict_tenant_by_size(&mut self, max_size: usize) {
+        match self {
+            PrefixTree::Char(tree) => tree.evict_tenant_by_size(max_size),
+            PrefixTree::Token(tree) => tree.evict_tenant_by_size(max_size),
+        }
+    }
+
+    fn used_size(&self) -> usize {
+        match self {
+            PrefixTree::Char(tree) => tree.get_used_size_per_tenant().values().sum(),
+            PrefixTree::Token(tree) => tree.get_used_size_per_tenant().values().sum(),
+        }
+    }
+}
 
 /// Cache-aware routing policy
 ///
 /// Routes requests based on cache affinity when load is balanced,
 /// switches to shortest-queue routing when load is imbalanced.
 /// Maintains separate trees per model for multi-model support.
-#[derive(Debug)]
 pub struct CacheAwarePolicy {
     config: CacheAwareConfig,
-    trees: Arc<Mutex<HashMap<String, Tree>>>, // model_id -> Tree
+    trees: Arc<Mutex<HashMap<String, PrefixTree>>>, // model_id -> PrefixTree
+    /// Set when the trees match on token ids
+    tokenizer: Option<Arc<dyn Tokenizer>>,
     eviction_handle: Option<thread::JoinHandle<()>>,
 }
 
+impl fmt::Debug for CacheAwarePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheAwarePolicy")
+            .field("config", &self.config)
+            .field("trees", &self.trees)
+            .field("token_level", &self.tokenizer.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
 impl CacheAwarePolicy {
     pub fn new() -> Self {
         Self::with_config(CacheAwareConfig::default())
     }
 
     pub fn with_config(config: CacheAwareConfig) -> Self {
-        let trees = Arc::new(Mutex::new(HashMap::<String, Tree>::new()));
+        Self::with_tokenizer(config, None)
+    }
+
+    /// Create the policy with the tokenizer the token tree matches requests with
+    pub fn with_tokenizer(config: CacheAwareConfig, tokenizer: Option<Arc<dyn Tokenizer>>) -> Self {
+        let tokenizer = match config.tree_type {
+            CacheTreeType::Char => None,
+            CacheTreeType::Token => {
+                if tokenizer.is_none() {
+                    warn!("The token cache tree requires a tokenizer, using the char tree");
+                }
+                tokenizer
+            }
+        };
+        let trees = Arc::new(Mutex::new(HashMap::<String, PrefixTree>::new()));
 
         // Start background eviction thread if configured
         let eviction_handle = if config.eviction_interval_secs > 0 {
@@ -116,10 +233,32 @@ impl CacheAwarePolicy {
         Self {
             config,
             trees,
+            tokenizer,
             eviction_handle,
         }
     }
 
+    fn new_tree(&self) -> PrefixTree {
+        match self.tokenizer {
+            Some(_) => PrefixTree::Token(TokenTree::new(self.config.block_size)),
+            None => PrefixTree::Char(Tree::new()),
+        }
+    }
+
+    /// The key `text` is matched by: the text itself, or its token ids
+    fn routing_key<'a>(&self, text: &'a str) -> RoutingKey<'a> {
+        match &self.tokenizer {
+            Some(tokenizer) => match tokenizer.encode(text) {
+                Ok(encoding) => RoutingKey::Tokens(encoding.token_ids().to_vec()),
+                Err(e) => {
+                    debug!("Failed to tokenize routing text: {}", e);
+                    RoutingKey::Tokens(Vec::new())
+                }
+            },
+            None => RoutingKey::Text(text),
+        }
+    }
+
     /// Initialize the tree with worker URLs (used only during initial setup)
     pub fn init_workers(&self, workers: &[Arc<dyn Worker>]) {
         if let Ok(mut trees) = self.trees.lock() {
@@ -138,9 +277,9 @@ impl CacheAwarePolicy {
 
             // Initialize tree for each model
             for (tree_key, model_workers) in model_workers {
-                let tree = trees.entry(tree_key).or_insert_with(Tree::new);
+                let tree = trees.entry(tree_key).or_insert_with(|| self.new_tree());
                 for worker in model_workers {
-                    tree.insert("", worker.url());
+                    tree.add_tenant(worker.url());
                 }
             }
         }
@@ -157,16 +296,18 @@ impl CacheAwarePolicy {
             } else {
                 model_id.to_string()
             };
-            let tree = trees.entry(tree_key).or_insert_with(Tree::new);
-            tree.insert("", worker.url());
+            let tree = trees.entry(tree_key).or_insert_with(|| self.new_tree());
+            tree.add_tenant(worker.url());
         }
     }
 
     /// Add a worker by URL and model (for backward compatibility)
     pub fn add_worker_by_url(&self, url: &str, model_id: &str) {
         if let Ok(mut trees) = self.trees.lock() {
-            let tree = trees.entry(model_id.to_string()).or_insert_with(Tree::new);
-            tree.insert("", url);
+            let tree = trees
+                .entry(model_id.to_string())
+                .or_insert_with(|| self.new_tree());
+            tree.add_tenant(url);
         }
     }
 
@@ -234,8 +375,11 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
             model_workers.entry(tree_key).or_default().push(*idx);
         }
 
+        let key = self.routing_key(request_text.unwrap_or(""));
+
         // Get current load statistics
-        let loads: Vec<usize> = workers.iter().map(|w| w.load()).collect();
+        let load_unit = self.config.load_unit;
+        let loads: Vec<usize> = workers.iter().map(|w| w.load_in(load_unit)).collect();
         let max_load = *loads.iter().max().unwrap_or(&0);
         let min_load = *loads.iter().min().unwrap_or(&0);
 
@@ -247,7 +391,7 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
             // Log load balancing trigger
             let worker_loads: Vec<(String, usize)> = workers
                 .iter()
-                .map(|w| (w.url().to_string(), w.load()))
+                .map(|w| (w.url().to_string(), w.load_in(load_unit)))
                 .collect();
 
             debug!(
@@ -261,11 +405,11 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
             // Use shortest queue when imbalanced
             let min_load_idx = healthy_indices
                 .iter()
-                .min_by_key(|&&idx| workers[idx].load())
+                .min_by_key(|&&idx| workers[idx].load_in(load_unit))
                 .copied()?;
 
             // Even in imbalanced mode, update the tree to maintain cache state
-            if let Some(text) = request_text {
+            if request_text.is_some() {
                 if let Ok(mut trees) = self.trees.lock() {
                     let model_id = workers[min_load_idx].model_id();
                     let tree_key = if model_id.is_empty() || model_id == "unknown" {
@@ -273,8 +417,8 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
                     } else {
                         model_id.to_string()
                     };
-                    let tree = trees.entry(tree_key).or_insert_with(Tree::new);
-                    tree.insert(text, workers[min_load_idx].url());
+                    let tree = trees.entry(tree_key).or_insert_with(|| self.new_tree());
+                    tree.insert(&key, workers[min_load_idx].url());
                 }
             }
 
@@ -287,21 +431,21 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
         }
 
         // Use cache-aware routing when balanced
-        let text = request_text.unwrap_or("");
-
         if let Ok(mut trees) = self.trees.lock() {
             let mut best_match_idx: Option<usize> = None;
             let mut best_match_rate: f32 = 0.0;
 
             // Find best match across all models
             for (model_id, worker_indices) in &model_workers {
-                let tree = trees.entry(model_id.clone()).or_insert_with(Tree::new);
+                let tree = trees
+                    .entry(model_id.clone())
+                    .or_insert_with(|| self.new_tree());
 
-                let (matched_text, matched_worker) = tree.prefix_match(text);
-                let match_rate = if text.is_empty() {
+                let (matched_len, matched_worker) = tree.prefix_match(&key);
+                let match_rate = if key.len() == 0 {
                     0.0
                 } else {
-                    matched_text.chars().count() as f32 / text.chars().count() as f32
+                    matched_len as f32 / key.len() as f32
                 };
 
                 // Check if this model has the best match
@@ -332,8 +476,10 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
                 let mut smallest_tree_size = usize::MAX;
 
                 for model_id in model_workers.keys() {
-                    let tree = trees.entry(model_id.clone()).or_insert_with(Tree::new);
-                    let size = tree.get_used_size_per_tenant().values().sum::<usize>();
+                    let tree = trees
+                        .entry(model_id.clone())
+                        .or_insert_with(|| self.new_tree());
+                    let size = tree.used_size();
                     if size < smallest_tree_size {
                         smallest_tree_size = size;
                         smallest_tree_model = model_id.clone();
@@ -344,7 +490,7 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
                 if let Some(worker_indices) = model_workers.get(&smallest_tree_model) {
                     worker_indices
                         .iter()
-                        .min_by_key(|&&idx| workers[idx].load())
+                        .min_by_key(|&&idx| workers[idx].load_in(load_unit))
                         .copied()
                         .unwrap_or(healthy_indices[0])
                 } else {
@@ -359,8 +505,8 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
             } else {
                 model_id.to_string()
             };
-            let tree = trees.entry(tree_key).or_insert_with(Tree::new);
-            tree.insert(text, workers[selected_idx].url());
+            let tree = trees.entry(tree_key).or_insert_with(|| self.new_tree());
+            tree.insert(&key, workers[selected_idx].url());
 
             // Increment processed counter
             workers[selected_idx].increment_processed();
@@ -382,7 +528,11 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
         true // Cache-aware policy needs request text for cache affinity
     }
 
-    fn on_request_complete(&self, worker_url: &str, success: bool) {
+    fn routing_tokenizer(&self) -> Option<&Arc<dyn Tokenizer>> {
+        self.tokenizer.as_ref()
+    }
+
+    fn on_request_complete(&self, worker_url: &str, success: bool, _timing: &RequestTiming) {
         // Could track success rates per worker for more intelligent routing
         if !success {
             // Optionally reduce affinity for failed requests
@@ -423,7 +573,7 @@ impl LoadBalancingPolicy for CacheAwarePolicy {
 
         let decode_idx = healthy_decode
             .iter()
-            .min_by_key(|&&idx| decode_workers[idx].load())
+            .min_by_key(|&&idx| decode_workers[idx].load_in(self.config.load_unit))
             .copied()?;
 
         Some((prefill_idx, decode_idx))
@@ -451,7 +601,9 @@ impl Drop for CacheAwarePolicy {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::LoadUnit;
     use crate::core::{BasicWorker, WorkerType};
+    use crate::tokenizer::mock::MockTokenizer;
 
     #[test]
     fn test_cache_aware_with_balanced_load() {
@@ -495,6 +647,9 @@ mod tests {
             balance_rel_threshold: 2.0,
             eviction_interval_secs: 0, // Disable eviction thread
             max_tree_size: 10000,
+            tree_type: CacheTreeType::Char,
+            block_size: 16,
+            load_unit: LoadUnit::Requests,
         });
 
         let worker1 = BasicWorker::new("http://w1:8000".to_string(), WorkerType::Regular);
@@ -516,6 +671,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cache_aware_with_token_load() {
+        let policy = CacheAwarePolicy::with_config(CacheAwareConfig {
+            balance_abs_threshold: 1000,
+            balance_rel_threshold: 2.0,
+            eviction_interval_secs: 0, // Disable eviction thread
+            load_unit: LoadUnit::Tokens,
+            ..Default::default()
+        });
+        let workers: Vec<Arc<dyn Worker>> = vec![
+            Arc::new(BasicWorker::new(
+                "http://w1:8000".to_string(),
+                WorkerType::Regular,
+            )),
+            Arc::new(BasicWorker::new(
+                "http://w2:8000".to_string(),
+                WorkerType::Regular,
+            )),
+        ];
+        policy.init_workers(&workers);
+
+        let idx = policy.select_worker(&workers, Some("test")).unwrap();
+        let other = 1 - idx;
+
+        // Many small requests on the cached worker stay within the token thresholds
+        for _ in 0..20 {
+            workers[idx].increment_load();
+        }
+        workers[idx].add_token_load(200);
+        assert_eq!(policy.select_worker(&workers, Some("test")), Some(idx));
+
+        // One long prompt imbalances the workers although they hold no more requests
+        workers[idx].add_token_load(100_000);
+        assert_eq!(policy.select_worker(&workers, Some("test")), Some(other));
+    }
+
     #[test]
     fn test_cache_aware_worker_removal() {
         let config = CacheAwareConfig {
@@ -548,4 +739,47 @@ mod tests {
         let idx = policy.select_worker(&workers, Some("test1")).unwrap();
         assert_eq!(idx, 1);
     }
+
+    #[test]
+    fn test_cache_aware_token_tree() {
+        let config = CacheAwareConfig {
+            eviction_interval_secs: 0, // Disable eviction thread
+            tree_type: CacheTreeType::Token,
+            block_size: 2,
+            ..Default::default()
+        };
+        // Without a tokenizer the policy falls back to the char tree
+        assert!(CacheAwarePolicy::with_config(config.clone())
+            .routing_tokenizer()
+            .is_none());
+
+        let tokenizer: Arc<dyn Tokenizer> = Arc::new(MockTokenizer::new());
+        let policy = CacheAwarePolicy::with_tokenizer(config, Some(tokenizer));
+        assert!(policy.routing_tokenizer().is_some());
+        let workers: Vec<Arc<dyn Worker>> = vec![
+            Arc::new(BasicWorker::new(
+                "http://w1:8000".to_string(),
+                WorkerType::Regular,
+            )),
+            Arc::new(BasicWorker::new(
+                "http://w2:8000".to_string(),
+                WorkerType::Regular,
+            )),
+        ];
+        policy.init_workers(&workers);
+
+        let idx = policy
+            .select_worker(&workers, Some("Hello world test token"))
+            .unwrap();
+        assert_eq!(idx, 0);
+        workers[0].increment_load();
+
+        // The first block of the three tokens is cached on worker 1
+        let idx = policy.select_worker(&workers, Some("Hello world test"));
+        assert_eq!(idx, Some(0));
+
+        // A shared first token is not a whole block, so the less loaded worker is used
+        let idx = policy.select_worker(&workers, Some("Hello test"));
+        assert_eq!(idx, Some(1));
+    }
 }
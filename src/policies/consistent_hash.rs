@@ -3,27 +3,63 @@
 //! This policy implements consistent hashing to route requests to workers based on
 //! session ID or user ID, ensuring that requests from the same user/session are
 //! consistently routed to the same worker for better cache locality.
+//!
+//! The affinity key can instead be taken from an ordered list of sources: request
+//! headers, JSON pointers into the request body, or the OpenAI `prompt_cache_key`.
+//! With a capacity factor, the policy implements consistent hashing with bounded
+//! loads: a worker whose load would exceed the factor times the average load is
+//! skipped for the next worker on the ring.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, RwLock};
 
+use axum::http::HeaderMap;
+use serde_json::Value;
 use tracing::debug;
 use tracing::info;
 
 use super::LoadBalancingPolicy;
 use super::get_healthy_worker_indices;
+use super::load_per_request;
+use crate::config::{HashKeySource, LoadUnit};
 use crate::core::Worker;
 use crate::metrics::RouterMetrics;
 
 /// Number of virtual nodes per physical worker (for better load distribution)
 const VIRTUAL_NODES_PER_WORKER: u32 = 160;
 
+/// Configuration for consistent hashing
+#[derive(Debug, Clone)]
+pub struct ConsistentHashConfig {
+    /// Virtual nodes per worker on the hash ring
+    pub virtual_nodes: u32,
+    /// Ordered sources of the affinity key; empty to scan the request text for session
+    /// and user ids
+    pub hash_key_sources: Vec<HashKeySource>,
+    /// Bound on a worker's load relative to the average load (None = unbounded)
+    pub capacity_factor: Option<f32>,
+    /// Unit of the loads the capacity bound applies to
+    pub load_unit: LoadUnit,
+}
+
+impl Default for ConsistentHashConfig {
+    fn default() -> Self {
+        Self {
+            virtual_nodes: VIRTUAL_NODES_PER_WORKER,
+            hash_key_sources: Vec::new(),
+            capacity_factor: None,
+            load_unit: LoadUnit::Requests,
+        }
+    }
+}
+
 /// Consistent hashing policy
 ///
 /// Routes requests based on session ID or user ID using consistent hashing,
 /// ensuring that requests from the same user/session consistently go to the same worker.
 #[derive(Debug)]
 pub struct ConsistentHashPolicy {
+    config: ConsistentHashConfig,
     /// Hash ring mapping hash values to worker URLs
     hash_ring: RwLock<BTreeMap<u64, String>>,
     /// Current set of workers (for detecting changes)
@@ -32,7 +68,12 @@ pub struct ConsistentHashPolicy {
 
 impl ConsistentHashPolicy {
     pub fn new() -> Self {
+        Self::with_config(ConsistentHashConfig::default())
+    }
+
+    pub fn with_config(config: ConsistentHashConfig) -> Self {
         Self {
+            config,
             hash_ring: RwLock::new(BTreeMap::new()),
             current_workers: RwLock::new(Vec::new()),
         }
@@ -262,7 +303,7 @@ impl ConsistentHashPolicy {
 
         for worker_url in &worker_urls {
             // Create virtual nodes for better distribution
-            for i in 0..VIRTUAL_NODES_PER_WORKER {
+            for i in 0..self.config.virtual_nodes {
                 let virtual_key = format!("{}:{}", worker_url, i);
                 let hash_value = Self::fbi_hash(&virtual_key);
                 new_ring.insert(hash_value, worker_url.clone());
@@ -282,7 +323,7 @@ impl ConsistentHashPolicy {
         info!(
             "Updated consistent hash ring with {} workers and {} virtual nodes",
             workers.len(),
-            workers.len() as u32 * VIRTUAL_NODES_PER_WORKER
+            workers.len() as u32 * self.config.virtual_nodes
         );
     }
 
@@ -313,6 +354,79 @@ impl ConsistentHashPolicy {
         selected_worker
     }
 
+    /// Find the worker for a hash key among those with capacity left
+    ///
+    /// Walks the ring from the key's position and takes the first available worker
+    /// whose load, counting this request, stays within `capacity_factor` times the
+    /// average load. Loads are in the configured unit; with tokens this request is
+    /// counted as the average in-flight request. Falls back to the unbounded choice if
+    /// no worker has capacity.
+    fn find_worker_with_capacity(
+        &self,
+        hash_key: &str,
+        workers: &[Arc<dyn Worker>],
+        capacity_factor: f32,
+    ) -> Option<String> {
+        let load_unit = self.config.load_unit;
+        let available: Vec<&Arc<dyn Worker>> =
+            workers.iter().filter(|w| w.is_available()).collect();
+        if available.is_empty() {
+            return self.find_worker_by_hash(hash_key);
+        }
+        let total_load: usize = available.iter().map(|w| w.load_in(load_unit)).sum();
+        let request_load = load_per_request(workers, load_unit);
+        let capacity = (capacity_factor as f64 * (total_load as f64 + request_load)
+            / available.len() as f64)
+            .ceil();
+
+        let hash_value = Self::fbi_hash(hash_key);
+        {
+            let ring = self.hash_ring.read().unwrap();
+            let mut visited = HashSet::new();
+            for (_, worker_url) in ring.range(hash_value..).chain(ring.range(..hash_value)) {
+                if !visited.insert(worker_url.as_str()) {
+                    continue;
+                }
+                let Some(worker) = available.iter().find(|w| w.url() == worker_url.as_str()) else {
+                    continue;
+                };
+                let load = worker.load_in(load_unit);
+                if load as f64 + request_load <= capacity {
+                    return Some(worker_url.clone());
+                }
+                debug!(
+                    "Consistent hash: worker '{}' is at capacity (load {}, capacity {}), trying the next one",
+                    worker_url, load, capacity
+                );
+            }
+        }
+
+        self.find_worker_by_hash(hash_key)
+    }
+
+    /// Value of an affinity key source in a request, None if missing or empty
+    fn source_value(
+        source: &HashKeySource,
+        headers: Option<&HeaderMap>,
+        body: &Value,
+    ) -> Option<String> {
+        let value = match source {
+            HashKeySource::Header(name) => headers?.get(name.as_str())?.to_str().ok()?.to_string(),
+            HashKeySource::JsonPointer(pointer) => Self::json_key(body.pointer(pointer)?)?,
+            HashKeySource::PromptCacheKey => Self::json_key(body.get("prompt_cache_key")?)?,
+        };
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// A JSON string or number as a key
+    fn json_key(value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
     /// Extract hash key from request text
     /// Priority: session_params.session_id > user field > legacy session_id > legacy user_id > request content
     fn extract_hash_key(&self, request_text: Option<&str>) -> String {
@@ -514,8 +628,13 @@ impl LoadBalancingPolicy for ConsistentHashPolicy {
         // Update hash ring if needed
         self.update_hash_ring(workers);
 
-        // Extract hash key from request text (legacy method)
-        let hash_key = self.extract_hash_key(request_text);
+        // With key sources the router passes the request's affinity key as its text,
+        // otherwise the key is extracted from the request text (legacy method)
+        let hash_key = if self.uses_affinity_key() {
+            request_text.unwrap_or("").to_string()
+        } else {
+            self.extract_hash_key(request_text)
+        };
 
         // DEBUG: Log the request text and extracted hash key
         if let Some(text) = request_text {
@@ -523,8 +642,12 @@ impl LoadBalancingPolicy for ConsistentHashPolicy {
         }
         info!("CONSISTENT_HASH_DEBUG: Extracted hash key: {}", hash_key);
 
-        // Find target worker using consistent hashing
-        let target_worker_url = match self.find_worker_by_hash(&hash_key) {
+        // Find target worker using consistent hashing, with bounded loads if configured
+        let target_worker = match self.config.capacity_factor {
+            Some(factor) => self.find_worker_with_capacity(&hash_key, workers, factor),
+            None => self.find_worker_by_hash(&hash_key),
+        };
+        let target_worker_url = match target_worker {
             Some(url) => {
                 info!("CONSISTENT_HASH_DEBUG: Hash key '{}' mapped to worker: {}", hash_key, url);
                 url
@@ -559,8 +682,8 @@ impl LoadBalancingPolicy for ConsistentHashPolicy {
 
         match selected_idx {
             Some(idx) => {
-                // Verify the worker is healthy
-                if workers[idx].is_healthy() && workers[idx].circuit_breaker().can_execute() {
+                // Verify the worker is available
+                if workers[idx].is_available() {
                     let worker_url = workers[idx].url();
                     info!("CONSISTENT_HASH_DEBUG: Selected worker at index {}: {}", idx, worker_url);
                     info!(
@@ -616,6 +739,18 @@ impl LoadBalancingPolicy for ConsistentHashPolicy {
         true // We need request text to extract session_id/user_id
     }
 
+    fn uses_affinity_key(&self) -> bool {
+        !self.config.hash_key_sources.is_empty()
+    }
+
+    fn affinity_key(&self, headers: Option<&HeaderMap>, body: &Value) -> Option<String> {
+        let key = self.config.hash_key_sources.iter().find_map(|source| {
+            Self::source_value(source, headers, body).map(|value| format!("{}:{}", source, value))
+        });
+        // Requests carrying none of the keys fall back to their session and user ids
+        Some(key.unwrap_or_else(|| self.extract_hash_key(Some(&body.to_string()))))
+    }
+
     fn reset(&self) {
         // Clear the hash ring and force rebuild on next request
         {
@@ -717,4 +852,85 @@ mod tests {
         assert_eq!(idx2, idx3);
         assert!(idx1.is_some());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_affinity_key_sources() {
+        let policy = ConsistentHashPolicy::with_config(ConsistentHashConfig {
+            hash_key_sources: vec![
+                HashKeySource::Header("x-session-id".to_string()),
+                HashKeySource::JsonPointer("/metadata/tenant".to_string()),
+                HashKeySource::PromptCacheKey,
+            ],
+            ..Default::default()
+        });
+        assert!(policy.uses_affinity_key());
+        assert!(!ConsistentHashPolicy::new().uses_affinity_key());
+
+        let body = serde_json::json!({
+            "metadata": {"tenant": 42},
+            "prompt_cache_key": "cache-abc",
+            "user": "alice"
+        });
+        let mut headers = HeaderMap::new();
+        headers.insert("x-session-id", "sess-1".parse().unwrap());
+
+        // Sources are tried in order
+        assert_eq!(
+            policy.affinity_key(Some(&headers), &body),
+            Some("header:x-session-id:sess-1".to_string())
+        );
+        assert_eq!(
+            policy.affinity_key(None, &body),
+            Some("json:/metadata/tenant:42".to_string())
+        );
+        let body = serde_json::json!({"prompt_cache_key": "cache-abc", "user": "alice"});
+        assert_eq!(
+            policy.affinity_key(None, &body),
+            Some("prompt_cache_key:cache-abc".to_string())
+        );
+
+        // Without any of the sources the legacy session/user extraction applies
+        let body = serde_json::json!({"user": "alice"});
+        assert_eq!(
+            policy.affinity_key(None, &body),
+            Some("user:alice".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bounded_loads_spill_to_next_worker() {
+        let policy = ConsistentHashPolicy::with_config(ConsistentHashConfig {
+            capacity_factor: Some(1.25),
+            ..Default::default()
+        });
+        let workers: Vec<Arc<dyn Worker>> = (1..=3)
+            .map(|i| {
+                Arc::new(BasicWorker::new(
+                    format!("http://worker{}:8000", i),
+                    WorkerType::Regular,
+                )) as Arc<dyn Worker>
+            })
+            .collect();
+
+        let request = r#"{"session_id": "hot_session"}"#;
+        let home = policy.select_worker(&workers, Some(request)).unwrap();
+
+        // Within capacity (ceil(1.25 * 7 / 3) = 3) the session stays on its worker
+        for worker in &workers {
+            worker.increment_load();
+            worker.increment_load();
+        }
+        assert_eq!(policy.select_worker(&workers, Some(request)), Some(home));
+
+        // Past capacity (ceil(1.25 * 9 / 3) = 4) it spills to another worker, deterministically
+        workers[home].increment_load();
+        workers[home].increment_load();
+        let spilled = policy.select_worker(&workers, Some(request)).unwrap();
+        assert_ne!(spilled, home);
+        assert_eq!(policy.select_worker(&workers, Some(request)), Some(spilled));
+
+        // Without a capacity factor loads are ignored
+        let unbounded = ConsistentHashPolicy::new();
+        assert_eq!(unbounded.select_worker(&workers, Some(request)), Some(home));
+    }
+}
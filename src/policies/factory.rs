@@ -1,11 +1,13 @@
 //! Factory for creating load balancing policies
 
 use super::{
-    CacheAwareConfig, CacheAwarePolicy, ConsistentHashPolicy, LoadBalancingPolicy, PowerOfTwoPolicy, RandomPolicy,
+    CacheAwareConfig, CacheAwarePolicy, ConsistentHashConfig, ConsistentHashPolicy, KvPressureConfig, KvPressurePolicy, LatencyConfig, LatencyPolicy, LoadBalancingPolicy, PowerOfTwoPolicy, RandomPolicy,
     RoundRobinPolicy,
 };
-use crate::config::PolicyConfig;
+use crate::config::{LoadUnit, PolicyConfig};
+use crate::tokenizer::traits::Tokenizer;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Factory for creating policy instances
 pub struct PolicyFactory;
@@ -13,16 +15,39 @@ pub struct PolicyFactory;
 impl PolicyFactory {
     /// Create a policy from configuration
     pub fn create_from_config(config: &PolicyConfig) -> Arc<dyn LoadBalancingPolicy> {
+        Self::create_with_tokenizer(config, None)
+    }
+
+    /// Create a policy from configuration, giving policies that match requests on token
+    /// ids the router's tokenizer
+    pub fn create_with_tokenizer(
+        config: &PolicyConfig,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+    ) -> Arc<dyn LoadBalancingPolicy> {
+        Self::create_with_options(config, tokenizer, LoadUnit::Requests)
+    }
+
+    /// Create a policy from configuration with the router's tokenizer, comparing worker
+    /// loads in the given unit where the policy balances load on the router's counters
+    pub fn create_with_options(
+        config: &PolicyConfig,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+        load_unit: LoadUnit,
+    ) -> Arc<dyn LoadBalancingPolicy> {
         match config {
             PolicyConfig::Random => Arc::new(RandomPolicy::new()),
             PolicyConfig::RoundRobin => Arc::new(RoundRobinPolicy::new()),
-            PolicyConfig::PowerOfTwo { .. } => Arc::new(PowerOfTwoPolicy::new()),
+            PolicyConfig::PowerOfTwo { .. } => {
+                Arc::new(PowerOfTwoPolicy::new().with_load_unit(load_unit))
+            }
             PolicyConfig::CacheAware {
                 cache_threshold,
                 balance_abs_threshold,
                 balance_rel_threshold,
                 eviction_interval_secs,
                 max_tree_size,
+                tree_type,
+                block_size,
             } => {
                 let config = CacheAwareConfig {
                     cache_threshold: *cache_threshold,
@@ -30,14 +55,43 @@ impl PolicyFactory {
                     balance_rel_threshold: *balance_rel_threshold,
                     eviction_interval_secs: *eviction_interval_secs,
                     max_tree_size: *max_tree_size,
+                    tree_type: *tree_type,
+                    block_size: *block_size,
+                    load_unit,
                 };
-                Arc::new(CacheAwarePolicy::with_config(config))
-            }
-            PolicyConfig::ConsistentHash { virtual_nodes: _ } => {
-                // Note: virtual_nodes parameter is available but not currently used
-                // The consistent hash policy uses a hardcoded value for now
-                Arc::new(ConsistentHashPolicy::new())
+                Arc::new(CacheAwarePolicy::with_tokenizer(config, tokenizer))
             }
+            PolicyConfig::ConsistentHash {
+                virtual_nodes,
+                hash_key_sources,
+                capacity_factor,
+            } => Arc::new(ConsistentHashPolicy::with_config(ConsistentHashConfig {
+                virtual_nodes: *virtual_nodes,
+                hash_key_sources: hash_key_sources.clone(),
+                capacity_factor: *capacity_factor,
+                load_unit,
+            })),
+            PolicyConfig::KvPressure {
+                queue_weight,
+                kv_cache_weight,
+                max_kv_cache_usage,
+                load_check_interval_secs: _,
+            } => Arc::new(KvPressurePolicy::with_config(KvPressureConfig {
+                queue_weight: *queue_weight,
+                kv_cache_weight: *kv_cache_weight,
+                max_kv_cache_usage: *max_kv_cache_usage,
+                load_unit,
+            })),
+            PolicyConfig::Latency {
+                ewma_alpha,
+                decay_half_life_secs,
+                expected_output_tokens,
+            } => Arc::new(LatencyPolicy::with_config(LatencyConfig {
+                ewma_alpha: *ewma_alpha,
+                decay_half_life: Duration::from_secs(*decay_half_life_secs),
+                expected_output_tokens: *expected_output_tokens,
+                load_unit,
+            })),
         }
     }
 
@@ -49,6 +103,8 @@ impl PolicyFactory {
             "power_of_two" | "poweroftwo" => Some(Arc::new(PowerOfTwoPolicy::new())),
             "cache_aware" | "cacheaware" => Some(Arc::new(CacheAwarePolicy::new())),
             "consistent_hash" | "consistenthash" => Some(Arc::new(ConsistentHashPolicy::new())),
+            "kv_pressure" | "kvpressure" => Some(Arc::new(KvPressurePolicy::new())),
+            "latency" => Some(Arc::new(LatencyPolicy::new())),
             _ => None,
         }
     }
@@ -57,6 +113,7 @@ impl PolicyFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CacheTreeType;
 
     #[test]
     fn test_create_from_config() {
@@ -81,14 +138,35 @@ mod tests {
             balance_rel_threshold: 1.5,
             eviction_interval_secs: 30,
             max_tree_size: 1000,
+            tree_type: CacheTreeType::Char,
+            block_size: 16,
         });
         assert_eq!(policy.name(), "cache_aware");
 
         // Test ConsistentHash
         let policy = PolicyFactory::create_from_config(&PolicyConfig::ConsistentHash {
             virtual_nodes: 160,
+            hash_key_sources: vec![],
+            capacity_factor: None,
         });
         assert_eq!(policy.name(), "consistent_hash");
+
+        // Test KvPressure
+        let policy = PolicyFactory::create_from_config(&PolicyConfig::KvPressure {
+            queue_weight: 2.0,
+            kv_cache_weight: 16.0,
+            max_kv_cache_usage: 0.95,
+            load_check_interval_secs: 5,
+        });
+        assert_eq!(policy.name(), "kv_pressure");
+
+        // Test Latency
+        let policy = PolicyFactory::create_from_config(&PolicyConfig::Latency {
+            ewma_alpha: 0.2,
+            decay_half_life_secs: 60,
+            expected_output_tokens: 256,
+        });
+        assert_eq!(policy.name(), "latency");
     }
 
     #[test]
@@ -103,6 +181,8 @@ mod tests {
         assert!(PolicyFactory::create_by_name("CacheAware").is_some());
         assert!(PolicyFactory::create_by_name("consistent_hash").is_some());
         assert!(PolicyFactory::create_by_name("ConsistentHash").is_some());
+        assert!(PolicyFactory::create_by_name("kv_pressure").is_some());
+        assert!(PolicyFactory::create_by_name("latency").is_some());
         assert!(PolicyFactory::create_by_name("unknown").is_none());
     }
 }
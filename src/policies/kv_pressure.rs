@@ -0,0 +1,320 @@
+//! KV-cache-pressure-aware load balancing policy
+
+use super::{get_healthy_worker_indices, load_per_request, LoadBalancingPolicy};
+use crate::config::LoadUnit;
+use crate::core::{Worker, WorkerLoad};
+use crate::metrics::RouterMetrics;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::debug;
+
+/// Configuration for the KV-cache-pressure policy
+#[derive(Debug, Clone)]
+pub struct KvPressureConfig {
+    /// Cost of a waiting request relative to a running one
+    pub queue_weight: f32,
+    /// Cost of a full KV cache, in running requests
+    pub kv_cache_weight: f32,
+    /// KV cache usage (0.0-1.0) above which a worker is only chosen if all are above it
+    pub max_kv_cache_usage: f32,
+    /// Unit of the router's in-flight counts, weighed as request equivalents
+    pub load_unit: LoadUnit,
+}
+
+impl Default for KvPressureConfig {
+    fn default() -> Self {
+        Self {
+            queue_weight: 2.0,
+            kv_cache_weight: 16.0,
+            max_kv_cache_usage: 0.95,
+            load_unit: LoadUnit::Requests,
+        }
+    }
+}
+
+/// KV-cache-pressure policy
+///
+/// Routes to the worker with the lowest cost, computed from the load its metrics
+/// reported: running requests, waiting requests weighted by `queue_weight` and the KV
+/// cache usage weighted by `kv_cache_weight`. A worker whose queue grows or whose KV
+/// cache fills up would preempt or delay new requests, so it is avoided even when its
+/// running request count is low. The router's in-flight load is already part of a
+/// snapshot's running requests, so only its growth since the snapshot was first used is
+/// added, which lets a burst spread over the workers between scrapes; workers without a
+/// snapshot are costed by their in-flight load alone. With the tokens load unit,
+/// in-flight load counts by its tokens relative to the average request.
+#[derive(Debug)]
+pub struct KvPressurePolicy {
+    config: KvPressureConfig,
+    /// Scraped loads by worker URL
+    loads: RwLock<HashMap<String, WorkerLoad>>,
+    /// In-flight load of each worker when its snapshot was first used, by worker URL
+    baselines: Mutex<HashMap<String, usize>>,
+}
+
+impl KvPressurePolicy {
+    pub fn new() -> Self {
+        Self::with_config(KvPressureConfig::default())
+    }
+
+    pub fn with_config(config: KvPressureConfig) -> Self {
+        Self {
+            config,
+            loads: RwLock::new(HashMap::new()),
+            baselines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Last scraped load of a worker
+    pub fn worker_load(&self, worker_url: &str) -> Option<WorkerLoad> {
+        self.loads.read().ok()?.get(worker_url).copied()
+    }
+
+    /// Whether the worker's KV cache is above `max_kv_cache_usage`
+    fn is_saturated(&self, load: Option<&WorkerLoad>) -> bool {
+        load.and_then(|load| load.kv_cache_usage)
+            .is_some_and(|usage| usage >= self.config.max_kv_cache_usage as f64)
+    }
+
+    /// `in_flight` is the router's load on the worker not yet reflected in `load`, in
+    /// request equivalents
+    fn cost(&self, load: Option<&WorkerLoad>, in_flight: f64) -> f64 {
+        let Some(load) = load else {
+            return in_flight;
+        };
+        load.running as f64
+            + self.config.queue_weight as f64 * load.waiting as f64
+            + self.config.kv_cache_weight as f64 * load.kv_cache_usage.unwrap_or(0.0)
+            + in_flight
+    }
+}
+
+impl LoadBalancingPolicy for KvPressurePolicy {
+    fn select_worker(
+        &self,
+        workers: &[Arc<dyn Worker>],
+        _request_text: Option<&str>,
+    ) -> Option<usize> {
+        let healthy_indices = get_healthy_worker_indices(workers);
+        if healthy_indices.is_empty() {
+            return None;
+        }
+
+        let loads = self.loads.read().ok()?;
+        let load_of = |idx: usize| loads.get(workers[idx].url());
+
+        // Saturated workers are a last resort
+        let unsaturated: Vec<usize> = healthy_indices
+            .iter()
+            .copied()
+            .filter(|&idx| !self.is_saturated(load_of(idx)))
+            .collect();
+        let candidates = if unsaturated.is_empty() {
+            &healthy_indices
+        } else {
+            &unsaturated
+        };
+
+        let per_request = load_per_request(workers, self.config.load_unit);
+        let mut baselines = self.baselines.lock().ok()?;
+        let selected_idx = candidates
+            .iter()
+            .copied()
+            .map(|idx| {
+                let worker = &workers[idx];
+                let in_flight = worker.load_in(self.config.load_unit);
+                let unscraped = match load_of(idx) {
+                    Some(_) => {
+                        let baseline = *baselines
+                            .entry(worker.url().to_string())
+                            .or_insert(in_flight);
+                        in_flight.saturating_sub(baseline)
+                    }
+                    None => in_flight,
+                };
+                let cost = self.cost(load_of(idx), unscraped as f64 / per_request);
+                (idx, cost)
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(idx, _)| idx)?;
+
+        debug!(
+            "KV pressure selection: {} (load {:?}, in flight {})",
+            workers[selected_idx].url(),
+            load_of(selected_idx),
+            workers[selected_idx].load()
+        );
+
+        workers[selected_idx].increment_processed();
+        RouterMetrics::record_processed_request(workers[selected_idx].url());
+        RouterMetrics::record_policy_decision(self.name(), workers[selected_idx].url());
+
+        Some(selected_idx)
+    }
+
+    fn name(&self) -> &'static str {
+        "kv_pressure"
+    }
+
+    fn update_loads(&self, loads: &HashMap<String, isize>) {
+        // Request counts alone, e.g. from a backend without KV cache metrics
+        let loads = loads
+            .iter()
+            .map(|(url, &requests)| {
+                let load = WorkerLoad {
+                    running: requests.max(0) as u64,
+                    ..Default::default()
+                };
+                (url.clone(), load)
+            })
+            .collect();
+        self.update_worker_loads(&loads);
+    }
+
+    fn update_worker_loads(&self, loads: &HashMap<String, WorkerLoad>) {
+        if let Ok(mut cached) = self.loads.write() {
+            *cached = loads.clone();
+        }
+        if let Ok(mut baselines) = self.baselines.lock() {
+            baselines.clear();
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Default for KvPressurePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+
+    fn workers() -> Vec<Arc<dyn Worker>> {
+        ["http://w1:8000", "http://w2:8000", "http://w3:8000"]
+            .iter()
+            .map(|url| {
+                Arc::new(BasicWorker::new(url.to_string(), WorkerType::Regular)) as Arc<dyn Worker>
+            })
+            .collect()
+    }
+
+    fn load(running: u64, waiting: u64, kv_cache_usage: f64) -> WorkerLoad {
+        WorkerLoad {
+            running,
+            waiting,
+            kv_cache_usage: Some(kv_cache_usage),
+        }
+    }
+
+    #[test]
+    fn test_kv_pressure_weighs_queue_and_kv_cache() {
+        let policy = KvPressurePolicy::new();
+        let workers = workers();
+
+        // w1 runs the fewest requests but queues, w2's cache is nearly full
+        policy.update_worker_loads(&HashMap::from([
+            ("http://w1:8000".to_string(), load(1, 4, 0.2)),
+            ("http://w2:8000".to_string(), load(2, 0, 0.9)),
+            ("http://w3:8000".to_string(), load(4, 0, 0.3)),
+        ]));
+        assert_eq!(policy.select_worker(&workers, None), Some(2));
+
+        // Saturated workers are skipped while another is below the limit
+        policy.update_worker_loads(&HashMap::from([
+            ("http://w1:8000".to_string(), load(0, 0, 0.97)),
+            ("http://w2:8000".to_string(), load(8, 2, 0.5)),
+            ("http://w3:8000".to_string(), load(0, 0, 1.0)),
+        ]));
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+
+        // ... and chosen by cost when all are saturated
+        policy.update_worker_loads(&HashMap::from([
+            ("http://w1:8000".to_string(), load(3, 0, 0.99)),
+            ("http://w2:8000".to_string(), load(1, 0, 0.96)),
+            ("http://w3:8000".to_string(), load(5, 1, 1.0)),
+        ]));
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+    }
+
+    #[test]
+    fn test_kv_pressure_without_metrics() {
+        let policy = KvPressurePolicy::new();
+        let workers = workers();
+
+        // Falls back to the router's in-flight requests
+        workers[0].increment_load();
+        workers[1].increment_load();
+        assert_eq!(policy.select_worker(&workers, None), Some(2));
+
+        // Plain request counts are treated as running requests
+        policy.update_loads(&HashMap::from([
+            ("http://w1:8000".to_string(), 0),
+            ("http://w2:8000".to_string(), 1),
+            ("http://w3:8000".to_string(), 5),
+        ]));
+        assert_eq!(policy.select_worker(&workers, None), Some(0));
+        assert_eq!(
+            policy
+                .worker_load("http://w3:8000")
+                .map(|load| load.running),
+            Some(5)
+        );
+
+        workers[0].set_healthy(false);
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+    }
+
+    #[test]
+    fn test_kv_pressure_counts_in_flight_once() {
+        let policy = KvPressurePolicy::new();
+        let workers = workers();
+
+        // w1's two in-flight requests are the running ones its snapshot reports
+        workers[0].increment_load();
+        workers[0].increment_load();
+        policy.update_worker_loads(&HashMap::from([
+            ("http://w1:8000".to_string(), load(2, 0, 0.0)),
+            ("http://w2:8000".to_string(), load(3, 0, 0.0)),
+            ("http://w3:8000".to_string(), load(10, 0, 0.0)),
+        ]));
+        assert_eq!(policy.select_worker(&workers, None), Some(0));
+
+        // Requests sent after the snapshot are added until the next one
+        workers[0].increment_load();
+        workers[0].increment_load();
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+    }
+
+    #[test]
+    fn test_kv_pressure_token_load_unit() {
+        let workers = workers();
+        // w1 holds one long prompt, w2 and w3 several short requests
+        for (worker, requests, tokens) in [
+            (&workers[0], 1, 10_000),
+            (&workers[1], 2, 200),
+            (&workers[2], 3, 300),
+        ] {
+            for _ in 0..requests {
+                worker.increment_load();
+            }
+            worker.add_token_load(tokens);
+        }
+
+        assert_eq!(
+            KvPressurePolicy::new().select_worker(&workers, None),
+            Some(0)
+        );
+        let policy = KvPressurePolicy::with_config(KvPressureConfig {
+            load_unit: LoadUnit::Tokens,
+            ..Default::default()
+        });
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+    }
+}
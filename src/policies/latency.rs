@@ -0,0 +1,419 @@
+//! Latency-aware load balancing policy
+
+use super::{get_healthy_worker_indices, load_per_request, LoadBalancingPolicy, RequestTiming};
+use crate::config::LoadUnit;
+use crate::core::Worker;
+use crate::metrics::RouterMetrics;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Error rate above which a worker's predicted time stops growing
+const MAX_ERROR_RATE: f64 = 0.9;
+
+/// Configuration for the latency policy
+#[derive(Debug, Clone)]
+pub struct LatencyConfig {
+    /// Weight of the newest sample in the moving averages (0.0-1.0]
+    pub ewma_alpha: f32,
+    /// Age after which a worker's stats count half as much
+    pub decay_half_life: Duration,
+    /// Output tokens assumed when predicting a request's completion time
+    pub expected_output_tokens: u32,
+    /// Unit of the router's in-flight counts, weighed as request equivalents
+    pub load_unit: LoadUnit,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        Self {
+            ewma_alpha: 0.2,
+            decay_half_life: Duration::from_secs(60),
+            expected_output_tokens: 256,
+            load_unit: LoadUnit::Requests,
+        }
+    }
+}
+
+/// Moving averages of one worker, in seconds
+#[derive(Debug, Clone, Copy)]
+struct LatencyStats {
+    ttft: Option<f64>,
+    tpot: Option<f64>,
+    error_rate: f64,
+    updated_at: Instant,
+}
+
+/// Latency estimate of a worker
+#[derive(Debug, Clone, Copy)]
+struct Estimate {
+    ttft: f64,
+    tpot: f64,
+    error_rate: f64,
+}
+
+/// Latency-aware policy
+///
+/// Keeps exponentially weighted moving averages of each worker's time to first token,
+/// time per output token and error rate, as measured by the routers while proxying
+/// streams, and routes to the worker with the lowest predicted completion time:
+///
+/// `(ttft * (1 + in_flight) + tpot * expected_output_tokens) / (1 - error_rate)`
+///
+/// Requests already in flight on a worker delay the prefill of a new one, and a
+/// failed attempt has to be retried elsewhere. With the tokens load unit, `in_flight`
+/// counts requests by their tokens relative to the average request. Stats fade towards the fleet average
+/// as they age, so a worker that was slow or failing gets traffic again once its
+/// numbers are no longer current. Workers without stats are assumed as fast as the
+/// fastest one and win ties, so that new workers get measured.
+#[derive(Debug)]
+pub struct LatencyPolicy {
+    config: LatencyConfig,
+    /// Stats by worker URL
+    stats: RwLock<HashMap<String, LatencyStats>>,
+}
+
+impl LatencyPolicy {
+    pub fn new() -> Self {
+        Self::with_config(LatencyConfig::default())
+    }
+
+    pub fn with_config(config: LatencyConfig) -> Self {
+        Self {
+            config,
+            stats: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Average time to first token of a worker, None before it streamed a response
+    pub fn time_to_first_token(&self, worker_url: &str) -> Option<Duration> {
+        let ttft = self.stats.read().ok()?.get(worker_url)?.ttft?;
+        Some(Duration::from_secs_f64(ttft))
+    }
+
+    /// Weight left of stats last updated at `updated_at`
+    fn freshness(&self, updated_at: Instant, now: Instant) -> f64 {
+        let half_life = self.config.decay_half_life.as_secs_f64();
+        if half_life <= 0.0 {
+            return 1.0;
+        }
+        let age = now.saturating_duration_since(updated_at).as_secs_f64();
+        0.5f64.powf(age / half_life)
+    }
+
+    /// Mean TTFT and TPOT over the workers with stats
+    fn fleet_average(stats: &HashMap<String, LatencyStats>) -> Estimate {
+        let mean = |values: Vec<f64>| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+        };
+        Estimate {
+            ttft: mean(stats.values().filter_map(|s| s.ttft).collect()),
+            tpot: mean(stats.values().filter_map(|s| s.tpot).collect()),
+            error_rate: 0.0,
+        }
+    }
+
+    /// Lowest TTFT and TPOT among the workers' current estimates
+    fn fleet_best(estimates: &[Estimate]) -> Estimate {
+        let min = |values: Vec<f64>| values.into_iter().reduce(f64::min).unwrap_or(0.0);
+        Estimate {
+            ttft: min(estimates.iter().map(|e| e.ttft).collect()),
+            tpot: min(estimates.iter().map(|e| e.tpot).collect()),
+            error_rate: 0.0,
+        }
+    }
+
+    /// A worker's stats faded towards the fleet average by their age
+    fn estimate(&self, stats: &LatencyStats, fleet: &Estimate, now: Instant) -> Estimate {
+        let weight = self.freshness(stats.updated_at, now);
+        let fade = |value: Option<f64>, average: f64| match value {
+            Some(value) => average + (value - average) * weight,
+            None => average,
+        };
+        Estimate {
+            ttft: fade(stats.ttft, fleet.ttft),
+            tpot: fade(stats.tpot, fleet.tpot),
+            error_rate: stats.error_rate * weight,
+        }
+    }
+
+    fn predicted_secs(&self, estimate: &Estimate, in_flight: f64) -> f64 {
+        let time = estimate.ttft * (1.0 + in_flight)
+            + estimate.tpot * self.config.expected_output_tokens as f64;
+        time / (1.0 - estimate.error_rate.min(MAX_ERROR_RATE))
+    }
+}
+
+impl LoadBalancingPolicy for LatencyPolicy {
+    fn select_worker(
+        &self,
+        workers: &[Arc<dyn Worker>],
+        _request_text: Option<&str>,
+    ) -> Option<usize> {
+        let healthy_indices = get_healthy_worker_indices(workers);
+        if healthy_indices.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let stats = self.stats.read().ok()?;
+        let fleet = Self::fleet_average(&stats);
+        let estimates: Vec<Option<Estimate>> = healthy_indices
+            .iter()
+            .map(|&idx| {
+                let stats = stats.get(workers[idx].url())?;
+                Some(self.estimate(stats, &fleet, now))
+            })
+            .collect();
+        let known: Vec<Estimate> = estimates.iter().flatten().copied().collect();
+        let best = Self::fleet_best(&known);
+
+        // Ties go to workers without stats, then to the worker with the least load
+        let load_unit = self.config.load_unit;
+        let per_request = load_per_request(workers, load_unit);
+        let selected_idx = healthy_indices
+            .iter()
+            .copied()
+            .zip(estimates)
+            .map(|(idx, estimate)| {
+                let measured = estimate.is_some();
+                let estimate = estimate.unwrap_or(best);
+                let in_flight = workers[idx].load_in(load_unit) as f64 / per_request;
+                let predicted = self.predicted_secs(&estimate, in_flight);
+                (idx, predicted, measured)
+            })
+            .min_by(|(a_idx, a, a_measured), (b_idx, b, b_measured)| {
+                let load = |idx: &usize| workers[*idx].load_in(load_unit);
+                a.total_cmp(b)
+                    .then_with(|| a_measured.cmp(b_measured))
+                    .then_with(|| load(a_idx).cmp(&load(b_idx)))
+            })
+            .map(|(idx, predicted, _)| {
+                debug!(
+                    "Latency selection: {} (predicted {:.3}s)",
+                    workers[idx].url(),
+                    predicted
+                );
+                idx
+            })?;
+
+        workers[selected_idx].increment_processed();
+        RouterMetrics::record_processed_request(workers[selected_idx].url());
+        RouterMetrics::record_policy_decision(self.name(), workers[selected_idx].url());
+
+        Some(selected_idx)
+    }
+
+    fn on_request_complete(&self, worker_url: &str, success: bool, timing: &RequestTiming) {
+        let Ok(mut stats) = self.stats.write() else {
+            return;
+        };
+        let now = Instant::now();
+        let fleet = Self::fleet_average(&stats);
+        let alpha = self.config.ewma_alpha as f64;
+        let ewma = |average: f64, sample: f64| average + alpha * (sample - average);
+
+        // A failed request's timing says nothing about how fast the worker generates
+        let timing = if success {
+            *timing
+        } else {
+            RequestTiming::default()
+        };
+
+        // Fold the fading into the stored stats before adding the sample
+        let previous = stats.get(worker_url).copied();
+        let current = previous.map(|s| self.estimate(&s, &fleet, now));
+        let update = |stored: Option<f64>, faded: Option<f64>, sample: Option<Duration>| {
+            let average = stored.and(faded);
+            match sample {
+                Some(sample) => {
+                    let sample = sample.as_secs_f64();
+                    Some(average.map_or(sample, |average| ewma(average, sample)))
+                }
+                None => average,
+            }
+        };
+        let updated = LatencyStats {
+            ttft: update(
+                previous.and_then(|s| s.ttft),
+                current.map(|e| e.ttft),
+                timing.ttft,
+            ),
+            tpot: update(
+                previous.and_then(|s| s.tpot),
+                current.map(|e| e.tpot),
+                timing.tpot,
+            ),
+            error_rate: ewma(
+                current.map_or(0.0, |e| e.error_rate),
+                if success { 0.0 } else { 1.0 },
+            ),
+            updated_at: now,
+        };
+        stats.insert(worker_url.to_string(), updated);
+    }
+
+    fn name(&self) -> &'static str {
+        "latency"
+    }
+
+    fn reset(&self) {
+        if let Ok(mut stats) = self.stats.write() {
+            stats.clear();
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Default for LatencyPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+
+    fn workers() -> Vec<Arc<dyn Worker>> {
+        ["http://w1:8000", "http://w2:8000"]
+            .iter()
+            .map(|url| {
+                Arc::new(BasicWorker::new(url.to_string(), WorkerType::Regular)) as Arc<dyn Worker>
+            })
+            .collect()
+    }
+
+    fn timing(ttft_ms: u64, tpot_ms: u64) -> RequestTiming {
+        RequestTiming {
+            ttft: Some(Duration::from_millis(ttft_ms)),
+            tpot: Some(Duration::from_millis(tpot_ms)),
+        }
+    }
+
+    #[test]
+    fn test_latency_prefers_faster_worker() {
+        let policy = LatencyPolicy::new();
+        let workers = workers();
+
+        // Without stats the less loaded worker wins
+        workers[0].increment_load();
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+        workers[0].decrement_load();
+
+        // w2 starts quickly but decodes slowly: 0.1 + 256 * 0.05 > 0.5 + 256 * 0.02
+        for _ in 0..5 {
+            policy.on_request_complete("http://w1:8000", true, &timing(500, 20));
+            policy.on_request_complete("http://w2:8000", true, &timing(100, 50));
+        }
+        assert_eq!(policy.select_worker(&workers, None), Some(0));
+
+        // In-flight requests delay the first token
+        for _ in 0..20 {
+            workers[0].increment_load();
+        }
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+    }
+
+    #[test]
+    fn test_latency_penalizes_errors() {
+        let policy = LatencyPolicy::new();
+        let workers = workers();
+        for _ in 0..5 {
+            policy.on_request_complete("http://w1:8000", true, &timing(100, 20));
+            policy.on_request_complete("http://w2:8000", true, &timing(120, 22));
+        }
+        assert_eq!(policy.select_worker(&workers, None), Some(0));
+
+        // Failures carry no timing but count against the worker
+        for _ in 0..3 {
+            policy.on_request_complete("http://w1:8000", false, &RequestTiming::default());
+        }
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+
+        // The averages survive a failure
+        let stats = policy.stats.read().unwrap();
+        let w1 = stats.get("http://w1:8000").unwrap();
+        assert!((w1.ttft.unwrap() - 0.1).abs() < 0.01);
+        assert!(w1.error_rate > 0.4);
+    }
+
+    #[test]
+    fn test_latency_tries_new_workers() {
+        let policy = LatencyPolicy::new();
+        let workers = workers();
+        policy.on_request_complete("http://w1:8000", true, &timing(200, 20));
+        assert_eq!(
+            policy.time_to_first_token("http://w1:8000"),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(policy.time_to_first_token("http://w2:8000"), None);
+
+        // An unmeasured worker is assumed as fast as the fastest one
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+
+        // ... which does not draw the whole burst while its first request is running
+        workers[1].increment_load();
+        assert_eq!(policy.select_worker(&workers, None), Some(0));
+    }
+
+    #[test]
+    fn test_latency_stats_decay() {
+        let policy = LatencyPolicy::with_config(LatencyConfig {
+            decay_half_life: Duration::from_millis(10),
+            ..Default::default()
+        });
+        let workers = workers();
+        for _ in 0..5 {
+            policy.on_request_complete("http://w1:8000", true, &timing(1000, 100));
+            policy.on_request_complete("http://w2:8000", true, &timing(100, 10));
+        }
+        assert_eq!(policy.select_worker(&workers, None), Some(1));
+
+        // Once the stats are stale both workers look average, and load decides
+        std::thread::sleep(Duration::from_millis(200));
+        workers[1].increment_load();
+        assert_eq!(policy.select_worker(&workers, None), Some(0));
+
+        // A fresh sample is weighed against the faded average, not the stale one
+        policy.on_request_complete("http://w1:8000", true, &timing(100, 10));
+        let stats = policy.stats.read().unwrap();
+        let w1 = stats.get("http://w1:8000").unwrap();
+        assert!(w1.ttft.unwrap() < 0.6);
+    }
+
+    #[test]
+    fn test_latency_token_load_unit() {
+        let workers = workers();
+        // w1 holds one long prompt, w2 two short requests
+        workers[0].increment_load();
+        workers[0].add_token_load(10_000);
+        for _ in 0..2 {
+            workers[1].increment_load();
+        }
+        workers[1].add_token_load(200);
+
+        let requests = LatencyPolicy::new();
+        let tokens = LatencyPolicy::with_config(LatencyConfig {
+            load_unit: LoadUnit::Tokens,
+            ..Default::default()
+        });
+        for policy in [&requests, &tokens] {
+            for url in ["http://w1:8000", "http://w2:8000"] {
+                policy.on_request_complete(url, true, &timing(100, 10));
+            }
+        }
+
+        assert_eq!(requests.select_worker(&workers, None), Some(0));
+        assert_eq!(tokens.select_worker(&workers, None), Some(1));
+    }
+}
@@ -3,21 +3,30 @@
 //! This module provides a unified abstraction for routing policies that work
 //! across both regular and prefill-decode (PD) routing modes.
 
-use crate::core::Worker;
+use crate::config::{CacheTreeType, LoadUnit};
+use crate::core::{Worker, WorkerLoad};
+use crate::tokenizer::traits::Tokenizer;
+use axum::http::HeaderMap;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 
 mod cache_aware;
 mod consistent_hash;
 mod factory;
+mod kv_pressure;
+mod latency;
 mod power_of_two;
 mod random;
 mod registry;
 mod round_robin;
 
 pub use cache_aware::CacheAwarePolicy;
-pub use consistent_hash::ConsistentHashPolicy;
+pub use consistent_hash::{ConsistentHashConfig, ConsistentHashPolicy};
 pub use factory::PolicyFactory;
+pub use kv_pressure::{KvPressureConfig, KvPressurePolicy};
+pub use latency::{LatencyConfig, LatencyPolicy};
 pub use power_of_two::PowerOfTwoPolicy;
 pub use random::RandomPolicy;
 pub use registry::PolicyRegistry;
@@ -57,8 +66,9 @@ pub trait LoadBalancingPolicy: Send + Sync + Debug {
     /// Update policy state after request completion
     ///
     /// This is called when a request completes (successfully or not) to allow
-    /// policies to update their internal state.
-    fn on_request_complete(&self, _worker_url: &str, _success: bool) {
+    /// policies to update their internal state. `timing` holds the latencies the
+    /// router observed while proxying the response.
+    fn on_request_complete(&self, _worker_url: &str, _success: bool, _timing: &RequestTiming) {
         // Default: no-op for stateless policies
     }
 
@@ -70,13 +80,47 @@ pub trait LoadBalancingPolicy: Send + Sync + Debug {
         false // Default: most policies don't need request text
     }
 
+    /// Tokenizer the policy matches requests with, if it routes on token ids
+    ///
+    /// Routers render chat requests with its chat template, so the routing key is the
+    /// prompt the worker sees.
+    fn routing_tokenizer(&self) -> Option<&Arc<dyn Tokenizer>> {
+        None
+    }
+
+    /// Check if this policy routes on an affinity key taken from request headers and body
+    fn uses_affinity_key(&self) -> bool {
+        false
+    }
+
+    /// Affinity key of a request, which routers pass to `select_worker` as its text
+    fn affinity_key(
+        &self,
+        _headers: Option<&HeaderMap>,
+        _body: &serde_json::Value,
+    ) -> Option<String> {
+        None
+    }
+
     /// Update worker load information
     ///
     /// This is called periodically with current load information for load-aware policies.
-    fn update_loads(&self, _loads: &std::collections::HashMap<String, isize>) {
+    fn update_loads(&self, _loads: &HashMap<String, isize>) {
         // Default: no-op for policies that don't use load information
     }
 
+    /// Update worker load snapshots scraped from the workers' metrics
+    ///
+    /// Policies weighing more than request counts override this; the default passes
+    /// each worker's running and waiting requests to `update_loads`.
+    fn update_worker_loads(&self, loads: &HashMap<String, WorkerLoad>) {
+        let requests = loads
+            .iter()
+            .map(|(url, load)| (url.clone(), load.requests()))
+            .collect();
+        self.update_loads(&requests);
+    }
+
     /// Reset any internal state
     ///
     /// This is useful for policies that maintain state (e.g., round-robin counters).
@@ -88,6 +132,18 @@ pub trait LoadBalancingPolicy: Send + Sync + Debug {
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
+/// Latencies of a completed request, as observed by the router
+///
+/// Only streamed responses reveal when generation started, so both are None for
+/// non-streaming requests and for requests that failed before the first token.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RequestTiming {
+    /// Time to first token: from sending the request to the first streamed event
+    pub ttft: Option<Duration>,
+    /// Time per output token: mean interval between the following streamed events
+    pub tpot: Option<Duration>,
+}
+
 /// Configuration for cache-aware policy
 #[derive(Debug, Clone)]
 pub struct CacheAwareConfig {
@@ -96,6 +152,12 @@ pub struct CacheAwareConfig {
     pub balance_rel_threshold: f32,
     pub eviction_interval_secs: u64,
     pub max_tree_size: usize,
+    /// Unit the trees match on; the token tree needs a tokenizer
+    pub tree_type: CacheTreeType,
+    /// Tokens per KV cache block for the token tree
+    pub block_size: usize,
+    /// Unit of the worker load the balance thresholds apply to
+    pub load_unit: LoadUnit,
 }
 
 impl Default for CacheAwareConfig {
@@ -106,16 +168,49 @@ impl Default for CacheAwareConfig {
             balance_rel_threshold: 1.1,
             eviction_interval_secs: 30,
             max_tree_size: 10000,
+            tree_type: CacheTreeType::Char,
+            block_size: 16,
+            load_unit: LoadUnit::Requests,
         }
     }
 }
 
-/// Helper function to filter healthy workers and return their indices
+/// Affinity key of a request under `policy`, None if the policy doesn't route on one
+pub fn request_affinity_key<T: serde::Serialize>(
+    policy: &dyn LoadBalancingPolicy,
+    headers: Option<&HeaderMap>,
+    request: &T,
+) -> Option<String> {
+    if !policy.uses_affinity_key() {
+        return None;
+    }
+    let body = serde_json::to_value(request).ok()?;
+    policy.affinity_key(headers, &body)
+}
+
+/// Load per in-flight request across `workers`, for expressing loads in `unit` as
+/// request equivalents
+///
+/// 1 for requests. For tokens it is the mean tokens held by a request in flight, so a
+/// worker holding one long prompt weighs like several short requests.
+pub(crate) fn load_per_request(workers: &[Arc<dyn Worker>], unit: LoadUnit) -> f64 {
+    if unit == LoadUnit::Requests {
+        return 1.0;
+    }
+    let requests: usize = workers.iter().map(|w| w.load()).sum();
+    let tokens: usize = workers.iter().map(|w| w.token_load()).sum();
+    if requests == 0 || tokens == 0 {
+        return 1.0;
+    }
+    tokens as f64 / requests as f64
+}
+
+/// Helper function to filter available (healthy, not draining) workers and return their indices
 pub(crate) fn get_healthy_worker_indices(workers: &[Arc<dyn Worker>]) -> Vec<usize> {
     workers
         .iter()
         .enumerate()
-        .filter(|(_, w)| w.is_healthy() && w.circuit_breaker().can_execute())
+        .filter(|(_, w)| w.is_available())
         .map(|(idx, _)| idx)
         .collect()
 }
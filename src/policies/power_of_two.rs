@@ -1,6 +1,7 @@
 //! Power-of-two choices load balancing policy
 
 use super::{get_healthy_worker_indices, LoadBalancingPolicy};
+use crate::config::LoadUnit;
 use crate::core::Worker;
 use crate::metrics::RouterMetrics;
 use rand::Rng;
@@ -16,16 +17,32 @@ use tracing::info;
 pub struct PowerOfTwoPolicy {
     /// Cached load information from external monitoring
     cached_loads: RwLock<HashMap<String, isize>>,
+    /// Unit of the worker loads compared
+    load_unit: LoadUnit,
 }
 
 impl PowerOfTwoPolicy {
     pub fn new() -> Self {
         Self {
             cached_loads: RwLock::new(HashMap::new()),
+            load_unit: LoadUnit::Requests,
         }
     }
 
+    /// Compare workers by the given load unit
+    ///
+    /// Loads from external monitoring count requests, so they are only used when
+    /// comparing requests.
+    pub fn with_load_unit(mut self, load_unit: LoadUnit) -> Self {
+        self.load_unit = load_unit;
+        self
+    }
+
     fn get_worker_load(&self, worker: &dyn Worker) -> isize {
+        if self.load_unit == LoadUnit::Tokens {
+            return worker.token_load() as isize;
+        }
+
         // First check cached loads (from external monitoring)
         if let Ok(loads) = self.cached_loads.read() {
             if let Some(&load) = loads.get(worker.url()) {
@@ -187,6 +204,39 @@ mod tests {
         assert!(w2_selected > 35); // Should win most of the time
     }
 
+    #[test]
+    fn test_power_of_two_with_token_load() {
+        let policy = PowerOfTwoPolicy::new().with_load_unit(LoadUnit::Tokens);
+        let workers: Vec<Arc<dyn Worker>> = vec![
+            Arc::new(BasicWorker::new(
+                "http://w1:8000".to_string(),
+                WorkerType::Regular,
+            )),
+            Arc::new(BasicWorker::new(
+                "http://w2:8000".to_string(),
+                WorkerType::Regular,
+            )),
+        ];
+
+        // worker1 holds fewer requests but far more tokens
+        workers[0].increment_load();
+        workers[0].add_token_load(50_000);
+        for _ in 0..5 {
+            workers[1].increment_load();
+        }
+        workers[1].add_token_load(500);
+
+        // Scraped request counts do not override token loads
+        let mut loads = HashMap::new();
+        loads.insert("http://w1:8000".to_string(), 0);
+        loads.insert("http://w2:8000".to_string(), 100);
+        policy.update_loads(&loads);
+
+        for _ in 0..20 {
+            assert_eq!(policy.select_worker(&workers, None), Some(1));
+        }
+    }
+
     #[test]
     fn test_power_of_two_single_worker() {
         let policy = PowerOfTwoPolicy::new();
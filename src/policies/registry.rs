@@ -5,11 +5,14 @@
 /// All subsequent workers of the same model use the established policy.
 /// When the last worker of a model is removed, the policy mapping is cleaned up.
 use super::{
-    CacheAwareConfig, CacheAwarePolicy, ConsistentHashPolicy, LoadBalancingPolicy, PowerOfTwoPolicy, RandomPolicy,
+    CacheAwareConfig, CacheAwarePolicy, KvPressureConfig, KvPressurePolicy, LatencyConfig,
+    LatencyPolicy, LoadBalancingPolicy, PolicyFactory, PowerOfTwoPolicy, RandomPolicy,
     RoundRobinPolicy,
 };
-use crate::config::types::PolicyConfig;
-use std::collections::HashMap;
+use crate::config::types::{LoadUnit, PolicyConfig};
+use crate::core::WorkerLoad;
+use crate::tokenizer::traits::Tokenizer;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use tracing::{debug, info, warn};
 
@@ -30,12 +33,33 @@ pub struct PolicyRegistry {
 
     /// Decode policy for PD mode
     decode_policy: Arc<RwLock<Option<Arc<dyn LoadBalancingPolicy>>>>,
+
+    /// Unit of the worker loads compared by policies created from hints
+    load_unit: LoadUnit,
 }
 
 impl PolicyRegistry {
     /// Create a new PolicyRegistry with a default policy
     pub fn new(default_policy_config: PolicyConfig) -> Self {
-        let default_policy = Self::create_policy_from_config(&default_policy_config);
+        Self::with_tokenizer(default_policy_config, None)
+    }
+
+    /// Create a new PolicyRegistry whose default policy may match requests on token ids
+    pub fn with_tokenizer(
+        default_policy_config: PolicyConfig,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+    ) -> Self {
+        Self::with_options(default_policy_config, tokenizer, LoadUnit::Requests)
+    }
+
+    /// Create a new PolicyRegistry whose policies compare worker loads in the given unit
+    pub fn with_options(
+        default_policy_config: PolicyConfig,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+        load_unit: LoadUnit,
+    ) -> Self {
+        let default_policy =
+            PolicyFactory::create_with_options(&default_policy_config, tokenizer, load_unit);
 
         Self {
             model_policies: Arc::new(RwLock::new(HashMap::new())),
@@ -43,6 +67,7 @@ impl PolicyRegistry {
             default_policy,
             prefill_policy: Arc::new(RwLock::new(None)),
             decode_policy: Arc::new(RwLock::new(None)),
+            load_unit,
         }
     }
 
@@ -170,8 +195,19 @@ impl PolicyRegistry {
         match policy_type {
             "round_robin" => Arc::new(RoundRobinPolicy::new()),
             "random" => Arc::new(RandomPolicy::new()),
-            "cache_aware" => Arc::new(CacheAwarePolicy::new()),
-            "power_of_two" => Arc::new(PowerOfTwoPolicy::new()),
+            "cache_aware" => Arc::new(CacheAwarePolicy::with_config(CacheAwareConfig {
+                load_unit: self.load_unit,
+                ..Default::default()
+            })),
+            "power_of_two" => Arc::new(PowerOfTwoPolicy::new().with_load_unit(self.load_unit)),
+            "kv_pressure" => Arc::new(KvPressurePolicy::with_config(KvPressureConfig {
+                load_unit: self.load_unit,
+                ..Default::default()
+            })),
+            "latency" => Arc::new(LatencyPolicy::with_config(LatencyConfig {
+                load_unit: self.load_unit,
+                ..Default::default()
+            })),
             _ => {
                 warn!("Unknown policy type '{}', using default", policy_type);
                 Arc::clone(&self.default_policy)
@@ -179,29 +215,25 @@ impl PolicyRegistry {
         }
     }
 
-    /// Create a policy from a PolicyConfig
-    fn create_policy_from_config(config: &PolicyConfig) -> Arc<dyn LoadBalancingPolicy> {
-        match config {
-            PolicyConfig::RoundRobin => Arc::new(RoundRobinPolicy::new()),
-            PolicyConfig::Random => Arc::new(RandomPolicy::new()),
-            PolicyConfig::CacheAware {
-                cache_threshold,
-                balance_abs_threshold,
-                balance_rel_threshold,
-                eviction_interval_secs,
-                max_tree_size,
-            } => {
-                let cache_config = CacheAwareConfig {
-                    cache_threshold: *cache_threshold,
-                    balance_abs_threshold: *balance_abs_threshold,
-                    balance_rel_threshold: *balance_rel_threshold,
-                    eviction_interval_secs: *eviction_interval_secs,
-                    max_tree_size: *max_tree_size,
-                };
-                Arc::new(CacheAwarePolicy::with_config(cache_config))
+    /// Pass scraped worker loads to every policy in use
+    pub fn update_worker_loads(&self, loads: &HashMap<String, WorkerLoad>) {
+        let mut policies: Vec<Arc<dyn LoadBalancingPolicy>> = self
+            .model_policies
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+        policies.push(self.get_default_policy());
+        policies.extend(self.prefill_policy.read().unwrap().clone());
+        policies.extend(self.decode_policy.read().unwrap().clone());
+
+        // Models without a policy hint share the default policy
+        let mut updated = HashSet::new();
+        for policy in policies {
+            if updated.insert(Arc::as_ptr(&policy) as *const ()) {
+                policy.update_worker_loads(loads);
             }
-            PolicyConfig::PowerOfTwo { .. } => Arc::new(PowerOfTwoPolicy::new()),
-            PolicyConfig::ConsistentHash { .. } => Arc::new(ConsistentHashPolicy::new()),
         }
     }
 
@@ -331,4 +363,36 @@ mod tests {
         let default = registry.get_default_policy();
         assert_eq!(default.name(), "round_robin");
     }
+
+    #[test]
+    fn test_update_worker_loads() {
+        let registry = PolicyRegistry::new(PolicyConfig::RoundRobin);
+        registry.on_worker_added("llama-3", Some("kv_pressure"));
+        let decode = Arc::new(KvPressurePolicy::new());
+        registry.set_decode_policy(decode.clone());
+
+        let loads = HashMap::from([(
+            "http://w1:8000".to_string(),
+            WorkerLoad {
+                running: 2,
+                waiting: 1,
+                kv_cache_usage: Some(0.5),
+            },
+        )]);
+        registry.update_worker_loads(&loads);
+
+        let model_policy = registry.get_policy("llama-3").unwrap();
+        let model_policy = model_policy
+            .as_any()
+            .downcast_ref::<KvPressurePolicy>()
+            .unwrap();
+        assert_eq!(
+            model_policy.worker_load("http://w1:8000"),
+            loads.get("http://w1:8000").copied()
+        );
+        assert_eq!(
+            decode.worker_load("http://w1:8000"),
+            loads.get("http://w1:8000").copied()
+        );
+    }
 }
@@ -0,0 +1,428 @@
+use crate::protocols::spec::{
+    ChatCompletionRequest, ChatMessage, ContentPart, Function, FunctionCallResponse,
+    FunctionChoice, ImageUrl, StreamOptions, StringOrArray, Tool, ToolCall, ToolChoice,
+    ToolChoiceValue, UserMessageContent,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// # Anthropic Messages API
+//
+// Request and response types of `POST /v1/messages`, and the translation of requests
+// into the OpenAI Chat Completions requests the workers serve.
+
+// ============= Content Blocks =============
+
+/// A block of message content
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<ToolResultContent>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        is_error: Option<bool>,
+    },
+    Thinking {
+        thinking: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ToolResultContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SystemPrompt {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+// ============= Request =============
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InputMessage {
+    /// "user" or "assistant"
+    pub role: String,
+    pub content: MessageContent,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub input_schema: Value,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagesToolChoice {
+    Auto,
+    Any,
+    Tool { name: String },
+    None,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MessagesMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MessagesRequest {
+    /// ID of the model to use
+    pub model: String,
+
+    /// Conversation so far, alternating user and assistant turns
+    pub messages: Vec<InputMessage>,
+
+    /// The maximum number of tokens to generate
+    pub max_tokens: u32,
+
+    /// System prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<SystemPrompt>,
+
+    /// Custom sequences that stop generation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+
+    /// Stream the response as server-sent events
+    #[serde(default)]
+    pub stream: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<i32>,
+
+    /// Tools the model may use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ToolDefinition>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<MessagesToolChoice>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<MessagesMetadata>,
+}
+
+// ============= Response =============
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MessagesUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MessagesResponse {
+    pub id: String,
+    /// Always "message"
+    #[serde(rename = "type")]
+    pub object_type: String,
+    /// Always "assistant"
+    pub role: String,
+    pub model: String,
+    pub content: Vec<ContentBlock>,
+    /// "end_turn", "max_tokens", "stop_sequence" or "tool_use"
+    pub stop_reason: Option<String>,
+    pub stop_sequence: Option<String>,
+    pub usage: MessagesUsage,
+}
+
+// ============= Translation to Chat Completions =============
+
+impl MessagesRequest {
+    /// Translate into the equivalent chat completion request
+    pub fn to_chat_completion_request(&self) -> Result<ChatCompletionRequest, String> {
+        let mut chat: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": self.model,
+            "messages": []
+        }))
+        .map_err(|e| format!("Failed to build chat request: {}", e))?;
+
+        if let Some(system) = &self.system {
+            let content = match system {
+                SystemPrompt::Text(text) => text.clone(),
+                SystemPrompt::Blocks(blocks) => block_text(blocks),
+            };
+            chat.messages.push(ChatMessage::System {
+                role: "system".to_string(),
+                content,
+                name: None,
+            });
+        }
+        for message in &self.messages {
+            match message.role.as_str() {
+                "user" => push_user_message(&mut chat.messages, &message.content),
+                "assistant" => chat.messages.push(assistant_message(&message.content)),
+                role => return Err(format!("Unsupported message role '{}'", role)),
+            }
+        }
+
+        chat.max_tokens = Some(self.max_tokens);
+        chat.stop = self.stop_sequences.clone().map(StringOrArray::Array);
+        chat.stream = self.stream;
+        if self.stream {
+            // Usage arrives in the final chunk and is reported in `message_delta`
+            chat.stream_options = Some(StreamOptions {
+                include_usage: Some(true),
+            });
+        }
+        chat.temperature = self.temperature;
+        chat.top_p = self.top_p;
+        chat.top_k = self.top_k;
+        chat.user = self
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.user_id.clone());
+        chat.tools = self.tools.as_ref().map(|tools| {
+            tools
+                .iter()
+                .map(|tool| Tool {
+                    tool_type: "function".to_string(),
+                    function: Function {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.input_schema.clone(),
+                    },
+                })
+                .collect()
+        });
+        chat.tool_choice = self.tool_choice.as_ref().map(|choice| match choice {
+            MessagesToolChoice::Auto => ToolChoice::Value(ToolChoiceValue::Auto),
+            MessagesToolChoice::Any => ToolChoice::Value(ToolChoiceValue::Required),
+            MessagesToolChoice::None => ToolChoice::Value(ToolChoiceValue::None),
+            MessagesToolChoice::Tool { name } => ToolChoice::Function {
+                tool_type: "function".to_string(),
+                function: FunctionChoice { name: name.clone() },
+            },
+        });
+
+        Ok(chat)
+    }
+}
+
+/// Concatenated text of the text blocks
+fn block_text(blocks: &[ContentBlock]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A user turn becomes one tool message per `tool_result` block, followed by a user
+/// message with the remaining content
+fn push_user_message(messages: &mut Vec<ChatMessage>, content: &MessageContent) {
+    let blocks = match content {
+        MessageContent::Text(text) => {
+            messages.push(ChatMessage::User {
+                role: "user".to_string(),
+                content: UserMessageContent::Text(text.clone()),
+                name: None,
+            });
+            return;
+        }
+        MessageContent::Blocks(blocks) => blocks,
+    };
+
+    let mut parts = Vec::new();
+    for block in blocks {
+        match block {
+            ContentBlock::Text { text } => parts.push(ContentPart::Text { text: text.clone() }),
+            ContentBlock::Image { source } => parts.push(ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: match source {
+                        ImageSource::Base64 { media_type, data } => {
+                            format!("data:{};base64,{}", media_type, data)
+                        }
+                        ImageSource::Url { url } => url.clone(),
+                    },
+                    detail: None,
+                },
+            }),
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } => {
+                let text = match content {
+                    Some(ToolResultContent::Text(text)) => text.clone(),
+                    Some(ToolResultContent::Blocks(blocks)) => block_text(blocks),
+                    None => String::new(),
+                };
+                messages.push(ChatMessage::Tool {
+                    role: "tool".to_string(),
+                    content: if *is_error == Some(true) {
+                        format!("Error: {}", text)
+                    } else {
+                        text
+                    },
+                    tool_call_id: tool_use_id.clone(),
+                });
+            }
+            ContentBlock::ToolUse { .. } | ContentBlock::Thinking { .. } => {}
+        }
+    }
+
+    let content = match parts.as_slice() {
+        [] => return,
+        [ContentPart::Text { text }] => UserMessageContent::Text(text.clone()),
+        _ => UserMessageContent::Parts(parts),
+    };
+    messages.push(ChatMessage::User {
+        role: "user".to_string(),
+        content,
+        name: None,
+    });
+}
+
+fn assistant_message(content: &MessageContent) -> ChatMessage {
+    let (text, tool_calls) = match content {
+        MessageContent::Text(text) => (text.clone(), Vec::new()),
+        MessageContent::Blocks(blocks) => {
+            let tool_calls = blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::ToolUse { id, name, input } => Some(ToolCall {
+                        id: id.clone(),
+                        tool_type: "function".to_string(),
+                        function: FunctionCallResponse {
+                            name: name.clone(),
+                            arguments: Some(input.to_string()),
+                        },
+                    }),
+                    _ => None,
+                })
+                .collect();
+            (block_text(blocks), tool_calls)
+        }
+    };
+
+    ChatMessage::Assistant {
+        role: "assistant".to_string(),
+        content: (!text.is_empty()).then_some(text),
+        name: None,
+        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+        function_call: None,
+        reasoning_content: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_request_to_chat_completion() {
+        let request: MessagesRequest = serde_json::from_value(json!({
+            "model": "m",
+            "max_tokens": 64,
+            "system": [{"type": "text", "text": "Be brief"}],
+            "stop_sequences": ["END"],
+            "tools": [{
+                "name": "get_weather",
+                "description": "Weather by city",
+                "input_schema": {"type": "object", "properties": {"city": {"type": "string"}}}
+            }],
+            "tool_choice": {"type": "tool", "name": "get_weather"},
+            "messages": [
+                {"role": "user", "content": "Weather in Paris?"},
+                {"role": "assistant", "content": [
+                    {"type": "text", "text": "Checking."},
+                    {"type": "tool_use", "id": "toolu_1", "name": "get_weather",
+                     "input": {"city": "Paris"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "Sunny"},
+                    {"type": "text", "text": "Thanks"}
+                ]}
+            ]
+        }))
+        .unwrap();
+
+        let chat = request.to_chat_completion_request().unwrap();
+        assert_eq!(chat.max_tokens, Some(64));
+        assert!(matches!(&chat.stop, Some(StringOrArray::Array(stop)) if stop == &["END"]));
+        assert!(matches!(
+            &chat.tool_choice,
+            Some(ToolChoice::Function { function, .. }) if function.name == "get_weather"
+        ));
+        assert_eq!(chat.tools.as_ref().unwrap()[0].function.name, "get_weather");
+
+        assert_eq!(chat.messages.len(), 5);
+        assert!(
+            matches!(&chat.messages[0], ChatMessage::System { content, .. } if content == "Be brief")
+        );
+        assert!(matches!(
+            &chat.messages[2],
+            ChatMessage::Assistant { content: Some(text), tool_calls: Some(calls), .. }
+                if text == "Checking."
+                    && calls[0].id == "toolu_1"
+                    && calls[0].function.arguments.as_deref() == Some(r#"{"city":"Paris"}"#)
+        ));
+        assert!(matches!(
+            &chat.messages[3],
+            ChatMessage::Tool { content, tool_call_id, .. }
+                if content == "Sunny" && tool_call_id == "toolu_1"
+        ));
+        assert!(matches!(
+            &chat.messages[4],
+            ChatMessage::User { content: UserMessageContent::Text(text), .. } if text == "Thanks"
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_role_is_rejected() {
+        let request: MessagesRequest = serde_json::from_value(json!({
+            "model": "m",
+            "max_tokens": 8,
+            "messages": [{"role": "system", "content": "hi"}]
+        }))
+        .unwrap();
+        assert!(request.to_chat_completion_request().is_err());
+    }
+}
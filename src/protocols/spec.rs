@@ -235,6 +235,10 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 
+    /// Key grouping requests that share a prompt prefix, for prompt cache affinity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_key: Option<String>,
+
     /// If specified, our system will make a best effort to sample deterministically
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<i64>,
@@ -366,6 +370,34 @@ impl GenerationRequest for ChatCompletionRequest {
         // Return empty string if no session_id - let routing policy handle this case
         String::new()
     }
+
+    fn prompt_text(&self) -> String {
+        let mut texts: Vec<&str> = Vec::with_capacity(self.messages.len());
+        for message in &self.messages {
+            match message {
+                ChatMessage::System { content, .. }
+                | ChatMessage::Tool { content, .. }
+                | ChatMessage::Function { content, .. } => texts.push(content),
+                ChatMessage::User { content, .. } => match content {
+                    UserMessageContent::Text(text) => texts.push(text),
+                    UserMessageContent::Parts(parts) => {
+                        texts.extend(parts.iter().filter_map(|part| match part {
+                            ContentPart::Text { text } => Some(text.as_str()),
+                            ContentPart::ImageUrl { .. } => None,
+                        }))
+                    }
+                },
+                ChatMessage::Assistant { content, .. } => {
+                    texts.extend(content.as_deref());
+                }
+            }
+        }
+        texts.join("\n")
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.max_completion_tokens.or(self.max_tokens)
+    }
 }
 
 // ============= Regular Response =============
@@ -577,6 +609,10 @@ impl GenerationRequest for CompletionRequest {
             StringOrArray::Array(v) => v.join(" "),
         }
     }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.max_tokens
+    }
 }
 
 // ============= Regular Response =============
@@ -641,13 +677,20 @@ pub struct CompletionStreamChoice {
 pub struct ResponseTool {
     #[serde(rename = "type")]
     pub r#type: ResponseToolType,
+    /// Name of the configured MCP server providing the tools (mcp tools only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_label: Option<String>,
+    /// Subset of the server's tools the model may call (mcp tools only, None = all)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ResponseToolType {
     WebSearchPreview,
     CodeInterpreter,
+    Mcp,
 }
 
 // ============= Reasoning Configuration =============
@@ -712,7 +755,7 @@ pub enum ResponseContentPart {
     #[serde(rename = "output_text")]
     OutputText {
         text: String,
-        #[serde(skip_serializing_if = "Vec::is_empty")]
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
         annotations: Vec<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         logprobs: Option<ChatLogProbs>,
@@ -758,6 +801,18 @@ pub enum ResponseOutputItem {
         output: Option<String>,
         status: String,
     },
+    #[serde(rename = "mcp_call")]
+    McpCall {
+        id: String,
+        server_label: String,
+        name: String,
+        arguments: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        status: String,
+    },
 }
 
 // ============= Service Tier =============
@@ -803,6 +858,7 @@ pub enum ResponseStatus {
     Completed,
     Failed,
     Cancelled,
+    Incomplete,
 }
 
 // ============= Include Fields =============
@@ -1001,6 +1057,10 @@ pub struct ResponsesRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 
+    /// Key grouping requests that share a prompt prefix, for prompt cache affinity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_key: Option<String>,
+
     // ============= VLLM Extensions =============
     /// Request ID
     #[serde(default = "generate_request_id")]
@@ -1199,6 +1259,14 @@ impl GenerationRequest for ResponsesRequest {
                 .join(" "),
         }
     }
+
+    fn request_id(&self) -> Option<&str> {
+        Some(&self.request_id)
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.max_output_tokens
+    }
 }
 
 fn generate_response_id() -> String {
@@ -1617,6 +1685,19 @@ pub struct ErrorDetail {
     pub code: Option<String>,
 }
 
+impl ErrorResponse {
+    pub fn new(message: impl Into<String>, error_type: &str, code: Option<&str>) -> Self {
+        Self {
+            error: ErrorDetail {
+                message: message.into(),
+                error_type: error_type.to_string(),
+                param: None,
+                code: code.map(str::to_string),
+            },
+        }
+    }
+}
+
 // ==================================================================
 // =            SGLANG SPEC - GENERATE API                          =
 // ==================================================================
@@ -1789,6 +1870,17 @@ impl GenerationRequest for GenerateRequest {
         // No text input found
         String::new()
     }
+
+    fn request_id(&self) -> Option<&str> {
+        self.rid.as_deref()
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.sampling_params
+            .as_ref()
+            .and_then(|params| params.max_new_tokens)
+            .or_else(|| self.parameters.as_ref()?.max_new_tokens)
+    }
 }
 
 // ==================================================================
@@ -1916,6 +2008,19 @@ impl GenerationRequest for RerankRequest {
     fn extract_text_for_routing(&self) -> String {
         self.query.clone()
     }
+
+    fn prompt_text(&self) -> String {
+        let mut text = self.query.clone();
+        for document in &self.documents {
+            text.push('\n');
+            text.push_str(document);
+        }
+        text
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        Some(0)
+    }
 }
 
 impl RerankRequest {
@@ -2045,6 +2150,10 @@ impl GenerationRequest for EmbeddingRequest {
             _ => String::new(),
         }
     }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        Some(0)
+    }
 }
 
 // ==================================================================
@@ -2066,6 +2175,21 @@ pub trait GenerationRequest: Send + Sync {
 
     /// Extract text content for routing decisions
     fn extract_text_for_routing(&self) -> String;
+
+    /// Get the client-provided request ID, if the request format carries one
+    fn request_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Text of the prompt, for estimating its token count
+    fn prompt_text(&self) -> String {
+        self.extract_text_for_routing()
+    }
+
+    /// Maximum number of tokens the request may generate, if it sets one
+    fn max_output_tokens(&self) -> Option<u32> {
+        None
+    }
 }
 
 /// Helper type for string or array of strings
@@ -2847,4 +2971,39 @@ mod tests {
         // Only top-level string elements are extracted
         assert_eq!(req.extract_text_for_routing(), "a");
     }
+
+    #[test]
+    fn test_generation_request_token_estimates() {
+        let chat: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "llama",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "Describe"},
+                    {"type": "image_url", "image_url": {"url": "http://img"}}
+                ]}
+            ],
+            "max_tokens": 64,
+            "max_completion_tokens": 32
+        }))
+        .unwrap();
+        assert_eq!(chat.prompt_text(), "Be brief.\nDescribe");
+        assert_eq!(chat.max_output_tokens(), Some(32));
+
+        let generate: GenerateRequest = serde_json::from_value(serde_json::json!({
+            "text": "Hello",
+            "sampling_params": {"max_new_tokens": 16}
+        }))
+        .unwrap();
+        assert_eq!(generate.prompt_text(), "Hello");
+        assert_eq!(generate.max_output_tokens(), Some(16));
+
+        // Embeddings generate nothing
+        let embedding: EmbeddingRequest = serde_json::from_value(serde_json::json!({
+            "model": "emb-model",
+            "input": "Hello"
+        }))
+        .unwrap();
+        assert_eq!(embedding.max_output_tokens(), Some(0));
+    }
 }
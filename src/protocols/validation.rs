@@ -891,6 +891,7 @@ mod tests {
                 frequency_penalty: Some(0.0),
                 logit_bias: None,
                 user: None,
+                prompt_cache_key: None,
                 seed: None,
                 logprobs: false,
                 top_logprobs: None,
@@ -31,6 +31,23 @@ pub struct WorkerConfigRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bootstrap_port: Option<u16>,
 
+    /// Inference backend in IGW mode (optional: "vllm", "trtllm"; default: router backend)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+
+    // OpenAI mode provider settings (optional, ignored by other routers)
+    /// API key sent to the provider in place of the client's credentials
+    #[serde(default, skip_serializing)]
+    pub api_key: Option<String>,
+
+    /// Client model names mapped to the provider's model names
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_map: HashMap<String, String>,
+
+    /// Share of first attempts the provider receives (default: 1, 0 = failover only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+
     // gRPC-specific configuration (optional, ignored in HTTP mode)
     /// Tokenizer path for gRPC mode
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -77,6 +94,9 @@ pub struct WorkerInfo {
     /// Whether the worker is healthy
     pub is_healthy: bool,
 
+    /// Whether the worker is draining
+    pub is_draining: bool,
+
     /// Current load on the worker
     pub load: usize,
 
@@ -143,9 +163,14 @@ pub struct WorkerUpdateRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cost: Option<f32>,
 
-    /// Update labels
+    /// Update labels (merged into the existing labels)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub labels: Option<HashMap<String, String>>,
+
+    /// Start (true) or stop (false) draining: a draining worker finishes its in-flight
+    /// requests but gets no new ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drain: Option<bool>,
 }
 
 /// Generic API response
@@ -0,0 +1,487 @@
+//! Anthropic Messages API front-end
+//!
+//! `POST /v1/messages` requests are translated into chat completion requests, routed
+//! through whichever router is active, and the chat completion responses are translated
+//! back into Anthropic messages. Streaming responses are re-emitted as Anthropic SSE
+//! events (`message_start`, `content_block_start`, `content_block_delta`, ...).
+
+use crate::protocols::anthropic::{ContentBlock, MessagesRequest, MessagesResponse, MessagesUsage};
+use crate::routers::http::responses::SseEventParser;
+use crate::routers::model_alias::{self, ModelAliases};
+use crate::routers::RouterTrait;
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::debug;
+
+/// Route an Anthropic Messages API request through `router`, applying the alias its
+/// model names, if any
+pub async fn route_messages(
+    router: &dyn RouterTrait,
+    aliases: &ModelAliases,
+    headers: HeaderMap,
+    request: &MessagesRequest,
+) -> Response {
+    let chat = match request.to_chat_completion_request() {
+        Ok(chat) => chat,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let response =
+        model_alias::route_with_alias(aliases, headers, chat, |headers, chat| async move {
+            router.route_chat(Some(&headers), &chat, None).await
+        })
+        .await;
+    if !response.status().is_success() {
+        return translate_error(response).await;
+    }
+
+    let message_id = format!("msg_{}", uuid::Uuid::new_v4().simple());
+    if request.stream {
+        return translate_stream(response, message_id, request);
+    }
+
+    let bytes = match to_bytes(response.into_body(), usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to read response body: {}", e),
+            )
+        }
+    };
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(completion) => Json(messages_response(&completion, message_id, request)).into_response(),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Failed to parse chat completion: {}", e),
+        ),
+    }
+}
+
+/// Translate a chat completion into an Anthropic message
+fn messages_response(
+    completion: &Value,
+    message_id: String,
+    request: &MessagesRequest,
+) -> MessagesResponse {
+    let choice = &completion["choices"][0];
+    let message = &choice["message"];
+
+    let mut content = Vec::new();
+    if let Some(text) = message["content"].as_str().filter(|text| !text.is_empty()) {
+        content.push(ContentBlock::Text {
+            text: text.to_string(),
+        });
+    }
+    for call in message["tool_calls"].as_array().into_iter().flatten() {
+        content.push(ContentBlock::ToolUse {
+            id: call["id"].as_str().unwrap_or_default().to_string(),
+            name: call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            input: tool_input(call["function"]["arguments"].as_str().unwrap_or_default()),
+        });
+    }
+
+    let (stop_reason, stop_sequence) = stop_reason(choice, request);
+    MessagesResponse {
+        id: message_id,
+        object_type: "message".to_string(),
+        role: "assistant".to_string(),
+        model: completion["model"]
+            .as_str()
+            .unwrap_or(&request.model)
+            .to_string(),
+        content,
+        stop_reason: Some(stop_reason.to_string()),
+        stop_sequence,
+        usage: usage(&completion["usage"]),
+    }
+}
+
+/// Map a chat completion `finish_reason` to an Anthropic stop reason and stop sequence
+fn stop_reason(choice: &Value, request: &MessagesRequest) -> (&'static str, Option<String>) {
+    match choice["finish_reason"].as_str() {
+        Some("length") => ("max_tokens", None),
+        Some("tool_calls") => ("tool_use", None),
+        _ => {
+            // vLLM reports the matched stop string in `stop_reason`
+            let matched = choice["stop_reason"].as_str().filter(|matched| {
+                request
+                    .stop_sequences
+                    .iter()
+                    .flatten()
+                    .any(|stop| stop == matched)
+            });
+            match matched {
+                Some(matched) => ("stop_sequence", Some(matched.to_string())),
+                None => ("end_turn", None),
+            }
+        }
+    }
+}
+
+fn usage(usage: &Value) -> MessagesUsage {
+    let count = |field: &str| usage[field].as_u64().unwrap_or_default() as u32;
+    MessagesUsage {
+        input_tokens: count("prompt_tokens"),
+        output_tokens: count("completion_tokens"),
+    }
+}
+
+/// Tool call arguments as a JSON object; unparseable arguments become an empty object
+fn tool_input(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({}))
+}
+
+/// Re-emit a chat completion stream as Anthropic SSE events
+fn translate_stream(response: Response, message_id: String, request: &MessagesRequest) -> Response {
+    let (tx, rx) = mpsc::unbounded_channel::<Result<Bytes, String>>();
+    let mut translator = StreamTranslator::new(message_id, request.clone());
+    let mut upstream = response.into_body().into_data_stream();
+
+    tokio::spawn(async move {
+        let mut parser = SseEventParser::default();
+        while let Some(chunk) = upstream.next().await {
+            let bytes = match chunk {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    debug!("Chat completion stream failed: {}", e);
+                    let _ = tx.send(Ok(sse_event(
+                        "error",
+                        &json!({
+                            "type": "error",
+                            "error": {"type": "api_error", "message": format!("Stream error: {}", e)}
+                        }),
+                    )));
+                    return;
+                }
+            };
+            let events: Vec<Bytes> = parser
+                .push(&bytes)
+                .iter()
+                .flat_map(|chunk| translator.on_chunk(chunk))
+                .collect();
+            for event in events {
+                if tx.send(Ok(event)).is_err() {
+                    return;
+                }
+            }
+        }
+        for event in translator.finish() {
+            let _ = tx.send(Ok(event));
+        }
+    });
+
+    let mut response = Response::new(Body::from_stream(UnboundedReceiverStream::new(rx)));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    response
+}
+
+/// Content block currently being streamed
+enum OpenBlock {
+    Text,
+    /// Tool call with the given index in the chat completion deltas
+    ToolUse(u64),
+}
+
+/// Turns chat completion chunks into Anthropic stream events
+struct StreamTranslator {
+    message_id: String,
+    request: MessagesRequest,
+    started: bool,
+    block: Option<OpenBlock>,
+    /// Index of the open block, or of the next one
+    block_index: usize,
+    stop_reason: &'static str,
+    stop_sequence: Option<String>,
+    usage: MessagesUsage,
+}
+
+impl StreamTranslator {
+    fn new(message_id: String, request: MessagesRequest) -> Self {
+        Self {
+            message_id,
+            request,
+            started: false,
+            block: None,
+            block_index: 0,
+            stop_reason: "end_turn",
+            stop_sequence: None,
+            usage: MessagesUsage::default(),
+        }
+    }
+
+    fn on_chunk(&mut self, chunk: &Value) -> Vec<Bytes> {
+        let mut events = Vec::new();
+        if !self.started {
+            self.started = true;
+            let model = chunk["model"].as_str().unwrap_or(&self.request.model);
+            events.push(sse_event(
+                "message_start",
+                &json!({
+                    "type": "message_start",
+                    "message": {
+                        "id": self.message_id,
+                        "type": "message",
+                        "role": "assistant",
+                        "model": model,
+                        "content": [],
+                        "stop_reason": null,
+                        "stop_sequence": null,
+                        "usage": {"input_tokens": 0, "output_tokens": 0}
+                    }
+                }),
+            ));
+        }
+        if chunk["usage"].is_object() {
+            self.usage = usage(&chunk["usage"]);
+        }
+
+        let Some(choice) = chunk["choices"].get(0) else {
+            return events;
+        };
+        let delta = &choice["delta"];
+        if let Some(text) = delta["content"].as_str().filter(|text| !text.is_empty()) {
+            if !matches!(self.block, Some(OpenBlock::Text)) {
+                self.start_block(
+                    OpenBlock::Text,
+                    json!({"type": "text", "text": ""}),
+                    &mut events,
+                );
+            }
+            events.push(self.block_delta(json!({"type": "text_delta", "text": text})));
+        }
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let tool_index = call["index"].as_u64().unwrap_or_default();
+            if !matches!(self.block, Some(OpenBlock::ToolUse(open)) if open == tool_index) {
+                let content_block = json!({
+                    "type": "tool_use",
+                    "id": call["id"].as_str().unwrap_or_default(),
+                    "name": call["function"]["name"].as_str().unwrap_or_default(),
+                    "input": {}
+                });
+                self.start_block(OpenBlock::ToolUse(tool_index), content_block, &mut events);
+            }
+            if let Some(arguments) = call["function"]["arguments"]
+                .as_str()
+                .filter(|arguments| !arguments.is_empty())
+            {
+                events.push(
+                    self.block_delta(
+                        json!({"type": "input_json_delta", "partial_json": arguments}),
+                    ),
+                );
+            }
+        }
+
+        if choice["finish_reason"].is_string() {
+            let (stop_reason, stop_sequence) = stop_reason(choice, &self.request);
+            self.stop_reason = stop_reason;
+            self.stop_sequence = stop_sequence;
+        }
+        events
+    }
+
+    /// Events closing the message once the upstream stream has ended
+    fn finish(&mut self) -> Vec<Bytes> {
+        let mut events = Vec::new();
+        if !self.started {
+            // The worker sent nothing usable; still produce a well-formed message
+            events.extend(self.on_chunk(&json!({})));
+        }
+        self.stop_block(&mut events);
+        events.push(sse_event(
+            "message_delta",
+            &json!({
+                "type": "message_delta",
+                "delta": {"stop_reason": self.stop_reason, "stop_sequence": self.stop_sequence},
+                "usage": {"output_tokens": self.usage.output_tokens}
+            }),
+        ));
+        events.push(sse_event("message_stop", &json!({"type": "message_stop"})));
+        events
+    }
+
+    fn start_block(&mut self, block: OpenBlock, content_block: Value, events: &mut Vec<Bytes>) {
+        self.stop_block(events);
+        events.push(sse_event(
+            "content_block_start",
+            &json!({
+                "type": "content_block_start",
+                "index": self.block_index,
+                "content_block": content_block
+            }),
+        ));
+        self.block = Some(block);
+    }
+
+    fn stop_block(&mut self, events: &mut Vec<Bytes>) {
+        if self.block.take().is_some() {
+            events.push(sse_event(
+                "content_block_stop",
+                &json!({"type": "content_block_stop", "index": self.block_index}),
+            ));
+            self.block_index += 1;
+        }
+    }
+
+    fn block_delta(&self, delta: Value) -> Bytes {
+        sse_event(
+            "content_block_delta",
+            &json!({"type": "content_block_delta", "index": self.block_index, "delta": delta}),
+        )
+    }
+}
+
+fn sse_event(event_type: &str, data: &Value) -> Bytes {
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event_type, data))
+}
+
+/// Convert a failed chat completion response into an Anthropic error
+async fn translate_error(response: Response) -> Response {
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap_or_default();
+    let message = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|body| body["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(&bytes).into_owned());
+    error_response(status, &message)
+}
+
+/// Anthropic-style error body
+fn error_response(status: StatusCode, message: &str) -> Response {
+    let error_type = match status {
+        StatusCode::BAD_REQUEST => "invalid_request_error",
+        StatusCode::UNAUTHORIZED => "authentication_error",
+        StatusCode::FORBIDDEN => "permission_error",
+        StatusCode::NOT_FOUND => "not_found_error",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limit_error",
+        StatusCode::SERVICE_UNAVAILABLE => "overloaded_error",
+        _ => "api_error",
+    };
+    let body = json!({
+        "type": "error",
+        "error": {"type": error_type, "message": message}
+    });
+    (status, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(stop_sequences: Option<Vec<&str>>) -> MessagesRequest {
+        serde_json::from_value(json!({
+            "model": "m",
+            "max_tokens": 16,
+            "stop_sequences": stop_sequences,
+            "messages": [{"role": "user", "content": "hi"}]
+        }))
+        .unwrap()
+    }
+
+    fn event_types(events: &[Bytes]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| {
+                let event = std::str::from_utf8(event).unwrap();
+                event.lines().next().unwrap()["event: ".len()..].to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chat_completion_to_message() {
+        let completion = json!({
+            "model": "m",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": "Let me check",
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 7, "total_tokens": 19}
+        });
+
+        let message = messages_response(&completion, "msg_1".to_string(), &request(None));
+        let message = serde_json::to_value(message).unwrap();
+        assert_eq!(message["type"], "message");
+        assert_eq!(message["stop_reason"], "tool_use");
+        assert_eq!(message["content"][0]["type"], "text");
+        assert_eq!(message["content"][1]["type"], "tool_use");
+        assert_eq!(message["content"][1]["input"]["city"], "Paris");
+        assert_eq!(message["usage"]["input_tokens"], 12);
+        assert_eq!(message["usage"]["output_tokens"], 7);
+    }
+
+    #[test]
+    fn test_stop_sequence_is_reported() {
+        let choice = json!({"finish_reason": "stop", "stop_reason": "END"});
+        assert_eq!(
+            stop_reason(&choice, &request(Some(vec!["END"]))),
+            ("stop_sequence", Some("END".to_string()))
+        );
+        assert_eq!(stop_reason(&choice, &request(None)), ("end_turn", None));
+    }
+
+    #[test]
+    fn test_stream_translation() {
+        let mut translator = StreamTranslator::new("msg_1".to_string(), request(None));
+        let mut events = Vec::new();
+        for chunk in [
+            json!({"model": "m", "choices": [{"delta": {"content": "Hel"}}]}),
+            json!({"choices": [{"delta": {"content": "lo"}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [{
+                "index": 0, "id": "call_1",
+                "function": {"name": "f", "arguments": "{\"a\":"}
+            }]}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [{
+                "index": 0, "function": {"arguments": "1}"}
+            }]}, "finish_reason": "tool_calls"}]}),
+            json!({"choices": [], "usage": {"prompt_tokens": 3, "completion_tokens": 4}}),
+        ] {
+            events.extend(translator.on_chunk(&chunk));
+        }
+        events.extend(translator.finish());
+
+        assert_eq!(
+            event_types(&events),
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        let message_delta = std::str::from_utf8(&events[9]).unwrap();
+        assert!(message_delta.contains(r#""stop_reason":"tool_use""#));
+        assert!(message_delta.contains(r#""output_tokens":4"#));
+    }
+}
@@ -4,7 +4,7 @@ use super::{
     http::{openai_router::OpenAIRouter, pd_router::PDRouter, router::Router, vllm_pd_router::VllmPDRouter},
     RouterTrait,
 };
-use crate::config::{ConnectionMode, PolicyConfig, RoutingMode};
+use crate::config::{ConnectionMode, OpenAIUpstream, PolicyConfig, RoutingMode};
 use crate::policies::PolicyFactory;
 use crate::server::AppContext;
 use std::sync::Arc;
@@ -129,10 +129,16 @@ impl RouterFactory {
         ctx: &Arc<AppContext>,
     ) -> Result<Box<dyn RouterTrait>, String> {
         // Initialize policies in PolicyRegistry - use specific policies if provided, otherwise fall back to main policy
-        let prefill_policy =
-            PolicyFactory::create_from_config(prefill_policy_config.unwrap_or(main_policy_config));
-        let decode_policy =
-            PolicyFactory::create_from_config(decode_policy_config.unwrap_or(main_policy_config));
+        let prefill_policy = PolicyFactory::create_with_options(
+            prefill_policy_config.unwrap_or(main_policy_config),
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
+        let decode_policy = PolicyFactory::create_with_options(
+            decode_policy_config.unwrap_or(main_policy_config),
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
 
         // Set the prefill and decode policies in the registry
         ctx.policy_registry.set_prefill_policy(prefill_policy);
@@ -153,10 +159,16 @@ impl RouterFactory {
         ctx: &Arc<AppContext>,
     ) -> Result<Box<dyn RouterTrait>, String> {
         // Initialize policies in PolicyRegistry - use specific policies if provided, otherwise fall back to main policy
-        let prefill_policy =
-            PolicyFactory::create_from_config(prefill_policy_config.unwrap_or(main_policy_config));
-        let decode_policy =
-            PolicyFactory::create_from_config(decode_policy_config.unwrap_or(main_policy_config));
+        let prefill_policy = PolicyFactory::create_with_options(
+            prefill_policy_config.unwrap_or(main_policy_config),
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
+        let decode_policy = PolicyFactory::create_with_options(
+            decode_policy_config.unwrap_or(main_policy_config),
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
 
         // Set the prefill and decode policies in the registry
         ctx.policy_registry.set_prefill_policy(prefill_policy);
@@ -179,7 +191,11 @@ impl RouterFactory {
         use super::grpc::router::GrpcRouter;
 
         // Create policy
-        let policy = PolicyFactory::create_from_config(policy_config);
+        let policy = PolicyFactory::create_with_options(
+            policy_config,
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
 
         // Create gRPC router with context
         let router = GrpcRouter::new(worker_urls.to_vec(), policy, ctx).await?;
@@ -198,21 +214,24 @@ impl RouterFactory {
     ) -> Result<Box<dyn RouterTrait>, String> {
         use super::grpc::pd_router::GrpcPDRouter;
 
-        // Create policies - use specific policies if provided, otherwise fall back to main policy
-        let prefill_policy =
-            PolicyFactory::create_from_config(prefill_policy_config.unwrap_or(main_policy_config));
-        let decode_policy =
-            PolicyFactory::create_from_config(decode_policy_config.unwrap_or(main_policy_config));
-
-        // Create gRPC PD router with context
-        let router = GrpcPDRouter::new(
-            prefill_urls.to_vec(),
-            decode_urls.to_vec(),
-            prefill_policy,
-            decode_policy,
-            ctx,
-        )
-        .await?;
+        // Initialize policies in PolicyRegistry - use specific policies if provided, otherwise fall back to main policy
+        let prefill_policy = PolicyFactory::create_with_options(
+            prefill_policy_config.unwrap_or(main_policy_config),
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
+        let decode_policy = PolicyFactory::create_with_options(
+            decode_policy_config.unwrap_or(main_policy_config),
+            ctx.tokenizer.clone(),
+            ctx.router_config.load_unit,
+        );
+
+        // Set the prefill and decode policies in the registry
+        ctx.policy_registry.set_prefill_policy(prefill_policy);
+        ctx.policy_registry.set_decode_policy(decode_policy);
+
+        // Create gRPC PD router with context (policies are in PolicyRegistry)
+        let router = GrpcPDRouter::new(prefill_urls.to_vec(), decode_urls.to_vec(), ctx).await?;
 
         Ok(Box::new(router))
     }
@@ -222,14 +241,20 @@ impl RouterFactory {
         worker_urls: Vec<String>,
         ctx: &Arc<AppContext>,
     ) -> Result<Box<dyn RouterTrait>, String> {
-        // Use the first worker URL as the OpenAI-compatible base
-        let base_url = worker_urls
-            .first()
-            .cloned()
-            .ok_or_else(|| "OpenAI mode requires at least one worker URL".to_string())?;
-
-        let router =
-            OpenAIRouter::new(base_url, Some(ctx.router_config.circuit_breaker.clone())).await?;
+        // Bare worker URLs plus the providers configured with credentials
+        let upstreams = worker_urls
+            .into_iter()
+            .map(OpenAIUpstream::new)
+            .chain(ctx.router_config.openai_upstreams.iter().cloned())
+            .collect();
+
+        let router = OpenAIRouter::with_upstreams(
+            upstreams,
+            Some(ctx.router_config.circuit_breaker.clone()),
+        )
+        .await?
+        // The client's Authorization holds a router key once router auth is enabled
+        .with_client_auth_forwarding(!ctx.router_config.auth.is_enabled());
 
         Ok(Box::new(router))
     }
@@ -2,3 +2,4 @@
 
 pub mod pd_router;
 pub mod router;
+pub mod utils;
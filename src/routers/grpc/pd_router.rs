@@ -1,68 +1,69 @@
 // PD (Prefill-Decode) gRPC Router Implementation
 
+use super::utils::{self, DecodeConfig, GenerationState, ResponseFormatter, StreamEnd};
 use crate::config::types::RetryConfig;
 use crate::core::{
-    BasicWorker, CircuitBreakerConfig, HealthChecker, HealthConfig, Worker, WorkerType,
+    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthChecker, HealthConfig,
+    OwnedWorkerLoadGuard, RequestAborter, RetryExecutor, TokenEstimate, TokenLoadGuard, Worker,
+    WorkerType,
 };
-use crate::grpc::VllmSchedulerClient;
+use crate::grpc::{proto, VllmSchedulerClient};
 use crate::metrics::RouterMetrics;
-use crate::policies::LoadBalancingPolicy;
-use crate::reasoning_parser::ParserFactory;
+use crate::policies::{request_affinity_key, LoadBalancingPolicy, PolicyRegistry};
+use crate::protocols::spec::{ChatCompletionRequest, CompletionRequest, GenerateRequest};
+use crate::routers::http::pd_types::{generate_room_id, get_hostname};
 use crate::routers::{RouterTrait, WorkerManagement};
 use crate::tokenizer::traits::Tokenizer;
-use crate::tool_parser::ParserRegistry;
 use async_trait::async_trait;
 use axum::{
     body::Body,
     extract::Request,
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
+    Json,
 };
-use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tracing::{info, warn};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// A selected (prefill, decode) worker pair
+type WorkerPair = (Arc<dyn Worker>, Arc<dyn Worker>);
+
+/// Affinity keys of a request for policies that route on one, None otherwise
+struct AffinityKeys {
+    prefill: Option<String>,
+    decode: Option<String>,
+}
 
 /// gRPC PD (Prefill-Decode) router implementation for SGLang
-#[allow(dead_code)] // Fields will be used once implementation is complete
 pub struct GrpcPDRouter {
     /// Prefill worker connections
     prefill_workers: Arc<RwLock<Vec<Arc<dyn Worker>>>>,
     /// Decode worker connections
     decode_workers: Arc<RwLock<Vec<Arc<dyn Worker>>>>,
-    /// gRPC clients for prefill workers
-    prefill_grpc_clients: Arc<RwLock<HashMap<String, VllmSchedulerClient>>>,
-    /// gRPC clients for decode workers
-    decode_grpc_clients: Arc<RwLock<HashMap<String, VllmSchedulerClient>>>,
-    /// Load balancing policy for prefill
-    prefill_policy: Arc<dyn LoadBalancingPolicy>,
-    /// Load balancing policy for decode
-    decode_policy: Arc<dyn LoadBalancingPolicy>,
+    /// Registry holding the prefill and decode load balancing policies
+    policy_registry: Arc<PolicyRegistry>,
     /// Tokenizer for handling text encoding/decoding
     tokenizer: Arc<dyn Tokenizer>,
-    /// Reasoning parser factory for structured reasoning outputs
-    reasoning_parser_factory: ParserFactory,
-    /// Tool parser registry for function/tool calls
-    tool_parser_registry: &'static ParserRegistry,
     /// Worker health checkers
     _prefill_health_checker: Option<HealthChecker>,
     _decode_health_checker: Option<HealthChecker>,
+    /// Aborts requests on both workers of a pair when the client disconnects
+    aborter: RequestAborter,
     /// Configuration
     timeout_secs: u64,
     interval_secs: u64,
     dp_aware: bool,
-    api_key: Option<String>,
     retry_config: RetryConfig,
-    circuit_breaker_config: CircuitBreakerConfig,
 }
 
 impl GrpcPDRouter {
     /// Create a new gRPC PD router
+    ///
+    /// The prefill and decode policies are taken from the context's `PolicyRegistry`.
     pub async fn new(
         prefill_urls: Vec<(String, Option<u16>)>,
         decode_urls: Vec<String>,
-        prefill_policy: Arc<dyn LoadBalancingPolicy>,
-        decode_policy: Arc<dyn LoadBalancingPolicy>,
         ctx: &Arc<crate::server::AppContext>,
     ) -> Result<Self, String> {
         // Update metrics
@@ -74,14 +75,6 @@ impl GrpcPDRouter {
             .as_ref()
             .ok_or_else(|| "gRPC PD router requires tokenizer".to_string())?
             .clone();
-        let reasoning_parser_factory = ctx
-            .reasoning_parser_factory
-            .as_ref()
-            .ok_or_else(|| "gRPC PD router requires reasoning parser factory".to_string())?
-            .clone();
-        let tool_parser_registry = ctx
-            .tool_parser_registry
-            .ok_or_else(|| "gRPC PD router requires tool parser registry".to_string())?;
 
         // Convert config CircuitBreakerConfig to core CircuitBreakerConfig
         let circuit_breaker_config = ctx.router_config.effective_circuit_breaker_config();
@@ -92,13 +85,33 @@ impl GrpcPDRouter {
             window_duration: Duration::from_secs(circuit_breaker_config.window_duration_secs),
         };
 
-        // Create gRPC clients for prefill workers
-        let mut prefill_grpc_clients = HashMap::new();
-        for (url, _bootstrap_port) in &prefill_urls {
+        let health_config = HealthConfig {
+            timeout_secs: ctx.router_config.health_check.timeout_secs,
+            check_interval_secs: ctx.router_config.health_check.check_interval_secs,
+            endpoint: ctx.router_config.health_check.endpoint.clone(),
+            failure_threshold: ctx.router_config.health_check.failure_threshold,
+            success_threshold: ctx.router_config.health_check.success_threshold,
+        };
+
+        // Create Prefill Worker trait objects with gRPC connection mode
+        let mut prefill_workers: Vec<Arc<dyn Worker>> = Vec::new();
+        for (url, bootstrap_port) in &prefill_urls {
             match VllmSchedulerClient::connect(url).await {
                 Ok(client) => {
-                    prefill_grpc_clients.insert(url.clone(), client);
                     info!("Connected to gRPC prefill worker at {}", url);
+                    let worker = BasicWorker::with_connection_mode(
+                        url.clone(),
+                        WorkerType::Prefill {
+                            bootstrap_port: *bootstrap_port,
+                        },
+                        crate::core::ConnectionMode::Grpc {
+                            port: *bootstrap_port,
+                        },
+                    )
+                    .with_circuit_breaker_config(core_cb_config.clone())
+                    .with_health_config(health_config.clone())
+                    .with_grpc_client(client);
+                    prefill_workers.push(Arc::new(worker));
                 }
                 Err(e) => {
                     warn!("Failed to connect to gRPC prefill worker at {}: {}", url, e);
@@ -107,13 +120,21 @@ impl GrpcPDRouter {
             }
         }
 
-        // Create gRPC clients for decode workers
-        let mut decode_grpc_clients = HashMap::new();
+        // Create Decode Worker trait objects with gRPC connection mode
+        let mut decode_workers: Vec<Arc<dyn Worker>> = Vec::new();
         for url in &decode_urls {
             match VllmSchedulerClient::connect(url).await {
                 Ok(client) => {
-                    decode_grpc_clients.insert(url.clone(), client);
                     info!("Connected to gRPC decode worker at {}", url);
+                    let worker = BasicWorker::with_connection_mode(
+                        url.clone(),
+                        WorkerType::Decode,
+                        crate::core::ConnectionMode::Grpc { port: None },
+                    )
+                    .with_circuit_breaker_config(core_cb_config.clone())
+                    .with_health_config(health_config.clone())
+                    .with_grpc_client(client);
+                    decode_workers.push(Arc::new(worker));
                 }
                 Err(e) => {
                     warn!("Failed to connect to gRPC decode worker at {}: {}", url, e);
@@ -122,65 +143,22 @@ impl GrpcPDRouter {
             }
         }
 
-        if prefill_grpc_clients.is_empty() && decode_grpc_clients.is_empty() {
+        if prefill_workers.is_empty() && decode_workers.is_empty() {
             return Err("Failed to connect to any gRPC workers".to_string());
         }
 
-        // Create Prefill Worker trait objects with gRPC connection mode
-        let prefill_workers: Vec<Arc<dyn Worker>> = prefill_urls
-            .iter()
-            .map(|(url, bootstrap_port)| {
-                let worker = BasicWorker::with_connection_mode(
-                    url.clone(),
-                    WorkerType::Prefill {
-                        bootstrap_port: *bootstrap_port,
-                    },
-                    crate::core::ConnectionMode::Grpc {
-                        port: *bootstrap_port,
-                    },
-                )
-                .with_circuit_breaker_config(core_cb_config.clone())
-                .with_health_config(HealthConfig {
-                    timeout_secs: ctx.router_config.health_check.timeout_secs,
-                    check_interval_secs: ctx.router_config.health_check.check_interval_secs,
-                    endpoint: ctx.router_config.health_check.endpoint.clone(),
-                    failure_threshold: ctx.router_config.health_check.failure_threshold,
-                    success_threshold: ctx.router_config.health_check.success_threshold,
-                });
-                Arc::new(worker) as Arc<dyn Worker>
-            })
-            .collect();
-
-        // Create Decode Worker trait objects with gRPC connection mode
-        let decode_workers: Vec<Arc<dyn Worker>> = decode_urls
-            .iter()
-            .map(|url| {
-                let worker = BasicWorker::with_connection_mode(
-                    url.clone(),
-                    WorkerType::Decode,
-                    crate::core::ConnectionMode::Grpc { port: None },
-                )
-                .with_circuit_breaker_config(core_cb_config.clone())
-                .with_health_config(HealthConfig {
-                    timeout_secs: ctx.router_config.health_check.timeout_secs,
-                    check_interval_secs: ctx.router_config.health_check.check_interval_secs,
-                    endpoint: ctx.router_config.health_check.endpoint.clone(),
-                    failure_threshold: ctx.router_config.health_check.failure_threshold,
-                    success_threshold: ctx.router_config.health_check.success_threshold,
-                });
-                Arc::new(worker) as Arc<dyn Worker>
-            })
-            .collect();
-
         // Initialize policies with workers if needed
-        if let Some(cache_aware) = prefill_policy
+        let policy_registry = Arc::clone(&ctx.policy_registry);
+        if let Some(cache_aware) = policy_registry
+            .get_prefill_policy()
             .as_any()
             .downcast_ref::<crate::policies::CacheAwarePolicy>()
         {
             cache_aware.init_workers(&prefill_workers);
         }
 
-        if let Some(cache_aware) = decode_policy
+        if let Some(cache_aware) = policy_registry
+            .get_decode_policy()
             .as_any()
             .downcast_ref::<crate::policies::CacheAwarePolicy>()
         {
@@ -202,23 +180,354 @@ impl GrpcPDRouter {
         Ok(GrpcPDRouter {
             prefill_workers,
             decode_workers,
-            prefill_grpc_clients: Arc::new(RwLock::new(prefill_grpc_clients)),
-            decode_grpc_clients: Arc::new(RwLock::new(decode_grpc_clients)),
-            prefill_policy,
-            decode_policy,
+            policy_registry,
             tokenizer,
-            reasoning_parser_factory,
-            tool_parser_registry,
             _prefill_health_checker: Some(prefill_health_checker),
             _decode_health_checker: Some(decode_health_checker),
+            aborter: RequestAborter::new(ctx.client.clone(), ctx.router_config.api_key.clone()),
             timeout_secs: ctx.router_config.worker_startup_timeout_secs,
             interval_secs: ctx.router_config.worker_startup_check_interval_secs,
             dp_aware: ctx.router_config.dp_aware,
-            api_key: ctx.router_config.api_key.clone(),
             retry_config: ctx.router_config.effective_retry_config(),
-            circuit_breaker_config: core_cb_config,
         })
     }
+
+    /// Select a pair of prefill and decode workers considering circuit breaker state
+    fn select_pd_pair(
+        &self,
+        prefill_text: Option<&str>,
+        decode_text: Option<&str>,
+    ) -> Result<WorkerPair, String> {
+        let prefill_workers = self.prefill_workers.read().unwrap().clone();
+        let decode_workers = self.decode_workers.read().unwrap().clone();
+
+        // Use separate policies for prefill and decode to avoid counter conflicts
+        let prefill_policy = self.policy_registry.get_prefill_policy();
+        let decode_policy = self.policy_registry.get_decode_policy();
+
+        let prefill =
+            pick_worker_by_policy(&prefill_workers, &*prefill_policy, prefill_text, "prefill")?;
+        let decode =
+            pick_worker_by_policy(&decode_workers, &*decode_policy, decode_text, "decode")?;
+
+        Ok((prefill, decode))
+    }
+
+    /// Affinity keys of a request under the prefill and decode policies
+    fn affinity_keys<T: serde::Serialize>(
+        &self,
+        headers: Option<&HeaderMap>,
+        request: &T,
+    ) -> AffinityKeys {
+        AffinityKeys {
+            prefill: request_affinity_key(
+                &*self.policy_registry.get_prefill_policy(),
+                headers,
+                request,
+            ),
+            decode: request_affinity_key(
+                &*self.policy_registry.get_decode_policy(),
+                headers,
+                request,
+            ),
+        }
+    }
+
+    /// Bootstrap parameters that let the decode worker pull the KV cache from the prefill worker
+    fn disaggregated_params(prefill: &dyn Worker) -> proto::DisaggregatedParams {
+        let bootstrap_port = match prefill.worker_type() {
+            WorkerType::Prefill { bootstrap_port } => bootstrap_port,
+            _ => None,
+        };
+
+        proto::DisaggregatedParams {
+            bootstrap_host: get_hostname(prefill.url()),
+            bootstrap_port: bootstrap_port.map(i32::from).unwrap_or(0),
+            // The proto carries the room as an int32
+            bootstrap_room: (generate_room_id() & i32::MAX as u64) as i32,
+        }
+    }
+
+    /// Route a generate request to a prefill/decode pair, retrying on retryable failures
+    ///
+    /// Policies keyed on request headers or fields route on their key from
+    /// `affinity_keys` instead of the prompt text.
+    async fn route_generation(
+        &self,
+        route: &str,
+        request: proto::GenerateRequest,
+        affinity_keys: AffinityKeys,
+        formatter: ResponseFormatter,
+        is_stream: bool,
+    ) -> Response {
+        let start = Instant::now();
+        let decode_config = request
+            .sampling_params
+            .as_ref()
+            .map(DecodeConfig::from_sampling_params)
+            .unwrap_or_default();
+        let routing_text = request
+            .tokenized
+            .as_ref()
+            .map(|t| t.original_text.clone())
+            .unwrap_or_default();
+
+        let response = RetryExecutor::execute_response_with_retry(
+            &self.retry_config,
+            |_: u32| async {
+                let (prefill, decode) = match self.select_pd_pair(
+                    Some(affinity_keys.prefill.as_deref().unwrap_or(&routing_text)),
+                    Some(affinity_keys.decode.as_deref().unwrap_or(&routing_text)),
+                ) {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        error!("Failed to select PD pair error={}", e);
+                        RouterMetrics::record_pd_error("server_selection");
+                        return (StatusCode::SERVICE_UNAVAILABLE, e).into_response();
+                    }
+                };
+
+                self.send_pd_request(
+                    route,
+                    prefill,
+                    decode,
+                    request.clone(),
+                    &decode_config,
+                    &formatter,
+                    is_stream,
+                )
+                .await
+            },
+            |res, _attempt| is_retryable_status(res.status()),
+            |delay, attempt| {
+                RouterMetrics::record_retry(route);
+                RouterMetrics::record_retry_backoff_duration(delay, attempt);
+            },
+            || RouterMetrics::record_retries_exhausted(route),
+        )
+        .await;
+
+        if response.status().is_success() {
+            RouterMetrics::record_pd_request(route);
+            RouterMetrics::record_pd_request_duration(route, start.elapsed());
+        }
+
+        response
+    }
+
+    /// Dispatch a request to both workers of a pair and stream the decode output back.
+    ///
+    /// As with the HTTP PD router, the prefill response is only waited for when
+    /// logprobs are requested; its logprobs are then placed ahead of the decode ones.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_pd_request(
+        &self,
+        route: &str,
+        prefill: Arc<dyn Worker>,
+        decode: Arc<dyn Worker>,
+        mut request: proto::GenerateRequest,
+        decode_config: &DecodeConfig,
+        formatter: &ResponseFormatter,
+        is_stream: bool,
+    ) -> Response {
+        let (prefill_client, decode_client) = match (prefill.grpc_client(), decode.grpc_client()) {
+            (Some(prefill_client), Some(decode_client)) => (prefill_client, decode_client),
+            _ => {
+                error!(
+                    "No gRPC client available for prefill={} or decode={}",
+                    prefill.url(),
+                    decode.url()
+                );
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "No gRPC client available for the selected workers",
+                )
+                    .into_response();
+            }
+        };
+        // Tonic clients are cheap to clone; don't hold the locks for the whole stream
+        let prefill_client = prefill_client.lock().await.clone();
+        let decode_client = decode_client.lock().await.clone();
+
+        request.disaggregated_params = Some(Self::disaggregated_params(prefill.as_ref()));
+        let request_id = request.request_id.clone();
+        let return_logprob = request.return_logprob;
+        let prompt_tokens = request
+            .tokenized
+            .as_ref()
+            .map(|t| t.input_ids.len() as u32)
+            .unwrap_or(0);
+
+        // Both workers hold the request until the decode output has been delivered. The
+        // prefill worker holds the prompt's tokens until decoding starts, the decode worker
+        // the prompt and decode budget until the request ends. Dropping the guards releases
+        // the loads, even if the response is abandoned
+        let load = (
+            OwnedWorkerLoadGuard::new(prefill.clone()),
+            OwnedWorkerLoadGuard::new(decode.clone()),
+        );
+        let tokens = utils::token_estimate(&request);
+        let prefill_tokens = TokenLoadGuard::new(
+            prefill.clone(),
+            TokenEstimate {
+                prompt_tokens: tokens.prompt_tokens,
+                decode_budget: 0,
+            },
+        );
+        let decode_tokens = TokenLoadGuard::new(decode.clone(), tokens);
+
+        debug!(
+            "Sending concurrent requests to prefill={} decode={}",
+            prefill.url(),
+            decode.url()
+        );
+        let prefill_future = {
+            let request = request.clone();
+            async move {
+                let (stream, first) = utils::open_generate_stream(prefill_client, request).await?;
+                utils::drain_generate_stream(stream, first).await
+            }
+        };
+        let decode_future = utils::open_generate_stream(decode_client, request);
+
+        let mut prefill_logprobs = Vec::new();
+        let decode_result = if return_logprob {
+            // When we need logprobs, wait for both workers
+            let (prefill_result, decode_result) = tokio::join!(prefill_future, decode_future);
+            match prefill_result {
+                Ok(logprobs) => {
+                    prefill.record_outcome(true);
+                    prefill_logprobs = logprobs;
+                }
+                Err((status, message)) => {
+                    prefill.record_outcome(false);
+                    RouterMetrics::record_pd_prefill_error(prefill.url());
+                    error!(
+                        "Prefill server returned error prefill_url={} status={} error={}",
+                        prefill.url(),
+                        status,
+                        message
+                    );
+                    return (status, format!("Prefill server error: {}", message)).into_response();
+                }
+            }
+            decode_result
+        } else {
+            // Only the decode output reaches the client; drain the prefill stream in the background
+            let prefill_worker = prefill.clone();
+            tokio::spawn(async move {
+                let result = prefill_future.await;
+                if let Err((status, message)) = &result {
+                    RouterMetrics::record_pd_prefill_error(prefill_worker.url());
+                    error!(
+                        "Prefill server returned error prefill_url={} status={} error={}. Decode will timeout without prefill KV cache.",
+                        prefill_worker.url(),
+                        status,
+                        message
+                    );
+                }
+                prefill_worker.record_outcome(result.is_ok());
+            });
+            decode_future.await
+        };
+
+        let (stream, first) = match decode_result {
+            Ok(opened) => opened,
+            Err((status, message)) => {
+                decode.record_outcome(false);
+                RouterMetrics::record_pd_decode_error(decode.url());
+                error!(
+                    "Decode server returned error decode_url={} status={} error={}",
+                    decode.url(),
+                    status,
+                    message
+                );
+                return (status, message).into_response();
+            }
+        };
+        // Decoding has started, so the prompt's KV cache has left the prefill worker
+        drop(prefill_tokens);
+        decode.record_outcome(true);
+        RouterMetrics::record_pd_prefill_request(prefill.url());
+        RouterMetrics::record_pd_decode_request(decode.url());
+
+        let mut state = GenerationState::new(self.tokenizer.clone(), decode_config, prompt_tokens);
+        if !prefill_logprobs.is_empty() {
+            let merged = prefill_logprobs
+                .iter()
+                .flat_map(|lp| utils::convert_logprobs(self.tokenizer.as_ref(), lp))
+                .collect();
+            state.prepend_logprobs(merged);
+        }
+
+        let on_finish = {
+            let aborter = self.aborter.clone();
+            let route = route.to_string();
+            let request_id = request_id.clone();
+            move |end: StreamEnd| {
+                if end == StreamEnd::ClientDisconnected {
+                    // The prefill worker may still hold the request's KV cache for the transfer
+                    aborter.abort(&route, &request_id, vec![prefill, decode]);
+                }
+                drop((load, decode_tokens));
+            }
+        };
+        utils::generation_response(
+            stream,
+            first,
+            state,
+            formatter.clone(),
+            request_id,
+            is_stream,
+            on_finish,
+        )
+        .await
+    }
+
+    fn worker_counts(workers: &[Arc<dyn Worker>]) -> (usize, usize) {
+        let healthy = workers.iter().filter(|w| w.is_healthy()).count();
+        (healthy, workers.len())
+    }
+}
+
+/// Select a worker using the policy, skipping unavailable workers
+fn pick_worker_by_policy(
+    workers: &[Arc<dyn Worker>],
+    policy: &dyn LoadBalancingPolicy,
+    request_text: Option<&str>,
+    worker_type: &str,
+) -> Result<Arc<dyn Worker>, String> {
+    if workers.is_empty() {
+        return Err(format!(
+            "No {} workers available. Please check if {} servers are configured and healthy.",
+            worker_type, worker_type
+        ));
+    }
+
+    let available_workers: Vec<Arc<dyn Worker>> = workers
+        .iter()
+        .filter(|w| w.is_available())
+        .cloned()
+        .collect();
+
+    if available_workers.is_empty() {
+        return Err(format!(
+            "No available {} workers (all circuits open or unhealthy)",
+            worker_type
+        ));
+    }
+
+    let selected_idx = policy
+        .select_worker(&available_workers, request_text)
+        .ok_or_else(|| {
+            format!(
+                "Policy {} failed to select a {} worker",
+                policy.name(),
+                worker_type
+            )
+        })?;
+
+    Ok(available_workers[selected_idx].clone())
 }
 
 impl std::fmt::Debug for GrpcPDRouter {
@@ -246,7 +555,27 @@ impl RouterTrait for GrpcPDRouter {
     }
 
     async fn health(&self, _req: Request<Body>) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let mut unhealthy_servers = Vec::new();
+        for worker in self.prefill_workers.read().unwrap().iter() {
+            if !worker.is_healthy() {
+                unhealthy_servers.push(format!("Prefill: {}", worker.url()));
+            }
+        }
+        for worker in self.decode_workers.read().unwrap().iter() {
+            if !worker.is_healthy() {
+                unhealthy_servers.push(format!("Decode: {}", worker.url()));
+            }
+        }
+
+        if unhealthy_servers.is_empty() {
+            (StatusCode::OK, "All servers healthy").into_response()
+        } else {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Unhealthy servers: {:?}", unhealthy_servers),
+            )
+                .into_response()
+        }
     }
 
     async fn health_generate(&self, _req: Request<Body>) -> Response {
@@ -267,29 +596,68 @@ impl RouterTrait for GrpcPDRouter {
 
     async fn route_generate(
         &self,
-        _headers: Option<&HeaderMap>,
-        _body: &crate::protocols::spec::GenerateRequest,
+        headers: Option<&HeaderMap>,
+        body: &GenerateRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let mut request = match utils::build_generate_request(self.tokenizer.as_ref(), body) {
+            Ok(request) => request,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+        // Prefill logprobs are merged into the decode output when requested
+        request.return_logprob = body.return_logprob;
+
+        self.route_generation(
+            "/generate",
+            request,
+            self.affinity_keys(headers, body),
+            ResponseFormatter::Generate,
+            body.stream,
+        )
+        .await
     }
 
     async fn route_chat(
         &self,
-        _headers: Option<&HeaderMap>,
-        _body: &crate::protocols::spec::ChatCompletionRequest,
+        headers: Option<&HeaderMap>,
+        body: &ChatCompletionRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let request = match utils::build_chat_request(self.tokenizer.as_ref(), body) {
+            Ok(request) => request,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
+        self.route_generation(
+            "/v1/chat/completions",
+            request,
+            self.affinity_keys(headers, body),
+            ResponseFormatter::for_chat(body),
+            body.stream,
+        )
+        .await
     }
 
     async fn route_completion(
         &self,
-        _headers: Option<&HeaderMap>,
-        _body: &crate::protocols::spec::CompletionRequest,
+        headers: Option<&HeaderMap>,
+        body: &CompletionRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let (request, prompt) = match utils::build_completion_request(self.tokenizer.as_ref(), body)
+        {
+            Ok(built) => built,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
+        self.route_generation(
+            "/v1/completions",
+            request,
+            self.affinity_keys(headers, body),
+            ResponseFormatter::for_completion(body, &prompt),
+            body.stream,
+        )
+        .await
     }
 
     async fn route_responses(
@@ -332,7 +700,25 @@ impl RouterTrait for GrpcPDRouter {
     }
 
     async fn get_worker_loads(&self) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let loads = |workers: &RwLock<Vec<Arc<dyn Worker>>>| -> Vec<serde_json::Value> {
+            workers
+                .read()
+                .unwrap()
+                .iter()
+                .map(|w| {
+                    serde_json::json!({
+                        "worker": w.url(),
+                        "load": w.load()
+                    })
+                })
+                .collect()
+        };
+
+        Json(serde_json::json!({
+            "prefill": loads(&self.prefill_workers),
+            "decode": loads(&self.decode_workers)
+        }))
+        .into_response()
     }
 
     fn router_type(&self) -> &'static str {
@@ -340,19 +726,96 @@ impl RouterTrait for GrpcPDRouter {
     }
 
     fn readiness(&self) -> Response {
-        (StatusCode::SERVICE_UNAVAILABLE).into_response()
+        // Ready if there is at least one healthy prefill AND one healthy decode worker
+        let (healthy_prefill_count, total_prefill) =
+            Self::worker_counts(&self.prefill_workers.read().unwrap());
+        let (healthy_decode_count, total_decode) =
+            Self::worker_counts(&self.decode_workers.read().unwrap());
+
+        let workers = serde_json::json!({
+            "prefill": {
+                "healthy": healthy_prefill_count,
+                "total": total_prefill
+            },
+            "decode": {
+                "healthy": healthy_decode_count,
+                "total": total_decode
+            }
+        });
+
+        if healthy_prefill_count > 0 && healthy_decode_count > 0 {
+            let mut body = workers;
+            body["status"] = serde_json::json!("ready");
+            Json(body).into_response()
+        } else {
+            let mut reasons = Vec::new();
+            if healthy_prefill_count == 0 {
+                reasons.push("no healthy prefill workers");
+            }
+            if healthy_decode_count == 0 {
+                reasons.push("no healthy decode workers");
+            }
+
+            let mut body = workers;
+            body["status"] = serde_json::json!("not_ready");
+            body["reason"] = serde_json::json!(reasons.join(", "));
+            (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response()
+        }
     }
 }
 
 #[async_trait]
 impl WorkerManagement for GrpcPDRouter {
     async fn add_worker(&self, _worker_url: &str) -> Result<String, String> {
-        Err("Not implemented".to_string())
+        // Workers are added per role; a bare URL doesn't say whether it's prefill or decode
+        Err("gRPC PD router does not support adding workers without a worker type".to_string())
     }
 
-    fn remove_worker(&self, _worker_url: &str) {}
+    fn remove_worker(&self, worker_url: &str) {
+        for (workers, policy, role) in [
+            (
+                &self.prefill_workers,
+                self.policy_registry.get_prefill_policy(),
+                "prefill",
+            ),
+            (
+                &self.decode_workers,
+                self.policy_registry.get_decode_policy(),
+                "decode",
+            ),
+        ] {
+            let mut workers = workers.write().unwrap();
+            let before = workers.len();
+            workers.retain(|w| w.url() != worker_url);
+            if workers.len() == before {
+                continue;
+            }
+            if let Some(cache_aware) = policy
+                .as_any()
+                .downcast_ref::<crate::policies::CacheAwarePolicy>()
+            {
+                cache_aware.remove_worker_by_url(worker_url);
+            }
+            info!("Removed gRPC {} worker: {}", role, worker_url);
+        }
+        RouterMetrics::set_active_workers(self.get_worker_urls().len());
+    }
 
     fn get_worker_urls(&self) -> Vec<String> {
-        vec![]
+        let mut urls: Vec<String> = self
+            .prefill_workers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|w| w.url().to_string())
+            .collect();
+        urls.extend(
+            self.decode_workers
+                .read()
+                .unwrap()
+                .iter()
+                .map(|w| w.url().to_string()),
+        );
+        urls
     }
 }
@@ -1,50 +1,48 @@
 // gRPC Router Implementation
 
+use super::utils::{self, DecodeConfig, GenerationState, ResponseFormatter, StreamEnd};
 use crate::config::types::RetryConfig;
 use crate::core::{
-    BasicWorker, CircuitBreakerConfig, HealthChecker, HealthConfig, Worker, WorkerType,
+    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthChecker, HealthConfig,
+    OwnedWorkerLoadGuard, RequestAborter, RetryExecutor, TokenLoadGuard, Worker, WorkerType,
 };
-use crate::grpc::VllmSchedulerClient;
+use crate::grpc::{proto, VllmSchedulerClient};
 use crate::metrics::RouterMetrics;
-use crate::policies::LoadBalancingPolicy;
-use crate::reasoning_parser::ParserFactory;
+use crate::policies::{request_affinity_key, LoadBalancingPolicy};
+use crate::protocols::spec::{ChatCompletionRequest, CompletionRequest, GenerateRequest};
 use crate::routers::{RouterTrait, WorkerManagement};
 use crate::tokenizer::traits::Tokenizer;
-use crate::tool_parser::ParserRegistry;
 use async_trait::async_trait;
 use axum::{
     body::Body,
     extract::Request,
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
+    Json,
 };
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tracing::{info, warn};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
 
 /// gRPC router implementation for SGLang
-#[allow(dead_code)] // Fields will be used once implementation is complete
 pub struct GrpcRouter {
     /// Worker connections
     workers: Arc<RwLock<Vec<Arc<dyn Worker>>>>,
-    /// gRPC clients for each worker
-    grpc_clients: Arc<RwLock<HashMap<String, VllmSchedulerClient>>>,
     /// Load balancing policy
     policy: Arc<dyn LoadBalancingPolicy>,
     /// Tokenizer for handling text encoding/decoding
     tokenizer: Arc<dyn Tokenizer>,
-    /// Reasoning parser factory for structured reasoning outputs
-    reasoning_parser_factory: ParserFactory,
-    /// Tool parser registry for function/tool calls
-    tool_parser_registry: &'static ParserRegistry,
     /// Worker health checker
     _health_checker: Option<HealthChecker>,
+    /// Health check configuration applied to new workers
+    health_config: HealthConfig,
+    /// Aborts requests on workers when the client disconnects
+    aborter: RequestAborter,
     /// Configuration
     timeout_secs: u64,
     interval_secs: u64,
     dp_aware: bool,
-    api_key: Option<String>,
     retry_config: RetryConfig,
     circuit_breaker_config: CircuitBreakerConfig,
 }
@@ -65,14 +63,6 @@ impl GrpcRouter {
             .as_ref()
             .ok_or_else(|| "gRPC router requires tokenizer".to_string())?
             .clone();
-        let reasoning_parser_factory = ctx
-            .reasoning_parser_factory
-            .as_ref()
-            .ok_or_else(|| "gRPC router requires reasoning parser factory".to_string())?
-            .clone();
-        let tool_parser_registry = ctx
-            .tool_parser_registry
-            .ok_or_else(|| "gRPC router requires tool parser registry".to_string())?;
 
         // Convert config CircuitBreakerConfig to core CircuitBreakerConfig
         let circuit_breaker_config = ctx.router_config.effective_circuit_breaker_config();
@@ -102,28 +92,22 @@ impl GrpcRouter {
             return Err("Failed to connect to any gRPC workers".to_string());
         }
 
+        let health_config = HealthConfig {
+            timeout_secs: ctx.router_config.health_check.timeout_secs,
+            check_interval_secs: ctx.router_config.health_check.check_interval_secs,
+            endpoint: ctx.router_config.health_check.endpoint.clone(),
+            failure_threshold: ctx.router_config.health_check.failure_threshold,
+            success_threshold: ctx.router_config.health_check.success_threshold,
+        };
+
         // Create Worker trait objects with gRPC connection mode
         let mut workers: Vec<Arc<dyn Worker>> = Vec::new();
 
         // Move clients from the HashMap to the workers
         for url in &worker_urls {
             if let Some(client) = grpc_clients.remove(url) {
-                let worker = BasicWorker::with_connection_mode(
-                    url.clone(),
-                    WorkerType::Regular,
-                    crate::core::ConnectionMode::Grpc { port: None },
-                )
-                .with_circuit_breaker_config(core_cb_config.clone())
-                .with_health_config(HealthConfig {
-                    timeout_secs: ctx.router_config.health_check.timeout_secs,
-                    check_interval_secs: ctx.router_config.health_check.check_interval_secs,
-                    endpoint: ctx.router_config.health_check.endpoint.clone(),
-                    failure_threshold: ctx.router_config.health_check.failure_threshold,
-                    success_threshold: ctx.router_config.health_check.success_threshold,
-                })
-                .with_grpc_client(client);
-
-                workers.push(Arc::new(worker) as Arc<dyn Worker>);
+                let worker = Self::create_worker(url, client, &core_cb_config, &health_config);
+                workers.push(worker);
             } else {
                 warn!("No gRPC client for worker {}, skipping", url);
             }
@@ -145,20 +129,194 @@ impl GrpcRouter {
 
         Ok(GrpcRouter {
             workers,
-            grpc_clients: Arc::new(RwLock::new(grpc_clients)),
             policy,
             tokenizer,
-            reasoning_parser_factory,
-            tool_parser_registry,
             _health_checker: Some(health_checker),
+            health_config,
+            aborter: RequestAborter::new(ctx.client.clone(), ctx.router_config.api_key.clone()),
             timeout_secs: ctx.router_config.worker_startup_timeout_secs,
             interval_secs: ctx.router_config.worker_startup_check_interval_secs,
             dp_aware: ctx.router_config.dp_aware,
-            api_key: ctx.router_config.api_key.clone(),
             retry_config: ctx.router_config.effective_retry_config(),
             circuit_breaker_config: core_cb_config,
         })
     }
+
+    fn create_worker(
+        url: &str,
+        client: VllmSchedulerClient,
+        circuit_breaker_config: &CircuitBreakerConfig,
+        health_config: &HealthConfig,
+    ) -> Arc<dyn Worker> {
+        let worker = BasicWorker::with_connection_mode(
+            url.to_string(),
+            WorkerType::Regular,
+            crate::core::ConnectionMode::Grpc { port: None },
+        )
+        .with_circuit_breaker_config(circuit_breaker_config.clone())
+        .with_health_config(health_config.clone())
+        .with_grpc_client(client);
+
+        Arc::new(worker)
+    }
+
+    /// Select an available worker using the load balancing policy
+    fn select_worker(&self, text: Option<&str>) -> Option<Arc<dyn Worker>> {
+        let workers = self.workers.read().unwrap();
+        let available: Vec<Arc<dyn Worker>> = workers
+            .iter()
+            .filter(|w| w.is_available())
+            .cloned()
+            .collect();
+        if available.is_empty() {
+            return None;
+        }
+
+        let idx = self.policy.select_worker(&available, text)?;
+        Some(available[idx].clone())
+    }
+
+    /// Route a generate request to a worker, retrying on retryable failures
+    ///
+    /// Policies keyed on request headers or fields route on `affinity_key` instead of the
+    /// prompt text.
+    async fn route_generation(
+        &self,
+        route: &str,
+        request: proto::GenerateRequest,
+        affinity_key: Option<String>,
+        formatter: ResponseFormatter,
+        is_stream: bool,
+    ) -> Response {
+        let start = Instant::now();
+        let decode_config = request
+            .sampling_params
+            .as_ref()
+            .map(DecodeConfig::from_sampling_params)
+            .unwrap_or_default();
+        let routing_text = affinity_key.unwrap_or_else(|| {
+            request
+                .tokenized
+                .as_ref()
+                .map(|t| t.original_text.clone())
+                .unwrap_or_default()
+        });
+
+        let response = RetryExecutor::execute_response_with_retry(
+            &self.retry_config,
+            |_: u32| async {
+                let worker = match self.select_worker(Some(&routing_text)) {
+                    Some(w) => w,
+                    None => {
+                        RouterMetrics::record_request_error(route, "no_available_workers");
+                        return (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            "No available workers (all circuits open or unhealthy)",
+                        )
+                            .into_response();
+                    }
+                };
+
+                let response = self
+                    .send_generate_request(
+                        route,
+                        worker.clone(),
+                        request.clone(),
+                        &decode_config,
+                        &formatter,
+                        is_stream,
+                    )
+                    .await;
+
+                worker.record_outcome(response.status().is_success());
+                response
+            },
+            |res, _attempt| is_retryable_status(res.status()),
+            |delay, attempt| {
+                RouterMetrics::record_retry(route);
+                RouterMetrics::record_retry_backoff_duration(delay, attempt);
+            },
+            || RouterMetrics::record_retries_exhausted(route),
+        )
+        .await;
+
+        if response.status().is_success() {
+            RouterMetrics::record_request(route);
+            RouterMetrics::record_generate_duration(start.elapsed());
+        } else if !is_retryable_status(response.status()) {
+            RouterMetrics::record_request_error(route, "non_retryable_error");
+        }
+
+        response
+    }
+
+    /// Send a generate request to a single worker and turn its token stream into a response
+    async fn send_generate_request(
+        &self,
+        route: &str,
+        worker: Arc<dyn Worker>,
+        request: proto::GenerateRequest,
+        decode_config: &DecodeConfig,
+        formatter: &ResponseFormatter,
+        is_stream: bool,
+    ) -> Response {
+        let client = match worker.grpc_client() {
+            Some(client) => client,
+            None => {
+                error!("No gRPC client available for worker {}", worker.url());
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("No gRPC client available for worker {}", worker.url()),
+                )
+                    .into_response();
+            }
+        };
+        // Tonic clients are cheap to clone; don't hold the lock for the whole stream
+        let client = client.lock().await.clone();
+
+        let request_id = request.request_id.clone();
+        let prompt_tokens = request
+            .tokenized
+            .as_ref()
+            .map(|t| t.input_ids.len() as u32)
+            .unwrap_or(0);
+
+        // The prompt is already tokenized; the decode budget is held until the request ends.
+        // Both loads are released when the guards drop, even if the response is abandoned
+        let token_load = TokenLoadGuard::new(worker.clone(), utils::token_estimate(&request));
+        let load = OwnedWorkerLoadGuard::new(worker.clone());
+
+        let (stream, first) = match utils::open_generate_stream(client, request).await {
+            Ok(opened) => opened,
+            Err((status, message)) => {
+                error!("{} (request_id={})", message, request_id);
+                return (status, message).into_response();
+            }
+        };
+
+        let state = GenerationState::new(self.tokenizer.clone(), decode_config, prompt_tokens);
+        let on_finish = {
+            let aborter = self.aborter.clone();
+            let route = route.to_string();
+            let request_id = request_id.clone();
+            move |end: StreamEnd| {
+                if end == StreamEnd::ClientDisconnected {
+                    aborter.abort(&route, &request_id, vec![worker]);
+                }
+                drop((load, token_load));
+            }
+        };
+        utils::generation_response(
+            stream,
+            first,
+            state,
+            formatter.clone(),
+            request_id,
+            is_stream,
+            on_finish,
+        )
+        .await
+    }
 }
 
 impl std::fmt::Debug for GrpcRouter {
@@ -179,7 +337,24 @@ impl RouterTrait for GrpcRouter {
     }
 
     async fn health(&self, _req: Request<Body>) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let unhealthy_servers: Vec<String> = self
+            .workers
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|w| !w.is_healthy())
+            .map(|w| w.url().to_string())
+            .collect();
+
+        if unhealthy_servers.is_empty() {
+            (StatusCode::OK, "All servers healthy").into_response()
+        } else {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Unhealthy servers: {:?}", unhealthy_servers),
+            )
+                .into_response()
+        }
     }
 
     async fn health_generate(&self, _req: Request<Body>) -> Response {
@@ -200,29 +375,66 @@ impl RouterTrait for GrpcRouter {
 
     async fn route_generate(
         &self,
-        _headers: Option<&HeaderMap>,
-        _body: &crate::protocols::spec::GenerateRequest,
+        headers: Option<&HeaderMap>,
+        body: &GenerateRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let request = match utils::build_generate_request(self.tokenizer.as_ref(), body) {
+            Ok(request) => request,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
+        self.route_generation(
+            "/generate",
+            request,
+            request_affinity_key(self.policy.as_ref(), headers, body),
+            ResponseFormatter::Generate,
+            body.stream,
+        )
+        .await
     }
 
     async fn route_chat(
         &self,
-        _headers: Option<&HeaderMap>,
-        _body: &crate::protocols::spec::ChatCompletionRequest,
+        headers: Option<&HeaderMap>,
+        body: &ChatCompletionRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let request = match utils::build_chat_request(self.tokenizer.as_ref(), body) {
+            Ok(request) => request,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
+        self.route_generation(
+            "/v1/chat/completions",
+            request,
+            request_affinity_key(self.policy.as_ref(), headers, body),
+            ResponseFormatter::for_chat(body),
+            body.stream,
+        )
+        .await
     }
 
     async fn route_completion(
         &self,
-        _headers: Option<&HeaderMap>,
-        _body: &crate::protocols::spec::CompletionRequest,
+        headers: Option<&HeaderMap>,
+        body: &CompletionRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let (request, prompt) = match utils::build_completion_request(self.tokenizer.as_ref(), body)
+        {
+            Ok(built) => built,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
+        self.route_generation(
+            "/v1/completions",
+            request,
+            request_affinity_key(self.policy.as_ref(), headers, body),
+            ResponseFormatter::for_completion(body, &prompt),
+            body.stream,
+        )
+        .await
     }
 
     async fn route_responses(
@@ -265,7 +477,20 @@ impl RouterTrait for GrpcRouter {
     }
 
     async fn get_worker_loads(&self) -> Response {
-        (StatusCode::NOT_IMPLEMENTED).into_response()
+        let loads: Vec<serde_json::Value> = self
+            .workers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|w| {
+                serde_json::json!({
+                    "worker": w.url(),
+                    "load": w.load()
+                })
+            })
+            .collect();
+
+        Json(serde_json::json!({ "workers": loads })).into_response()
     }
 
     fn router_type(&self) -> &'static str {
@@ -273,17 +498,81 @@ impl RouterTrait for GrpcRouter {
     }
 
     fn readiness(&self) -> Response {
-        (StatusCode::SERVICE_UNAVAILABLE).into_response()
+        let workers = self.workers.read().unwrap();
+        let healthy_count = workers.iter().filter(|w| w.is_healthy()).count();
+        let total_workers = workers.len();
+
+        if healthy_count > 0 {
+            Json(serde_json::json!({
+                "status": "ready",
+                "healthy_workers": healthy_count,
+                "total_workers": total_workers
+            }))
+            .into_response()
+        } else {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({
+                    "status": "not_ready",
+                    "reason": "no healthy workers available",
+                    "total_workers": total_workers
+                })),
+            )
+                .into_response()
+        }
     }
 }
 
 #[async_trait]
 impl WorkerManagement for GrpcRouter {
-    async fn add_worker(&self, _worker_url: &str) -> Result<String, String> {
-        Err("Not implemented".to_string())
+    async fn add_worker(&self, worker_url: &str) -> Result<String, String> {
+        if self
+            .workers
+            .read()
+            .unwrap()
+            .iter()
+            .any(|w| w.url() == worker_url)
+        {
+            return Err(format!("Worker {} already exists", worker_url));
+        }
+
+        let client = VllmSchedulerClient::connect(worker_url)
+            .await
+            .map_err(|e| format!("Failed to connect to gRPC worker {}: {}", worker_url, e))?;
+        let worker = Self::create_worker(
+            worker_url,
+            client,
+            &self.circuit_breaker_config,
+            &self.health_config,
+        );
+
+        let mut workers = self.workers.write().unwrap();
+        workers.push(worker.clone());
+        if let Some(cache_aware) = self
+            .policy
+            .as_any()
+            .downcast_ref::<crate::policies::CacheAwarePolicy>()
+        {
+            cache_aware.add_worker(worker.as_ref());
+        }
+        RouterMetrics::set_active_workers(workers.len());
+        info!("Added gRPC worker: {}", worker_url);
+        Ok(format!("Successfully added worker: {}", worker_url))
     }
 
-    fn remove_worker(&self, _worker_url: &str) {}
+    fn remove_worker(&self, worker_url: &str) {
+        let mut workers = self.workers.write().unwrap();
+        workers.retain(|w| w.url() != worker_url);
+        if let Some(cache_aware) = self
+            .policy
+            .as_any()
+            .downcast_ref::<crate::policies::CacheAwarePolicy>()
+        {
+            cache_aware.remove_worker_by_url(worker_url);
+        }
+        RouterMetrics::set_active_workers(workers.len());
+        info!("Removed gRPC worker: {}", worker_url);
+    }
 
     fn get_worker_urls(&self) -> Vec<String> {
         self.workers
@@ -0,0 +1,1450 @@
+//! Shared helpers for the gRPC routers
+//!
+//! Converts OpenAI-style requests into scheduler `GenerateRequest`s and turns the
+//! token stream coming back from a worker into OpenAI-compatible responses.
+
+use crate::core::token_load::DEFAULT_DECODE_BUDGET;
+use crate::core::TokenEstimate;
+use crate::grpc::{proto, VllmSchedulerClient};
+use crate::protocols::spec::{
+    ChatChoice, ChatCompletionRequest, ChatCompletionResponse, ChatCompletionStreamResponse,
+    ChatLogProbs, ChatLogProbsContent, ChatMessage, ChatMessageDelta, ChatStreamChoice,
+    CompletionChoice, CompletionRequest, CompletionResponse, CompletionStreamChoice,
+    CompletionStreamResponse, ContentPart, GenerateRequest, GenerationRequest, InputIds, LoRAPath,
+    ResponseFormat, StringOrArray, TopLogProb, Usage, UserMessageContent,
+};
+use crate::tokenizer::chat_template::ChatMessage as TemplateMessage;
+use crate::tokenizer::stop::{SequenceDecoderOutput, StopSequenceConfig, StopSequenceDecoder};
+use crate::tokenizer::stream::DecodeStream;
+use crate::tokenizer::traits::{TokenIdType, Tokenizer};
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::Bytes;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::Streaming;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Tokens a scheduler request is expected to cost a worker: its tokenized prompt plus
+/// its decode budget
+pub fn token_estimate(request: &proto::GenerateRequest) -> TokenEstimate {
+    let prompt_tokens = request.tokenized.as_ref().map_or(0, |t| t.input_ids.len());
+    let decode_budget = request
+        .sampling_params
+        .as_ref()
+        .map(|params| params.max_new_tokens)
+        .filter(|&max| max > 0)
+        .map_or(DEFAULT_DECODE_BUDGET, |max| max as usize);
+    TokenEstimate {
+        prompt_tokens,
+        decode_budget,
+    }
+}
+
+/// Current unix timestamp in seconds, used for the `created` field
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format a payload as a single SSE `data:` event
+pub fn sse_event<T: Serialize>(payload: &T) -> Bytes {
+    match serde_json::to_string(payload) {
+        Ok(json) => Bytes::from(format!("data: {}\n\n", json)),
+        Err(e) => Bytes::from(format!(
+            "data: {}\n\n",
+            json!({"error": {"message": format!("Failed to serialize chunk: {}", e)}})
+        )),
+    }
+}
+
+/// The terminating SSE event
+pub fn sse_done() -> Bytes {
+    Bytes::from_static(b"data: [DONE]\n\n")
+}
+
+// ============= Request conversion =============
+
+/// Render the chat messages of a request into a prompt using the tokenizer's chat template
+pub fn render_chat_prompt(
+    tokenizer: &dyn Tokenizer,
+    request: &ChatCompletionRequest,
+) -> Result<String, String> {
+    let mut messages = Vec::with_capacity(request.messages.len());
+    for message in &request.messages {
+        let (role, content) = match message {
+            ChatMessage::System { role, content, .. } => (role.clone(), content.clone()),
+            ChatMessage::User { role, content, .. } => {
+                let text = match content {
+                    UserMessageContent::Text(text) => text.clone(),
+                    UserMessageContent::Parts(parts) => {
+                        let mut texts = Vec::new();
+                        for part in parts {
+                            match part {
+                                ContentPart::Text { text } => texts.push(text.as_str()),
+                                ContentPart::ImageUrl { .. } => {
+                                    return Err(
+                                        "Image inputs are not supported in gRPC mode".to_string()
+                                    );
+                                }
+                            }
+                        }
+                        texts.join("\n")
+                    }
+                };
+                (role.clone(), text)
+            }
+            ChatMessage::Assistant { role, content, .. } => {
+                (role.clone(), content.clone().unwrap_or_default())
+            }
+            ChatMessage::Tool { role, content, .. } => (role.clone(), content.clone()),
+            ChatMessage::Function { role, content, .. } => (role.clone(), content.clone()),
+        };
+        messages.push(TemplateMessage::new(role, content));
+    }
+
+    tokenizer
+        .apply_chat_template(&messages, !request.continue_final_message)
+        .map_err(|e| format!("Failed to apply chat template: {}", e))
+}
+
+/// Tokenize a prompt into the scheduler's input format
+pub fn tokenize(tokenizer: &dyn Tokenizer, text: &str) -> Result<proto::TokenizedInput, String> {
+    let encoding = tokenizer
+        .encode(text)
+        .map_err(|e| format!("Failed to tokenize input: {}", e))?;
+
+    Ok(proto::TokenizedInput {
+        original_text: text.to_string(),
+        input_ids: encoding.token_ids().iter().map(|&id| id as i32).collect(),
+    })
+}
+
+/// Pick at most one structured output constraint
+fn build_constraint(
+    regex: Option<&String>,
+    json_schema: Option<String>,
+    ebnf: Option<&String>,
+) -> Result<Option<proto::sampling_params::Constraint>, String> {
+    let mut constraints = Vec::new();
+    if let Some(regex) = regex {
+        constraints.push(proto::sampling_params::Constraint::Regex(regex.clone()));
+    }
+    if let Some(schema) = json_schema {
+        constraints.push(proto::sampling_params::Constraint::JsonSchema(schema));
+    }
+    if let Some(ebnf) = ebnf {
+        constraints.push(proto::sampling_params::Constraint::EbnfGrammar(
+            ebnf.clone(),
+        ));
+    }
+
+    if constraints.len() > 1 {
+        return Err("Only one of regex, ebnf or json_schema can be specified".to_string());
+    }
+    Ok(constraints.pop())
+}
+
+fn lora_path_to_string(lora_path: Option<&LoRAPath>) -> Result<String, String> {
+    match lora_path {
+        None | Some(LoRAPath::Single(None)) => Ok(String::new()),
+        Some(LoRAPath::Single(Some(path))) => Ok(path.clone()),
+        Some(LoRAPath::Batch(_)) => {
+            Err("Batch LoRA paths are not supported in gRPC mode".to_string())
+        }
+    }
+}
+
+/// Map OpenAI chat sampling options onto scheduler sampling params
+pub fn chat_sampling_params(
+    request: &ChatCompletionRequest,
+) -> Result<proto::SamplingParams, String> {
+    let json_schema = match &request.response_format {
+        Some(ResponseFormat::JsonSchema { json_schema }) => Some(
+            serde_json::to_string(&json_schema.schema)
+                .map_err(|e| format!("Invalid json_schema: {}", e))?,
+        ),
+        Some(ResponseFormat::JsonObject) => Some(r#"{"type": "object"}"#.to_string()),
+        _ => None,
+    };
+
+    Ok(proto::SamplingParams {
+        temperature: request.temperature.unwrap_or(1.0),
+        top_p: request.top_p.unwrap_or(1.0),
+        top_k: request.top_k.unwrap_or(-1),
+        min_p: request.min_p.unwrap_or(0.0),
+        frequency_penalty: request.frequency_penalty.unwrap_or(0.0),
+        presence_penalty: request.presence_penalty.unwrap_or(0.0),
+        repetition_penalty: request.repetition_penalty.unwrap_or(1.0),
+        max_new_tokens: request
+            .max_completion_tokens
+            .or(request.max_tokens)
+            .unwrap_or(0) as i32,
+        stop: request
+            .stop
+            .as_ref()
+            .map(|s| s.to_vec())
+            .unwrap_or_default(),
+        stop_token_ids: request.stop_token_ids.clone().unwrap_or_default(),
+        skip_special_tokens: request.skip_special_tokens,
+        spaces_between_special_tokens: true,
+        constraint: build_constraint(request.regex.as_ref(), json_schema, request.ebnf.as_ref())?,
+        lora_path: lora_path_to_string(request.lora_path.as_ref())?,
+        n: request.n.unwrap_or(1) as i32,
+        min_new_tokens: request.min_tokens.unwrap_or(0) as i32,
+        ignore_eos: request.ignore_eos,
+        no_stop_trim: request.no_stop_trim,
+        logit_bias: request.logit_bias.clone().unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+/// Map OpenAI completion sampling options onto scheduler sampling params
+pub fn completion_sampling_params(
+    request: &CompletionRequest,
+) -> Result<proto::SamplingParams, String> {
+    Ok(proto::SamplingParams {
+        temperature: request.temperature.unwrap_or(1.0),
+        top_p: request.top_p.unwrap_or(1.0),
+        top_k: request.top_k.unwrap_or(-1),
+        min_p: request.min_p.unwrap_or(0.0),
+        frequency_penalty: request.frequency_penalty.unwrap_or(0.0),
+        presence_penalty: request.presence_penalty.unwrap_or(0.0),
+        repetition_penalty: request.repetition_penalty.unwrap_or(1.0),
+        max_new_tokens: request.max_tokens.unwrap_or(0) as i32,
+        stop: request
+            .stop
+            .as_ref()
+            .map(|s| s.to_vec())
+            .unwrap_or_default(),
+        stop_token_ids: request.stop_token_ids.clone().unwrap_or_default(),
+        skip_special_tokens: request.skip_special_tokens,
+        spaces_between_special_tokens: true,
+        constraint: build_constraint(
+            request.regex.as_ref(),
+            request.json_schema.clone(),
+            request.ebnf.as_ref(),
+        )?,
+        lora_path: lora_path_to_string(request.lora_path.as_ref())?,
+        n: request.n.unwrap_or(1) as i32,
+        min_new_tokens: request.min_tokens.unwrap_or(0) as i32,
+        ignore_eos: request.ignore_eos,
+        no_stop_trim: request.no_stop_trim,
+        logit_bias: request.logit_bias.clone().unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+/// Map native /generate sampling options onto scheduler sampling params
+pub fn generate_sampling_params(
+    request: &GenerateRequest,
+) -> Result<proto::SamplingParams, String> {
+    let params = request.sampling_params.clone().unwrap_or_default();
+    let legacy = request.parameters.clone().unwrap_or_default();
+
+    let stop = match (&params.stop, &legacy.stop) {
+        (Some(stop), _) => stop.to_vec(),
+        (None, Some(stop)) => stop.clone(),
+        (None, None) => Vec::new(),
+    };
+
+    Ok(proto::SamplingParams {
+        temperature: params.temperature.or(legacy.temperature).unwrap_or(1.0),
+        top_p: params.top_p.or(legacy.top_p).unwrap_or(1.0),
+        top_k: params
+            .top_k
+            .or(legacy.top_k.map(|k| k as i32))
+            .unwrap_or(-1),
+        min_p: params.min_p.unwrap_or(0.0),
+        frequency_penalty: params.frequency_penalty.unwrap_or(0.0),
+        presence_penalty: params.presence_penalty.unwrap_or(0.0),
+        repetition_penalty: params
+            .repetition_penalty
+            .or(legacy.repetition_penalty)
+            .unwrap_or(1.0),
+        max_new_tokens: params.max_new_tokens.or(legacy.max_new_tokens).unwrap_or(0) as i32,
+        stop,
+        stop_token_ids: params.stop_token_ids.clone().unwrap_or_default(),
+        skip_special_tokens: params.skip_special_tokens.unwrap_or(true),
+        spaces_between_special_tokens: true,
+        constraint: build_constraint(
+            params.regex.as_ref(),
+            params.json_schema.clone(),
+            params.ebnf.as_ref(),
+        )?,
+        lora_path: lora_path_to_string(request.lora_path.as_ref())?,
+        n: 1,
+        min_new_tokens: params.min_tokens.unwrap_or(0) as i32,
+        ignore_eos: params.ignore_eos.unwrap_or(false),
+        no_stop_trim: params.no_stop_trim.unwrap_or(false),
+        ..Default::default()
+    })
+}
+
+/// Extract the single prompt of a completion request
+pub fn completion_prompt(request: &CompletionRequest) -> Result<String, String> {
+    match &request.prompt {
+        StringOrArray::String(prompt) => Ok(prompt.clone()),
+        StringOrArray::Array(prompts) if prompts.len() == 1 => Ok(prompts[0].clone()),
+        StringOrArray::Array(_) => Err("Batch prompts are not supported in gRPC mode".to_string()),
+    }
+}
+
+// ============= Detokenization =============
+
+/// Router-side stop handling for a generation, mirrored from the sampling params
+#[derive(Debug, Clone, Default)]
+pub struct DecodeConfig {
+    pub stop: Vec<String>,
+    pub stop_token_ids: Vec<TokenIdType>,
+    pub skip_special_tokens: bool,
+    pub no_stop_trim: bool,
+}
+
+impl DecodeConfig {
+    pub fn from_sampling_params(params: &proto::SamplingParams) -> Self {
+        Self {
+            stop: params.stop.clone(),
+            stop_token_ids: params
+                .stop_token_ids
+                .iter()
+                .map(|&id| id as TokenIdType)
+                .collect(),
+            skip_special_tokens: params.skip_special_tokens,
+            no_stop_trim: params.no_stop_trim,
+        }
+    }
+
+    /// Whether the router has stop sequences or tokens to enforce
+    fn has_stop_conditions(&self) -> bool {
+        !self.stop.is_empty() || !self.stop_token_ids.is_empty()
+    }
+
+    fn stop_config(&self) -> StopSequenceConfig {
+        let mut config = StopSequenceConfig::default();
+        for sequence in &self.stop {
+            config = if self.no_stop_trim {
+                config.with_visible_stop_sequence(sequence.clone())
+            } else {
+                config.with_stop_sequence(sequence.clone())
+            };
+        }
+        for &token_id in &self.stop_token_ids {
+            config = if self.no_stop_trim {
+                config.with_visible_stop_token(token_id)
+            } else {
+                config.with_stop_token(token_id)
+            };
+        }
+        config
+    }
+}
+
+/// Incremental detokenizer of a generation; stop conditions need the stop sequence
+/// decoder, which holds back text that may be the start of a stop sequence
+enum Detokenizer {
+    Stream(DecodeStream),
+    Stop(StopSequenceDecoder),
+}
+
+impl Detokenizer {
+    fn new(tokenizer: Arc<dyn Tokenizer>, config: &DecodeConfig) -> Self {
+        if config.has_stop_conditions() {
+            Self::Stop(StopSequenceDecoder::new(
+                tokenizer,
+                config.stop_config(),
+                config.skip_special_tokens,
+            ))
+        } else {
+            Self::Stream(DecodeStream::new(
+                tokenizer,
+                &[],
+                config.skip_special_tokens,
+            ))
+        }
+    }
+
+    fn process_token(&mut self, token_id: TokenIdType) -> anyhow::Result<SequenceDecoderOutput> {
+        match self {
+            Self::Stream(stream) => Ok(match stream.step(token_id)? {
+                Some(text) => SequenceDecoderOutput::Text(text),
+                None => SequenceDecoderOutput::Held,
+            }),
+            Self::Stop(decoder) => decoder.process_token(token_id),
+        }
+    }
+
+    /// Text still held back, once the generation is over
+    fn flush(&mut self) -> anyhow::Result<Option<String>> {
+        match self {
+            Self::Stream(stream) => stream.flush(),
+            Self::Stop(decoder) => Ok(match decoder.flush() {
+                SequenceDecoderOutput::Text(text) => Some(text),
+                _ => None,
+            }),
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        match self {
+            Self::Stream(_) => false,
+            Self::Stop(decoder) => decoder.is_stopped(),
+        }
+    }
+}
+
+/// Output of processing a single streamed chunk
+#[derive(Debug, Default)]
+pub struct ChunkOutput {
+    /// Newly decoded text, if the chunk completed any
+    pub text: Option<String>,
+    /// Logprobs carried by the chunk
+    pub logprobs: Vec<ChatLogProbsContent>,
+}
+
+/// Incremental detokenizer for a single generation stream
+pub struct GenerationState {
+    tokenizer: Arc<dyn Tokenizer>,
+    decoder: Detokenizer,
+    text: String,
+    logprobs: Vec<ChatLogProbsContent>,
+    /// Logprobs from another worker still to be attached to the first streamed delta
+    pending_logprobs: Vec<ChatLogProbsContent>,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    finish_reason: Option<String>,
+}
+
+impl GenerationState {
+    pub fn new(tokenizer: Arc<dyn Tokenizer>, config: &DecodeConfig, prompt_tokens: u32) -> Self {
+        Self {
+            decoder: Detokenizer::new(tokenizer.clone(), config),
+            tokenizer,
+            text: String::new(),
+            logprobs: Vec::new(),
+            pending_logprobs: Vec::new(),
+            prompt_tokens,
+            completion_tokens: 0,
+            finish_reason: None,
+        }
+    }
+
+    /// Feed a streamed chunk from the worker
+    pub fn process_chunk(
+        &mut self,
+        chunk: &proto::GenerateStreamChunk,
+    ) -> Result<ChunkOutput, String> {
+        if chunk.prompt_tokens > 0 {
+            self.prompt_tokens = chunk.prompt_tokens as u32;
+        }
+        self.completion_tokens = if chunk.completion_tokens > 0 {
+            chunk.completion_tokens as u32
+        } else {
+            self.completion_tokens + 1
+        };
+
+        let mut logprobs = chunk
+            .logprobs
+            .as_ref()
+            .map(|lp| convert_logprobs(self.tokenizer.as_ref(), lp))
+            .unwrap_or_default();
+        self.logprobs.extend(logprobs.iter().cloned());
+        if !self.pending_logprobs.is_empty() {
+            let mut merged = std::mem::take(&mut self.pending_logprobs);
+            merged.append(&mut logprobs);
+            logprobs = merged;
+        }
+
+        let text = self.process_token(chunk.token_id as TokenIdType)?;
+        Ok(ChunkOutput { text, logprobs })
+    }
+
+    fn process_token(&mut self, token_id: TokenIdType) -> Result<Option<String>, String> {
+        if self.decoder.is_stopped() {
+            return Ok(None);
+        }
+
+        let output = self
+            .decoder
+            .process_token(token_id)
+            .map_err(|e| format!("Failed to decode token {}: {}", token_id, e))?;
+
+        let text = match output {
+            SequenceDecoderOutput::Text(text) => text,
+            SequenceDecoderOutput::Held => String::new(),
+            SequenceDecoderOutput::Stopped => {
+                self.finish_reason = Some("stop".to_string());
+                String::new()
+            }
+            SequenceDecoderOutput::StoppedWithText(text) => {
+                self.finish_reason = Some("stop".to_string());
+                text
+            }
+        };
+
+        if text.is_empty() {
+            Ok(None)
+        } else {
+            self.text.push_str(&text);
+            Ok(Some(text))
+        }
+    }
+
+    /// Finish the generation, returning any text still held by the stop decoder
+    pub fn finish(
+        &mut self,
+        complete: Option<&proto::GenerateComplete>,
+    ) -> Result<Option<String>, String> {
+        let mut remaining = String::new();
+
+        if let Some(complete) = complete {
+            // Workers that don't stream chunks only report the final output ids
+            if self.completion_tokens == 0 && !complete.output_ids.is_empty() {
+                self.completion_tokens = complete.output_ids.len() as u32;
+                for &token_id in &complete.output_ids {
+                    if let Some(text) = self.process_token(token_id as TokenIdType)? {
+                        remaining.push_str(&text);
+                    }
+                }
+            }
+
+            if self.finish_reason.is_none() {
+                self.finish_reason = Some(finish_reason_str(complete.finish_reason()).to_string());
+            }
+        }
+
+        if !self.decoder.is_stopped() {
+            let flushed = self
+                .decoder
+                .flush()
+                .map_err(|e| format!("Failed to decode remaining tokens: {}", e))?;
+            if let Some(text) = flushed {
+                self.text.push_str(&text);
+                remaining.push_str(&text);
+            }
+        }
+
+        Ok(if remaining.is_empty() {
+            None
+        } else {
+            Some(remaining)
+        })
+    }
+
+    /// Put logprobs reported by another worker (the prefill side of a PD pair)
+    /// ahead of this generation's own
+    pub fn prepend_logprobs(&mut self, logprobs: Vec<ChatLogProbsContent>) {
+        self.logprobs.splice(0..0, logprobs.iter().cloned());
+        self.pending_logprobs = logprobs;
+    }
+
+    /// Whether a router-side stop condition was hit
+    pub fn is_stopped(&self) -> bool {
+        self.decoder.is_stopped()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn logprobs(&self) -> &[ChatLogProbsContent] {
+        &self.logprobs
+    }
+
+    pub fn finish_reason(&self) -> &str {
+        self.finish_reason.as_deref().unwrap_or("stop")
+    }
+
+    pub fn usage(&self) -> Usage {
+        Usage {
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+            total_tokens: self.prompt_tokens + self.completion_tokens,
+            completion_tokens_details: None,
+        }
+    }
+}
+
+fn finish_reason_str(reason: proto::generate_complete::FinishReason) -> &'static str {
+    match reason {
+        proto::generate_complete::FinishReason::Length => "length",
+        proto::generate_complete::FinishReason::Abort => "abort",
+        proto::generate_complete::FinishReason::Stop
+        | proto::generate_complete::FinishReason::EosToken
+        | proto::generate_complete::FinishReason::StopStr => "stop",
+    }
+}
+
+/// Convert scheduler logprobs into OpenAI chat logprobs
+pub fn convert_logprobs(
+    tokenizer: &dyn Tokenizer,
+    logprobs: &proto::LogProbs,
+) -> Vec<ChatLogProbsContent> {
+    let token_text = |index: usize, texts: &[String], ids: &[i32]| -> String {
+        texts
+            .get(index)
+            .cloned()
+            .or_else(|| {
+                ids.get(index)
+                    .and_then(|&id| tokenizer.id_to_token(id as TokenIdType))
+            })
+            .unwrap_or_default()
+    };
+
+    logprobs
+        .token_logprobs
+        .iter()
+        .enumerate()
+        .map(|(i, &logprob)| {
+            let token = token_text(i, &logprobs.token_texts, &logprobs.token_ids);
+            let top_logprobs = logprobs
+                .top_logprobs
+                .get(i)
+                .map(|top| {
+                    top.values
+                        .iter()
+                        .enumerate()
+                        .map(|(j, &value)| {
+                            let token = token_text(j, &top.token_texts, &top.token_ids);
+                            TopLogProb {
+                                bytes: Some(token.as_bytes().to_vec()),
+                                token,
+                                logprob: value,
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            ChatLogProbsContent {
+                bytes: Some(token.as_bytes().to_vec()),
+                token,
+                logprob,
+                top_logprobs,
+            }
+        })
+        .collect()
+}
+
+// ============= Response assembly =============
+
+/// Shapes generation output into the response format of the originating endpoint
+#[derive(Debug, Clone)]
+pub enum ResponseFormatter {
+    /// /v1/chat/completions
+    Chat {
+        model: String,
+        include_usage: bool,
+        logprobs: bool,
+    },
+    /// /v1/completions
+    Completion {
+        model: String,
+        include_usage: bool,
+        echo_prompt: Option<String>,
+    },
+    /// /generate
+    Generate,
+}
+
+impl ResponseFormatter {
+    pub fn for_chat(request: &ChatCompletionRequest) -> Self {
+        ResponseFormatter::Chat {
+            model: request.model.clone(),
+            include_usage: request
+                .stream_options
+                .as_ref()
+                .and_then(|o| o.include_usage)
+                .unwrap_or(false),
+            logprobs: request.logprobs,
+        }
+    }
+
+    pub fn for_completion(request: &CompletionRequest, prompt: &str) -> Self {
+        ResponseFormatter::Completion {
+            model: request.model.clone(),
+            include_usage: request
+                .stream_options
+                .as_ref()
+                .and_then(|o| o.include_usage)
+                .unwrap_or(false),
+            echo_prompt: request.echo.then(|| prompt.to_string()),
+        }
+    }
+
+    /// Events emitted before any generated text
+    pub fn stream_start(&self, id: &str, created: u64) -> Vec<Bytes> {
+        match self {
+            ResponseFormatter::Chat { model, .. } => {
+                vec![sse_event(&chat_chunk(
+                    id,
+                    created,
+                    model,
+                    ChatMessageDelta {
+                        role: Some("assistant".to_string()),
+                        content: None,
+                        tool_calls: None,
+                        function_call: None,
+                        reasoning_content: None,
+                    },
+                    None,
+                    None,
+                ))]
+            }
+            ResponseFormatter::Completion {
+                model,
+                echo_prompt: Some(prompt),
+                ..
+            } => vec![sse_event(&completion_chunk(
+                id, created, model, prompt, None,
+            ))],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Event carrying newly generated text
+    pub fn stream_delta(
+        &self,
+        id: &str,
+        created: u64,
+        state: &GenerationState,
+        delta: &str,
+        logprobs: Vec<ChatLogProbsContent>,
+    ) -> Bytes {
+        match self {
+            ResponseFormatter::Chat {
+                model,
+                logprobs: want_logprobs,
+                ..
+            } => sse_event(&chat_chunk(
+                id,
+                created,
+                model,
+                ChatMessageDelta {
+                    role: None,
+                    content: Some(delta.to_string()),
+                    tool_calls: None,
+                    function_call: None,
+                    reasoning_content: None,
+                },
+                (*want_logprobs && !logprobs.is_empty()).then_some(ChatLogProbs {
+                    content: Some(logprobs),
+                }),
+                None,
+            )),
+            ResponseFormatter::Completion { model, .. } => {
+                sse_event(&completion_chunk(id, created, model, delta, None))
+            }
+            ResponseFormatter::Generate => sse_event(&generate_body(id, state, false)),
+        }
+    }
+
+    /// Events emitted once the generation has finished, including `[DONE]`
+    pub fn stream_end(&self, id: &str, created: u64, state: &GenerationState) -> Vec<Bytes> {
+        let mut events = Vec::new();
+        match self {
+            ResponseFormatter::Chat {
+                model,
+                include_usage,
+                ..
+            } => {
+                events.push(sse_event(&chat_chunk(
+                    id,
+                    created,
+                    model,
+                    ChatMessageDelta {
+                        role: None,
+                        content: None,
+                        tool_calls: None,
+                        function_call: None,
+                        reasoning_content: None,
+                    },
+                    None,
+                    Some(state.finish_reason().to_string()),
+                )));
+                if *include_usage {
+                    events.push(sse_event(&ChatCompletionStreamResponse {
+                        id: id.to_string(),
+                        object: "chat.completion.chunk".to_string(),
+                        created,
+                        model: model.clone(),
+                        system_fingerprint: None,
+                        choices: vec![],
+                        usage: Some(state.usage()),
+                    }));
+                }
+            }
+            ResponseFormatter::Completion {
+                model,
+                include_usage,
+                ..
+            } => {
+                events.push(sse_event(&completion_chunk(
+                    id,
+                    created,
+                    model,
+                    "",
+                    Some(state.finish_reason().to_string()),
+                )));
+                if *include_usage {
+                    events.push(sse_event(&json!({
+                        "id": id,
+                        "object": "text_completion",
+                        "created": created,
+                        "model": model,
+                        "choices": [],
+                        "usage": state.usage(),
+                    })));
+                }
+            }
+            ResponseFormatter::Generate => {
+                events.push(sse_event(&generate_body(id, state, true)));
+            }
+        }
+        events.push(sse_done());
+        events
+    }
+
+    /// Full response body for a non-streaming request
+    pub fn complete(&self, id: &str, created: u64, state: &GenerationState) -> Value {
+        match self {
+            ResponseFormatter::Chat {
+                model, logprobs, ..
+            } => {
+                let response = ChatCompletionResponse {
+                    id: id.to_string(),
+                    object: "chat.completion".to_string(),
+                    created,
+                    model: model.clone(),
+                    choices: vec![ChatChoice {
+                        index: 0,
+                        message: ChatMessage::Assistant {
+                            role: "assistant".to_string(),
+                            content: Some(state.text().to_string()),
+                            name: None,
+                            tool_calls: None,
+                            function_call: None,
+                            reasoning_content: None,
+                        },
+                        logprobs: logprobs.then(|| ChatLogProbs {
+                            content: Some(state.logprobs().to_vec()),
+                        }),
+                        finish_reason: Some(state.finish_reason().to_string()),
+                        matched_stop: None,
+                        hidden_states: None,
+                    }],
+                    usage: Some(state.usage()),
+                    system_fingerprint: None,
+                };
+                serde_json::to_value(response).unwrap_or(Value::Null)
+            }
+            ResponseFormatter::Completion {
+                model, echo_prompt, ..
+            } => {
+                let text = match echo_prompt {
+                    Some(prompt) => format!("{}{}", prompt, state.text()),
+                    None => state.text().to_string(),
+                };
+                let response = CompletionResponse {
+                    id: id.to_string(),
+                    object: "text_completion".to_string(),
+                    created,
+                    model: model.clone(),
+                    choices: vec![CompletionChoice {
+                        text,
+                        index: 0,
+                        logprobs: None,
+                        finish_reason: Some(state.finish_reason().to_string()),
+                        matched_stop: None,
+                        hidden_states: None,
+                    }],
+                    usage: Some(state.usage()),
+                    system_fingerprint: None,
+                };
+                serde_json::to_value(response).unwrap_or(Value::Null)
+            }
+            ResponseFormatter::Generate => generate_body(id, state, true),
+        }
+    }
+}
+
+fn chat_chunk(
+    id: &str,
+    created: u64,
+    model: &str,
+    delta: ChatMessageDelta,
+    logprobs: Option<ChatLogProbs>,
+    finish_reason: Option<String>,
+) -> ChatCompletionStreamResponse {
+    ChatCompletionStreamResponse {
+        id: id.to_string(),
+        object: "chat.completion.chunk".to_string(),
+        created,
+        model: model.to_string(),
+        system_fingerprint: None,
+        choices: vec![ChatStreamChoice {
+            index: 0,
+            delta,
+            logprobs,
+            finish_reason,
+        }],
+        usage: None,
+    }
+}
+
+fn completion_chunk(
+    id: &str,
+    created: u64,
+    model: &str,
+    text: &str,
+    finish_reason: Option<String>,
+) -> CompletionStreamResponse {
+    CompletionStreamResponse {
+        id: id.to_string(),
+        object: "text_completion".to_string(),
+        created,
+        choices: vec![CompletionStreamChoice {
+            text: text.to_string(),
+            index: 0,
+            logprobs: None,
+            finish_reason,
+        }],
+        model: model.to_string(),
+        system_fingerprint: None,
+    }
+}
+
+/// Native /generate body: cumulative text plus meta info
+fn generate_body(id: &str, state: &GenerationState, finished: bool) -> Value {
+    let usage = state.usage();
+    json!({
+        "text": state.text(),
+        "meta_info": {
+            "id": id,
+            "finish_reason": if finished {
+                json!({"type": state.finish_reason()})
+            } else {
+                Value::Null
+            },
+            "prompt_tokens": usage.prompt_tokens,
+            "completion_tokens": usage.completion_tokens,
+        }
+    })
+}
+
+// ============= Worker streams =============
+
+/// Convert a /generate request into a scheduler request
+pub fn build_generate_request(
+    tokenizer: &dyn Tokenizer,
+    body: &GenerateRequest,
+) -> Result<proto::GenerateRequest, String> {
+    let tokenized = match &body.input_ids {
+        Some(InputIds::Single(ids)) => proto::TokenizedInput {
+            original_text: body.text.clone().unwrap_or_default(),
+            input_ids: ids.clone(),
+        },
+        Some(InputIds::Batch(_)) => {
+            return Err("Batch input_ids are not supported in gRPC mode".to_string());
+        }
+        None => tokenize(tokenizer, &body.extract_text_for_routing())?,
+    };
+    let sampling_params = generate_sampling_params(body)?;
+
+    let request_id = body
+        .rid
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+    Ok(generate_request(
+        request_id,
+        tokenized,
+        sampling_params,
+        None,
+    ))
+}
+
+/// Convert a chat completion request into a scheduler request
+pub fn build_chat_request(
+    tokenizer: &dyn Tokenizer,
+    body: &ChatCompletionRequest,
+) -> Result<proto::GenerateRequest, String> {
+    if body.n.unwrap_or(1) > 1 {
+        return Err("n > 1 is not supported in gRPC mode".to_string());
+    }
+
+    let prompt = render_chat_prompt(tokenizer, body)?;
+    let tokenized = tokenize(tokenizer, &prompt)?;
+    let sampling_params = chat_sampling_params(body)?;
+
+    let top_logprobs_num = body.logprobs.then(|| body.top_logprobs.unwrap_or(0) as i32);
+    Ok(generate_request(
+        format!("chatcmpl-{}", Uuid::new_v4().simple()),
+        tokenized,
+        sampling_params,
+        top_logprobs_num,
+    ))
+}
+
+/// Convert a completion request into a scheduler request, also returning the prompt
+/// so it can be echoed back
+pub fn build_completion_request(
+    tokenizer: &dyn Tokenizer,
+    body: &CompletionRequest,
+) -> Result<(proto::GenerateRequest, String), String> {
+    if body.n.unwrap_or(1) > 1 {
+        return Err("n > 1 is not supported in gRPC mode".to_string());
+    }
+
+    let prompt = completion_prompt(body)?;
+    let tokenized = tokenize(tokenizer, &prompt)?;
+    let sampling_params = completion_sampling_params(body)?;
+
+    let request = generate_request(
+        format!("cmpl-{}", Uuid::new_v4().simple()),
+        tokenized,
+        sampling_params,
+        None,
+    );
+    Ok((request, prompt))
+}
+
+/// Wrap a tokenized prompt in a scheduler generate request
+pub fn generate_request(
+    request_id: String,
+    tokenized: proto::TokenizedInput,
+    sampling_params: proto::SamplingParams,
+    top_logprobs_num: Option<i32>,
+) -> proto::GenerateRequest {
+    proto::GenerateRequest {
+        request_id,
+        tokenized: Some(tokenized),
+        sampling_params: Some(sampling_params),
+        return_logprob: top_logprobs_num.is_some(),
+        logprob_start_len: -1,
+        top_logprobs_num: top_logprobs_num.unwrap_or(0),
+        ..Default::default()
+    }
+}
+
+/// Open a generate stream on a worker and wait for its first message, so that
+/// upstream failures surface as HTTP errors (and can be retried) before any
+/// bytes reach the client
+pub async fn open_generate_stream(
+    mut client: VllmSchedulerClient,
+    request: proto::GenerateRequest,
+) -> Result<(Streaming<proto::GenerateResponse>, proto::GenerateResponse), (StatusCode, String)> {
+    let mut stream = client.generate_stream(request).await.map_err(|e| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Failed to send request to gRPC worker: {}", e),
+        )
+    })?;
+
+    match stream.message().await {
+        Ok(Some(first)) => Ok((stream, first)),
+        Ok(None) => Err((
+            StatusCode::BAD_GATEWAY,
+            "gRPC worker closed the stream without a response".to_string(),
+        )),
+        Err(status) => Err(grpc_status_error(&status)),
+    }
+}
+
+/// Consume a generate stream to completion, keeping only the logprobs it reports
+pub async fn drain_generate_stream(
+    mut stream: Streaming<proto::GenerateResponse>,
+    first: proto::GenerateResponse,
+) -> Result<Vec<proto::LogProbs>, (StatusCode, String)> {
+    let mut logprobs = Vec::new();
+    let mut next = Some(first);
+    while let Some(message) = next {
+        match message.response {
+            Some(proto::generate_response::Response::Chunk(chunk)) => {
+                logprobs.extend(chunk.logprobs);
+            }
+            Some(proto::generate_response::Response::Complete(complete)) => {
+                if logprobs.is_empty() {
+                    logprobs = complete.all_logprobs;
+                }
+                break;
+            }
+            Some(proto::generate_response::Response::Error(err)) => {
+                return Err(worker_error(err));
+            }
+            None => {}
+        }
+        next = stream.message().await.map_err(|s| grpc_status_error(&s))?;
+    }
+    Ok(logprobs)
+}
+
+/// How a generation stream ended, as seen from the router
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// The generation finished or failed; the worker is no longer working on it
+    Finished,
+    /// The client went away while the worker was still generating
+    ClientDisconnected,
+}
+
+/// Turn a worker's generate stream into a response in the endpoint's format.
+///
+/// `on_finish` runs exactly once, after the stream has been consumed or abandoned;
+/// callers use it to release worker load and to abort requests whose client disconnected.
+pub async fn generation_response<F>(
+    mut stream: Streaming<proto::GenerateResponse>,
+    first: proto::GenerateResponse,
+    mut state: GenerationState,
+    formatter: ResponseFormatter,
+    request_id: String,
+    is_stream: bool,
+    on_finish: F,
+) -> Response
+where
+    F: FnOnce(StreamEnd) + Send + 'static,
+{
+    let created = current_timestamp();
+
+    if !is_stream {
+        let result = collect_generation(&mut stream, first, &mut state).await;
+        on_finish(StreamEnd::Finished);
+        return match result {
+            Ok(()) => Json(formatter.complete(&request_id, created, &state)).into_response(),
+            Err(error) => error.into_response(),
+        };
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Result<Bytes, String>>();
+
+    tokio::spawn(async move {
+        let end = forward_stream(
+            &mut stream,
+            first,
+            &mut state,
+            &formatter,
+            &request_id,
+            created,
+            &tx,
+        )
+        .await;
+        if end == StreamEnd::ClientDisconnected {
+            debug!("Client disconnected from stream {}", request_id);
+        }
+        on_finish(end);
+    });
+
+    let mut response = Response::new(Body::from_stream(UnboundedReceiverStream::new(rx)));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    response
+}
+
+/// Forward a worker stream to the client as SSE events until either side is done
+async fn forward_stream(
+    stream: &mut Streaming<proto::GenerateResponse>,
+    first: proto::GenerateResponse,
+    state: &mut GenerationState,
+    formatter: &ResponseFormatter,
+    request_id: &str,
+    created: u64,
+    tx: &UnboundedSender<Result<Bytes, String>>,
+) -> StreamEnd {
+    if !formatter
+        .stream_start(request_id, created)
+        .into_iter()
+        .all(|event| tx.send(Ok(event)).is_ok())
+    {
+        return StreamEnd::ClientDisconnected;
+    }
+
+    let mut next = Some(first);
+    loop {
+        let Some(message) = next else {
+            let remaining = state.finish(None).ok().flatten();
+            finish_stream(state, formatter, request_id, created, tx, remaining);
+            return StreamEnd::Finished;
+        };
+        match process_message(state, message) {
+            Ok(StreamStep::Continue(output)) => {
+                if let Some(text) = output.text {
+                    let event =
+                        formatter.stream_delta(request_id, created, state, &text, output.logprobs);
+                    if tx.send(Ok(event)).is_err() {
+                        return StreamEnd::ClientDisconnected;
+                    }
+                }
+            }
+            Ok(StreamStep::Complete(remaining)) => {
+                finish_stream(state, formatter, request_id, created, tx, remaining);
+                return StreamEnd::Finished;
+            }
+            Err((_, message)) => {
+                let _ = tx.send(Ok(sse_event(&json!({
+                    "error": {"message": message}
+                }))));
+                return StreamEnd::Finished;
+            }
+        }
+        if state.is_stopped() {
+            // Dropping the stream cancels the generation on the worker
+            let remaining = state.finish(None).ok().flatten();
+            finish_stream(state, formatter, request_id, created, tx, remaining);
+            return StreamEnd::Finished;
+        }
+
+        // Notice a disconnect while the worker is still busy, not only on the next send
+        let message = tokio::select! {
+            biased;
+            _ = tx.closed() => return StreamEnd::ClientDisconnected,
+            message = stream.message() => message,
+        };
+        next = match message {
+            Ok(message) => message,
+            Err(status) => {
+                let _ = tx.send(Ok(sse_event(&json!({
+                    "error": {"message": status.message()}
+                }))));
+                return StreamEnd::Finished;
+            }
+        };
+    }
+}
+
+/// Read a non-streaming generation to the end
+async fn collect_generation(
+    stream: &mut Streaming<proto::GenerateResponse>,
+    first: proto::GenerateResponse,
+    state: &mut GenerationState,
+) -> Result<(), (StatusCode, String)> {
+    let finish = |state: &mut GenerationState| {
+        state
+            .finish(None)
+            .map(|_| ())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    };
+
+    let mut next = Some(first);
+    loop {
+        let Some(message) = next else {
+            return finish(state);
+        };
+        if let StreamStep::Complete(_) = process_message(state, message)? {
+            return Ok(());
+        }
+        if state.is_stopped() {
+            return finish(state);
+        }
+        next = stream.message().await.map_err(|s| grpc_status_error(&s))?;
+    }
+}
+
+/// Result of handling one message from the worker stream
+enum StreamStep {
+    Continue(ChunkOutput),
+    /// The generation finished; carries any text still held by the decoder
+    Complete(Option<String>),
+}
+
+fn process_message(
+    state: &mut GenerationState,
+    message: proto::GenerateResponse,
+) -> Result<StreamStep, (StatusCode, String)> {
+    match message.response {
+        Some(proto::generate_response::Response::Chunk(chunk)) => state
+            .process_chunk(&chunk)
+            .map(StreamStep::Continue)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e)),
+        Some(proto::generate_response::Response::Complete(complete)) => state
+            .finish(Some(&complete))
+            .map(StreamStep::Complete)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e)),
+        Some(proto::generate_response::Response::Error(err)) => Err(worker_error(err)),
+        None => Ok(StreamStep::Continue(ChunkOutput::default())),
+    }
+}
+
+/// Emit any held text and the closing events of a stream
+fn finish_stream(
+    state: &GenerationState,
+    formatter: &ResponseFormatter,
+    request_id: &str,
+    created: u64,
+    tx: &UnboundedSender<Result<Bytes, String>>,
+    remaining: Option<String>,
+) {
+    if let Some(text) = remaining {
+        let _ = tx.send(Ok(formatter.stream_delta(
+            request_id,
+            created,
+            state,
+            &text,
+            Vec::new(),
+        )));
+    }
+    for event in formatter.stream_end(request_id, created, state) {
+        if tx.send(Ok(event)).is_err() {
+            break;
+        }
+    }
+}
+
+/// Map an error reported inside the generate stream onto its HTTP status
+fn worker_error(err: proto::GenerateError) -> (StatusCode, String) {
+    let status = err
+        .http_status_code
+        .parse::<u16>()
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    warn!(
+        "gRPC worker returned error: {} ({})",
+        err.message, err.details
+    );
+    (status, err.message)
+}
+
+/// Map a gRPC status onto the closest HTTP status code
+pub fn grpc_status_error(status: &tonic::Status) -> (StatusCode, String) {
+    let code = match status.code() {
+        tonic::Code::InvalidArgument | tonic::Code::OutOfRange => StatusCode::BAD_REQUEST,
+        tonic::Code::NotFound => StatusCode::NOT_FOUND,
+        tonic::Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        tonic::Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        tonic::Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        tonic::Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (code, format!("gRPC worker error: {}", status.message()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::mock::MockTokenizer;
+
+    fn chunk(token_id: i32) -> proto::GenerateStreamChunk {
+        proto::GenerateStreamChunk {
+            token_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_chat_prompt_fallback_template() {
+        let tokenizer = MockTokenizer::new();
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "test",
+            "messages": [
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": "Hello world"}
+            ]
+        }))
+        .unwrap();
+
+        let prompt = render_chat_prompt(&tokenizer, &request).unwrap();
+        assert_eq!(prompt, "system: Be brief\nuser: Hello world\nassistant: ");
+    }
+
+    #[test]
+    fn test_chat_sampling_params_mapping() {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "test",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "temperature": 0.2,
+            "max_tokens": 16,
+            "max_completion_tokens": 32,
+            "stop": ["."],
+            "regex": "[a-z]+"
+        }))
+        .unwrap();
+
+        let params = chat_sampling_params(&request).unwrap();
+        assert_eq!(params.temperature, 0.2);
+        assert_eq!(params.top_p, 1.0);
+        assert_eq!(params.top_k, -1);
+        assert_eq!(params.max_new_tokens, 32);
+        assert_eq!(params.stop, vec![".".to_string()]);
+        assert_eq!(
+            params.constraint,
+            Some(proto::sampling_params::Constraint::Regex(
+                "[a-z]+".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_conflicting_constraints_rejected() {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "test",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "regex": "[a-z]+",
+            "ebnf": "root ::= \"a\""
+        }))
+        .unwrap();
+
+        assert!(chat_sampling_params(&request).is_err());
+    }
+
+    #[test]
+    fn test_generation_state_streams_text() {
+        let tokenizer = Arc::new(MockTokenizer::new());
+        let mut state = GenerationState::new(tokenizer, &DecodeConfig::default(), 2);
+
+        let first = state.process_chunk(&chunk(1)).unwrap();
+        assert_eq!(first.text.as_deref(), Some("Hello"));
+        let second = state.process_chunk(&chunk(2)).unwrap();
+        assert_eq!(second.text.as_deref(), Some(" world"));
+
+        let complete = proto::GenerateComplete {
+            finish_reason: proto::generate_complete::FinishReason::Length as i32,
+            ..Default::default()
+        };
+        assert_eq!(state.finish(Some(&complete)).unwrap(), None);
+        assert_eq!(state.text(), "Hello world");
+        assert_eq!(state.finish_reason(), "length");
+        assert_eq!(state.usage().total_tokens, 4);
+    }
+
+    #[test]
+    fn test_generation_state_applies_stop_sequence() {
+        let tokenizer = Arc::new(MockTokenizer::new());
+        let config = DecodeConfig {
+            stop: vec!["world".to_string()],
+            skip_special_tokens: true,
+            ..Default::default()
+        };
+        let mut state = GenerationState::new(tokenizer, &config, 0);
+
+        state.process_chunk(&chunk(1)).unwrap();
+        state.process_chunk(&chunk(2)).unwrap();
+        assert!(state.is_stopped());
+        // Tokens after the stop are ignored
+        assert!(state.process_chunk(&chunk(3)).unwrap().text.is_none());
+
+        state.finish(None).unwrap();
+        assert_eq!(state.text(), "Hello ");
+        assert_eq!(state.finish_reason(), "stop");
+    }
+
+    #[test]
+    fn test_completion_prompt_rejects_batches() {
+        let request: CompletionRequest = serde_json::from_value(json!({
+            "model": "test",
+            "prompt": ["a", "b"]
+        }))
+        .unwrap();
+        assert!(completion_prompt(&request).is_err());
+    }
+}
@@ -1,6 +1,81 @@
+use crate::core::Worker;
 use axum::body::Body;
 use axum::extract::Request;
 use axum::http::HeaderMap;
+use std::collections::HashMap;
+
+/// Header with the minimum worker priority a request may be routed to
+pub const WORKER_PRIORITY_HEADER: &str = "x-worker-priority";
+
+/// Header with the maximum worker cost a request may be routed to
+pub const MAX_COST_HEADER: &str = "x-max-cost";
+
+/// Header with `key=value` labels, comma-separated, a worker must carry to serve a request
+pub const WORKER_SELECTOR_HEADER: &str = "x-worker-selector";
+
+/// Per-request limits on which workers may serve a request, taken from request headers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkerConstraints {
+    /// Workers with a lower priority are skipped
+    pub min_priority: Option<u32>,
+    /// Workers with a higher cost are skipped
+    pub max_cost: Option<f32>,
+    /// Workers missing any of these labels are skipped
+    pub labels: Vec<(String, String)>,
+}
+
+impl WorkerConstraints {
+    /// Parse `x-worker-priority`, `x-max-cost` and `x-worker-selector`; malformed values
+    /// are ignored
+    pub fn from_headers(headers: Option<&HeaderMap>) -> Self {
+        let header = |name: &str| {
+            headers
+                .and_then(|h| h.get(name))
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+        };
+        Self {
+            min_priority: header(WORKER_PRIORITY_HEADER).and_then(|s| s.parse().ok()),
+            max_cost: header(MAX_COST_HEADER)
+                .and_then(|s| s.parse::<f32>().ok())
+                .filter(|cost| cost.is_finite()),
+            labels: header(WORKER_SELECTOR_HEADER)
+                .map(parse_worker_selector)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether the request may be routed to `worker`
+    pub fn allows(&self, worker: &dyn Worker) -> bool {
+        let labels = &worker.metadata().labels;
+        self.min_priority.is_none_or(|min| worker.priority() >= min)
+            && self.max_cost.is_none_or(|max| worker.cost() <= max)
+            && self
+                .labels
+                .iter()
+                .all(|(key, value)| labels.get(key) == Some(value))
+    }
+}
+
+/// Parse a `key=value,key=value` worker selector; entries without `=` are ignored
+pub fn parse_worker_selector(selector: &str) -> Vec<(String, String)> {
+    selector
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+/// Format labels as an `x-worker-selector` value, sorted by key
+pub fn format_worker_selector(labels: &HashMap<String, String>) -> String {
+    let mut entries: Vec<_> = labels
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    entries.sort();
+    entries.join(",")
+}
 
 /// Copy request headers to a Vec of name-value string pairs
 /// Used for forwarding headers to backend workers
@@ -51,3 +126,69 @@ fn should_forward_header(name: &str) -> bool {
         "host" // Should not forward the backend's host header
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+
+    #[test]
+    fn test_worker_constraints() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            WorkerConstraints::from_headers(Some(&headers)),
+            WorkerConstraints::default()
+        );
+
+        headers.insert(WORKER_PRIORITY_HEADER, "60".parse().unwrap());
+        headers.insert(MAX_COST_HEADER, "0.5".parse().unwrap());
+        let constraints = WorkerConstraints::from_headers(Some(&headers));
+
+        let worker = |priority: &str, cost: &str| {
+            BasicWorker::new("http://w:8000".to_string(), WorkerType::Regular).with_labels(
+                HashMap::from([
+                    ("priority".to_string(), priority.to_string()),
+                    ("cost".to_string(), cost.to_string()),
+                ]),
+            )
+        };
+        assert!(constraints.allows(&worker("60", "0.5")));
+        assert!(!constraints.allows(&worker("59", "0.5")));
+        assert!(!constraints.allows(&worker("80", "0.6")));
+
+        headers.insert(MAX_COST_HEADER, "cheap".parse().unwrap());
+        assert_eq!(
+            WorkerConstraints::from_headers(Some(&headers)).max_cost,
+            None
+        );
+    }
+
+    #[test]
+    fn test_worker_selector() {
+        let labels = HashMap::from([
+            ("tier".to_string(), "large".to_string()),
+            ("region".to_string(), "us".to_string()),
+        ]);
+        let selector = format_worker_selector(&labels);
+        assert_eq!(selector, "region=us,tier=large");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(WORKER_SELECTOR_HEADER, selector.parse().unwrap());
+        let constraints = WorkerConstraints::from_headers(Some(&headers));
+        assert_eq!(constraints.labels.len(), 2);
+
+        let worker = |labels: HashMap<String, String>| {
+            BasicWorker::new("http://w:8000".to_string(), WorkerType::Regular).with_labels(labels)
+        };
+        assert!(constraints.allows(&worker(labels)));
+        assert!(!constraints.allows(&worker(HashMap::from([(
+            "tier".to_string(),
+            "large".to_string()
+        )]))));
+
+        assert_eq!(
+            parse_worker_selector(" tier = large ,junk,=x"),
+            vec![("tier".to_string(), "large".to_string())]
+        );
+    }
+}
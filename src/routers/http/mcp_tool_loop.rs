@@ -0,0 +1,723 @@
+//! Router-side tool loop for Responses API requests that declare MCP tools
+//!
+//! The tools of the requested MCP servers are offered to the model as function tools.
+//! Whenever the model calls one, the router executes it through `McpClientManager`,
+//! appends the result to the conversation and invokes the model again, until it answers
+//! without calling a tool or the iteration limit is reached. Every tool execution is
+//! reported as an `mcp_call` output item and, for streaming requests, as
+//! `response.mcp_call.*` events.
+
+use crate::data_connector::SharedResponseStorage;
+use crate::mcp::{McpClientManager, ToolInfo};
+use crate::protocols::spec::{
+    ChatCompletionRequest, ChatMessage, ErrorResponse, Function, FunctionCallResponse,
+    ResponseContentPart, ResponseInput, ResponseInputOutputItem, ResponseOutputItem,
+    ResponseStatus, ResponseToolType, ResponsesRequest, ResponsesResponse, Tool, ToolCall,
+    UsageInfo, UserMessageContent,
+};
+use crate::routers::http::responses;
+use crate::routers::RouterTrait;
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, warn};
+
+/// Whether `request` declares any MCP tools
+pub fn has_mcp_tools(request: &ResponsesRequest) -> bool {
+    request
+        .tools
+        .iter()
+        .any(|tool| tool.r#type == ResponseToolType::Mcp)
+}
+
+/// Executes the MCP tool loop of Responses API requests
+#[derive(Debug, Clone)]
+pub struct McpToolLoop {
+    mcp: Arc<McpClientManager>,
+    /// Router whose chat route invokes the model
+    router: Arc<dyn RouterTrait>,
+    /// Maximum number of tool-calling rounds before the response is marked incomplete
+    max_iterations: usize,
+}
+
+impl McpToolLoop {
+    pub fn new(
+        mcp: Arc<McpClientManager>,
+        router: Arc<dyn RouterTrait>,
+        max_iterations: usize,
+    ) -> Self {
+        Self {
+            mcp,
+            router,
+            max_iterations,
+        }
+    }
+
+    /// Run `request` through the tool loop, invoking the model via the router's chat route.
+    ///
+    /// `body` is the request as sent by the client and `request` the one with its
+    /// `previous_response_id` expanded; the result is stored under `body` if it asks for it.
+    pub async fn run(
+        &self,
+        storage: &SharedResponseStorage,
+        headers: Option<&HeaderMap>,
+        body: &ResponsesRequest,
+        request: ResponsesRequest,
+        model_id: Option<&str>,
+    ) -> Response {
+        if body.background {
+            return bad_request("Background responses do not support MCP tools".to_string());
+        }
+        let tools = match self.resolve_tools(&request) {
+            Ok(tools) => tools,
+            Err(message) => return bad_request(message),
+        };
+
+        let run = ToolLoopRun {
+            tool_loop: self.clone(),
+            headers: headers.cloned(),
+            model_id: model_id.map(str::to_string),
+            request,
+            tools,
+        };
+
+        if !body.stream {
+            let raw = match run.execute(&mut EventSink::disabled()).await {
+                Ok(raw) => raw,
+                Err(response) => return response,
+            };
+            if body.store {
+                responses::persist(storage, body, raw.clone()).await;
+            }
+            return Json(raw).into_response();
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let storage = storage.clone();
+        let body = body.clone();
+        tokio::spawn(async move {
+            // Stop calling the model and tools once the client has gone away
+            let disconnected = tx.clone();
+            let mut events = EventSink::new(tx);
+            let result = tokio::select! {
+                result = run.execute(&mut events) => result,
+                _ = disconnected.closed() => {
+                    debug!(
+                        "Client disconnected, stopping the tool loop of response {}",
+                        body.request_id
+                    );
+                    return;
+                }
+            };
+            match result {
+                Ok(raw) => {
+                    if body.store {
+                        responses::persist(&storage, &body, raw.clone()).await;
+                    }
+                    events.emit("response.completed", json!({ "response": raw }));
+                }
+                Err(response) => {
+                    let status = response.status();
+                    let message = to_bytes(response.into_body(), usize::MAX)
+                        .await
+                        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                        .unwrap_or_default();
+                    events.emit(
+                        "response.failed",
+                        json!({
+                            "response": {
+                                "id": body.request_id,
+                                "object": "response",
+                                "status": "failed",
+                                "error": {
+                                    "code": "server_error",
+                                    "message": format!("Model returned {}: {}", status, message)
+                                }
+                            }
+                        }),
+                    );
+                }
+            }
+            events.done();
+        });
+
+        let mut response = Response::new(Body::from_stream(UnboundedReceiverStream::new(rx)));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        response
+    }
+
+    /// The MCP tools `request` may use, from the servers named by its `mcp` tools
+    fn resolve_tools(&self, request: &ResponsesRequest) -> Result<Vec<ToolInfo>, String> {
+        let servers = self.mcp.list_servers();
+        let mut tools = Vec::new();
+        for tool in request
+            .tools
+            .iter()
+            .filter(|tool| tool.r#type == ResponseToolType::Mcp)
+        {
+            let Some(label) = tool.server_label.as_deref() else {
+                return Err("MCP tools require a server_label".to_string());
+            };
+            if !servers.iter().any(|server| server == label) {
+                return Err(format!("Unknown MCP server '{}'", label));
+            }
+            tools.extend(
+                self.mcp
+                    .list_server_tools(label)
+                    .into_iter()
+                    .filter(|info| {
+                        tool.allowed_tools
+                            .as_ref()
+                            .is_none_or(|allowed| allowed.contains(&info.name))
+                    }),
+            );
+        }
+        Ok(tools)
+    }
+}
+
+/// A single request going through the tool loop
+struct ToolLoopRun {
+    tool_loop: McpToolLoop,
+    headers: Option<HeaderMap>,
+    model_id: Option<String>,
+    request: ResponsesRequest,
+    tools: Vec<ToolInfo>,
+}
+
+impl ToolLoopRun {
+    /// Drive the conversation to a final answer and return the response object.
+    /// A failed model invocation is returned as the worker's response.
+    async fn execute(&self, events: &mut EventSink) -> Result<Value, Response> {
+        let created_at = chrono::Utc::now().timestamp();
+        let mut model = self.request.model.clone().unwrap_or_default();
+        let in_progress = self.response_object(
+            &model,
+            created_at,
+            Vec::new(),
+            ResponseStatus::InProgress,
+            None,
+        );
+        events.emit("response.created", json!({ "response": in_progress }));
+
+        let mut chat = self.chat_request()?;
+        let mut output = Vec::new();
+        let mut usage = None;
+        let mut iterations = 0;
+        let status = loop {
+            let completion = self.invoke_model(&chat).await?;
+            if let Some(name) = completion.get("model").and_then(Value::as_str) {
+                model = name.to_string();
+            }
+            add_usage(&mut usage, &completion);
+
+            let message = &completion["choices"][0]["message"];
+            let content = message
+                .get("content")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let tool_calls: Vec<ToolCall> = match message.get("tool_calls") {
+                Some(calls) if !calls.is_null() => {
+                    serde_json::from_value(calls.clone()).map_err(|e| {
+                        server_error(format!("Failed to parse model tool calls: {}", e))
+                    })?
+                }
+                _ => Vec::new(),
+            };
+
+            if tool_calls.is_empty() {
+                output.push(final_message(content, output.len(), events));
+                break ResponseStatus::Completed;
+            }
+            if iterations == self.tool_loop.max_iterations {
+                debug!(
+                    "Response {} reached the MCP iteration limit of {}",
+                    self.request.request_id, self.tool_loop.max_iterations
+                );
+                if !content.is_empty() {
+                    output.push(final_message(content, output.len(), events));
+                }
+                break ResponseStatus::Incomplete;
+            }
+            iterations += 1;
+
+            chat.messages.push(ChatMessage::Assistant {
+                role: "assistant".to_string(),
+                content: (!content.is_empty()).then_some(content),
+                name: None,
+                tool_calls: Some(tool_calls.clone()),
+                function_call: None,
+                reasoning_content: None,
+            });
+            for call in tool_calls {
+                let (item, result) = self.call_tool(&call, output.len(), events).await;
+                output.push(item);
+                chat.messages.push(ChatMessage::Tool {
+                    role: "tool".to_string(),
+                    content: result,
+                    tool_call_id: call.id,
+                });
+            }
+        };
+
+        Ok(self.response_object(&model, created_at, output, status, usage))
+    }
+
+    /// Chat completion request carrying the conversation and the MCP tools
+    #[allow(clippy::result_large_err)]
+    fn chat_request(&self) -> Result<ChatCompletionRequest, Response> {
+        let mut chat: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": self.request.model.clone().unwrap_or_default(),
+            "messages": []
+        }))
+        .map_err(|e| bad_request(format!("Failed to build chat request: {}", e)))?;
+
+        chat.messages = chat_messages(&self.request);
+        chat.tools = Some(
+            self.tools
+                .iter()
+                .map(|tool| Tool {
+                    tool_type: "function".to_string(),
+                    function: Function {
+                        name: qualified_name(tool),
+                        description: Some(tool.description.clone())
+                            .filter(|description| !description.is_empty()),
+                        parameters: tool
+                            .parameters
+                            .clone()
+                            .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+                    },
+                })
+                .collect(),
+        );
+        chat.tool_choice = Some(self.request.tool_choice.clone());
+        chat.max_tokens = self.request.max_output_tokens;
+        chat.temperature = self.request.temperature;
+        chat.top_p = self.request.top_p;
+        Ok(chat)
+    }
+
+    async fn invoke_model(&self, chat: &ChatCompletionRequest) -> Result<Value, Response> {
+        let response = self
+            .tool_loop
+            .router
+            .route_chat(self.headers.as_ref(), chat, self.model_id.as_deref())
+            .await;
+        if !response.status().is_success() {
+            return Err(response);
+        }
+
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map_err(|e| server_error(format!("Failed to read model response: {}", e)))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| server_error(format!("Failed to parse model response: {}", e)))
+    }
+
+    /// Execute one tool call, returning its `mcp_call` output item and the text fed back
+    /// to the model
+    async fn call_tool(
+        &self,
+        call: &ToolCall,
+        output_index: usize,
+        events: &mut EventSink,
+    ) -> (ResponseOutputItem, String) {
+        let id = format!("mcp_{}", uuid::Uuid::new_v4().simple());
+        let arguments = call
+            .function
+            .arguments
+            .clone()
+            .unwrap_or_else(|| "{}".to_string());
+        let tool = self
+            .tools
+            .iter()
+            .find(|tool| qualified_name(tool) == call.function.name);
+        let name = tool.map_or_else(|| call.function.name.clone(), |tool| tool.name.clone());
+        let server_label = tool.map(|tool| tool.server.clone()).unwrap_or_default();
+
+        let mcp_call = |output: Option<String>, error: Option<String>, status: &str| {
+            ResponseOutputItem::McpCall {
+                id: id.clone(),
+                server_label: server_label.clone(),
+                name: name.clone(),
+                arguments: arguments.clone(),
+                output,
+                error,
+                status: status.to_string(),
+            }
+        };
+        events.emit(
+            "response.output_item.added",
+            json!({ "output_index": output_index, "item": mcp_call(None, None, "in_progress") }),
+        );
+        events.emit(
+            "response.mcp_call.in_progress",
+            json!({ "output_index": output_index, "item_id": id }),
+        );
+
+        let result = match tool {
+            Some(tool) => match serde_json::from_str::<Map<String, Value>>(&arguments) {
+                Ok(args) => self
+                    .tool_loop
+                    .mcp
+                    .call_server_tool(&tool.server, &tool.name, Some(args))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| {
+                        let text = result
+                            .content
+                            .iter()
+                            .filter_map(|content| content.raw.as_text())
+                            .map(|text| text.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        if result.is_error == Some(true) {
+                            Err(text)
+                        } else {
+                            Ok(text)
+                        }
+                    }),
+                Err(e) => Err(format!("Invalid tool arguments: {}", e)),
+            },
+            None => Err(format!("Tool '{}' is not available", name)),
+        };
+
+        let (item, text) = match result {
+            Ok(text) => {
+                events.emit(
+                    "response.mcp_call.completed",
+                    json!({ "output_index": output_index, "item_id": id }),
+                );
+                (mcp_call(Some(text.clone()), None, "completed"), text)
+            }
+            Err(error) => {
+                warn!("MCP tool '{}' failed: {}", name, error);
+                events.emit(
+                    "response.mcp_call.failed",
+                    json!({ "output_index": output_index, "item_id": id }),
+                );
+                let text = format!("Error: {}", error);
+                (mcp_call(None, Some(error), "failed"), text)
+            }
+        };
+        events.emit(
+            "response.output_item.done",
+            json!({ "output_index": output_index, "item": item }),
+        );
+        (item, text)
+    }
+
+    fn response_object(
+        &self,
+        model: &str,
+        created_at: i64,
+        output: Vec<ResponseOutputItem>,
+        status: ResponseStatus,
+        usage: Option<UsageInfo>,
+    ) -> Value {
+        ResponsesResponse::from_request(
+            &self.request,
+            &HashMap::new(),
+            model.to_string(),
+            created_at,
+            output,
+            status,
+            usage,
+        )
+        .to_response_format()
+    }
+}
+
+/// Function name `tool` is offered to the model under, qualified by its server so tools
+/// of the same name on different servers stay apart
+fn qualified_name(tool: &ToolInfo) -> String {
+    format!("{}__{}", tool.server, tool.name)
+}
+
+/// Convert the Responses API input of `request` into chat messages
+fn chat_messages(request: &ResponsesRequest) -> Vec<ChatMessage> {
+    let mut messages = Vec::new();
+    if let Some(instructions) = &request.instructions {
+        messages.push(ChatMessage::System {
+            role: "system".to_string(),
+            content: instructions.clone(),
+            name: None,
+        });
+    }
+
+    let items = match &request.input {
+        ResponseInput::Text(text) => {
+            messages.push(user_message(text.clone()));
+            return messages;
+        }
+        ResponseInput::Items(items) => items,
+    };
+    for item in items {
+        match item {
+            ResponseInputOutputItem::Message { role, content, .. } => {
+                let text = content
+                    .iter()
+                    .map(|ResponseContentPart::OutputText { text, .. }| text.as_str())
+                    .collect::<String>();
+                messages.push(match role.as_str() {
+                    "assistant" => ChatMessage::Assistant {
+                        role: "assistant".to_string(),
+                        content: Some(text),
+                        name: None,
+                        tool_calls: None,
+                        function_call: None,
+                        reasoning_content: None,
+                    },
+                    "system" | "developer" => ChatMessage::System {
+                        role: "system".to_string(),
+                        content: text,
+                        name: None,
+                    },
+                    _ => user_message(text),
+                });
+            }
+            ResponseInputOutputItem::FunctionToolCall {
+                id,
+                name,
+                arguments,
+                output,
+                ..
+            } => {
+                messages.push(ChatMessage::Assistant {
+                    role: "assistant".to_string(),
+                    content: None,
+                    name: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: id.clone(),
+                        tool_type: "function".to_string(),
+                        function: FunctionCallResponse {
+                            name: name.clone(),
+                            arguments: Some(arguments.clone()),
+                        },
+                    }]),
+                    function_call: None,
+                    reasoning_content: None,
+                });
+                if let Some(output) = output {
+                    messages.push(ChatMessage::Tool {
+                        role: "tool".to_string(),
+                        content: output.clone(),
+                        tool_call_id: id.clone(),
+                    });
+                }
+            }
+            ResponseInputOutputItem::Reasoning { .. } => {}
+        }
+    }
+    messages
+}
+
+fn user_message(text: String) -> ChatMessage {
+    ChatMessage::User {
+        role: "user".to_string(),
+        content: UserMessageContent::Text(text),
+        name: None,
+    }
+}
+
+/// Record the final assistant message, emitting its text events
+fn final_message(text: String, output_index: usize, events: &mut EventSink) -> ResponseOutputItem {
+    let id = format!("msg_{}", uuid::Uuid::new_v4().simple());
+    let message = |text: &str, status: &str| {
+        ResponseOutputItem::new_message(
+            id.clone(),
+            "assistant".to_string(),
+            vec![ResponseContentPart::OutputText {
+                text: text.to_string(),
+                annotations: Vec::new(),
+                logprobs: None,
+            }],
+            status.to_string(),
+        )
+    };
+
+    events.emit(
+        "response.output_item.added",
+        json!({ "output_index": output_index, "item": message("", "in_progress") }),
+    );
+    let text_event = json!({ "output_index": output_index, "item_id": id, "content_index": 0 });
+    let mut delta = text_event.clone();
+    delta["delta"] = json!(text);
+    events.emit("response.output_text.delta", delta);
+    let mut done = text_event;
+    done["text"] = json!(text);
+    events.emit("response.output_text.done", done);
+
+    let item = message(&text, "completed");
+    events.emit(
+        "response.output_item.done",
+        json!({ "output_index": output_index, "item": item }),
+    );
+    item
+}
+
+/// Add the usage of a chat completion to the running total
+fn add_usage(total: &mut Option<UsageInfo>, completion: &Value) {
+    let Some(usage) = completion.get("usage") else {
+        return;
+    };
+    let count = |field: &str| usage.get(field).and_then(Value::as_u64).unwrap_or_default() as u32;
+    let total = total.get_or_insert(UsageInfo {
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        total_tokens: 0,
+        reasoning_tokens: None,
+        prompt_tokens_details: None,
+    });
+    total.prompt_tokens += count("prompt_tokens");
+    total.completion_tokens += count("completion_tokens");
+    total.total_tokens += count("total_tokens");
+}
+
+fn bad_request(message: String) -> Response {
+    let error = ErrorResponse::new(message, "invalid_request_error", None);
+    (StatusCode::BAD_REQUEST, Json(error)).into_response()
+}
+
+fn server_error(message: String) -> Response {
+    let error = ErrorResponse::new(message, "internal_error", None);
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+}
+
+/// Writes Responses API stream events; a no-op for non-streaming requests
+struct EventSink {
+    tx: Option<mpsc::UnboundedSender<Result<Bytes, String>>>,
+    sequence_number: u64,
+}
+
+impl EventSink {
+    fn new(tx: mpsc::UnboundedSender<Result<Bytes, String>>) -> Self {
+        Self {
+            tx: Some(tx),
+            sequence_number: 0,
+        }
+    }
+
+    fn disabled() -> Self {
+        Self {
+            tx: None,
+            sequence_number: 0,
+        }
+    }
+
+    fn emit(&mut self, event_type: &str, mut event: Value) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        event["type"] = json!(event_type);
+        event["sequence_number"] = json!(self.sequence_number);
+        self.sequence_number += 1;
+        let _ = tx.send(Ok(Bytes::from(format!(
+            "event: {}\ndata: {}\n\n",
+            event_type, event
+        ))));
+    }
+
+    fn done(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(Ok(Bytes::from_static(b"data: [DONE]\n\n")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_messages_from_response_input() {
+        let request: ResponsesRequest = serde_json::from_value(json!({
+            "instructions": "Be brief",
+            "input": [
+                {"type": "message", "id": "m1", "role": "user",
+                 "content": [{"type": "output_text", "text": "Search for rust"}]},
+                {"type": "function_tool_call", "id": "call_1", "name": "search",
+                 "arguments": "{\"q\":\"rust\"}", "output": "found"},
+                {"type": "message", "id": "m2", "role": "assistant",
+                 "content": [{"type": "output_text", "text": "Found it"}]}
+            ]
+        }))
+        .unwrap();
+
+        let messages = chat_messages(&request);
+        assert_eq!(messages.len(), 5);
+        assert!(
+            matches!(&messages[0], ChatMessage::System { content, .. } if content == "Be brief")
+        );
+        assert!(matches!(
+            &messages[1],
+            ChatMessage::User { content: UserMessageContent::Text(text), .. }
+                if text == "Search for rust"
+        ));
+        assert!(matches!(
+            &messages[2],
+            ChatMessage::Assistant { tool_calls: Some(calls), .. } if calls[0].id == "call_1"
+        ));
+        assert!(matches!(
+            &messages[3],
+            ChatMessage::Tool { content, tool_call_id, .. }
+                if content == "found" && tool_call_id == "call_1"
+        ));
+        assert!(matches!(
+            &messages[4],
+            ChatMessage::Assistant { content: Some(text), .. } if text == "Found it"
+        ));
+    }
+
+    #[test]
+    fn test_usage_is_summed_across_model_calls() {
+        let mut usage = None;
+        let completion =
+            json!({"usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}});
+        add_usage(&mut usage, &completion);
+        add_usage(&mut usage, &completion);
+        add_usage(&mut usage, &json!({}));
+        let usage = usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 20);
+        assert_eq!(usage.completion_tokens, 10);
+        assert_eq!(usage.total_tokens, 30);
+    }
+
+    #[test]
+    fn test_events_carry_type_and_sequence_number() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut events = EventSink::new(tx);
+        events.emit("response.created", json!({}));
+        events.emit("response.mcp_call.in_progress", json!({"item_id": "mcp_1"}));
+        events.done();
+        EventSink::disabled().emit("response.created", json!({}));
+
+        let first = rx.try_recv().unwrap().unwrap();
+        assert!(first.starts_with(b"event: response.created\n"));
+        let second = String::from_utf8(rx.try_recv().unwrap().unwrap().to_vec()).unwrap();
+        let data: Value = serde_json::from_str(
+            second
+                .lines()
+                .find_map(|line| line.strip_prefix("data: "))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(data["type"], "response.mcp_call.in_progress");
+        assert_eq!(data["sequence_number"], 1);
+        assert_eq!(
+            rx.try_recv().unwrap().unwrap(),
+            Bytes::from_static(b"data: [DONE]\n\n")
+        );
+    }
+}
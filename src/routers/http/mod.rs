@@ -1,8 +1,11 @@
 //! HTTP router implementations
 
+pub mod mcp_tool_loop;
 pub mod openai_router;
 pub mod pd_router;
 pub mod pd_types;
+pub mod responses;
 pub mod router;
+mod stream_timer;
 pub mod vllm_pd_router;
 pub mod vllm_service_discovery;
@@ -1,51 +1,123 @@
 //! OpenAI router implementation (reqwest-based)
+//!
+//! Requests go to one of several OpenAI-compatible upstream providers. The first
+//! attempt is spread by provider weight; connection errors, 5xx and 429 responses
+//! and open circuits fail over to the remaining providers in descending weight order.
 
-use crate::config::CircuitBreakerConfig;
+use crate::config::{CircuitBreakerConfig, OpenAIUpstream};
 use crate::core::{CircuitBreaker, CircuitBreakerConfig as CoreCircuitBreakerConfig};
 use crate::protocols::spec::{
     ChatCompletionRequest, CompletionRequest, GenerateRequest, RerankRequest,
 };
+use crate::protocols::worker_spec::WorkerConfigRequest;
+use crate::routers::WorkerManagement;
 use async_trait::async_trait;
 use axum::{
     body::Body,
     extract::Request,
-    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
     response::{IntoResponse, Response},
 };
 use futures_util::StreamExt;
+use rand::Rng;
+use serde_json::Value;
 use std::{
     any::Any,
-    sync::atomic::{AtomicBool, Ordering},
+    cmp::Reverse,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
 };
+use tracing::warn;
+
+/// An upstream provider and its circuit breaker
+#[derive(Debug)]
+struct Upstream {
+    /// Provider settings, with the URL's trailing slash removed
+    config: OpenAIUpstream,
+    circuit_breaker: CircuitBreaker,
+}
+
+impl Upstream {
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.config.url, path)
+    }
+
+    /// Authenticate with the provider's API key, or else forward the client's
+    ///
+    /// `headers` is None when the client's Authorization holds a router key, which must
+    /// not reach the provider.
+    fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+        headers: Option<&HeaderMap>,
+    ) -> reqwest::RequestBuilder {
+        match (
+            &self.config.api_key,
+            headers.and_then(|h| h.get(AUTHORIZATION)),
+        ) {
+            (Some(api_key), _) => request.bearer_auth(api_key),
+            (None, Some(auth)) => request.header(AUTHORIZATION, auth),
+            (None, None) => request,
+        }
+    }
+
+    /// Rename the request's model to the name the provider knows it by
+    fn map_model(&self, payload: &mut Value) {
+        if let Some(model) = payload.get_mut("model") {
+            if let Some(mapped) = model.as_str().and_then(|m| self.config.model_map.get(m)) {
+                *model = Value::String(mapped.clone());
+            }
+        }
+    }
+}
+
+/// Whether an upstream response should be retried on the next provider
+fn is_failover_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
 
 /// Router for OpenAI backend
 #[derive(Debug)]
 pub struct OpenAIRouter {
-    /// HTTP client for upstream OpenAI-compatible API
+    /// HTTP client for upstream OpenAI-compatible APIs
     client: reqwest::Client,
-    /// Base URL for identification (no trailing slash)
-    base_url: String,
-    /// Circuit breaker
-    circuit_breaker: CircuitBreaker,
+    /// Upstream providers, managed through the worker API
+    upstreams: RwLock<Vec<Arc<Upstream>>>,
+    /// Circuit breaker settings of every provider
+    circuit_breaker_config: CoreCircuitBreakerConfig,
     /// Health status
     healthy: AtomicBool,
+    /// Forward the client's Authorization to providers without an API key; off when
+    /// the router authenticates clients itself
+    forward_client_auth: bool,
 }
 
 impl OpenAIRouter {
-    /// Create a new OpenAI router
+    /// Create a new OpenAI router with a single upstream
     pub async fn new(
         base_url: String,
         circuit_breaker_config: Option<CircuitBreakerConfig>,
+    ) -> Result<Self, String> {
+        Self::with_upstreams(vec![OpenAIUpstream::new(base_url)], circuit_breaker_config).await
+    }
+
+    /// Create a new OpenAI router failing over between the given upstreams
+    pub async fn with_upstreams(
+        upstreams: Vec<OpenAIUpstream>,
+        circuit_breaker_config: Option<CircuitBreakerConfig>,
     ) -> Result<Self, String> {
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(300))
             .build()
             .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-        let base_url = base_url.trim_end_matches('/').to_string();
-
         // Convert circuit breaker config
-        let core_cb_config = circuit_breaker_config
+        let circuit_breaker_config = circuit_breaker_config
             .map(|cb| CoreCircuitBreakerConfig {
                 failure_threshold: cb.failure_threshold,
                 success_threshold: cb.success_threshold,
@@ -54,29 +126,151 @@ impl OpenAIRouter {
             })
             .unwrap_or_default();
 
-        let circuit_breaker = CircuitBreaker::with_config(core_cb_config);
-
-        Ok(Self {
+        let router = Self {
             client,
-            base_url,
-            circuit_breaker,
+            upstreams: RwLock::new(Vec::new()),
+            circuit_breaker_config,
             healthy: AtomicBool::new(true),
-        })
+            forward_client_auth: true,
+        };
+        for upstream in upstreams {
+            router.add_upstream(upstream)?;
+        }
+        Ok(router)
+    }
+
+    /// Set whether the client's Authorization is forwarded to providers without an
+    /// API key; disable when router auth is enabled so router keys stay private
+    pub fn with_client_auth_forwarding(mut self, forward: bool) -> Self {
+        self.forward_client_auth = forward;
+        self
+    }
+
+    /// Headers whose Authorization may be forwarded to providers
+    fn forwardable<'a>(&self, headers: Option<&'a HeaderMap>) -> Option<&'a HeaderMap> {
+        headers.filter(|_| self.forward_client_auth)
+    }
+
+    /// Add an upstream provider
+    pub fn add_upstream(&self, mut config: OpenAIUpstream) -> Result<String, String> {
+        config.url = config.url.trim_end_matches('/').to_string();
+        url::Url::parse(&config.url)
+            .map_err(|e| format!("Invalid OpenAI upstream URL '{}': {}", config.url, e))?;
+
+        let mut upstreams = self.upstreams.write().unwrap();
+        if upstreams.iter().any(|u| u.config.url == config.url) {
+            return Err(format!("OpenAI upstream {} already exists", config.url));
+        }
+        let message = format!("Successfully added OpenAI upstream: {}", config.url);
+        upstreams.push(Arc::new(Upstream {
+            config,
+            circuit_breaker: CircuitBreaker::with_config(self.circuit_breaker_config.clone()),
+        }));
+        Ok(message)
+    }
+
+    /// Upstreams in the order a request tries them: one picked by weight, then the
+    /// rest by descending weight
+    fn attempt_order(&self) -> Vec<Arc<Upstream>> {
+        let mut upstreams = self.upstreams.read().unwrap().clone();
+        // Stable sort, so equal weights keep their configured order
+        upstreams.sort_by_key(|u| Reverse(u.config.weight));
+
+        let total: u64 = upstreams.iter().map(|u| u64::from(u.config.weight)).sum();
+        if total > 0 {
+            let mut pick = rand::rng().random_range(0..total);
+            let index = upstreams
+                .iter()
+                .position(|u| {
+                    let weight = u64::from(u.config.weight);
+                    if pick < weight {
+                        return true;
+                    }
+                    pick -= weight;
+                    false
+                })
+                .unwrap_or(0);
+            let first = upstreams.remove(index);
+            upstreams.insert(0, first);
+        }
+        upstreams
+    }
+
+    /// Send a request built per upstream, failing over until a provider answers
+    ///
+    /// When every provider fails, the last provider's error response is relayed, or a
+    /// 503 if none could be reached.
+    async fn send_with_failover<F>(&self, build: F) -> Result<reqwest::Response, Response>
+    where
+        F: Fn(&Upstream) -> reqwest::RequestBuilder,
+    {
+        let mut last_response = None;
+        let mut last_error = "No OpenAI upstream available".to_string();
+
+        for upstream in self.attempt_order() {
+            if !upstream.circuit_breaker.can_execute() {
+                continue;
+            }
+            match build(&upstream).send().await {
+                Ok(resp) if is_failover_status(resp.status()) => {
+                    upstream.circuit_breaker.record_failure();
+                    warn!(
+                        "OpenAI upstream {} returned {}, failing over",
+                        upstream.config.url,
+                        resp.status()
+                    );
+                    last_response = Some(resp);
+                }
+                Ok(resp) => {
+                    upstream.circuit_breaker.record_success();
+                    return Ok(resp);
+                }
+                Err(e) => {
+                    upstream.circuit_breaker.record_failure();
+                    warn!(
+                        "Failed to contact OpenAI upstream {}, failing over: {}",
+                        upstream.config.url, e
+                    );
+                    last_error = format!("Failed to contact upstream: {}", e);
+                }
+            }
+        }
+
+        last_response.ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, last_error).into_response())
     }
 }
 
 #[async_trait]
 impl super::super::WorkerManagement for OpenAIRouter {
-    async fn add_worker(&self, _worker_url: &str) -> Result<String, String> {
-        Err("Cannot add workers to OpenAI router".to_string())
+    async fn add_worker(&self, worker_url: &str) -> Result<String, String> {
+        self.add_upstream(OpenAIUpstream::new(worker_url))
     }
 
-    fn remove_worker(&self, _worker_url: &str) {
-        // No-op for OpenAI router
+    async fn add_worker_with_config(&self, config: &WorkerConfigRequest) -> Result<String, String> {
+        let mut upstream = OpenAIUpstream::new(config.url.clone());
+        upstream.api_key = config.api_key.clone();
+        upstream.model_map = config.model_map.clone();
+        if let Some(weight) = config.weight {
+            upstream.weight = weight;
+        }
+        self.add_upstream(upstream)
+    }
+
+    fn remove_worker(&self, worker_url: &str) {
+        let worker_url = worker_url.trim_end_matches('/');
+        self.upstreams
+            .write()
+            .unwrap()
+            .retain(|u| u.config.url != worker_url);
     }
 
     fn get_worker_urls(&self) -> Vec<String> {
-        vec![self.base_url.clone()]
+        self.upstreams
+            .read()
+            .unwrap()
+            .iter()
+            .map(|u| u.config.url.clone())
+            .collect()
     }
 }
 
@@ -87,34 +281,29 @@ impl super::super::RouterTrait for OpenAIRouter {
     }
 
     async fn health(&self, _req: Request<Body>) -> Response {
-        // Simple upstream probe: GET {base}/v1/models without auth
-        let url = format!("{}/v1/models", self.base_url);
-        match self
-            .client
-            .get(&url)
-            .timeout(std::time::Duration::from_secs(2))
-            .send()
-            .await
-        {
-            Ok(resp) => {
-                let code = resp.status();
-                // Treat success and auth-required as healthy (endpoint reachable)
-                if code.is_success() || code.as_u16() == 401 || code.as_u16() == 403 {
-                    (StatusCode::OK, "OK").into_response()
-                } else {
-                    (
-                        StatusCode::SERVICE_UNAVAILABLE,
-                        format!("Upstream status: {}", code),
-                    )
-                        .into_response()
+        // Simple upstream probe: GET {base}/v1/models without auth; one reachable
+        // provider is enough to serve requests
+        let mut failure = "No OpenAI upstream configured".to_string();
+        for upstream in self.attempt_order() {
+            match self
+                .client
+                .get(upstream.endpoint("/v1/models"))
+                .timeout(std::time::Duration::from_secs(2))
+                .send()
+                .await
+            {
+                Ok(resp) => {
+                    let code = resp.status();
+                    // Treat success and auth-required as healthy (endpoint reachable)
+                    if code.is_success() || code.as_u16() == 401 || code.as_u16() == 403 {
+                        return (StatusCode::OK, "OK").into_response();
+                    }
+                    failure = format!("Upstream status: {}", code);
                 }
+                Err(e) => failure = format!("Upstream error: {}", e),
             }
-            Err(e) => (
-                StatusCode::SERVICE_UNAVAILABLE,
-                format!("Upstream error: {}", e),
-            )
-                .into_response(),
         }
+        (StatusCode::SERVICE_UNAVAILABLE, failure).into_response()
     }
 
     async fn health_generate(&self, _req: Request<Body>) -> Response {
@@ -123,51 +312,46 @@ impl super::super::RouterTrait for OpenAIRouter {
     }
 
     async fn get_server_info(&self, _req: Request<Body>) -> Response {
+        let base_urls = self.get_worker_urls();
         let info = serde_json::json!({
             "router_type": "openai",
-            "workers": 1,
-            "base_url": &self.base_url
+            "workers": base_urls.len(),
+            "base_urls": base_urls
         });
         (StatusCode::OK, info.to_string()).into_response()
     }
 
     async fn get_models(&self, req: Request<Body>) -> Response {
-        // Proxy to upstream /v1/models; forward Authorization header if provided
+        // Proxy to upstream /v1/models with the provider's or the client's credentials
         let headers = req.headers();
-
-        let mut upstream = self.client.get(format!("{}/v1/models", self.base_url));
-
-        if let Some(auth) = headers
-            .get("authorization")
-            .or_else(|| headers.get("Authorization"))
+        let res = match self
+            .send_with_failover(|upstream| {
+                upstream.authorize(
+                    self.client.get(upstream.endpoint("/v1/models")),
+                    self.forwardable(Some(headers)),
+                )
+            })
+            .await
         {
-            upstream = upstream.header("Authorization", auth);
-        }
+            Ok(res) => res,
+            Err(response) => return response,
+        };
 
-        match upstream.send().await {
-            Ok(res) => {
-                let status = StatusCode::from_u16(res.status().as_u16())
-                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-                let content_type = res.headers().get(CONTENT_TYPE).cloned();
-                match res.bytes().await {
-                    Ok(body) => {
-                        let mut response = Response::new(axum::body::Body::from(body));
-                        *response.status_mut() = status;
-                        if let Some(ct) = content_type {
-                            response.headers_mut().insert(CONTENT_TYPE, ct);
-                        }
-                        response
-                    }
-                    Err(e) => (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to read upstream response: {}", e),
-                    )
-                        .into_response(),
+        let status = StatusCode::from_u16(res.status().as_u16())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let content_type = res.headers().get(CONTENT_TYPE).cloned();
+        match res.bytes().await {
+            Ok(body) => {
+                let mut response = Response::new(axum::body::Body::from(body));
+                *response.status_mut() = status;
+                if let Some(ct) = content_type {
+                    response.headers_mut().insert(CONTENT_TYPE, ct);
                 }
+                response
             }
             Err(e) => (
-                StatusCode::BAD_GATEWAY,
-                format!("Failed to contact upstream: {}", e),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read upstream response: {}", e),
             )
                 .into_response(),
         }
@@ -202,10 +386,6 @@ impl super::super::RouterTrait for OpenAIRouter {
         body: &ChatCompletionRequest,
         _model_id: Option<&str>,
     ) -> Response {
-        if !self.circuit_breaker.can_execute() {
-            return (StatusCode::SERVICE_UNAVAILABLE, "Circuit breaker open").into_response();
-        }
-
         // Serialize request body, removing VLLM-only fields
         let mut payload = match serde_json::to_value(body) {
             Ok(v) => v,
@@ -241,31 +421,26 @@ impl super::super::RouterTrait for OpenAIRouter {
             }
         }
 
-        let url = format!("{}/v1/chat/completions", self.base_url);
-        let mut req = self.client.post(&url).json(&payload);
-
-        // Forward Authorization header if provided
-        if let Some(h) = headers {
-            if let Some(auth) = h.get("authorization").or_else(|| h.get("Authorization")) {
-                req = req.header("Authorization", auth);
-            }
-        }
-
-        // Accept SSE when stream=true
-        if body.stream {
-            req = req.header("Accept", "text/event-stream");
-        }
-
-        let resp = match req.send().await {
+        let resp = match self
+            .send_with_failover(|upstream| {
+                let mut payload = payload.clone();
+                upstream.map_model(&mut payload);
+                let mut req = upstream.authorize(
+                    self.client
+                        .post(upstream.endpoint("/v1/chat/completions"))
+                        .json(&payload),
+                    self.forwardable(headers),
+                );
+                // Accept SSE when stream=true
+                if body.stream {
+                    req = req.header("Accept", "text/event-stream");
+                }
+                req
+            })
+            .await
+        {
             Ok(r) => r,
-            Err(e) => {
-                self.circuit_breaker.record_failure();
-                return (
-                    StatusCode::SERVICE_UNAVAILABLE,
-                    format!("Failed to contact upstream: {}", e),
-                )
-                    .into_response();
-            }
+            Err(response) => return response,
         };
 
         let status = StatusCode::from_u16(resp.status().as_u16())
@@ -276,7 +451,6 @@ impl super::super::RouterTrait for OpenAIRouter {
             let content_type = resp.headers().get(CONTENT_TYPE).cloned();
             match resp.bytes().await {
                 Ok(body) => {
-                    self.circuit_breaker.record_success();
                     let mut response = Response::new(axum::body::Body::from(body));
                     *response.status_mut() = status;
                     if let Some(ct) = content_type {
@@ -284,14 +458,11 @@ impl super::super::RouterTrait for OpenAIRouter {
                     }
                     response
                 }
-                Err(e) => {
-                    self.circuit_breaker.record_failure();
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to read response: {}", e),
-                    )
-                        .into_response()
-                }
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read response: {}", e),
+                )
+                    .into_response(),
             }
         } else {
             // Stream SSE bytes to client
@@ -388,7 +559,13 @@ impl super::super::RouterTrait for OpenAIRouter {
     }
 
     fn readiness(&self) -> Response {
-        if self.healthy.load(Ordering::Acquire) && self.circuit_breaker.can_execute() {
+        let available = self
+            .upstreams
+            .read()
+            .unwrap()
+            .iter()
+            .any(|u| u.circuit_breaker.can_execute());
+        if self.healthy.load(Ordering::Acquire) && available {
             (StatusCode::OK, "Ready").into_response()
         } else {
             (StatusCode::SERVICE_UNAVAILABLE, "Not ready").into_response()
@@ -1,19 +1,23 @@
 // PD (Prefill-Decode) Router Implementation
 // This module handles routing for disaggregated prefill-decode systems
 use super::pd_types::{api_path, PDRouterError};
-use crate::config::types::RetryConfig;
+use crate::config::types::{LoadUnit, RetryConfig};
 use crate::core::{
-    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig, RetryExecutor, Worker,
-    WorkerFactory, WorkerLoadGuard, WorkerRegistry, WorkerType,
+    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig, ModelCatalog,
+    RequestAborter, RetryExecutor, TokenEstimate, TokenLoadGuard, Worker, WorkerFactory,
+    WorkerLoadGuard, WorkerMetrics, WorkerRegistry, WorkerType,
 };
 use crate::metrics::RouterMetrics;
-use crate::policies::{LoadBalancingPolicy, PolicyRegistry};
+use crate::policies::{request_affinity_key, LoadBalancingPolicy, PolicyRegistry, RequestTiming};
 use crate::protocols::spec::{
-    ChatCompletionRequest, ChatMessage, CompletionRequest, GenerateRequest, RerankRequest,
-    ResponsesRequest, StringOrArray, UserMessageContent,
+    ChatCompletionRequest, ChatMessage, CompletionRequest, GenerateRequest, GenerationRequest,
+    RerankRequest, ResponsesRequest, StringOrArray, UserMessageContent,
 };
-use crate::routers::header_utils;
+use crate::routers::grpc::utils::render_chat_prompt;
+use crate::routers::header_utils::{self, WorkerConstraints};
+use crate::routers::http::stream_timer::StreamTimer;
 use crate::routers::{RouterTrait, WorkerManagement};
+use crate::tokenizer::traits::Tokenizer;
 use async_trait::async_trait;
 use axum::{
     body::Body,
@@ -33,21 +37,47 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, info, warn};
 
-#[derive(Debug)]
 pub struct PDRouter {
     pub worker_registry: Arc<WorkerRegistry>,
     pub policy_registry: Arc<PolicyRegistry>,
+    pub model_catalog: Arc<ModelCatalog>,
     pub worker_startup_timeout_secs: u64,
     pub worker_startup_check_interval_secs: u64,
-    pub worker_loads: Arc<tokio::sync::watch::Receiver<HashMap<String, isize>>>,
-    pub load_monitor_handle: Option<Arc<tokio::task::JoinHandle<()>>>,
+    pub worker_metrics: Arc<WorkerMetrics>,
     pub client: Client,
     // Dedicated client for prefill fire-and-forget (non-logprob) requests
     pub prefill_client: Client,
     pub retry_config: RetryConfig,
     pub circuit_breaker_config: CircuitBreakerConfig,
+    // Key the router authenticates to workers with
+    api_key: Option<String>,
     // Channel for sending prefill responses to background workers for draining
     prefill_drain_tx: mpsc::Sender<reqwest::Response>,
+    // Aborts streaming requests on both workers when the client disconnects
+    aborter: RequestAborter,
+    // Counts prompt tokens when policies balance token-weighted load; bytes are used without it
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+}
+
+impl std::fmt::Debug for PDRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PDRouter")
+            .field("worker_registry", &self.worker_registry)
+            .field("policy_registry", &self.policy_registry)
+            .field("model_catalog", &self.model_catalog)
+            .field(
+                "worker_startup_timeout_secs",
+                &self.worker_startup_timeout_secs,
+            )
+            .field(
+                "worker_startup_check_interval_secs",
+                &self.worker_startup_check_interval_secs,
+            )
+            .field("retry_config", &self.retry_config)
+            .field("circuit_breaker_config", &self.circuit_breaker_config)
+            .field("tokenizer", &self.tokenizer.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 // Request context for PD router operations
@@ -59,6 +89,36 @@ struct PDRequestContext<'a> {
     return_logprob: bool,
     request_text: Option<String>,
     model_id: Option<&'a str>,
+    tokens: TokenEstimate,
+}
+
+// Estimated tokens a PD request holds on its workers
+struct PDTokenLoad {
+    // The prompt, held on the prefill worker until the first token is generated
+    prefill: Option<TokenLoadGuard>,
+    // The prompt and decode budget, held on the decode worker until the request ends
+    decode: TokenLoadGuard,
+}
+
+impl PDTokenLoad {
+    fn new(prefill: Arc<dyn Worker>, decode: Arc<dyn Worker>, tokens: TokenEstimate) -> Self {
+        let prompt = TokenEstimate {
+            decode_budget: 0,
+            ..tokens
+        };
+        Self {
+            prefill: Some(TokenLoadGuard::new(prefill, prompt)),
+            decode: TokenLoadGuard::new(decode, tokens),
+        }
+    }
+
+    // Account for tokens generated by the decode worker
+    fn drain(&mut self, tokens: usize) {
+        if tokens > 0 {
+            self.prefill = None;
+            self.decode.drain(tokens);
+        }
+    }
 }
 
 impl PDRouter {
@@ -219,7 +279,6 @@ impl PDRouter {
         }
 
         // Create Worker for the new prefill server with circuit breaker configuration
-        // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
         let worker = WorkerFactory::create_prefill_with_config(
             url.clone(),
             bootstrap_port,
@@ -228,8 +287,13 @@ impl PDRouter {
 
         let worker_arc: Arc<dyn Worker> = Arc::from(worker);
 
-        // Register the worker in the registry
+        // Register the worker in the registry under the models it serves
         self.worker_registry.register(worker_arc.clone());
+        let worker_arc = self
+            .worker_registry
+            .discover_served_models(&url, self.api_key.as_deref())
+            .await
+            .unwrap_or(worker_arc);
 
         // Notify PolicyRegistry about the new worker
         let model_id = worker_arc.model_id();
@@ -260,7 +324,6 @@ impl PDRouter {
         }
 
         // Create Worker for the new decode server with circuit breaker configuration
-        // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
         let worker = WorkerFactory::create_decode_with_config(
             url.clone(),
             self.circuit_breaker_config.clone(),
@@ -268,8 +331,13 @@ impl PDRouter {
 
         let worker_arc: Arc<dyn Worker> = Arc::from(worker);
 
-        // Register the worker in the registry
+        // Register the worker in the registry under the models it serves
         self.worker_registry.register(worker_arc.clone());
+        let worker_arc = self
+            .worker_registry
+            .discover_served_models(&url, self.api_key.as_deref())
+            .await
+            .unwrap_or(worker_arc);
 
         // Notify PolicyRegistry about the new worker
         let model_id = worker_arc.model_id();
@@ -434,43 +502,17 @@ impl PDRouter {
             )
             .await?;
         }
+        for url in &all_urls {
+            ctx.worker_registry
+                .discover_served_models(url, ctx.router_config.api_key.as_deref())
+                .await;
+        }
 
         // Initialize cache-aware policies with workers from registry
         // Note: We need to get workers by type and convert to Box<dyn Worker> for CacheAwarePolicy
         // This is a temporary workaround until CacheAwarePolicy is updated to work with Arc<dyn Worker>
         // TODO: Update CacheAwarePolicy to accept Arc<dyn Worker> instead of Box<dyn Worker>
 
-        // Set up background load monitoring for power-of-two selection
-        let (tx, rx) = tokio::sync::watch::channel(HashMap::new());
-        let worker_loads = Arc::new(rx);
-
-        // Get policies from registry to check if we need load monitoring
-        let prefill_policy = ctx.policy_registry.get_prefill_policy();
-        let decode_policy = ctx.policy_registry.get_decode_policy();
-
-        let load_monitor_handle =
-            if prefill_policy.name() == "power_of_two" || decode_policy.name() == "power_of_two" {
-                let monitor_urls = all_urls.clone();
-                let monitor_interval = ctx.router_config.worker_startup_check_interval_secs;
-                let monitor_client = ctx.client.clone();
-                let prefill_policy_clone = Arc::clone(&prefill_policy);
-                let decode_policy_clone = Arc::clone(&decode_policy);
-
-                Some(Arc::new(tokio::spawn(async move {
-                    Self::monitor_worker_loads_with_client(
-                        monitor_urls,
-                        tx,
-                        monitor_interval,
-                        monitor_client,
-                        prefill_policy_clone,
-                        decode_policy_clone,
-                    )
-                    .await;
-                })))
-            } else {
-                None
-            };
-
         // Note: Health checking is now handled centrally by RouterManager
         // Individual routers no longer need to manage health checkers
 
@@ -555,20 +597,36 @@ impl PDRouter {
         Ok(PDRouter {
             worker_registry: Arc::clone(&ctx.worker_registry),
             policy_registry: Arc::clone(&ctx.policy_registry),
+            model_catalog: Arc::clone(&ctx.model_catalog),
             worker_startup_timeout_secs: ctx.router_config.worker_startup_timeout_secs,
             worker_startup_check_interval_secs: ctx
                 .router_config
                 .worker_startup_check_interval_secs,
-            worker_loads,
-            load_monitor_handle,
+            worker_metrics: Arc::clone(&ctx.worker_metrics),
             client: ctx.client.clone(),
             prefill_client,
             prefill_drain_tx,
             retry_config: ctx.router_config.effective_retry_config(),
             circuit_breaker_config: core_cb_config,
+            api_key: ctx.router_config.api_key.clone(),
+            aborter: RequestAborter::new(ctx.client.clone(), ctx.router_config.api_key.clone()),
+            tokenizer: ctx
+                .tokenizer
+                .clone()
+                .filter(|_| ctx.router_config.load_unit == LoadUnit::Tokens),
         })
     }
 
+    // Estimate the tokens a request will hold on its workers
+    async fn token_estimate<T: GenerationRequest>(&self, request: &T) -> TokenEstimate {
+        TokenEstimate::estimate(
+            request.prompt_text(),
+            request.max_output_tokens(),
+            self.tokenizer.clone(),
+        )
+        .await
+    }
+
     // Helper to handle server selection errors
     fn handle_server_selection_error(error: String) -> Response {
         error!("Failed to select PD pair error={}", error);
@@ -703,6 +761,21 @@ impl PDRouter {
         context: PDRequestContext<'_>,
     ) -> Response {
         let start_time = Instant::now();
+        let constraints = WorkerConstraints::from_headers(headers);
+        // Policies keyed on request headers or fields route on that key instead of the text
+        let prefill_key = request_affinity_key(
+            &*self.policy_registry.get_prefill_policy(),
+            headers,
+            original_request,
+        );
+        let decode_key = request_affinity_key(
+            &*self.policy_registry.get_decode_policy(),
+            headers,
+            original_request,
+        );
+        // Borrowed by every attempt
+        let constraints = &constraints;
+        let (prefill_key, decode_key) = (&prefill_key, &decode_key);
 
         let route = context.route;
         RetryExecutor::execute_response_with_retry(
@@ -716,7 +789,12 @@ impl PDRouter {
                     async move {
                         // Select workers fresh for each attempt
                         let (prefill, decode) = match self
-                            .select_pd_pair(context.request_text.as_deref(), context.model_id)
+                            .select_pd_pair(
+                                prefill_key.as_deref().or(context.request_text.as_deref()),
+                                decode_key.as_deref().or(context.request_text.as_deref()),
+                                context.model_id,
+                                constraints,
+                            )
                             .await
                         {
                             Ok(pair) => pair,
@@ -749,7 +827,22 @@ impl PDRouter {
                             Err(e) => return Self::handle_serialization_error(e),
                         };
 
+                        // Give both workers the same request ID so the request can be aborted
+                        if context.batch_size.is_none() {
+                            if let Some(obj) = json_request.as_object_mut() {
+                                if !obj.get("rid").is_some_and(Value::is_string) {
+                                    obj.insert(
+                                        "rid".to_string(),
+                                        Value::from(uuid::Uuid::new_v4().to_string()),
+                                    );
+                                }
+                            }
+                        }
+
                         // Execute the actual dual dispatch
+                        let is_stream = context.is_stream;
+                        let token_load =
+                            PDTokenLoad::new(prefill.clone(), decode.clone(), context.tokens);
                         let response = self
                             .execute_dual_dispatch_internal(
                                 headers,
@@ -757,6 +850,7 @@ impl PDRouter {
                                 context,
                                 prefill.as_ref(),
                                 decode.as_ref(),
+                                token_load,
                                 start_time,
                             )
                             .await;
@@ -767,6 +861,17 @@ impl PDRouter {
                         prefill.record_outcome(not_error);
                         decode.record_outcome(not_error);
 
+                        // Streams report their latencies once they end
+                        if !is_stream {
+                            let timing = RequestTiming::default();
+                            self.policy_registry
+                                .get_prefill_policy()
+                                .on_request_complete(prefill.url(), not_error, &timing);
+                            self.policy_registry
+                                .get_decode_policy()
+                                .on_request_complete(decode.url(), not_error, &timing);
+                        }
+
                         response
                     }
                 }
@@ -784,6 +889,26 @@ impl PDRouter {
         .await
     }
 
+    /// Report a failed stream that ended before a response stream was created, since
+    /// the dispatch loop leaves reporting streams to the response stream
+    fn report_unstreamed_failure(
+        &self,
+        context: &PDRequestContext<'_>,
+        prefill: &dyn Worker,
+        decode: &dyn Worker,
+    ) {
+        if !context.is_stream {
+            return;
+        }
+        let timing = RequestTiming::default();
+        self.policy_registry
+            .get_prefill_policy()
+            .on_request_complete(prefill.url(), false, &timing);
+        self.policy_registry
+            .get_decode_policy()
+            .on_request_complete(decode.url(), false, &timing);
+    }
+
     async fn handle_decode_error_response(
         &self,
         res: reqwest::Response,
@@ -825,6 +950,10 @@ impl PDRouter {
                 Some(response_headers),
                 prefill,
                 decode,
+                context.route,
+                None,
+                StreamTimer::start(),
+                None,
             )
         } else {
             // Handle non-streaming error response
@@ -836,6 +965,7 @@ impl PDRouter {
     }
 
     // Internal method that performs the actual dual dispatch (without retry logic)
+    #[allow(clippy::too_many_arguments)]
     async fn execute_dual_dispatch_internal(
         &self,
         headers: Option<&HeaderMap>,
@@ -843,6 +973,7 @@ impl PDRouter {
         context: PDRequestContext<'_>,
         prefill: &dyn Worker,
         decode: &dyn Worker,
+        token_load: PDTokenLoad, // Held until the response is done
         start_time: Instant,
     ) -> Response {
         // For non-streaming: use guard for automatic load management
@@ -852,6 +983,11 @@ impl PDRouter {
         } else {
             None
         };
+        let request_id = json_request
+            .get("rid")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let timer = StreamTimer::start();
 
         // Build decode request with shared client
         let decode_request = self.build_post_with_headers(
@@ -923,7 +1059,10 @@ impl PDRouter {
                         .await
                     {
                         Ok((_, body)) => body,
-                        Err(error_response) => return error_response,
+                        Err(error_response) => {
+                            self.report_unstreamed_failure(&context, prefill, decode);
+                            return error_response;
+                        }
                     };
 
                     if context.is_stream {
@@ -947,6 +1086,10 @@ impl PDRouter {
                             Some(response_headers),
                             prefill,
                             decode,
+                            context.route,
+                            request_id,
+                            timer,
+                            Some(token_load),
                         )
                     } else {
                         // Non-streaming response with logprobs
@@ -966,6 +1109,7 @@ impl PDRouter {
                         "Decode request failed"
                     );
                     RouterMetrics::record_pd_decode_error(decode.url());
+                    self.report_unstreamed_failure(&context, prefill, decode);
                     (
                         StatusCode::BAD_GATEWAY,
                         format!("Decode server error: {}", e),
@@ -1071,6 +1215,10 @@ impl PDRouter {
                             Some(response_headers),
                             prefill,
                             decode,
+                            context.route,
+                            request_id,
+                            timer,
+                            Some(token_load),
                         )
                     } else {
                         // Non-streaming response without logprobs - direct passthrough like fast version
@@ -1100,6 +1248,7 @@ impl PDRouter {
                         "Decode request failed"
                     );
                     RouterMetrics::record_pd_decode_error(decode.url());
+                    self.report_unstreamed_failure(&context, prefill, decode);
                     (
                         StatusCode::BAD_GATEWAY,
                         format!("Decode server error: {}", e),
@@ -1118,17 +1267,20 @@ impl PDRouter {
         prefill_policy.needs_request_text() || decode_policy.needs_request_text()
     }
 
-    // Select a pair of prefill and decode servers considering circuit breaker state
+    // Select a pair of prefill and decode servers considering circuit breaker state and
+    // the request's worker constraints
     async fn select_pd_pair(
         &self,
-        request_text: Option<&str>,
+        prefill_text: Option<&str>,
+        decode_text: Option<&str>,
         model_id: Option<&str>,
+        constraints: &WorkerConstraints,
     ) -> Result<(Arc<dyn Worker>, Arc<dyn Worker>), String> {
         // Get workers from registry - filter by model if provided
-        let prefill_workers = if let Some(model) = model_id {
+        let prefill_workers: Vec<_> = if let Some(model) = model_id {
             // Get model-specific workers and filter for prefill type
             self.worker_registry
-                .get_by_model_fast(model)
+                .get_serving_model(model)
                 .into_iter()
                 .filter(|w| matches!(w.worker_type(), WorkerType::Prefill { .. }))
                 .collect()
@@ -1136,10 +1288,10 @@ impl PDRouter {
             self.worker_registry.get_prefill_workers()
         };
 
-        let decode_workers = if let Some(model) = model_id {
+        let decode_workers: Vec<_> = if let Some(model) = model_id {
             // Get model-specific workers and filter for decode type
             self.worker_registry
-                .get_by_model_fast(model)
+                .get_serving_model(model)
                 .into_iter()
                 .filter(|w| matches!(w.worker_type(), WorkerType::Decode))
                 .collect()
@@ -1147,6 +1299,16 @@ impl PDRouter {
             self.worker_registry.get_decode_workers()
         };
 
+        // Drop workers outside the request's priority and cost limits
+        let prefill_workers: Vec<_> = prefill_workers
+            .into_iter()
+            .filter(|w| constraints.allows(w.as_ref()))
+            .collect();
+        let decode_workers: Vec<_> = decode_workers
+            .into_iter()
+            .filter(|w| constraints.allows(w.as_ref()))
+            .collect();
+
         // Select workers using helper function
         // Use separate policies for prefill and decode to avoid counter conflicts
         let prefill_policy = self.policy_registry.get_prefill_policy();
@@ -1155,14 +1317,14 @@ impl PDRouter {
         let prefill = Self::pick_worker_by_policy_arc(
             &prefill_workers,
             &*prefill_policy,
-            request_text,
+            prefill_text,
             "prefill",
         )?;
 
         let decode = Self::pick_worker_by_policy_arc(
             &decode_workers,
             &*decode_policy,
-            request_text,
+            decode_text,
             "decode",
         )?;
 
@@ -1213,52 +1375,6 @@ impl PDRouter {
         Ok(available_workers[selected_idx].clone())
     }
 
-    // Background task to monitor worker loads with shared client
-    async fn monitor_worker_loads_with_client(
-        worker_urls: Vec<String>,
-        tx: tokio::sync::watch::Sender<HashMap<String, isize>>,
-        interval_secs: u64,
-        client: Client,
-        prefill_policy: Arc<dyn LoadBalancingPolicy>,
-        decode_policy: Arc<dyn LoadBalancingPolicy>,
-    ) {
-        loop {
-            let mut loads = HashMap::new();
-
-            let futures: Vec<_> = worker_urls
-                .iter()
-                .map(|url| {
-                    let client = client.clone();
-                    let url = url.clone();
-                    async move {
-                        let load = get_worker_load(&client, &url).await.unwrap_or(0);
-                        (url, load)
-                    }
-                })
-                .collect();
-
-            let results = futures_util::future::join_all(futures).await;
-
-            for (url, load) in results {
-                loads.insert(url, load);
-            }
-
-            debug!("Worker loads updated: {:?}", loads);
-
-            // Update both policies with current loads
-            prefill_policy.update_loads(&loads);
-            decode_policy.update_loads(&loads);
-
-            // Check if receiver is still active
-            if tx.send(loads).is_err() {
-                info!("Load monitor receiver dropped, shutting down monitor task");
-                break;
-            }
-
-            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
-        }
-    }
-
     // Helper to create a streaming response
     #[allow(clippy::too_many_arguments)]
     fn create_streaming_response(
@@ -1271,6 +1387,10 @@ impl PDRouter {
         headers: Option<HeaderMap>,
         prefill: &dyn Worker,
         decode: &dyn Worker,
+        route: &str,
+        request_id: Option<String>,
+        mut timer: StreamTimer,
+        mut token_load: Option<PDTokenLoad>,
     ) -> Response {
         // For streaming, increment load now - will be decremented when streaming completes
         prefill.increment_load();
@@ -1284,15 +1404,36 @@ impl PDRouter {
 
         // Clone the registry for the spawned task
         let registry = self.worker_registry.clone();
+        let prefill_policy = self.policy_registry.get_prefill_policy();
+        let decode_policy = self.policy_registry.get_decode_policy();
+        let aborter = self.aborter.clone();
+        let route = route.to_string();
 
         tokio::spawn(async move {
             // Use a flag to track whether stream completed successfully
             let mut stream_completed = false;
+            let mut client_disconnected = false;
+            let mut stream_failed = false;
 
             futures_util::pin_mut!(stream);
-            while let Some(chunk_result) = stream.next().await {
+            loop {
+                let chunk_result = tokio::select! {
+                    biased;
+                    _ = tx.closed() => {
+                        client_disconnected = true;
+                        break;
+                    }
+                    chunk_result = stream.next() => chunk_result,
+                };
+                let Some(chunk_result) = chunk_result else {
+                    break;
+                };
                 match chunk_result {
                     Ok(chunk) => {
+                        let events = timer.observe(&chunk);
+                        if let Some(token_load) = &mut token_load {
+                            token_load.drain(events);
+                        }
                         // Check for stream end marker to decrement load early
                         let is_done = chunk
                             .as_ref()
@@ -1308,6 +1449,7 @@ impl PDRouter {
                         };
 
                         if tx.send(Ok(result)).is_err() {
+                            client_disconnected = !is_done;
                             break;
                         }
 
@@ -1323,14 +1465,26 @@ impl PDRouter {
                             RouterMetrics::record_pd_stream_error(url);
                         }
                         let _ = tx.send(Err(format!("Stream error: {}", e)));
+                        stream_failed = true;
                         break;
                     }
                 }
             }
 
+            // The first token waits for the prefill, the following ones for the decode
+            let success = status.is_success() && !stream_failed;
+            let timing = timer.finish();
+            let prefill_timing = RequestTiming {
+                tpot: None,
+                ..timing
+            };
+            prefill_policy.on_request_complete(&prefill_url, success, &prefill_timing);
+            decode_policy.on_request_complete(&decode_url_str, success, &timing);
+
             // Always decrement load after streaming (either completes or errors)
             // Find and decrement prefill worker
-            if let Some(worker) = registry.get_by_url(&prefill_url) {
+            let prefill_worker = registry.get_by_url(&prefill_url);
+            if let Some(worker) = &prefill_worker {
                 worker.decrement_load();
                 debug!(
                     "Decremented load for prefill worker: {} (stream_completed: {})",
@@ -1339,13 +1493,21 @@ impl PDRouter {
             }
 
             // Find and decrement decode worker
-            if let Some(worker) = registry.get_by_url(&decode_url_str) {
+            let decode_worker = registry.get_by_url(&decode_url_str);
+            if let Some(worker) = &decode_worker {
                 worker.decrement_load();
                 debug!(
                     "Decremented load for decode worker: {} (stream_completed: {})",
                     decode_url_str, stream_completed
                 );
             }
+
+            // The client went away mid-stream: stop generation on both workers
+            if let (true, Some(request_id)) = (client_disconnected, request_id) {
+                debug!("Client disconnected from stream for request {}", request_id);
+                let workers = prefill_worker.into_iter().chain(decode_worker).collect();
+                aborter.abort(&route, &request_id, workers);
+            }
         });
 
         let stream = UnboundedReceiverStream::new(rx);
@@ -1579,41 +1741,6 @@ impl PDRouter {
     }
 }
 
-// Helper functions
-
-async fn get_worker_load(client: &Client, worker_url: &str) -> Option<isize> {
-    match client.get(format!("{}/get_load", worker_url)).send().await {
-        Ok(res) if res.status().is_success() => match res.bytes().await {
-            Ok(bytes) => match serde_json::from_slice::<Value>(&bytes) {
-                Ok(data) => data
-                    .get("load")
-                    .and_then(|v| v.as_i64())
-                    .map(|v| v as isize),
-                Err(e) => {
-                    debug!("Failed to parse load response from {}: {}", worker_url, e);
-                    None
-                }
-            },
-            Err(e) => {
-                debug!("Failed to read load response from {}: {}", worker_url, e);
-                None
-            }
-        },
-        Ok(res) => {
-            debug!(
-                "Worker {} returned non-success status: {}",
-                worker_url,
-                res.status()
-            );
-            None
-        }
-        Err(e) => {
-            debug!("Failed to get load from {}: {}", worker_url, e);
-            None
-        }
-    }
-}
-
 #[async_trait]
 impl WorkerManagement for PDRouter {
     async fn add_worker(&self, _worker_url: &str) -> Result<String, String> {
@@ -1687,7 +1814,10 @@ impl RouterTrait for PDRouter {
         // Note: This endpoint actually causes the model to generate tokens, so we only test one pair
 
         // Select a random worker pair using the policy
-        let (prefill, decode) = match self.select_pd_pair(None, None).await {
+        let (prefill, decode) = match self
+            .select_pd_pair(None, None, None, &WorkerConstraints::default())
+            .await
+        {
             Ok(pair) => pair,
             Err(e) => {
                 return (
@@ -1771,13 +1901,8 @@ impl RouterTrait for PDRouter {
             .await
     }
 
-    async fn get_models(&self, req: Request<Body>) -> Response {
-        // Extract headers first to avoid Send issues
-        let headers = header_utils::copy_request_headers(&req);
-
-        // Proxy to first prefill worker
-        self.proxy_to_first_prefill_worker("v1/models", Some(headers))
-            .await
+    async fn get_models(&self, _req: Request<Body>) -> Response {
+        self.model_catalog.models_response().await
     }
 
     async fn get_model_info(&self, req: Request<Body>) -> Response {
@@ -1825,6 +1950,7 @@ impl RouterTrait for PDRouter {
             return_logprob,
             request_text,
             model_id,
+            tokens: self.token_estimate(body).await,
         };
 
         // Execute with retry and bootstrap injection
@@ -1841,8 +1967,18 @@ impl RouterTrait for PDRouter {
         let is_stream = body.stream;
         let return_logprob = body.logprobs;
 
-        // Extract text for cache-aware routing
-        let request_text = if self.policies_need_request_text() {
+        // Extract text for cache-aware routing: the rendered prompt for policies routing
+        // on token ids, else the first message
+        let routing_tokenizer = self
+            .policy_registry
+            .get_prefill_policy()
+            .routing_tokenizer()
+            .cloned();
+        let request_text = if let Some(tokenizer) = routing_tokenizer {
+            render_chat_prompt(tokenizer.as_ref(), body)
+                .map_err(|e| debug!("Routing chat request without its prompt: {}", e))
+                .ok()
+        } else if self.policies_need_request_text() {
             body.messages.first().and_then(|msg| match msg {
                 ChatMessage::User { content, .. } => match content {
                     UserMessageContent::Text(text) => Some(text.clone()),
@@ -1866,6 +2002,7 @@ impl RouterTrait for PDRouter {
             return_logprob,
             request_text,
             model_id,
+            tokens: self.token_estimate(body).await,
         };
 
         // Execute with retry and bootstrap injection
@@ -1903,6 +2040,7 @@ impl RouterTrait for PDRouter {
             return_logprob,
             request_text,
             model_id,
+            tokens: self.token_estimate(body).await,
         };
 
         // Execute with retry and bootstrap injection
@@ -1972,6 +2110,7 @@ impl RouterTrait for PDRouter {
             return_logprob: false,
             request_text: req_text,
             model_id,
+            tokens: self.token_estimate(body).await,
         };
 
         // Execute with retry and bootstrap injection
@@ -2024,9 +2163,9 @@ impl RouterTrait for PDRouter {
         // Process prefill workers
         let prefill_urls = self.get_prefill_worker_urls();
         for worker_url in prefill_urls {
-            match get_worker_load(&self.client, &worker_url).await {
+            match self.worker_metrics.fetch_load(&worker_url).await {
                 Some(load) => {
-                    loads.insert(format!("prefill_{}", worker_url), load);
+                    loads.insert(format!("prefill_{}", worker_url), load.requests());
                 }
                 None => {
                     errors.push(format!("Failed to get load from prefill {}", worker_url));
@@ -2037,9 +2176,9 @@ impl RouterTrait for PDRouter {
         // Process decode workers
         let decode_urls = self.get_decode_worker_urls();
         for worker_url in decode_urls {
-            match get_worker_load(&self.client, &worker_url).await {
+            match self.worker_metrics.fetch_load(&worker_url).await {
                 Some(load) => {
-                    loads.insert(format!("decode_{}", worker_url), load);
+                    loads.insert(format!("decode_{}", worker_url), load.requests());
                 }
                 None => {
                     errors.push(format!("Failed to get load from decode {}", worker_url));
@@ -2123,18 +2262,35 @@ mod tests {
         let policy_registry =
             Arc::new(PolicyRegistry::new(crate::config::PolicyConfig::RoundRobin));
 
+        let model_catalog = Arc::new(ModelCatalog::new(
+            worker_registry.clone(),
+            Client::new(),
+            None,
+            Duration::from_secs(30),
+        ));
+
+        let worker_metrics = Arc::new(WorkerMetrics::new(
+            worker_registry.clone(),
+            policy_registry.clone(),
+            Client::new(),
+            None,
+        ));
+
         PDRouter {
             worker_registry,
             policy_registry,
+            model_catalog,
+            worker_metrics,
             worker_startup_timeout_secs: 5,
             worker_startup_check_interval_secs: 1,
-            worker_loads: Arc::new(tokio::sync::watch::channel(HashMap::new()).1),
-            load_monitor_handle: None,
             client: Client::new(),
             prefill_client: Client::new(),
             prefill_drain_tx: mpsc::channel(100).0,
             retry_config: RetryConfig::default(),
             circuit_breaker_config: CircuitBreakerConfig::default(),
+            api_key: None,
+            aborter: RequestAborter::new(Client::new(), None),
+            tokenizer: None,
         }
     }
 
@@ -2297,7 +2453,9 @@ mod tests {
         router.worker_registry.register(Arc::from(healthy_worker));
         router.worker_registry.register(Arc::from(decode_worker));
 
-        let result = router.select_pd_pair(None, None).await;
+        let result = router
+            .select_pd_pair(None, None, None, &WorkerConstraints::default())
+            .await;
 
         assert!(result.is_ok());
         let (prefill, _decode) = result.unwrap();
@@ -2311,7 +2469,9 @@ mod tests {
     async fn test_empty_worker_lists() {
         let router = create_test_pd_router();
 
-        let result = router.select_pd_pair(None, None).await;
+        let result = router
+            .select_pd_pair(None, None, None, &WorkerConstraints::default())
+            .await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("No prefill workers available"));
@@ -2356,32 +2516,45 @@ mod tests {
     // ============= Load Monitoring Tests =============
 
     #[tokio::test]
-    async fn test_load_monitor_updates() {
+    async fn test_load_updates_reach_pd_policies() {
         let power_of_two_policy = Arc::new(crate::policies::PowerOfTwoPolicy::new());
-        let mut router = create_test_pd_router();
-        // Set power_of_two policies in the registry
+        let kv_pressure_policy = Arc::new(crate::policies::KvPressurePolicy::new());
+        let router = create_test_pd_router();
         router
             .policy_registry
             .set_prefill_policy(power_of_two_policy.clone());
         router
             .policy_registry
-            .set_decode_policy(power_of_two_policy);
-
-        // Create load channel
-        let (tx, rx) = tokio::sync::watch::channel(HashMap::new());
-        router.worker_loads = Arc::new(rx);
+            .set_decode_policy(kv_pressure_policy.clone());
 
-        // Simulate load updates
+        // Simulate a scrape
+        let load = |running, kv_cache_usage| crate::core::WorkerLoad {
+            running,
+            waiting: 0,
+            kv_cache_usage: Some(kv_cache_usage),
+        };
         let mut loads = HashMap::new();
-        loads.insert("http://worker1".to_string(), 10);
-        loads.insert("http://worker2".to_string(), 5);
-
-        let _ = tx.send(loads.clone());
+        loads.insert("http://worker1".to_string(), load(10, 0.9));
+        loads.insert("http://worker2".to_string(), load(5, 0.1));
+        router.policy_registry.update_worker_loads(&loads);
 
-        // Router should receive updates
-        let received = router.worker_loads.borrow().clone();
-        assert_eq!(received.get("http://worker1"), Some(&10));
-        assert_eq!(received.get("http://worker2"), Some(&5));
+        // Both policies prefer the less loaded worker
+        let workers: Vec<Arc<dyn Worker>> = ["http://worker1", "http://worker2"]
+            .iter()
+            .map(|url| {
+                Arc::from(create_test_worker(
+                    url.to_string(),
+                    WorkerType::Decode,
+                    true,
+                ))
+            })
+            .collect();
+        assert_eq!(power_of_two_policy.select_worker(&workers, None), Some(1));
+        assert_eq!(kv_pressure_policy.select_worker(&workers, None), Some(1));
+        assert_eq!(
+            kv_pressure_policy.worker_load("http://worker1"),
+            Some(load(10, 0.9))
+        );
     }
 
     // ============= Worker Load Tests =============
@@ -2459,14 +2632,27 @@ mod tests {
             None,
             prefill_ref.as_ref(),
             decode_ref.as_ref(),
+            "/generate",
+            None,
+            StreamTimer::start(),
+            Some(PDTokenLoad::new(
+                prefill_ref.clone(),
+                decode_ref.clone(),
+                TokenEstimate {
+                    prompt_tokens: 100,
+                    decode_budget: 10,
+                },
+            )),
         );
 
         // Load should be incremented immediately
         assert_eq!(prefill_ref.load(), 1);
         assert_eq!(decode_ref.load(), 1);
+        assert_eq!(prefill_ref.token_load(), 100);
+        assert_eq!(decode_ref.token_load(), 110);
 
         // Send some data through the stream
-        tx.send(bytes::Bytes::from("test data")).unwrap();
+        tx.send(bytes::Bytes::from("test data\n")).unwrap();
 
         // Give time for the spawned task to process
         sleep(Duration::from_millis(10)).await;
@@ -2474,6 +2660,14 @@ mod tests {
         // Load should still be 1 (streaming in progress)
         assert_eq!(prefill_ref.load(), 1);
         assert_eq!(decode_ref.load(), 1);
+        assert_eq!(prefill_ref.token_load(), 100);
+
+        // The first generated tokens end the prefill and drain the decode budget
+        tx.send(bytes::Bytes::from("data: {\"a\":1}\n\ndata: {\"a\":2}\n\n"))
+            .unwrap();
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(prefill_ref.token_load(), 0);
+        assert_eq!(decode_ref.token_load(), 108);
 
         // Close the stream
         drop(tx);
@@ -2484,6 +2678,7 @@ mod tests {
         // Load should be decremented after streaming completes
         assert_eq!(prefill_ref.load(), 0);
         assert_eq!(decode_ref.load(), 0);
+        assert_eq!(decode_ref.token_load(), 0);
     }
 
     // ============= Concurrent Operations Tests =============
@@ -36,7 +36,8 @@ pub fn get_hostname(url: &str) -> String {
     // Simple hostname extraction without external dependencies
     let url = url
         .trim_start_matches("http://")
-        .trim_start_matches("https://");
+        .trim_start_matches("https://")
+        .trim_start_matches("grpc://");
     url.split(':').next().unwrap_or("localhost").to_string()
 }
 
@@ -0,0 +1,742 @@
+//! Router-managed Responses API state
+//!
+//! Workers are treated as stateless for the Responses API: the router stores every
+//! response it relays, expands `previous_response_id` into the full conversation before
+//! forwarding a request, and serves retrieval, deletion and input item listing from its
+//! own `ResponseStorage`. Background responses run as router-side tasks that record
+//! their status and partial output in the same storage.
+
+use crate::core::{OwnedWorkerLoadGuard, RequestAborter, TokenLoadGuard, Worker};
+use crate::data_connector::{
+    ResponseId, ResponseStorageError, SharedResponseStorage, StoredResponse,
+};
+use crate::policies::LoadBalancingPolicy;
+use crate::protocols::spec::{
+    ErrorResponse, GenerationRequest, ResponseContentPart, ResponseInput, ResponseInputOutputItem,
+    ResponseOutputItem, ResponseStatus, ResponsesRequest, ResponsesResponse,
+};
+use crate::routers::http::stream_timer::StreamTimer;
+use axum::{
+    body::{to_bytes, Body},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use dashmap::DashMap;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::{
+    sync::{oneshot, Mutex},
+    task::JoinHandle,
+};
+use tracing::{debug, error, warn};
+
+/// SSE event type carrying the final response object of a streamed response
+const RESPONSE_COMPLETED_EVENT: &str = "response.completed";
+
+/// SSE event type carrying a chunk of output text
+const OUTPUT_TEXT_DELTA_EVENT: &str = "response.output_text.delta";
+
+/// Minimum interval between partial output updates of a running background response
+const PROGRESS_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Expand `previous_response_id` into the stored conversation so any worker can serve
+/// the request. The id is removed from the forwarded request.
+pub async fn expand_previous_response(
+    storage: &SharedResponseStorage,
+    request: &mut ResponsesRequest,
+) -> Result<(), Response> {
+    let Some(previous_id) = request.previous_response_id.take() else {
+        return Ok(());
+    };
+    let previous_id = ResponseId::from_string(previous_id);
+
+    let chain = storage
+        .get_response_chain(&previous_id, None)
+        .await
+        .map_err(storage_error)?;
+    if chain.latest_response_id() != Some(&previous_id) {
+        return Err(not_found(&previous_id.0));
+    }
+
+    let mut items = Vec::new();
+    for (input, output) in chain.build_context(None) {
+        items.push(message_item("user", input));
+        if !output.is_empty() {
+            items.push(message_item("assistant", output));
+        }
+    }
+    match std::mem::replace(&mut request.input, ResponseInput::Items(Vec::new())) {
+        ResponseInput::Text(text) => items.push(message_item("user", text)),
+        ResponseInput::Items(current) => items.extend(current),
+    }
+    request.input = ResponseInput::Items(items);
+
+    Ok(())
+}
+
+/// Store the response relayed for `request` and return it to the client unchanged.
+///
+/// Non-streaming bodies are buffered and stored before returning; streaming bodies are
+/// stored once their `response.completed` event has passed through.
+pub async fn store_response(
+    storage: &SharedResponseStorage,
+    request: &ResponsesRequest,
+    response: Response,
+) -> Response {
+    let (parts, body) = response.into_parts();
+
+    if request.stream {
+        let storage = storage.clone();
+        let request = request.clone();
+        let mut parser = SseEventParser::default();
+        let mut stored = false;
+        let stream = body.into_data_stream().map(move |chunk| {
+            if let (Ok(bytes), false) = (&chunk, stored) {
+                if let Some(raw) = parser.push(bytes).into_iter().find_map(completed_response) {
+                    stored = true;
+                    let storage = storage.clone();
+                    let request = request.clone();
+                    tokio::spawn(async move { persist(&storage, &request, raw).await });
+                }
+            }
+            chunk
+        });
+        return Response::from_parts(parts, Body::from_stream(stream));
+    }
+
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read response body: {}", e),
+            )
+                .into_response()
+        }
+    };
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(raw) => persist(storage, request, raw).await,
+        Err(e) => warn!("Not storing unparseable responses body: {}", e),
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Look up a stored response. Returns `None` when the router does not hold it.
+pub async fn get_response(storage: &SharedResponseStorage, response_id: &str) -> Option<Response> {
+    match storage
+        .get_response(&ResponseId::from_string(response_id.to_string()))
+        .await
+    {
+        Ok(Some(stored)) => Some(Json(response_object(&stored)).into_response()),
+        Ok(None) => None,
+        Err(e) => Some(storage_error(e)),
+    }
+}
+
+/// Delete a stored response
+pub async fn delete_response(storage: &SharedResponseStorage, response_id: &str) -> Response {
+    let id = ResponseId::from_string(response_id.to_string());
+    match storage.get_response(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found(response_id),
+        Err(e) => return storage_error(e),
+    }
+    if let Err(e) = storage.delete_response(&id).await {
+        return storage_error(e);
+    }
+
+    Json(json!({
+        "id": response_id,
+        "object": "response",
+        "deleted": true
+    }))
+    .into_response()
+}
+
+/// List the input items a stored response was created from
+pub async fn list_input_items(storage: &SharedResponseStorage, response_id: &str) -> Response {
+    let stored = match storage
+        .get_response(&ResponseId::from_string(response_id.to_string()))
+        .await
+    {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return not_found(response_id),
+        Err(e) => return storage_error(e),
+    };
+
+    let item_id = |item: Option<&Value>| item.and_then(|i| i.get("id")).cloned();
+    Json(json!({
+        "object": "list",
+        "data": stored.input_items,
+        "first_id": item_id(stored.input_items.first()),
+        "last_id": item_id(stored.input_items.last()),
+        "has_more": false
+    }))
+    .into_response()
+}
+
+/// Convert a relayed response object into a `StoredResponse` and store it
+pub async fn persist(storage: &SharedResponseStorage, request: &ResponsesRequest, raw: Value) {
+    let Some(id) = raw.get("id").and_then(Value::as_str).map(str::to_string) else {
+        warn!("Not storing response without an id");
+        return;
+    };
+
+    let output = raw.get("output").and_then(Value::as_array);
+    let output_items = || output.into_iter().flatten();
+
+    let mut stored = StoredResponse::new(
+        request.extract_text_for_routing(),
+        output_items().filter_map(message_text).collect(),
+        request
+            .previous_response_id
+            .clone()
+            .map(ResponseId::from_string),
+    );
+    stored.id = ResponseId::from_string(id.clone());
+    stored.input_items = match &request.input {
+        ResponseInput::Text(text) => vec![message_item("user", text.clone())],
+        ResponseInput::Items(items) => items.clone(),
+    }
+    .iter()
+    .filter_map(|item| serde_json::to_value(item).ok())
+    .collect();
+    stored.instructions = request.instructions.clone();
+    stored.tool_calls = output_items()
+        .filter(|item| {
+            matches!(
+                item.get("type").and_then(Value::as_str),
+                Some("function_tool_call" | "function_call" | "mcp_call")
+            )
+        })
+        .cloned()
+        .collect();
+    stored.metadata = request.metadata.clone().unwrap_or_default();
+    stored.user = request.user.clone();
+    stored.model = raw
+        .get("model")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| request.model.clone());
+    stored.raw_response = Some(raw);
+
+    if let Err(e) = storage.store_response(stored).await {
+        error!("Failed to store response {}: {}", id, e);
+    }
+}
+
+/// Text of an assistant message output item
+fn message_text(item: &Value) -> Option<String> {
+    if item.get("type").and_then(Value::as_str) != Some("message") {
+        return None;
+    }
+    let text = item
+        .get("content")?
+        .as_array()?
+        .iter()
+        .filter_map(|part| part.get("text").and_then(Value::as_str))
+        .collect::<String>();
+    Some(text)
+}
+
+/// The response object returned by `GET /v1/responses/{id}`
+fn response_object(stored: &StoredResponse) -> Value {
+    if let Some(raw) = &stored.raw_response {
+        return raw.clone();
+    }
+    json!({
+        "id": stored.id.0,
+        "object": "response",
+        "created_at": stored.created_at.timestamp(),
+        "model": stored.model,
+        "status": "completed",
+        "output": [message_item("assistant", stored.output.clone())],
+        "previous_response_id": stored.previous_response_id.as_ref().map(|id| &id.0),
+        "metadata": stored.metadata,
+    })
+}
+
+fn message_item(role: &str, text: String) -> ResponseInputOutputItem {
+    ResponseInputOutputItem::Message {
+        id: format!("msg_{}", uuid::Uuid::new_v4().simple()),
+        role: role.to_string(),
+        content: vec![ResponseContentPart::OutputText {
+            text,
+            annotations: Vec::new(),
+            logprobs: None,
+        }],
+        status: None,
+    }
+}
+
+pub fn not_found(response_id: &str) -> Response {
+    let error = ErrorResponse::new(
+        format!("Response with id '{}' not found.", response_id),
+        "invalid_request_error",
+        Some("response_not_found"),
+    );
+    (StatusCode::NOT_FOUND, Json(error)).into_response()
+}
+
+fn storage_error(e: ResponseStorageError) -> Response {
+    error!("Response storage error: {}", e);
+    let error = ErrorResponse::new(e.to_string(), "internal_error", None);
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+}
+
+/// Incrementally splits an SSE stream into the JSON payloads of its `data:` lines
+#[derive(Default)]
+pub(crate) struct SseEventParser {
+    pending: Vec<u8>,
+}
+
+impl SseEventParser {
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<Value> {
+        self.pending.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            if let Some(data) = line.strip_prefix(b"data:") {
+                if let Ok(event) = serde_json::from_slice(data) {
+                    events.push(event);
+                }
+            }
+        }
+        events
+    }
+}
+
+/// The response object carried by a `response.completed` event
+fn completed_response(mut event: Value) -> Option<Value> {
+    if event.get("type").and_then(Value::as_str) != Some(RESPONSE_COMPLETED_EVENT) {
+        return None;
+    }
+    event.get_mut("response").map(Value::take)
+}
+
+// ============= Background responses =============
+
+/// Background responses currently executing on the router, keyed by response id
+#[derive(Debug, Clone, Default)]
+pub struct BackgroundResponses {
+    tasks: Arc<DashMap<String, BackgroundTask>>,
+}
+
+#[derive(Debug)]
+struct BackgroundTask {
+    handle: JoinHandle<()>,
+    /// Set on cancel; held across each store write of the task
+    cancelled: Arc<Mutex<bool>>,
+    /// Worker the upstream request landed on, once one accepted it
+    worker: Arc<OnceLock<Arc<dyn Worker>>>,
+    /// Request ID the worker knows the upstream request by
+    request_id: String,
+}
+
+/// Upstream request of a background response, once a worker accepted it
+pub struct BackgroundUpstream {
+    /// Streaming response of the worker
+    pub(crate) response: reqwest::Response,
+    pub(crate) worker: Arc<dyn Worker>,
+    /// Told the outcome and latencies of the response once it finishes
+    pub(crate) policy: Arc<dyn LoadBalancingPolicy>,
+    pub(crate) timer: StreamTimer,
+    pub(crate) load: OwnedWorkerLoadGuard,
+    pub(crate) token_load: TokenLoadGuard,
+}
+
+impl BackgroundResponses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `request` as `queued`, run it in a router-side task and return the queued
+    /// response object.
+    ///
+    /// `upstream` sends the streaming request to a worker, retrying as the router does;
+    /// it fails with the message recorded on the response. The status and partial output
+    /// of the response are recorded in `storage` as it runs.
+    pub async fn start<F>(
+        &self,
+        storage: &SharedResponseStorage,
+        request: &ResponsesRequest,
+        upstream: F,
+    ) -> Response
+    where
+        F: Future<Output = Result<BackgroundUpstream, String>> + Send + 'static,
+    {
+        let response_id = request.request_id.clone();
+        let queued = queued_response_object(request);
+        persist(storage, request, queued.clone()).await;
+
+        // The task only starts once it is registered, so it can always deregister itself
+        let (registered_tx, registered_rx) = oneshot::channel();
+        let worker = Arc::new(OnceLock::new());
+        let cancelled = Arc::new(Mutex::new(false));
+        let handle = tokio::spawn({
+            let tasks = self.tasks.clone();
+            let recorder = BackgroundRecorder {
+                storage: storage.clone(),
+                request: request.clone(),
+                cancelled: cancelled.clone(),
+            };
+            let queued = queued.clone();
+            let response_id = response_id.clone();
+            let worker = worker.clone();
+            async move {
+                if registered_rx.await.is_err() {
+                    return;
+                }
+                run_background_response(&recorder, queued, upstream, &worker).await;
+                tasks.remove(&response_id);
+            }
+        });
+        self.tasks.insert(
+            response_id.clone(),
+            BackgroundTask {
+                handle,
+                cancelled,
+                worker,
+                request_id: response_id,
+            },
+        );
+        let _ = registered_tx.send(());
+
+        Json(queued).into_response()
+    }
+
+    /// Cancel a background response, aborting its upstream request on the worker it
+    /// landed on. Responses that already finished are returned unchanged.
+    pub async fn cancel(
+        &self,
+        storage: &SharedResponseStorage,
+        aborter: &RequestAborter,
+        response_id: &str,
+    ) -> Response {
+        if let Some((_, task)) = self.tasks.remove(response_id) {
+            // Aborting the task does not stop a store write already running on the
+            // blocking pool, so wait that write out and skip any later ones
+            *task.cancelled.lock().await = true;
+            task.handle.abort();
+            let _ = task.handle.await;
+            if let Some(worker) = task.worker.get() {
+                aborter.abort("/v1/responses", &task.request_id, vec![worker.clone()]);
+            }
+        }
+
+        let mut stored = match storage
+            .get_response(&ResponseId::from_string(response_id.to_string()))
+            .await
+        {
+            Ok(Some(stored)) => stored,
+            Ok(None) => return not_found(response_id),
+            Err(e) => return storage_error(e),
+        };
+        if let Some(raw) = stored.raw_response.as_mut().filter(|raw| !is_finished(raw)) {
+            set_status(raw, ResponseStatus::Cancelled);
+            if let Err(e) = storage.store_response(stored.clone()).await {
+                return storage_error(e);
+            }
+        }
+
+        Json(response_object(&stored)).into_response()
+    }
+}
+
+/// Stores the progress of a background response until it is cancelled
+struct BackgroundRecorder {
+    storage: SharedResponseStorage,
+    request: ResponsesRequest,
+    cancelled: Arc<Mutex<bool>>,
+}
+
+impl BackgroundRecorder {
+    async fn record(&self, raw: Value) {
+        let cancelled = self.cancelled.lock().await;
+        if !*cancelled {
+            persist(&self.storage, &self.request, raw).await;
+        }
+    }
+}
+
+/// Drive a background response to completion, recording progress with `recorder` and
+/// the worker it lands on in `landed`
+async fn run_background_response<F>(
+    recorder: &BackgroundRecorder,
+    mut raw: Value,
+    upstream: F,
+    landed: &OnceLock<Arc<dyn Worker>>,
+) where
+    F: Future<Output = Result<BackgroundUpstream, String>>,
+{
+    set_status(&mut raw, ResponseStatus::InProgress);
+    recorder.record(raw.clone()).await;
+
+    let BackgroundUpstream {
+        response,
+        worker,
+        policy,
+        mut timer,
+        load: _load,
+        mut token_load,
+    } = match upstream.await {
+        Ok(upstream) => upstream,
+        Err(message) => return fail_background_response(recorder, raw, message).await,
+    };
+    let _ = landed.set(worker.clone());
+
+    let success =
+        stream_background_response(recorder, raw, response, &mut timer, &mut token_load).await;
+    policy.on_request_complete(worker.url(), success, &timer.finish());
+}
+
+/// Record the streamed output of a background response, returning whether the stream
+/// finished without error
+async fn stream_background_response(
+    recorder: &BackgroundRecorder,
+    mut raw: Value,
+    res: reqwest::Response,
+    timer: &mut StreamTimer,
+    token_load: &mut TokenLoadGuard,
+) -> bool {
+    let message_id = format!("msg_{}", uuid::Uuid::new_v4().simple());
+    let mut parser = SseEventParser::default();
+    let mut text = String::new();
+    let mut flushed_len = 0;
+    let mut last_flush = Instant::now();
+    // Workers that stream whole response objects rather than typed events
+    let mut snapshot = None;
+
+    let mut stream = res.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let bytes = match chunk {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let message = format!("Stream error: {}", e);
+                fail_background_response(recorder, raw, message).await;
+                return false;
+            }
+        };
+        token_load.drain(timer.observe(&bytes));
+        for event in parser.push(&bytes) {
+            match event.get("type").and_then(Value::as_str) {
+                Some(OUTPUT_TEXT_DELTA_EVENT) => {
+                    if let Some(delta) = event.get("delta").and_then(Value::as_str) {
+                        text.push_str(delta);
+                    }
+                }
+                Some(RESPONSE_COMPLETED_EVENT) => {
+                    if let Some(completed) = completed_response(event) {
+                        let completed = with_identity(completed, &raw);
+                        recorder.record(completed).await;
+                        return true;
+                    }
+                }
+                _ if event.get("object").and_then(Value::as_str) == Some("response") => {
+                    snapshot = Some(event);
+                }
+                _ => {}
+            }
+        }
+
+        if text.len() > flushed_len && last_flush.elapsed() >= PROGRESS_FLUSH_INTERVAL {
+            raw["output"] = partial_output(&message_id, &text, "in_progress");
+            recorder.record(raw.clone()).await;
+            flushed_len = text.len();
+            last_flush = Instant::now();
+        }
+    }
+
+    // The stream ended without a completed event; finish with what was received
+    let mut completed = match snapshot {
+        Some(snapshot) if text.is_empty() => with_identity(snapshot, &raw),
+        _ => {
+            raw["output"] = partial_output(&message_id, &text, "completed");
+            raw
+        }
+    };
+    set_status(&mut completed, ResponseStatus::Completed);
+    recorder.record(completed).await;
+    true
+}
+
+async fn fail_background_response(recorder: &BackgroundRecorder, mut raw: Value, message: String) {
+    debug!(
+        "Background response {} failed: {}",
+        recorder.request.request_id, message
+    );
+    set_status(&mut raw, ResponseStatus::Failed);
+    raw["error"] = json!({ "code": "server_error", "message": message });
+    recorder.record(raw).await;
+}
+
+/// The response object returned when a background response is accepted
+fn queued_response_object(request: &ResponsesRequest) -> Value {
+    let mut raw = ResponsesResponse::from_request(
+        request,
+        &HashMap::new(),
+        request.model.clone().unwrap_or_default(),
+        chrono::Utc::now().timestamp(),
+        Vec::new(),
+        ResponseStatus::Queued,
+        None,
+    )
+    .to_response_format();
+    raw["background"] = Value::Bool(true);
+    raw
+}
+
+/// Give a worker-produced response object the id and flags of the router's response
+fn with_identity(mut object: Value, template: &Value) -> Value {
+    object["id"] = template["id"].clone();
+    object["background"] = Value::Bool(true);
+    object
+}
+
+fn partial_output(message_id: &str, text: &str, status: &str) -> Value {
+    let item = ResponseOutputItem::new_message(
+        message_id.to_string(),
+        "assistant".to_string(),
+        vec![ResponseContentPart::OutputText {
+            text: text.to_string(),
+            annotations: Vec::new(),
+            logprobs: None,
+        }],
+        status.to_string(),
+    );
+    json!([item])
+}
+
+fn set_status(raw: &mut Value, status: ResponseStatus) {
+    raw["status"] = serde_json::to_value(status).unwrap_or(Value::Null);
+}
+
+fn is_finished(raw: &Value) -> bool {
+    matches!(
+        raw.get("status").and_then(Value::as_str),
+        Some("completed" | "failed" | "cancelled" | "incomplete")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_connector::MemoryResponseStorage;
+    use std::sync::Arc;
+
+    fn request(body: Value) -> ResponsesRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn input_texts(request: &ResponsesRequest) -> Vec<(String, String)> {
+        let ResponseInput::Items(items) = &request.input else {
+            panic!("expected expanded input items");
+        };
+        items
+            .iter()
+            .map(|item| match item {
+                ResponseInputOutputItem::Message { role, content, .. } => {
+                    let ResponseContentPart::OutputText { text, .. } = &content[0];
+                    (role.clone(), text.clone())
+                }
+                other => panic!("unexpected item {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_store_and_expand_previous_response() {
+        let storage: SharedResponseStorage = Arc::new(MemoryResponseStorage::new());
+
+        let first = request(json!({"input": "What is 2+2?", "model": "m"}));
+        let worker_response = Json(json!({
+            "id": "resp_1",
+            "object": "response",
+            "model": "m",
+            "status": "completed",
+            "output": [{
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": "4"}]
+            }]
+        }))
+        .into_response();
+        let response = store_response(&storage, &first, worker_response).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut second = request(json!({
+            "input": "And times 3?",
+            "previous_response_id": "resp_1"
+        }));
+        expand_previous_response(&storage, &mut second)
+            .await
+            .unwrap();
+        assert!(second.previous_response_id.is_none());
+        assert_eq!(
+            input_texts(&second),
+            vec![
+                ("user".to_string(), "What is 2+2?".to_string()),
+                ("assistant".to_string(), "4".to_string()),
+                ("user".to_string(), "And times 3?".to_string()),
+            ]
+        );
+
+        let mut unknown = request(json!({"input": "hi", "previous_response_id": "resp_x"}));
+        let err = expand_previous_response(&storage, &mut unknown)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_get_list_and_delete_stored_response() {
+        let storage: SharedResponseStorage = Arc::new(MemoryResponseStorage::new());
+        let req = request(json!({"input": "Hello", "model": "m"}));
+        persist(
+            &storage,
+            &req,
+            json!({"id": "resp_2", "object": "response", "output": []}),
+        )
+        .await;
+
+        let got = get_response(&storage, "resp_2").await.unwrap();
+        assert_eq!(got.status(), StatusCode::OK);
+        assert!(get_response(&storage, "resp_missing").await.is_none());
+
+        let listed = list_input_items(&storage, "resp_2").await;
+        let body = to_bytes(listed.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["object"], "list");
+        assert_eq!(body["data"][0]["content"][0]["text"], "Hello");
+        assert_eq!(body["first_id"], body["data"][0]["id"]);
+
+        let deleted = delete_response(&storage, "resp_2").await;
+        assert_eq!(deleted.status(), StatusCode::OK);
+        let deleted = delete_response(&storage, "resp_2").await;
+        assert_eq!(deleted.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_completed_event_across_chunks() {
+        let mut parser = SseEventParser::default();
+        let events =
+            parser.push(b"event: response.created\ndata: {\"type\":\"response.created\"}\n\n");
+        assert!(events.into_iter().find_map(completed_response).is_none());
+        assert!(parser
+            .push(b"event: response.completed\ndata: {\"type\":\"response.comp")
+            .is_empty());
+        let raw = parser
+            .push(b"leted\",\"response\":{\"id\":\"resp_3\"}}\n\n")
+            .into_iter()
+            .find_map(completed_response)
+            .unwrap();
+        assert_eq!(raw["id"], "resp_3");
+        assert!(parser.push(b"data: [DONE]\n\n").is_empty());
+    }
+}
@@ -1,23 +1,28 @@
-use crate::config::types::RetryConfig;
+use crate::config::types::{LoadUnit, RetryConfig, WorkerBackend};
 use crate::core::{
-    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig, RetryExecutor, Worker,
-    WorkerRegistry, WorkerType,
+    is_retryable_status, BackendProfile, BasicWorker, CircuitBreakerConfig, HealthConfig,
+    ModelCatalog, OwnedWorkerLoadGuard, RequestAborter, RetryExecutor, TokenEstimate,
+    TokenLoadGuard, Worker, WorkerMetrics, WorkerRegistry, WorkerType,
 };
+use crate::data_connector::SharedResponseStorage;
 use crate::metrics::RouterMetrics;
-use crate::policies::{LoadBalancingPolicy, PolicyRegistry};
+use crate::policies::{request_affinity_key, LoadBalancingPolicy, PolicyRegistry};
 use crate::protocols::spec::{
-    ChatCompletionRequest, CompletionRequest, EmbeddingRequest, GenerateRequest, GenerationRequest,
-    RerankRequest, RerankResponse, RerankResult, ResponsesRequest,
+    ChatCompletionRequest, CompletionRequest, EmbeddingRequest, ErrorResponse, GenerateRequest,
+    GenerationRequest, RerankRequest, RerankResponse, RerankResult, ResponsesRequest,
 };
-use crate::routers::header_utils;
+use crate::routers::grpc::utils::render_chat_prompt;
+use crate::routers::header_utils::{self, WorkerConstraints};
+use crate::routers::http::mcp_tool_loop::{self, McpToolLoop};
+use crate::routers::http::responses::{self, BackgroundResponses, BackgroundUpstream};
+use crate::routers::http::stream_timer::StreamTimer;
 use crate::routers::{RouterTrait, WorkerManagement};
+use crate::tokenizer::traits::Tokenizer;
 use axum::body::to_bytes;
 use axum::{
     body::Body,
     extract::Request,
-    http::{
-        header::CONTENT_LENGTH, header::CONTENT_TYPE, HeaderMap, HeaderValue, Method, StatusCode,
-    },
+    http::{header::CONTENT_LENGTH, header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -29,20 +34,70 @@ use std::time::{Duration, Instant};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, info, warn};
 
+/// Header carrying the request ID shared between the client, router and worker
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Whether an SSE chunk contains the end-of-stream marker
+fn contains_done_marker(bytes: &[u8]) -> bool {
+    bytes.windows(12).any(|window| window == b"data: [DONE]")
+}
+
+/// Labels recording the backend of a worker the router creates itself
+fn backend_labels(backend: WorkerBackend) -> HashMap<String, String> {
+    HashMap::from([("backend".to_string(), backend.as_str().to_string())])
+}
+
 /// Regular router that uses injected load balancing policies
-#[derive(Debug)]
+#[derive(Clone)]
 pub struct Router {
     worker_registry: Arc<WorkerRegistry>,
     policy_registry: Arc<PolicyRegistry>,
+    model_catalog: Arc<ModelCatalog>,
+    worker_metrics: Arc<WorkerMetrics>,
     client: Client,
     worker_startup_timeout_secs: u64,
     worker_startup_check_interval_secs: u64,
     dp_aware: bool,
     api_key: Option<String>,
+    /// Backend of workers this router adds itself
+    backend: WorkerBackend,
     retry_config: RetryConfig,
     circuit_breaker_config: CircuitBreakerConfig,
-    _worker_loads: Arc<tokio::sync::watch::Receiver<HashMap<String, isize>>>,
-    _load_monitor_handle: Option<Arc<tokio::task::JoinHandle<()>>>,
+    aborter: RequestAborter,
+    response_storage: SharedResponseStorage,
+    background_responses: BackgroundResponses,
+    /// Tool loop for responses requests with MCP tools; None without an MCP config
+    mcp_tool_loop: Option<McpToolLoop>,
+    /// Counts prompt tokens when policies balance token-weighted load; bytes are used without it
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    /// Copy of this router for work that outlives a request, such as background responses
+    /// and the MCP tool loop. The copy has neither a copy nor a tool loop of its own, so the
+    /// router never references itself
+    shared: Option<Arc<Router>>,
+}
+
+impl std::fmt::Debug for Router {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Router")
+            .field("worker_registry", &self.worker_registry)
+            .field("policy_registry", &self.policy_registry)
+            .field(
+                "worker_startup_timeout_secs",
+                &self.worker_startup_timeout_secs,
+            )
+            .field(
+                "worker_startup_check_interval_secs",
+                &self.worker_startup_check_interval_secs,
+            )
+            .field("dp_aware", &self.dp_aware)
+            .field("backend", &self.backend)
+            .field("retry_config", &self.retry_config)
+            .field("circuit_breaker_config", &self.circuit_breaker_config)
+            .field("background_responses", &self.background_responses)
+            .field("mcp_tool_loop", &self.mcp_tool_loop)
+            .field("tokenizer", &self.tokenizer.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl Router {
@@ -67,9 +122,13 @@ impl Router {
 
         let worker_urls = if ctx.router_config.dp_aware {
             // worker address now in the format of "http://host:port@dp_rank"
-            Self::get_dp_aware_workers(&worker_urls, &ctx.router_config.api_key)
-                .await
-                .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?
+            Self::get_dp_aware_workers(
+                &worker_urls,
+                &ctx.router_config.api_key,
+                ctx.router_config.backend,
+            )
+            .await
+            .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?
         } else {
             worker_urls
         };
@@ -83,11 +142,8 @@ impl Router {
             window_duration: Duration::from_secs(circuit_breaker_config.window_duration_secs),
         };
 
-        // Register workers in the registry
-        // In IGW mode, we need to fetch model info from workers
+        // Register workers in the registry under the models they serve
         for url in &worker_urls {
-            // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
-            // For now, create worker without model_id
             let worker = BasicWorker::new(url.clone(), WorkerType::Regular)
                 .with_circuit_breaker_config(core_cb_config.clone())
                 .with_health_config(HealthConfig {
@@ -96,10 +152,16 @@ impl Router {
                     endpoint: ctx.router_config.health_check.endpoint.clone(),
                     failure_threshold: ctx.router_config.health_check.failure_threshold,
                     success_threshold: ctx.router_config.health_check.success_threshold,
-                });
+                })
+                .with_labels(backend_labels(ctx.router_config.backend));
 
-            let worker_arc = Arc::new(worker);
+            let worker_arc: Arc<dyn Worker> = Arc::new(worker);
             ctx.worker_registry.register(worker_arc.clone());
+            let worker_arc = ctx
+                .worker_registry
+                .discover_served_models(url, ctx.router_config.api_key.as_deref())
+                .await
+                .unwrap_or(worker_arc);
 
             // Notify PolicyRegistry about the new worker
             let model_id = worker_arc.model_id();
@@ -112,41 +174,16 @@ impl Router {
                     .as_any()
                     .downcast_ref::<crate::policies::CacheAwarePolicy>()
                 {
-                    let worker_dyn: Arc<dyn Worker> = worker_arc.clone();
-                    cache_aware.init_workers(std::slice::from_ref(&worker_dyn));
+                    cache_aware.init_workers(std::slice::from_ref(&worker_arc));
                 }
             }
         }
 
-        // Setup load monitoring for PowerOfTwo policy
-        let (tx, rx) = tokio::sync::watch::channel(HashMap::new());
-        let worker_loads = Arc::new(rx);
-
-        // Check if default policy is power_of_two for load monitoring
-        let default_policy = ctx.policy_registry.get_default_policy();
-        let load_monitor_handle = if default_policy.name() == "power_of_two" {
-            let monitor_urls = worker_urls.clone();
-            let monitor_interval = ctx.router_config.worker_startup_check_interval_secs;
-            let policy_clone = default_policy.clone();
-            let client_clone = ctx.client.clone();
-
-            Some(Arc::new(tokio::spawn(async move {
-                Self::monitor_worker_loads(
-                    monitor_urls,
-                    tx,
-                    monitor_interval,
-                    policy_clone,
-                    client_clone,
-                )
-                .await;
-            })))
-        } else {
-            None
-        };
-
-        Ok(Router {
+        let mut router = Router {
             worker_registry: ctx.worker_registry.clone(),
             policy_registry: ctx.policy_registry.clone(),
+            model_catalog: ctx.model_catalog.clone(),
+            worker_metrics: ctx.worker_metrics.clone(),
             client: ctx.client.clone(),
             worker_startup_timeout_secs: ctx.router_config.worker_startup_timeout_secs,
             worker_startup_check_interval_secs: ctx
@@ -154,11 +191,33 @@ impl Router {
                 .worker_startup_check_interval_secs,
             dp_aware: ctx.router_config.dp_aware,
             api_key: ctx.router_config.api_key.clone(),
+            backend: ctx.router_config.backend,
             retry_config: ctx.router_config.effective_retry_config(),
             circuit_breaker_config: core_cb_config,
-            _worker_loads: worker_loads,
-            _load_monitor_handle: load_monitor_handle,
-        })
+            aborter: RequestAborter::new(ctx.client.clone(), ctx.router_config.api_key.clone()),
+            response_storage: ctx.response_storage.clone(),
+            background_responses: BackgroundResponses::new(),
+            mcp_tool_loop: None,
+            tokenizer: ctx
+                .tokenizer
+                .clone()
+                .filter(|_| ctx.router_config.load_unit == LoadUnit::Tokens),
+            shared: None,
+        };
+        let shared = Arc::new(router.clone());
+        router.mcp_tool_loop = ctx
+            .mcp_manager
+            .clone()
+            .map(|mcp| McpToolLoop::new(mcp, shared.clone(), ctx.router_config.mcp.max_iterations));
+        router.shared = Some(shared);
+        Ok(router)
+    }
+
+    /// This router behind an `Arc`, for work that outlives a request
+    fn shared(&self) -> Arc<Router> {
+        self.shared
+            .clone()
+            .unwrap_or_else(|| Arc::new(self.clone()))
     }
 
     /// Get the current list of worker URLs
@@ -285,9 +344,17 @@ impl Router {
         }
     }
 
-    async fn get_worker_dp_size(worker_url: &str, api_key: &Option<String>) -> Result<usize, String> {
+    async fn get_worker_dp_size(
+        worker_url: &str,
+        api_key: &Option<String>,
+        backend: WorkerBackend,
+    ) -> Result<usize, String> {
+        // Backends without server info run DP internally behind a single endpoint
+        let Some(server_info_endpoint) = BackendProfile::of(backend).server_info_endpoint else {
+            return Ok(1);
+        };
         let client = reqwest::Client::new();
-        let mut req_builder = client.get(format!("{}/get_server_info", worker_url));
+        let mut req_builder = client.get(format!("{}{}", worker_url, server_info_endpoint));
         if let Some(key) = api_key {
             req_builder = req_builder.bearer_auth(key);
         }
@@ -325,11 +392,12 @@ impl Router {
     async fn get_dp_aware_workers(
         worker_urls: &[String],
         api_key: &Option<String>,
+        backend: WorkerBackend,
     ) -> Result<Vec<String>, String> {
         let mut dp_aware_workers: Vec<String> = Vec::new();
 
         for url in worker_urls {
-            match Self::get_worker_dp_size(url, api_key).await {
+            match Self::get_worker_dp_size(url, api_key, backend).await {
                 Ok(dp_size) => {
                     // Use all available DP ranks for consistent hashing validation
                     for rank in 0..dp_size {
@@ -427,13 +495,27 @@ impl Router {
         response
     }
 
-    // Helper method to proxy GET requests to the first available worker
-    async fn proxy_get_request(&self, req: Request<Body>, endpoint: &str) -> Response {
+    /// Backend of a registered worker; vLLM for unknown workers
+    fn worker_backend(&self, worker_url: &str) -> WorkerBackend {
+        self.worker_registry
+            .get_by_url(worker_url)
+            .map(|worker| worker.backend())
+            .unwrap_or_default()
+    }
+
+    // Helper method to proxy GET requests to the first available worker; `endpoint` picks
+    // the path for the worker's backend
+    async fn proxy_get_request(
+        &self,
+        req: Request<Body>,
+        endpoint: fn(&BackendProfile) -> &'static str,
+    ) -> Response {
         let headers = header_utils::copy_request_headers(&req);
 
         match self.select_first_worker() {
             Ok(worker_url) => {
-                let mut request_builder = self.client.get(format!("{}/{}", worker_url, endpoint));
+                let endpoint = endpoint(BackendProfile::of(self.worker_backend(&worker_url)));
+                let mut request_builder = self.client.get(format!("{}{}", worker_url, endpoint));
                 for (name, value) in headers {
                     let name_lc = name.to_lowercase();
                     if name_lc != "content-type" && name_lc != "content-length" {
@@ -475,35 +557,80 @@ impl Router {
         }
     }
 
-    /// Select worker for a specific model considering circuit breaker state
+    /// Policy routing requests for a model, the default one for requests without a model
+    fn policy_for_model(&self, model_id: Option<&str>) -> Arc<dyn LoadBalancingPolicy> {
+        match model_id {
+            Some(model) => self.policy_registry.get_policy_or_default(model),
+            None => self.policy_registry.get_default_policy(),
+        }
+    }
+
+    /// Select worker for a specific model considering circuit breaker state and the
+    /// request's worker constraints
     fn select_worker_for_model(
         &self,
         model_id: Option<&str>,
         text: Option<&str>,
+        constraints: &WorkerConstraints,
     ) -> Option<Arc<dyn Worker>> {
-        // Get workers for the specified model (O(1) lookup if model_id is provided)
+        // Get workers for the specified model (O(1) lookup if model_id is provided),
+        // falling back to workers whose models are unknown
         let workers = match model_id {
-            Some(model) => self.worker_registry.get_by_model_fast(model),
+            Some(model) => self.worker_registry.get_serving_model(model),
             None => self.worker_registry.get_all(),
         };
 
         let available: Vec<Arc<dyn Worker>> = workers
             .iter()
-            .filter(|w| w.is_available())
+            .filter(|w| w.is_available() && constraints.allows(w.as_ref()))
             .cloned()
             .collect();
         if available.is_empty() {
             return None;
         }
 
-        // Get the appropriate policy for this model
-        let policy = match model_id {
-            Some(model) => self.policy_registry.get_policy_or_default(model),
-            None => self.policy_registry.get_default_policy(),
+        let idx = self
+            .policy_for_model(model_id)
+            .select_worker(&available, text)?;
+        Some(available[idx].clone())
+    }
+
+    /// Routing text of a chat request: its session id, else the prompt rendered with the
+    /// chat template of a policy that routes on token ids
+    fn chat_routing_text(&self, body: &ChatCompletionRequest, model_id: Option<&str>) -> String {
+        let text = body.extract_text_for_routing();
+        if !text.is_empty() {
+            return text;
+        }
+
+        let policy = self.policy_for_model(model_id);
+        let Some(tokenizer) = policy.routing_tokenizer() else {
+            return text;
         };
+        render_chat_prompt(tokenizer.as_ref(), body).unwrap_or_else(|e| {
+            debug!("Routing chat request without its prompt: {}", e);
+            text
+        })
+    }
 
-        let idx = policy.select_worker(&available, text)?;
-        Some(available[idx].clone())
+    /// Affinity key of a request, if the model's policy routes on one instead of the text
+    fn affinity_key<T: serde::Serialize>(
+        &self,
+        headers: Option<&HeaderMap>,
+        request: &T,
+        model_id: Option<&str>,
+    ) -> Option<String> {
+        request_affinity_key(self.policy_for_model(model_id).as_ref(), headers, request)
+    }
+
+    /// Estimate the tokens a request will hold on its worker
+    async fn token_estimate<T: GenerationRequest>(&self, request: &T) -> TokenEstimate {
+        TokenEstimate::estimate(
+            request.prompt_text(),
+            request.max_output_tokens(),
+            self.tokenizer.clone(),
+        )
+        .await
     }
 
     pub async fn route_typed_request<T: GenerationRequest + serde::Serialize + Clone>(
@@ -512,16 +639,37 @@ impl Router {
         typed_req: &T,
         route: &str,
         model_id: Option<&str>,
+    ) -> Response {
+        let text = typed_req.extract_text_for_routing();
+        self.route_typed_request_with_text(headers, typed_req, route, model_id, text)
+            .await
+    }
+
+    /// Route a request, choosing the worker by `text`
+    async fn route_typed_request_with_text<T: GenerationRequest + serde::Serialize + Clone>(
+        &self,
+        headers: Option<&HeaderMap>,
+        typed_req: &T,
+        route: &str,
+        model_id: Option<&str>,
+        text: String,
     ) -> Response {
         let start = Instant::now();
         let is_stream = typed_req.is_stream();
-        let text = typed_req.extract_text_for_routing();
+        // Needed to abort the request on the worker if a streaming client disconnects
+        let request_id = Self::request_id(headers, typed_req);
+        let constraints = WorkerConstraints::from_headers(headers);
+        let tokens = self.token_estimate(typed_req).await;
+        let text = self
+            .affinity_key(headers, typed_req, model_id)
+            .unwrap_or(text);
 
         let response = RetryExecutor::execute_response_with_retry(
             &self.retry_config,
             // operation per attempt
             |_: u32| async {
-                let worker = match self.select_worker_for_model(model_id, Some(&text)) {
+                let worker = match self.select_worker_for_model(model_id, Some(&text), &constraints)
+                {
                     Some(w) => w,
                     None => {
                         RouterMetrics::record_request_error(route, "no_available_workers");
@@ -533,53 +681,28 @@ impl Router {
                     }
                 };
 
-                // Optional load tracking for cache-aware policy
-                // Get the policy for this model to check if it's cache-aware
-                let policy = match model_id {
-                    Some(model) => self.policy_registry.get_policy_or_default(model),
-                    None => self.policy_registry.get_default_policy(),
-                };
-
-                let load_incremented = if policy.name() == "cache_aware" {
-                    worker.increment_load();
-                    RouterMetrics::set_running_requests(worker.url(), worker.load());
-                    true
-                } else {
-                    false
-                };
-
-                // Keep a clone for potential cleanup on retry
-                let worker_for_cleanup = if load_incremented {
-                    Some(worker.clone())
-                } else {
-                    None
-                };
+                // Track in-flight requests for load-based policies and worker draining;
+                // send_typed_request decrements the load once the response is done
+                worker.increment_load();
+                RouterMetrics::set_running_requests(worker.url(), worker.load());
+                let token_load = TokenLoadGuard::new(worker.clone(), tokens);
 
                 let response = self
                     .send_typed_request(
                         headers,
                         typed_req,
                         route,
+                        &request_id,
                         worker.url(),
+                        self.policy_for_model(model_id),
+                        token_load,
                         is_stream,
-                        load_incremented,
+                        true,
                     )
                     .await;
 
                 worker.record_outcome(response.status().is_success());
 
-                // For retryable failures, we need to decrement load since send_typed_request
-                // won't have done it (it only decrements on success or non-retryable failures)
-                if is_retryable_status(response.status()) && load_incremented {
-                    if let Some(cleanup_worker) = worker_for_cleanup {
-                        cleanup_worker.decrement_load();
-                        RouterMetrics::set_running_requests(
-                            cleanup_worker.url(),
-                            cleanup_worker.load(),
-                        );
-                    }
-                }
-
                 response
             },
             // should_retry predicate
@@ -605,112 +728,154 @@ impl Router {
         response
     }
 
-    // Helper: return base worker URL (strips DP suffix when enabled)
-    fn worker_base_url(&self, worker_url: &str) -> String {
-        if self.dp_aware {
-            if let Ok((prefix, _)) = Self::extract_dp_rank(worker_url) {
-                return prefix.to_string();
-            }
-        }
-        worker_url.to_string()
-    }
-
-    // Generic simple routing for GET/POST without JSON body
-    async fn route_simple_request(
+    /// Run a background responses request on the router and return the `queued` response
+    async fn route_background_response(
         &self,
         headers: Option<&HeaderMap>,
-        endpoint: &str,
-        method: Method,
+        body: &ResponsesRequest,
+        mut request: ResponsesRequest,
+        model_id: Option<&str>,
     ) -> Response {
-        // TODO: currently the vllm worker is using in-memory state management, so this implementation has to fan out to all workers.
-        // Eventually, we need to have router to manage the chat history with a proper database, will update this implementation accordingly.
-        let worker_urls = self.get_worker_urls();
-        if worker_urls.is_empty() {
-            return (StatusCode::SERVICE_UNAVAILABLE, "No available workers").into_response();
+        let route = "/v1/responses";
+        if !body.store {
+            let error = ErrorResponse::new(
+                "Background responses require store to be enabled",
+                "invalid_request_error",
+                None,
+            );
+            return (StatusCode::BAD_REQUEST, Json(error)).into_response();
         }
 
-        let mut last_response: Option<Response> = None;
-        for worker_url in worker_urls {
-            let base = self.worker_base_url(&worker_url);
-
-            let url = format!("{}/{}", base, endpoint);
-            let mut request_builder = match method {
-                Method::GET => self.client.get(url),
-                Method::POST => self.client.post(url),
-                _ => {
-                    return (
-                        StatusCode::METHOD_NOT_ALLOWED,
-                        "Unsupported method for simple routing",
+        let text = self
+            .affinity_key(headers, &request, model_id)
+            .unwrap_or_else(|| request.extract_text_for_routing());
+        let constraints = WorkerConstraints::from_headers(headers);
+        let tokens = self.token_estimate(body).await;
+
+        // The worker streams the response so partial output can be recorded as it arrives
+        request.background = false;
+        request.store = false;
+        request.stream = true;
+        let upstream = {
+            let router = self.shared();
+            let headers = headers.cloned();
+            let model_id = model_id.map(str::to_string);
+            async move {
+                router
+                    .send_background_request(
+                        headers.as_ref(),
+                        &request,
+                        model_id.as_deref(),
+                        &text,
+                        &constraints,
+                        tokens,
                     )
-                        .into_response()
-                }
-            };
-
-            if let Some(hdrs) = headers {
-                for (name, value) in hdrs {
-                    let name_lc = name.as_str().to_lowercase();
-                    if name_lc != "content-type" && name_lc != "content-length" {
-                        request_builder = request_builder.header(name, value);
-                    }
-                }
+                    .await
             }
+        };
 
-            match request_builder.send().await {
-                Ok(res) => {
-                    let status = StatusCode::from_u16(res.status().as_u16())
-                        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-                    let response_headers = header_utils::preserve_response_headers(res.headers());
-                    match res.bytes().await {
-                        Ok(body) => {
-                            let mut response = Response::new(axum::body::Body::from(body));
-                            *response.status_mut() = status;
-                            *response.headers_mut() = response_headers;
-                            if status.is_success() {
-                                return response;
-                            }
-                            last_response = Some(response);
-                        }
-                        Err(e) => {
-                            last_response = Some(
-                                (
-                                    StatusCode::INTERNAL_SERVER_ERROR,
-                                    format!("Failed to read response: {}", e),
-                                )
-                                    .into_response(),
-                            );
-                        }
-                    }
+        RouterMetrics::record_request(route);
+        self.background_responses
+            .start(&self.response_storage, body, upstream)
+            .await
+    }
+
+    /// Send the upstream request of a background response, retrying retryable failures
+    /// on newly selected workers like foreground requests
+    async fn send_background_request(
+        &self,
+        headers: Option<&HeaderMap>,
+        request: &ResponsesRequest,
+        model_id: Option<&str>,
+        text: &str,
+        constraints: &WorkerConstraints,
+        tokens: TokenEstimate,
+    ) -> Result<BackgroundUpstream, String> {
+        let route = "/v1/responses";
+        let last_error = parking_lot::Mutex::new(String::new());
+        let result = RetryExecutor::execute_with_retry(&self.retry_config, |attempt: u32| {
+            let last_error = &last_error;
+            async move {
+                if attempt > 0 {
+                    RouterMetrics::record_retry(route);
                 }
-                Err(e) => {
-                    last_response = Some(
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            format!("Request failed: {}", e),
-                        )
-                            .into_response(),
-                    );
+                match self
+                    .try_background_request(headers, request, model_id, text, constraints, tokens)
+                    .await
+                {
+                    Ok(upstream) => Ok(Ok(upstream)),
+                    Err((message, true)) => {
+                        *last_error.lock() = message;
+                        Err(())
+                    }
+                    Err((message, false)) => Ok(Err(message)),
                 }
             }
-        }
-
-        last_response
-            .unwrap_or_else(|| (StatusCode::BAD_GATEWAY, "No worker response").into_response())
-    }
+        })
+        .await;
 
-    // Route a GET request with provided headers to a specific endpoint
-    async fn route_get_request(&self, headers: Option<&HeaderMap>, endpoint: &str) -> Response {
-        self.route_simple_request(headers, endpoint, Method::GET)
-            .await
+        result.unwrap_or_else(|_| {
+            RouterMetrics::record_retries_exhausted(route);
+            Err(last_error.into_inner())
+        })
     }
 
-    // Route a POST request with empty body to a specific endpoint
-    async fn route_post_empty_request(
+    /// One attempt at the upstream request of a background response. Failures carry
+    /// whether they may be retried.
+    async fn try_background_request(
         &self,
         headers: Option<&HeaderMap>,
-        endpoint: &str,
-    ) -> Response {
-        self.route_simple_request(headers, endpoint, Method::POST)
-            .await
+        request: &ResponsesRequest,
+        model_id: Option<&str>,
+        text: &str,
+        constraints: &WorkerConstraints,
+        tokens: TokenEstimate,
+    ) -> Result<BackgroundUpstream, (String, bool)> {
+        let route = "/v1/responses";
+        let Some(worker) = self.select_worker_for_model(model_id, Some(text), constraints) else {
+            RouterMetrics::record_request_error(route, "no_available_workers");
+            let message = "No available workers (all circuits open or unhealthy)".to_string();
+            return Err((message, true));
+        };
+        let upstream = self
+            .typed_request_builder(headers, request, route, &request.request_id, worker.url())
+            .map_err(|response| {
+                let message = format!("Failed to build worker request: {}", response.status());
+                (message, false)
+            })?;
+
+        // Held until the response finishes streaming
+        let load = OwnedWorkerLoadGuard::new(worker.clone());
+        let token_load = TokenLoadGuard::new(worker.clone(), tokens);
+        let policy = self.policy_for_model(model_id);
+        let timer = StreamTimer::start();
+        match upstream.send().await {
+            Ok(response) if response.status().is_success() => {
+                worker.record_outcome(true);
+                Ok(BackgroundUpstream {
+                    response,
+                    worker,
+                    policy,
+                    timer,
+                    load,
+                    token_load,
+                })
+            }
+            Ok(response) => {
+                worker.record_outcome(false);
+                policy.on_request_complete(worker.url(), false, &timer.finish());
+                let status = StatusCode::from_u16(response.status().as_u16())
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                let body = response.text().await.unwrap_or_default();
+                let message = format!("Worker returned {}: {}", status, body);
+                Err((message, is_retryable_status(status)))
+            }
+            Err(e) => {
+                worker.record_outcome(false);
+                policy.on_request_complete(worker.url(), false, &timer.finish());
+                Err((format!("Request failed: {}", e), true))
+            }
+        }
     }
 
     // TODO (rui): Better accommodate to the Worker abstraction
@@ -730,52 +895,74 @@ impl Router {
         }
     }
 
-    // Send typed request directly without conversion
-    async fn send_typed_request<T: serde::Serialize>(
+    /// Request ID the worker will use: the body `rid`, then `x-request-id`, then a fresh one
+    fn request_id<T: GenerationRequest>(headers: Option<&HeaderMap>, typed_req: &T) -> String {
+        typed_req
+            .request_id()
+            .map(str::to_string)
+            .or_else(|| {
+                headers
+                    .and_then(|h| h.get(REQUEST_ID_HEADER))
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    }
+
+    // Build the worker request for a typed request, carrying over the client's headers
+    #[allow(clippy::result_large_err)]
+    fn typed_request_builder<T: serde::Serialize>(
         &self,
         headers: Option<&HeaderMap>,
         typed_req: &T,
         route: &str,
+        request_id: &str,
         worker_url: &str,
-        is_stream: bool,
-        load_incremented: bool, // Whether load was incremented for this request
-    ) -> Response {
+    ) -> Result<reqwest::RequestBuilder, Response> {
         let (mut request_builder, extracted_dp_rank) = if self.dp_aware {
             let (worker_url_prefix, dp_rank) = match Self::extract_dp_rank(worker_url) {
                 Ok(tup) => tup,
                 Err(e) => {
                     error!("Failed to extract dp_rank: {}", e);
-                    return (
+                    return Err((
                         StatusCode::INTERNAL_SERVER_ERROR,
                         format!("Failed to extract dp_rank: {}", e),
                     )
-                        .into_response();
-                }
-            };
-
-            // Parse the request body
-            let mut json_val = match serde_json::to_value(typed_req) {
-                Ok(j) => j,
-                Err(e) => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        format!("Convert into serde_json::Value failed: {}", e),
-                    )
-                        .into_response();
+                        .into_response());
                 }
             };
 
-            // Use the original json_val without modification
-
-            (self.client
-                .post(format!("{}{}", worker_url_prefix, route))
-                .json(&json_val), Some(dp_rank))
+            (
+                self.client.post(format!("{}{}", worker_url_prefix, route)),
+                Some(dp_rank),
+            )
         } else {
-            (self.client
-                .post(format!("{}{}", worker_url, route))
-                .json(typed_req), None) // Use json() directly with typed request
+            (self.client.post(format!("{}{}", worker_url, route)), None)
+        };
+
+        // Parse the request body so it can be adapted to the worker's backend
+        let mut json_val = match serde_json::to_value(typed_req) {
+            Ok(j) => j,
+            Err(e) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Convert into serde_json::Value failed: {}", e),
+                )
+                    .into_response());
+            }
         };
 
+        // Give the worker the request ID the router would abort, as the PD router does,
+        // unless the body already names one
+        if let Some(fields) = json_val.as_object_mut() {
+            let named = |field: &str| fields.get(field).is_some_and(|value| !value.is_null());
+            if !named("rid") && !named("request_id") {
+                fields.insert("rid".to_string(), serde_json::Value::from(request_id));
+            }
+        }
+        BackendProfile::of(self.worker_backend(worker_url)).rewrite_request(route, &mut json_val);
+        request_builder = request_builder.json(&json_val);
+
         // Copy all headers from original request if provided
         if let Some(headers) = headers {
             for (name, value) in headers {
@@ -786,11 +973,40 @@ impl Router {
             }
         }
 
+        // Make sure the worker uses the same request ID we would abort
+        if !headers.is_some_and(|h| h.contains_key(REQUEST_ID_HEADER)) {
+            request_builder = request_builder.header(REQUEST_ID_HEADER, request_id);
+        }
+
         // Add X-data-parallel-rank header for DP-aware routing
         if let Some(dp_rank) = extracted_dp_rank {
             request_builder = request_builder.header("X-data-parallel-rank", dp_rank.to_string());
         }
 
+        Ok(request_builder)
+    }
+
+    // Send typed request directly without conversion
+    #[allow(clippy::too_many_arguments)]
+    async fn send_typed_request<T: serde::Serialize>(
+        &self,
+        headers: Option<&HeaderMap>,
+        typed_req: &T,
+        route: &str,
+        request_id: &str,
+        worker_url: &str,
+        policy: Arc<dyn LoadBalancingPolicy>, // Told the outcome and latencies of the request
+        mut token_load: TokenLoadGuard, // Tokens held on the worker until the response is done
+        is_stream: bool,
+        load_incremented: bool, // Whether load was incremented for this request
+    ) -> Response {
+        let request_builder =
+            match self.typed_request_builder(headers, typed_req, route, request_id, worker_url) {
+                Ok(request_builder) => request_builder,
+                Err(response) => return response,
+            };
+
+        let mut timer = StreamTimer::start();
         let res = match request_builder.send().await {
             Ok(res) => res,
             Err(e) => {
@@ -798,6 +1014,7 @@ impl Router {
                     "Failed to send typed request worker_url={} route={} error={}",
                     worker_url, route, e
                 );
+                policy.on_request_complete(worker_url, false, &timer.finish());
 
                 // Decrement load on error if it was incremented
                 if load_incremented {
@@ -830,18 +1047,12 @@ impl Router {
                     response
                 }
                 Err(e) => {
-                    // IMPORTANT: Decrement load on error before returning
-                    if load_incremented {
-                        if let Some(worker) = self.worker_registry.get_by_url(worker_url) {
-                            worker.decrement_load();
-                            RouterMetrics::set_running_requests(worker_url, worker.load());
-                        }
-                    }
-
                     let error_msg = format!("Failed to get response body: {}", e);
                     (StatusCode::INTERNAL_SERVER_ERROR, error_msg).into_response()
                 }
             };
+            // Without a stream there is no first token to time
+            policy.on_request_complete(worker_url, response.status().is_success(), &timer.finish());
 
             // Decrement load counter for non-streaming requests if it was incremented
             if load_incremented {
@@ -856,7 +1067,9 @@ impl Router {
             // For streaming with load tracking, we need to manually decrement when done
             let registry = Arc::clone(&self.worker_registry);
             let worker_url = worker_url.to_string();
-
+            let aborter = self.aborter.clone();
+            let route = route.to_string();
+            let request_id = request_id.to_string();
 
             // Preserve headers for streaming response
             let mut response_headers = header_utils::preserve_response_headers(res.headers());
@@ -869,16 +1082,26 @@ impl Router {
             // Spawn task to forward stream and detect completion
             tokio::spawn(async move {
                 let mut stream = stream;
+                let mut done = false;
                 let mut decremented = false;
-                while let Some(chunk) = stream.next().await {
+                let mut client_disconnected = false;
+                let mut stream_failed = false;
+                loop {
+                    let chunk = tokio::select! {
+                        biased;
+                        _ = tx.closed() => {
+                            client_disconnected = true;
+                            break;
+                        }
+                        chunk = stream.next() => chunk,
+                    };
+                    let Some(chunk) = chunk else { break };
                     match chunk {
                         Ok(bytes) => {
+                            token_load.drain(timer.observe(&bytes));
                             // Check for stream end marker
-                            if bytes
-                                .as_ref()
-                                .windows(12)
-                                .any(|window| window == b"data: [DONE]")
-                            {
+                            if contains_done_marker(&bytes) {
+                                done = true;
                                 if let Some(worker) = registry.get_by_url(&worker_url) {
                                     worker.decrement_load();
                                     RouterMetrics::set_running_requests(&worker_url, worker.load());
@@ -886,20 +1109,31 @@ impl Router {
                                 }
                             }
                             if tx.send(Ok(bytes)).is_err() {
+                                client_disconnected = true;
                                 break;
                             }
                         }
                         Err(e) => {
                             let _ = tx.send(Err(format!("Stream error: {}", e)));
+                            stream_failed = true;
                             break;
                         }
                     }
                 }
-                if !decremented {
-                    if let Some(worker) = registry.get_by_url(&worker_url) {
+                policy.on_request_complete(
+                    &worker_url,
+                    status.is_success() && !stream_failed,
+                    &timer.finish(),
+                );
+                if let Some(worker) = registry.get_by_url(&worker_url) {
+                    if !decremented {
                         worker.decrement_load();
                         RouterMetrics::set_running_requests(&worker_url, worker.load());
                     }
+                    if client_disconnected && !done {
+                        debug!("Client disconnected from stream for request {}", request_id);
+                        aborter.abort(&route, &request_id, vec![worker]);
+                    }
                 }
             });
 
@@ -919,23 +1153,55 @@ impl Router {
 
             let stream = res.bytes_stream();
             let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let registry = Arc::clone(&self.worker_registry);
+            let worker_url = worker_url.to_string();
+            let aborter = self.aborter.clone();
+            let route = route.to_string();
+            let request_id = request_id.to_string();
 
             // Spawn task to forward stream
             tokio::spawn(async move {
                 let mut stream = stream;
-                while let Some(chunk) = stream.next().await {
+                let mut done = false;
+                let mut client_disconnected = false;
+                let mut stream_failed = false;
+                loop {
+                    let chunk = tokio::select! {
+                        biased;
+                        _ = tx.closed() => {
+                            client_disconnected = true;
+                            break;
+                        }
+                        chunk = stream.next() => chunk,
+                    };
+                    let Some(chunk) = chunk else { break };
                     match chunk {
                         Ok(bytes) => {
+                            token_load.drain(timer.observe(&bytes));
+                            done |= contains_done_marker(&bytes);
                             if tx.send(Ok(bytes)).is_err() {
+                                client_disconnected = true;
                                 break;
                             }
                         }
                         Err(e) => {
                             let _ = tx.send(Err(format!("Stream error: {}", e)));
+                            stream_failed = true;
                             break;
                         }
                     }
                 }
+                policy.on_request_complete(
+                    &worker_url,
+                    status.is_success() && !stream_failed,
+                    &timer.finish(),
+                );
+                if client_disconnected && !done {
+                    if let Some(worker) = registry.get_by_url(&worker_url) {
+                        debug!("Client disconnected from stream for request {}", request_id);
+                        aborter.abort(&route, &request_id, vec![worker]);
+                    }
+                }
             });
 
             let stream = UnboundedReceiverStream::new(rx);
@@ -948,6 +1214,16 @@ impl Router {
         }
     }
 
+    /// Register a new worker under the models it serves
+    async fn register_worker(&self, worker: BasicWorker) -> Arc<dyn Worker> {
+        let worker_arc: Arc<dyn Worker> = Arc::new(worker);
+        self.worker_registry.register(worker_arc.clone());
+        self.worker_registry
+            .discover_served_models(worker_arc.url(), self.api_key.as_deref())
+            .await
+            .unwrap_or(worker_arc)
+    }
+
     pub async fn add_worker(&self, worker_url: &str) -> Result<String, String> {
         let start_time = std::time::Instant::now();
         let client = reqwest::Client::builder()
@@ -974,9 +1250,12 @@ impl Router {
                             // Need to contact the worker to extract the dp_size,
                             // and add them as multiple workers
                             let url_vec = vec![String::from(worker_url)];
-                            let dp_url_vec = Self::get_dp_aware_workers(&url_vec, &self.api_key)
-                                .await
-                                .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?;
+                            let dp_url_vec =
+                                Self::get_dp_aware_workers(&url_vec, &self.api_key, self.backend)
+                                    .await
+                                    .map_err(|e| {
+                                        format!("Failed to get dp-aware workers: {}", e)
+                                    })?;
                             let mut worker_added: bool = false;
                             for dp_url in &dp_url_vec {
                                 if self.worker_registry.get_by_url(dp_url).is_some() {
@@ -984,15 +1263,14 @@ impl Router {
                                     continue;
                                 }
                                 info!("Added worker: {}", dp_url);
-                                // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
                                 let new_worker =
                                     BasicWorker::new(dp_url.to_string(), WorkerType::Regular)
                                         .with_circuit_breaker_config(
                                             self.circuit_breaker_config.clone(),
-                                        );
+                                        )
+                                        .with_labels(backend_labels(self.backend));
 
-                                let worker_arc = Arc::new(new_worker);
-                                self.worker_registry.register(worker_arc.clone());
+                                let worker_arc = self.register_worker(new_worker).await;
 
                                 // Notify PolicyRegistry about the new worker
                                 let model_id = worker_arc.model_id();
@@ -1021,15 +1299,14 @@ impl Router {
                             }
                             info!("Added worker: {}", worker_url);
 
-                            // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
                             let new_worker =
                                 BasicWorker::new(worker_url.to_string(), WorkerType::Regular)
                                     .with_circuit_breaker_config(
                                         self.circuit_breaker_config.clone(),
-                                    );
+                                    )
+                                    .with_labels(backend_labels(self.backend));
 
-                            let worker_arc = Arc::new(new_worker);
-                            self.worker_registry.register(worker_arc.clone());
+                            let worker_arc = self.register_worker(new_worker).await;
 
                             // Notify PolicyRegistry about the new worker
                             let model_id = worker_arc.model_id();
@@ -1165,135 +1442,10 @@ impl Router {
     }
 
     async fn get_worker_load(&self, worker_url: &str) -> Option<isize> {
-        let worker_url = if self.dp_aware {
-            // Need to extract the URL from "http://host:port@dp_rank"
-            let (worker_url_prefix, _dp_rank) = match Self::extract_dp_rank(worker_url) {
-                Ok(tup) => tup,
-                Err(e) => {
-                    error!("Failed to extract dp_rank: {}", e);
-                    return None;
-                }
-            };
-            worker_url_prefix
-        } else {
-            worker_url
-        };
-
-        match self
-            .client
-            .get(format!("{}/get_load", worker_url))
-            .send()
+        self.worker_metrics
+            .fetch_load(worker_url)
             .await
-        {
-            Ok(res) if res.status().is_success() => match res.bytes().await {
-                Ok(bytes) => match serde_json::from_slice::<serde_json::Value>(&bytes) {
-                    Ok(data) => data
-                        .get("load")
-                        .and_then(|v| v.as_i64())
-                        .map(|v| v as isize),
-                    Err(e) => {
-                        debug!("Failed to parse load response from {}: {}", worker_url, e);
-                        None
-                    }
-                },
-                Err(e) => {
-                    debug!("Failed to read load response from {}: {}", worker_url, e);
-                    None
-                }
-            },
-            Ok(res) => {
-                debug!(
-                    "Worker {} returned non-success status: {}",
-                    worker_url,
-                    res.status()
-                );
-                None
-            }
-            Err(e) => {
-                debug!("Failed to get load from {}: {}", worker_url, e);
-                None
-            }
-        }
-    }
-
-    // Background task to monitor worker loads
-    async fn monitor_worker_loads(
-        worker_urls: Vec<String>,
-        tx: tokio::sync::watch::Sender<HashMap<String, isize>>,
-        interval_secs: u64,
-        policy: Arc<dyn LoadBalancingPolicy>,
-        client: Client,
-    ) {
-        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
-
-        loop {
-            interval.tick().await;
-
-            let mut loads = HashMap::new();
-            for url in &worker_urls {
-                if let Some(load) = Self::get_worker_load_static(&client, url).await {
-                    loads.insert(url.clone(), load);
-                }
-            }
-
-            if !loads.is_empty() {
-                // Update policy with new loads
-                policy.update_loads(&loads);
-
-                // Send to watchers
-                if let Err(e) = tx.send(loads) {
-                    error!("Failed to send load update: {}", e);
-                }
-            }
-        }
-    }
-
-    // Static version of get_worker_load for use in monitoring task
-    async fn get_worker_load_static(client: &reqwest::Client, worker_url: &str) -> Option<isize> {
-        let worker_url = if worker_url.contains("@") {
-            // Need to extract the URL from "http://host:port@dp_rank"
-            let (worker_url_prefix, _dp_rank) = match Self::extract_dp_rank(worker_url) {
-                Ok(tup) => tup,
-                Err(e) => {
-                    debug!("Failed to extract dp_rank: {}", e);
-                    return None;
-                }
-            };
-            worker_url_prefix
-        } else {
-            worker_url
-        };
-
-        match client.get(format!("{}/get_load", worker_url)).send().await {
-            Ok(res) if res.status().is_success() => match res.bytes().await {
-                Ok(bytes) => match serde_json::from_slice::<serde_json::Value>(&bytes) {
-                    Ok(data) => data
-                        .get("load")
-                        .and_then(|v| v.as_i64())
-                        .map(|v| v as isize),
-                    Err(e) => {
-                        debug!("Failed to parse load response from {}: {}", worker_url, e);
-                        None
-                    }
-                },
-                Err(e) => {
-                    debug!("Failed to read load response from {}: {}", worker_url, e);
-                    None
-                }
-            },
-            Ok(res) => {
-                debug!(
-                    "Worker {} returned non-success status: {}",
-                    worker_url,
-                    res.status()
-                );
-                None
-            }
-            Err(e) => {
-                debug!("Failed to get load from {}: {}", worker_url, e);
-                None
-            }
-        }
+            .map(|load| load.requests())
     }
 
     async fn build_rerank_response(
@@ -1359,19 +1511,20 @@ impl RouterTrait for Router {
     }
 
     async fn health_generate(&self, req: Request<Body>) -> Response {
-        self.proxy_get_request(req, "health_generate").await
+        self.proxy_get_request(req, |_| "/health_generate").await
     }
 
     async fn get_server_info(&self, req: Request<Body>) -> Response {
-        self.proxy_get_request(req, "get_server_info").await
+        self.proxy_get_request(req, |_| "/get_server_info").await
     }
 
-    async fn get_models(&self, req: Request<Body>) -> Response {
-        self.proxy_get_request(req, "v1/models").await
+    async fn get_models(&self, _req: Request<Body>) -> Response {
+        self.model_catalog.models_response().await
     }
 
     async fn get_model_info(&self, req: Request<Body>) -> Response {
-        self.proxy_get_request(req, "get_model_info").await
+        self.proxy_get_request(req, |profile| profile.model_info_endpoint)
+            .await
     }
 
     async fn route_generate(
@@ -1390,7 +1543,8 @@ impl RouterTrait for Router {
         body: &ChatCompletionRequest,
         model_id: Option<&str>,
     ) -> Response {
-        self.route_typed_request(headers, body, "/v1/chat/completions", model_id)
+        let text = self.chat_routing_text(body, model_id);
+        self.route_typed_request_with_text(headers, body, "/v1/chat/completions", model_id, text)
             .await
     }
 
@@ -1410,18 +1564,65 @@ impl RouterTrait for Router {
         body: &ResponsesRequest,
         model_id: Option<&str>,
     ) -> Response {
-        self.route_typed_request(headers, body, "/v1/responses", model_id)
+        let mut request = body.clone();
+        if let Err(response) =
+            responses::expand_previous_response(&self.response_storage, &mut request).await
+        {
+            return response;
+        }
+        if mcp_tool_loop::has_mcp_tools(&request) {
+            let Some(tool_loop) = &self.mcp_tool_loop else {
+                let error = ErrorResponse::new(
+                    "MCP tools are not enabled on this router",
+                    "invalid_request_error",
+                    None,
+                );
+                return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+            };
+            return tool_loop
+                .run(&self.response_storage, headers, body, request, model_id)
+                .await;
+        }
+        if body.background {
+            return self
+                .route_background_response(headers, body, request, model_id)
+                .await;
+        }
+        // The router owns the conversation state, so workers don't need to keep it
+        request.store = false;
+
+        let response = self
+            .route_typed_request(headers, &request, "/v1/responses", model_id)
+            .await;
+        if body.store && response.status().is_success() {
+            responses::store_response(&self.response_storage, body, response).await
+        } else {
+            response
+        }
+    }
+
+    async fn get_response(&self, _headers: Option<&HeaderMap>, response_id: &str) -> Response {
+        responses::get_response(&self.response_storage, response_id)
             .await
+            .unwrap_or_else(|| responses::not_found(response_id))
     }
 
-    async fn get_response(&self, headers: Option<&HeaderMap>, response_id: &str) -> Response {
-        let endpoint = format!("v1/responses/{}", response_id);
-        self.route_get_request(headers, &endpoint).await
+    async fn cancel_response(&self, _headers: Option<&HeaderMap>, response_id: &str) -> Response {
+        self.background_responses
+            .cancel(&self.response_storage, &self.aborter, response_id)
+            .await
     }
 
-    async fn cancel_response(&self, headers: Option<&HeaderMap>, response_id: &str) -> Response {
-        let endpoint = format!("v1/responses/{}/cancel", response_id);
-        self.route_post_empty_request(headers, &endpoint).await
+    async fn delete_response(&self, _headers: Option<&HeaderMap>, response_id: &str) -> Response {
+        responses::delete_response(&self.response_storage, response_id).await
+    }
+
+    async fn list_response_input_items(
+        &self,
+        _headers: Option<&HeaderMap>,
+        response_id: &str,
+    ) -> Response {
+        responses::list_input_items(&self.response_storage, response_id).await
     }
 
     async fn route_embeddings(
@@ -1579,7 +1780,6 @@ impl RouterTrait for Router {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
 
     fn create_test_regular_router() -> Router {
         // Create registries
@@ -1594,19 +1794,37 @@ mod tests {
         worker_registry.register(Arc::new(worker1));
         worker_registry.register(Arc::new(worker2));
 
-        let (_, rx) = tokio::sync::watch::channel(HashMap::new());
+        let model_catalog = Arc::new(ModelCatalog::new(
+            worker_registry.clone(),
+            Client::new(),
+            None,
+            Duration::from_secs(30),
+        ));
+        let worker_metrics = Arc::new(WorkerMetrics::new(
+            worker_registry.clone(),
+            policy_registry.clone(),
+            Client::new(),
+            None,
+        ));
         Router {
             worker_registry,
             policy_registry,
+            model_catalog,
+            worker_metrics,
             worker_startup_timeout_secs: 5,
             worker_startup_check_interval_secs: 1,
             dp_aware: false,
             api_key: None,
+            backend: WorkerBackend::Vllm,
             client: Client::new(),
             retry_config: RetryConfig::default(),
             circuit_breaker_config: CircuitBreakerConfig::default(),
-            _worker_loads: Arc::new(rx),
-            _load_monitor_handle: None,
+            aborter: RequestAborter::new(Client::new(), None),
+            response_storage: Arc::new(crate::data_connector::MemoryResponseStorage::new()),
+            background_responses: BackgroundResponses::new(),
+            mcp_tool_loop: None,
+            tokenizer: None,
+            shared: None,
         }
     }
 
@@ -1631,6 +1849,33 @@ mod tests {
         assert!(url == "http://worker1:8080" || url == "http://worker2:8080");
     }
 
+    #[test]
+    fn test_worker_request_carries_request_id() {
+        let router = create_test_regular_router();
+        let body = |request: serde_json::Value| -> serde_json::Value {
+            let request = router
+                .typed_request_builder(None, &request, "/generate", "req-1", "http://worker1:8080")
+                .unwrap()
+                .build()
+                .unwrap();
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap()
+        };
+
+        assert_eq!(body(serde_json::json!({"text": "hi"}))["rid"], "req-1");
+        assert_eq!(
+            body(serde_json::json!({"text": "hi", "rid": null}))["rid"],
+            "req-1"
+        );
+        // Request IDs the client chose are kept
+        assert_eq!(
+            body(serde_json::json!({"text": "hi", "rid": "mine"}))["rid"],
+            "mine"
+        );
+        let responses = body(serde_json::json!({"input": "hi", "request_id": "resp_1"}));
+        assert_eq!(responses["request_id"], "resp_1");
+        assert!(responses.get("rid").is_none());
+    }
+
     #[tokio::test]
     async fn test_wait_for_healthy_workers_empty_list() {
         // Empty list will return error immediately
@@ -0,0 +1,136 @@
+//! Latency measurement of proxied streams
+
+use crate::policies::RequestTiming;
+use std::time::Instant;
+
+const EVENT_PREFIX: &[u8] = b"data:";
+const DONE_EVENT: &[u8] = b"[DONE]";
+/// Bytes of a line needed to tell whether it is a generation event
+const LINE_HEAD_LEN: usize = EVENT_PREFIX.len() + 1 + DONE_EVENT.len() + 1;
+
+/// Times the server-sent events of a streamed response
+///
+/// Every `data:` line but the final `[DONE]` is taken to carry one step of generation,
+/// so the time to the first event is the time to first token and the mean interval
+/// between the following events the time per output token. Lines may be split across
+/// chunks, so an event is counted once its line ends.
+#[derive(Debug)]
+pub(crate) struct StreamTimer {
+    start: Instant,
+    first_event: Option<Instant>,
+    last_event: Option<Instant>,
+    events: u32,
+    /// Start of the line not yet ended by the chunks seen so far
+    line_head: Vec<u8>,
+}
+
+impl StreamTimer {
+    /// Start timing a request that is about to be sent
+    pub(crate) fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            first_event: None,
+            last_event: None,
+            events: 0,
+            line_head: Vec::with_capacity(LINE_HEAD_LEN),
+        }
+    }
+
+    /// Record a chunk of the stream as it arrives, returning the number of generation
+    /// events it carries
+    pub(crate) fn observe(&mut self, chunk: &[u8]) -> usize {
+        let mut events = 0;
+        for segment in chunk.split_inclusive(|&b| b == b'\n') {
+            let room = LINE_HEAD_LEN - self.line_head.len();
+            self.line_head
+                .extend_from_slice(&segment[..room.min(segment.len())]);
+            if segment.ends_with(b"\n") {
+                if is_generation_event(&self.line_head) {
+                    events += 1;
+                }
+                self.line_head.clear();
+            }
+        }
+        if events == 0 {
+            return 0;
+        }
+        let now = Instant::now();
+        self.first_event.get_or_insert(now);
+        self.last_event = Some(now);
+        self.events += events;
+        events as usize
+    }
+
+    /// Latencies observed so far
+    pub(crate) fn finish(&self) -> RequestTiming {
+        let ttft = self
+            .first_event
+            .map(|first| first.duration_since(self.start));
+        let tpot = match (self.first_event, self.last_event) {
+            (Some(first), Some(last)) if self.events > 1 => {
+                Some(last.duration_since(first) / (self.events - 1))
+            }
+            _ => None,
+        };
+        RequestTiming { ttft, tpot }
+    }
+}
+
+/// Whether a line, of which only the first `LINE_HEAD_LEN` bytes are given, is a `data:`
+/// field other than `[DONE]`
+fn is_generation_event(line_head: &[u8]) -> bool {
+    let Some(data) = line_head.strip_prefix(EVENT_PREFIX) else {
+        return false;
+    };
+    let data = data.strip_prefix(b" ").unwrap_or(data);
+    let data = data.strip_suffix(b"\n").unwrap_or(data);
+    let data = data.strip_suffix(b"\r").unwrap_or(data);
+    data != DONE_EVENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stream_timer() {
+        let mut timer = StreamTimer::start();
+        assert_eq!(timer.finish(), RequestTiming::default());
+
+        std::thread::sleep(Duration::from_millis(20));
+        timer.observe(b"data: {\"choices\":[]}\n\n");
+        let ttft = timer.finish().ttft.unwrap();
+        assert!(ttft >= Duration::from_millis(20));
+        assert_eq!(timer.finish().tpot, None);
+
+        // Chunks without events, e.g. keep-alive comments, are not timed
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(timer.observe(b": ping\n\n"), 0);
+        assert_eq!(
+            timer.observe(b"data: {\"a\":1}\n\ndata: {\"a\":2}\n\ndata: [DONE]\n\n"),
+            2
+        );
+        let timing = timer.finish();
+        assert_eq!(timing.ttft, Some(ttft));
+        let tpot = timing.tpot.unwrap();
+        assert!(tpot >= Duration::from_millis(5));
+        assert!(tpot < ttft);
+    }
+
+    #[test]
+    fn test_stream_timer_split_lines() {
+        let mut timer = StreamTimer::start();
+
+        // Events are counted when their line ends, wherever the chunks split it
+        assert_eq!(timer.observe(b"da"), 0);
+        assert_eq!(timer.observe(b"ta: {\"a\":1}"), 0);
+        assert_eq!(timer.observe(b"\n\ndata: {\"a\":2}\r\n\ndata: [DO"), 2);
+        assert_eq!(timer.observe(b"NE]\n\n"), 0);
+
+        // `data: ` inside a payload is not an event
+        assert_eq!(timer.observe(b"data: {\"text\":\"data: x\"}\n\n"), 1);
+        assert_eq!(timer.observe(b"data:{\"a\":3}\n\n"), 1);
+        assert_eq!(timer.events, 4);
+    }
+}
@@ -13,11 +13,14 @@ use crate::protocols::spec::{
     ChatCompletionRequest, CompletionRequest, EmbeddingRequest, GenerateRequest, RerankRequest,
     ResponsesRequest,
 };
+use crate::protocols::worker_spec::WorkerConfigRequest;
 
+pub mod anthropic;
 pub mod factory;
 pub mod grpc;
 pub mod header_utils;
 pub mod http;
+pub mod model_alias;
 pub mod router_manager;
 
 pub use factory::RouterFactory;
@@ -33,6 +36,12 @@ pub trait WorkerManagement: Send + Sync {
     /// Add a worker to the router
     async fn add_worker(&self, worker_url: &str) -> Result<String, String>;
 
+    /// Add a worker from a full `/workers` configuration; routers without per-worker
+    /// settings only use its URL
+    async fn add_worker_with_config(&self, config: &WorkerConfigRequest) -> Result<String, String> {
+        self.add_worker(&config.url).await
+    }
+
     /// Remove a worker from the router
     fn remove_worker(&self, worker_url: &str);
 
@@ -0,0 +1,409 @@
+//! Model aliases
+//!
+//! Clients address models by public names that the workers may not know. A
+//! [`ModelAlias`] maps such a name to a backend model id and/or a set of worker labels
+//! and carries a parameter policy. Chat and completion requests for an alias are
+//! rewritten before they reach the router, and responses report the alias again.
+
+use crate::config::{ModelAlias, ParameterPolicy};
+use crate::protocols::spec::GenerationRequest;
+use crate::routers::header_utils::{format_worker_selector, WORKER_SELECTOR_HEADER};
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// The model aliases of the router, in configured order
+#[derive(Debug, Clone, Default)]
+pub struct ModelAliases {
+    aliases: Vec<ModelAlias>,
+}
+
+impl ModelAliases {
+    pub fn new(aliases: Vec<ModelAlias>) -> Self {
+        Self { aliases }
+    }
+
+    /// The alias with the given public name
+    pub fn get(&self, name: &str) -> Option<&ModelAlias> {
+        self.aliases.iter().find(|alias| alias.name == name)
+    }
+
+    /// Add the aliases to a successful `/v1/models` response
+    pub async fn list_models(&self, response: Response) -> Response {
+        if self.aliases.is_empty() || !response.status().is_success() {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let bytes = match to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read models response: {}", e),
+                )
+                    .into_response()
+            }
+        };
+        let mut models: Value = match serde_json::from_slice(&bytes) {
+            Ok(models) => models,
+            Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+        };
+        let Some(data) = models.get_mut("data").and_then(Value::as_array_mut) else {
+            return Response::from_parts(parts, Body::from(bytes));
+        };
+
+        for alias in &self.aliases {
+            if data.iter().any(|model| model["id"] == alias.name.as_str()) {
+                continue;
+            }
+            data.push(json!({
+                "id": alias.name,
+                "object": "model",
+                "created": 0,
+                "owned_by": "vllm-router",
+                "root": alias.model_id.as_deref().unwrap_or(&alias.name),
+            }));
+        }
+
+        parts.headers.remove(CONTENT_LENGTH);
+        Response::from_parts(parts, Body::from(models.to_string()))
+    }
+}
+
+/// Route `request` with `route`, applying the alias its model names, if any
+///
+/// The request's model and parameters are rewritten and the alias' worker labels are
+/// passed to the router as an `x-worker-selector` header.
+pub async fn route_with_alias<T, F, Fut>(
+    aliases: &ModelAliases,
+    mut headers: HeaderMap,
+    request: T,
+    route: F,
+) -> Response
+where
+    T: GenerationRequest + Serialize + DeserializeOwned,
+    F: FnOnce(HeaderMap, T) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let Some(alias) = request.get_model().and_then(|model| aliases.get(model)) else {
+        return route(headers, request).await;
+    };
+
+    let request = match rewrite_request(alias, &request) {
+        Ok(request) => request,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to apply model alias '{}': {}", alias.name, e),
+            )
+                .into_response()
+        }
+    };
+    if !alias.worker_selector.is_empty() {
+        match HeaderValue::from_str(&format_worker_selector(&alias.worker_selector)) {
+            Ok(selector) => {
+                headers.insert(WORKER_SELECTOR_HEADER, selector);
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "Invalid worker selector of model alias '{}': {}",
+                        alias.name, e
+                    ),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    let response = route(headers, request).await;
+    restore_model(response, &alias.name).await
+}
+
+/// Apply the alias' model id and parameter policy to a request
+fn rewrite_request<T: Serialize + DeserializeOwned>(
+    alias: &ModelAlias,
+    request: &T,
+) -> Result<T, String> {
+    let mut value = serde_json::to_value(request).map_err(|e| e.to_string())?;
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| "request is not a JSON object".to_string())?;
+
+    if let Some(model_id) = &alias.model_id {
+        fields.insert("model".to_string(), Value::String(model_id.clone()));
+    }
+    apply_parameter_policy(&alias.parameters, fields);
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Strip disallowed fields, then fill in defaults, then cap numeric fields
+fn apply_parameter_policy(policy: &ParameterPolicy, fields: &mut serde_json::Map<String, Value>) {
+    for field in &policy.strip {
+        fields.remove(field);
+    }
+    for (field, default) in &policy.defaults {
+        if fields.get(field).is_none_or(Value::is_null) {
+            fields.insert(field.clone(), default.clone());
+        }
+    }
+    for (field, max) in &policy.max {
+        let Some(value) = fields.get_mut(field) else {
+            continue;
+        };
+        if let (Some(current), Some(limit)) = (value.as_f64(), max.as_f64()) {
+            if current > limit {
+                // Keep the field's type: integer fields like max_tokens reject a float
+                *value = if value.is_f64() {
+                    Value::from(limit)
+                } else {
+                    Value::from(limit.floor() as i64)
+                };
+            }
+        }
+    }
+}
+
+/// Report `name` as the model of a response, JSON or SSE
+async fn restore_model(response: Response, name: &str) -> Response {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    if content_type.starts_with("text/event-stream") {
+        return restore_model_in_stream(response, name.to_string());
+    }
+    if !content_type.starts_with("application/json") {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read response body: {}", e),
+            )
+                .into_response()
+        }
+    };
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(mut value) if value.get("model").is_some() => {
+            value["model"] = Value::String(name.to_string());
+            parts.headers.remove(CONTENT_LENGTH);
+            Body::from(value.to_string())
+        }
+        _ => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+fn restore_model_in_stream(response: Response, name: String) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let mut upstream = body.into_data_stream();
+    let (tx, rx) = mpsc::unbounded_channel::<Result<Bytes, String>>();
+
+    tokio::spawn(async move {
+        let mut rewriter = SseModelRewriter::new(name);
+        while let Some(chunk) = upstream.next().await {
+            let bytes = match chunk {
+                Ok(bytes) => rewriter.push(&bytes),
+                Err(e) => {
+                    let _ = tx.send(Err(format!("Stream error: {}", e)));
+                    return;
+                }
+            };
+            if !bytes.is_empty() && tx.send(Ok(bytes)).is_err() {
+                return;
+            }
+        }
+        let rest = rewriter.finish();
+        if !rest.is_empty() {
+            let _ = tx.send(Ok(rest));
+        }
+    });
+
+    parts.headers.remove(CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from_stream(UnboundedReceiverStream::new(rx)))
+}
+
+/// Rewrites the `model` of every JSON `data:` line of an SSE stream, passing other lines
+/// through unchanged
+struct SseModelRewriter {
+    name: String,
+    /// Bytes of an incomplete line
+    pending: Vec<u8>,
+}
+
+impl SseModelRewriter {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Rewrite the complete lines received so far
+    fn push(&mut self, bytes: &[u8]) -> Bytes {
+        self.pending.extend_from_slice(bytes);
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Bytes::new();
+        };
+        let lines: Vec<u8> = self.pending.drain(..=end).collect();
+        self.rewrite(&lines)
+    }
+
+    /// Rewrite whatever is left once the stream ends
+    fn finish(&mut self) -> Bytes {
+        let rest = std::mem::take(&mut self.pending);
+        self.rewrite(&rest)
+    }
+
+    fn rewrite(&self, lines: &[u8]) -> Bytes {
+        let mut out = Vec::with_capacity(lines.len());
+        for line in lines.split_inclusive(|&b| b == b'\n') {
+            let data = line
+                .strip_prefix(b"data:")
+                .and_then(|data| serde_json::from_slice::<Value>(data).ok());
+            match data {
+                Some(mut event) if event.get("model").is_some() => {
+                    event["model"] = Value::String(self.name.clone());
+                    out.extend_from_slice(b"data: ");
+                    out.extend_from_slice(event.to_string().as_bytes());
+                    if line.ends_with(b"\n") {
+                        out.push(b'\n');
+                    }
+                }
+                _ => out.extend_from_slice(line),
+            }
+        }
+        Bytes::from(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::spec::ChatCompletionRequest;
+    use std::collections::HashMap;
+
+    fn alias() -> ModelAlias {
+        ModelAlias {
+            name: "gpt-4o-mini".to_string(),
+            model_id: Some("llama-3-8b".to_string()),
+            worker_selector: HashMap::new(),
+            parameters: ParameterPolicy {
+                strip: vec!["logit_bias".to_string()],
+                defaults: HashMap::from([("temperature".to_string(), json!(0.2))]),
+                max: HashMap::from([("max_tokens".to_string(), serde_json::Number::from(256))]),
+            },
+        }
+    }
+
+    #[test]
+    fn test_rewrite_request() {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "gpt-4o-mini",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "max_tokens": 1000,
+            "logit_bias": {"42": 10.0}
+        }))
+        .unwrap();
+
+        let rewritten = rewrite_request(&alias(), &request).unwrap();
+        assert_eq!(rewritten.model, "llama-3-8b");
+        assert_eq!(rewritten.temperature, Some(0.2));
+        assert_eq!(rewritten.max_tokens, Some(256));
+        assert!(rewritten.logit_bias.is_none());
+
+        // Values the client set within the limits are kept
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "gpt-4o-mini",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "max_tokens": 100,
+            "temperature": 0.9
+        }))
+        .unwrap();
+        let rewritten = rewrite_request(&alias(), &request).unwrap();
+        assert_eq!(rewritten.temperature, Some(0.9));
+        assert_eq!(rewritten.max_tokens, Some(100));
+    }
+
+    #[test]
+    fn test_max_keeps_field_type() {
+        let policy = ParameterPolicy {
+            max: HashMap::from([
+                (
+                    "max_tokens".to_string(),
+                    serde_json::Number::from_f64(256.5).unwrap(),
+                ),
+                ("temperature".to_string(), serde_json::Number::from(1)),
+            ]),
+            ..Default::default()
+        };
+        let mut fields = json!({"max_tokens": 1000, "temperature": 1.5})
+            .as_object()
+            .unwrap()
+            .clone();
+
+        apply_parameter_policy(&policy, &mut fields);
+        assert_eq!(fields["max_tokens"], json!(256));
+        assert!(fields["max_tokens"].is_i64());
+        assert_eq!(fields["temperature"], json!(1.0));
+        assert!(fields["temperature"].is_f64());
+    }
+
+    #[test]
+    fn test_sse_model_rewriter() {
+        let mut rewriter = SseModelRewriter::new("gpt-4o-mini".to_string());
+        // Lines split across chunks are rewritten once complete
+        assert!(rewriter
+            .push(b"data: {\"model\":\"llama-3-8b\",\"choices\"")
+            .is_empty());
+        let out = rewriter.push(b":[]}\n\ndata: [DONE]\n\n");
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            "data: {\"choices\":[],\"model\":\"gpt-4o-mini\"}\n\ndata: [DONE]\n\n"
+        );
+        assert!(rewriter.finish().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let aliases = ModelAliases::new(vec![alias()]);
+        let upstream = axum::Json(json!({
+            "object": "list",
+            "data": [{"id": "llama-3-8b", "object": "model"}]
+        }))
+        .into_response();
+
+        let response = aliases.list_models(upstream).await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let models: Value = serde_json::from_slice(&body).unwrap();
+        let ids: Vec<_> = models["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["llama-3-8b", "gpt-4o-mini"]);
+        assert_eq!(models["data"][1]["root"], "llama-3-8b");
+    }
+}
@@ -4,16 +4,20 @@
 //! - Single Router Mode (enable_igw=false): Router owns workers directly
 //! - Multi-Router Mode (enable_igw=true): RouterManager coordinates everything
 
-use crate::config::RouterConfig;
-use crate::core::{CircuitBreakerConfig, Worker, WorkerFactory, WorkerRegistry, WorkerType};
+use crate::config::{RouterConfig, WorkerBackend};
+use crate::core::{
+    BackendProfile, CircuitBreakerConfig, ConnectionMode, ModelCatalog, Worker, WorkerFactory,
+    WorkerRegistry, WorkerType,
+};
 use crate::protocols::spec::{
     ChatCompletionRequest, CompletionRequest, EmbeddingRequest, GenerateRequest, RerankRequest,
     ResponsesRequest,
 };
 use crate::protocols::worker_spec::{
     ServerInfo, WorkerApiResponse, WorkerConfigRequest, WorkerErrorResponse, WorkerInfo,
-    WorkerListResponse, WorkerStats, WorkerTypeStats,
+    WorkerListResponse, WorkerStats, WorkerTypeStats, WorkerUpdateRequest,
 };
+use crate::routers::header_utils::WorkerConstraints;
 use crate::routers::{RouterTrait, WorkerManagement};
 use async_trait::async_trait;
 use axum::{
@@ -48,6 +52,9 @@ pub struct RouterManager {
     /// Policy registry for managing model-to-policy mappings
     policy_registry: Arc<crate::policies::PolicyRegistry>,
 
+    /// Models served by the registered workers, for `/v1/models`
+    model_catalog: Arc<ModelCatalog>,
+
     /// All routers managed by this manager
     /// RouterId examples: "http-regular", "http-pd", "grpc-regular", "grpc-pd"
     routers: Arc<DashMap<RouterId, Arc<dyn RouterTrait>>>,
@@ -59,7 +66,6 @@ pub struct RouterManager {
     client: reqwest::Client,
 
     /// Configuration
-    #[allow(dead_code)] // May be used in future enhancements
     config: RouterConfig,
 }
 
@@ -70,10 +76,12 @@ impl RouterManager {
         client: reqwest::Client,
         worker_registry: Arc<WorkerRegistry>,
         policy_registry: Arc<crate::policies::PolicyRegistry>,
+        model_catalog: Arc<ModelCatalog>,
     ) -> Self {
         Self {
             worker_registry,
             policy_registry,
+            model_catalog,
             routers: Arc::new(DashMap::new()),
             default_router: Arc::new(std::sync::RwLock::new(None)),
             client,
@@ -157,30 +165,40 @@ impl RouterManager {
         // Build labels from configuration
         let mut labels = config.labels.clone();
 
-        // Query server info if model_id not provided
+        let backend = match config.backend.as_deref() {
+            Some(backend) => backend.parse().map_err(|error| WorkerErrorResponse {
+                error,
+                code: "INVALID_BACKEND".to_string(),
+            })?,
+            None => self.config.backend,
+        };
+
+        // Query server info if model_id not provided; without either, the worker is
+        // known by the models it reports serving
         let model_id = if let Some(model_id) = config.model_id {
-            model_id
+            Some(model_id)
         } else {
-            match self.query_server_info(&config.url).await {
+            match self.query_server_info(&config.url, backend).await {
                 Ok(info) => {
                     // Extract model_id from server info
-                    info.model_id
-                        .or_else(|| {
-                            info.model_path
-                                .as_ref()
-                                .and_then(|path| path.split('/').next_back().map(|s| s.to_string()))
-                        })
-                        .unwrap_or_else(|| "unknown".to_string())
+                    info.model_id.or_else(|| {
+                        info.model_path
+                            .as_ref()
+                            .and_then(|path| path.split('/').next_back().map(|s| s.to_string()))
+                    })
                 }
                 Err(e) => {
                     warn!("Failed to query server info from {}: {}", config.url, e);
-                    "unknown".to_string()
+                    None
                 }
             }
         };
 
         // Add configuration to labels
-        labels.insert("model_id".to_string(), model_id.clone());
+        if let Some(model_id) = model_id {
+            labels.insert("model_id".to_string(), model_id);
+        }
+        labels.insert("backend".to_string(), backend.as_str().to_string());
 
         if let Some(priority) = config.priority {
             labels.insert("priority".to_string(), priority.to_string());
@@ -226,14 +244,20 @@ impl RouterManager {
             ),
         };
 
-        // Register worker
+        // Register worker under the models it serves
         let worker_arc: Arc<dyn Worker> = Arc::from(worker);
         let worker_id = self.worker_registry.register(worker_arc.clone());
+        let worker_arc = self
+            .worker_registry
+            .discover_served_models(&config.url, self.config.api_key.as_deref())
+            .await
+            .unwrap_or(worker_arc);
+        let model_id = worker_arc.model_id();
 
         // Notify PolicyRegistry about the new worker
         // Extract policy hint from labels if provided
         let policy_hint = labels.get("policy").map(|s| s.as_str());
-        let policy = self.policy_registry.on_worker_added(&model_id, policy_hint);
+        let policy = self.policy_registry.on_worker_added(model_id, policy_hint);
 
         // Log which type of router would handle this worker (for debugging)
         let expected_router = match config.worker_type.as_deref() {
@@ -331,6 +355,22 @@ impl RouterManager {
         }
     }
 
+    /// Update a worker's metadata and draining state in place
+    pub fn update_worker(
+        &self,
+        url: &str,
+        update: &WorkerUpdateRequest,
+    ) -> Result<WorkerApiResponse, WorkerErrorResponse> {
+        let worker =
+            apply_worker_update(&self.worker_registry, &self.policy_registry, url, update)?;
+
+        Ok(WorkerApiResponse {
+            success: true,
+            message: format!("Worker {} updated successfully", url),
+            worker: Some(self.worker_to_info("unknown", &worker)),
+        })
+    }
+
     /// Get worker by URL
     pub fn get_worker(&self, url: &str) -> Option<WorkerInfo> {
         self.worker_registry
@@ -339,8 +379,15 @@ impl RouterManager {
     }
 
     /// Query server info from a worker URL
-    async fn query_server_info(&self, url: &str) -> Result<ServerInfo, String> {
-        let info_url = format!("{}/get_server_info", url.trim_end_matches('/'));
+    async fn query_server_info(
+        &self,
+        url: &str,
+        backend: WorkerBackend,
+    ) -> Result<ServerInfo, String> {
+        let endpoint = BackendProfile::of(backend)
+            .server_info_endpoint
+            .ok_or_else(|| format!("The {} backend has no server info", backend.as_str()))?;
+        let info_url = format!("{}{}", url.trim_end_matches('/'), endpoint);
 
         match self.client.get(&info_url).send().await {
             Ok(response) => {
@@ -373,6 +420,7 @@ impl RouterManager {
                 WorkerType::Decode => "decode".to_string(),
             },
             is_healthy: worker.is_healthy(),
+            is_draining: worker.is_draining(),
             load: worker.load(),
             connection_mode: format!("{:?}", worker.connection_mode()),
             tokenizer_path: worker.tokenizer_path().map(|s| s.to_string()),
@@ -383,24 +431,47 @@ impl RouterManager {
         }
     }
 
+    /// Workers a router serves, based on the worker types and connection mode it handles
+    fn workers_for_router(
+        &self,
+        router: &dyn RouterTrait,
+        model_id: Option<&str>,
+    ) -> Vec<Arc<dyn Worker>> {
+        let is_grpc = router.router_type().starts_with("grpc");
+        let is_pd = router.is_pd_mode();
+
+        self.worker_registry
+            .get_workers_filtered(model_id, None, None, false)
+            .into_iter()
+            .filter(|w| matches!(w.connection_mode(), ConnectionMode::Grpc { .. }) == is_grpc)
+            .filter(|w| match w.worker_type() {
+                WorkerType::Regular => !is_pd,
+                WorkerType::Prefill { .. } | WorkerType::Decode => is_pd,
+            })
+            .collect()
+    }
+
+    /// Score a worker from its priority, cost and current load; higher is better
+    fn score_worker(worker: &dyn Worker) -> f64 {
+        let priority = worker.priority().min(100) as f64 / 100.0;
+        let cheapness = 1.0 / (1.0 + worker.cost().max(0.0) as f64);
+        let idleness = 1.0 / (1.0 + worker.load() as f64);
+        priority + cheapness + idleness
+    }
+
     /// Get the appropriate router for a request based on headers and request content
+    ///
+    /// Routers are scored by how well they match the `x-prefer-pd` preference, then by the
+    /// best of their available workers that satisfy the request's `x-worker-priority` floor
+    /// and `x-max-cost` ceiling. Routers without such workers are only picked if no router
+    /// has any, so the chosen router reports the lack of workers.
     pub fn select_router_for_request(
         &self,
         headers: Option<&HeaderMap>,
         model_id: Option<&str>,
     ) -> Option<Arc<dyn RouterTrait>> {
-        // Extract priority and cost preferences from headers if available
-        let _priority_threshold = headers.and_then(|h| {
-            h.get("x-worker-priority")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<u32>().ok())
-        });
-
-        let _max_cost = headers.and_then(|h| {
-            h.get("x-max-cost")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<f32>().ok())
-        });
+        // Extract priority and cost limits from headers if available
+        let constraints = WorkerConstraints::from_headers(headers);
 
         // Check if PD (prefill-decode) mode is preferred from headers
         let prefer_pd = headers
@@ -433,7 +504,7 @@ impl RouterManager {
 
         // Score routers based on worker attributes and request preferences
         let mut best_router = None;
-        let mut best_score = 0.0;
+        let mut best_score = (false, 0.0, 0.0);
 
         for router in candidate_routers {
             let mut score = 1.0;
@@ -446,17 +517,22 @@ impl RouterManager {
                 score += 1.0; // Bonus for matching regular preference
             }
 
-            // Get workers for this router and evaluate based on priority/cost
-            // Note: This would require routers to expose their workers or stats
-            // For now, we'll use a simple selection based on router type
-
-            // TODO: Once routers expose worker stats, we can evaluate:
-            // - Average worker priority vs priority_threshold
-            // - Average worker cost vs max_cost
-            // - Current load and health status
-
-            if score > best_score {
-                best_score = score;
+            // Ties between preferences are broken by the best worker this router
+            // could send the request to
+            let best_worker = self
+                .workers_for_router(router.as_ref(), model_id)
+                .iter()
+                .filter(|w| w.is_available() && constraints.allows(w.as_ref()))
+                .map(|w| Self::score_worker(w.as_ref()))
+                .max_by(|a, b| a.total_cmp(b));
+
+            let router_score = (
+                best_worker.is_some(),
+                score,
+                best_worker.unwrap_or_default(),
+            );
+            if router_score > best_score {
+                best_score = router_score;
                 best_router = Some(router);
             }
         }
@@ -465,6 +541,88 @@ impl RouterManager {
     }
 }
 
+/// Apply a worker update to the registry.
+///
+/// Metadata changes replace the worker with a copy that shares its load, health and
+/// circuit breaker state, so in-flight requests and per-URL policy state (e.g. the
+/// cache-aware tree) are kept. Used by both single-router and multi-router mode.
+pub fn apply_worker_update(
+    worker_registry: &WorkerRegistry,
+    policy_registry: &crate::policies::PolicyRegistry,
+    url: &str,
+    update: &WorkerUpdateRequest,
+) -> Result<Arc<dyn Worker>, WorkerErrorResponse> {
+    let invalid = |error: String| WorkerErrorResponse {
+        error,
+        code: "INVALID_UPDATE".to_string(),
+    };
+    if let Some(cost) = update.cost {
+        if !cost.is_finite() || cost < 0.0 {
+            return Err(invalid(format!("Invalid cost {}: must be >= 0", cost)));
+        }
+    }
+
+    let mut worker = worker_registry
+        .get_by_url(url)
+        .ok_or_else(|| WorkerErrorResponse {
+            error: format!("Worker with URL {} not found", url),
+            code: "WORKER_NOT_FOUND".to_string(),
+        })?;
+
+    if update.priority.is_some() || update.cost.is_some() || update.labels.is_some() {
+        let mut labels = worker.metadata().labels.clone();
+        if let Some(new_labels) = &update.labels {
+            labels.extend(new_labels.clone());
+        }
+        if let Some(priority) = update.priority {
+            labels.insert("priority".to_string(), priority.to_string());
+        }
+        if let Some(cost) = update.cost {
+            labels.insert("cost".to_string(), cost.to_string());
+        }
+
+        let old_model = worker.model_id().to_string();
+        worker = worker_registry
+            .update_labels(url, labels)
+            .ok_or_else(|| invalid(format!("Worker with URL {} was removed", url)))?;
+
+        if worker.model_id() != old_model {
+            policy_registry.on_worker_removed(&old_model);
+            let policy_hint = worker.metadata().labels.get("policy").cloned();
+            policy_registry.on_worker_added(worker.model_id(), policy_hint.as_deref());
+            info!(
+                "Moved worker {} from model {} to model {}",
+                url,
+                old_model,
+                worker.model_id()
+            );
+        }
+        info!(
+            "Updated worker {}: priority={}, cost={}",
+            url,
+            worker.priority(),
+            worker.cost()
+        );
+    }
+
+    if let Some(drain) = update.drain {
+        if worker.is_draining() != drain {
+            worker.set_draining(drain);
+            if drain {
+                info!(
+                    "Draining worker {} ({} requests in flight)",
+                    url,
+                    worker.load()
+                );
+            } else {
+                info!("Worker {} is no longer draining", url);
+            }
+        }
+    }
+
+    Ok(worker)
+}
+
 /// RouterManager implements RouterTrait to act as a meta-router
 /// that delegates requests to the appropriate underlying router
 #[async_trait]
@@ -480,6 +638,10 @@ impl WorkerManagement for RouterManager {
             cost: None,
             labels: std::collections::HashMap::new(),
             bootstrap_port: None,
+            backend: None,
+            api_key: None,
+            model_map: std::collections::HashMap::new(),
+            weight: None,
             tokenizer_path: None,
             reasoning_parser: None,
             tool_parser: None,
@@ -543,23 +705,9 @@ impl RouterTrait for RouterManager {
             .into_response()
     }
 
-    /// Get available models - query from worker registry
+    /// Get available models - aggregated from all registered workers
     async fn get_models(&self, _req: Request<Body>) -> Response {
-        // Get models from worker registry
-        let models = self.worker_registry.get_models();
-
-        if models.is_empty() {
-            (StatusCode::SERVICE_UNAVAILABLE, "No models available").into_response()
-        } else {
-            (
-                StatusCode::OK,
-                serde_json::json!({
-                    "models": models
-                })
-                .to_string(),
-            )
-                .into_response()
-        }
+        self.model_catalog.models_response().await
     }
 
     /// Get model information
@@ -815,3 +963,106 @@ impl std::fmt::Debug for RouterManager {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{PolicyConfig, RoutingMode};
+    use crate::core::BasicWorker;
+    use crate::routers::RouterFactory;
+    use crate::server::AppContext;
+    use std::collections::HashMap;
+
+    fn worker(url: &str, worker_type: WorkerType, priority: u32, cost: f32) -> Arc<dyn Worker> {
+        let labels = HashMap::from([
+            ("model_id".to_string(), "m".to_string()),
+            ("priority".to_string(), priority.to_string()),
+            ("cost".to_string(), cost.to_string()),
+        ]);
+        Arc::new(BasicWorker::new(url.to_string(), worker_type).with_labels(labels))
+    }
+
+    /// A manager with a reserved regular worker and cheap spot PD workers
+    async fn manager() -> RouterManager {
+        let config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            PolicyConfig::Random,
+        );
+        let context =
+            Arc::new(AppContext::new(config.clone(), reqwest::Client::new(), 16, None).unwrap());
+        let manager = RouterManager::new(
+            config.clone(),
+            reqwest::Client::new(),
+            context.worker_registry.clone(),
+            context.policy_registry.clone(),
+            context.model_catalog.clone(),
+        );
+
+        let regular = RouterFactory::create_regular_router(&[], &context)
+            .await
+            .unwrap();
+        manager.register_router(
+            RouterId::new("http-regular".to_string()),
+            Arc::from(regular),
+        );
+        let pd = RouterFactory::create_pd_router(&[], &[], None, None, &config.policy, &context)
+            .await
+            .unwrap();
+        manager.register_router(RouterId::new("http-pd".to_string()), Arc::from(pd));
+
+        let registry = &context.worker_registry;
+        registry.register(worker("http://reserved:8000", WorkerType::Regular, 90, 2.0));
+        registry.register(worker(
+            "http://spot-prefill:8000",
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+            10,
+            0.3,
+        ));
+        registry.register(worker(
+            "http://spot-decode:8000",
+            WorkerType::Decode,
+            10,
+            0.3,
+        ));
+
+        manager
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_select_router_by_worker_cost_and_priority() {
+        let manager = manager().await;
+        let select = |h: &[(&'static str, &'static str)]| {
+            manager
+                .select_router_for_request(Some(&headers(h)), None)
+                .unwrap()
+                .is_pd_mode()
+        };
+
+        // Without constraints the regular router is preferred
+        assert!(!select(&[]));
+
+        // Cheap traffic goes to the spot workers behind the PD router
+        assert!(select(&[("x-max-cost", "0.5")]));
+
+        // Premium traffic stays on reserved capacity even when PD is preferred
+        assert!(!select(&[
+            ("x-prefer-pd", "true"),
+            ("x-worker-priority", "50")
+        ]));
+
+        // Without any eligible worker, preferences decide
+        assert!(select(&[("x-prefer-pd", "true"), ("x-max-cost", "0.1")]));
+    }
+}
@@ -1,21 +1,30 @@
 use crate::{
+    auth::{self, ApiKeyStore},
     config::{ConnectionMode, HistoryBackend, RouterConfig},
-    core::{WorkerRegistry, WorkerType},
-    data_connector::{MemoryResponseStorage, NoOpResponseStorage, SharedResponseStorage},
+    core::{ModelCatalog, TenantRateLimiter, WorkerMetrics, WorkerRegistry, WorkerType},
+    data_connector::{
+        FileResponseStorage, MemoryResponseStorage, NoOpResponseStorage, SharedResponseStorage,
+    },
     logging::{self, LoggingConfig},
+    mcp::{McpClientManager, McpConfig, RouterMcpServer},
     metrics::{self, PrometheusConfig},
-    middleware::{self, QueuedRequest, TokenBucket},
+    middleware::{self, AdmissionQueue, ShutdownState, TokenBucket},
     policies::PolicyRegistry,
     protocols::{
+        anthropic::MessagesRequest,
         spec::{
-            ChatCompletionRequest, CompletionRequest, EmbeddingRequest, GenerateRequest,
-            RerankRequest, ResponsesRequest, V1RerankReqInput,
+            ChatCompletionRequest, CompletionRequest, EmbeddingRequest, ErrorResponse,
+            GenerateRequest, RerankRequest, ResponsesRequest, V1RerankReqInput,
+        },
+        worker_spec::{
+            WorkerApiResponse, WorkerConfigRequest, WorkerErrorResponse, WorkerUpdateRequest,
         },
-        worker_spec::{WorkerApiResponse, WorkerConfigRequest, WorkerErrorResponse},
     },
     reasoning_parser::ParserFactory,
     routers::{
-        router_manager::{RouterId, RouterManager},
+        anthropic,
+        model_alias::{self, ModelAliases},
+        router_manager::{apply_worker_update, RouterId, RouterManager},
         RouterFactory, RouterTrait,
     },
     service_discovery::{start_service_discovery, ServiceDiscoveryConfig},
@@ -23,16 +32,19 @@ use crate::{
     tool_parser::ParserRegistry,
 };
 use axum::{
+    body::to_bytes,
     extract::{Path, Query, Request, State},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post},
     serve, Json, Router,
 };
+use futures::FutureExt;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::json;
 use std::{
+    future::IntoFuture,
     sync::atomic::{AtomicBool, Ordering},
     sync::Arc,
     time::Duration,
@@ -52,6 +64,13 @@ pub struct AppContext {
     pub policy_registry: Arc<PolicyRegistry>,
     pub router_manager: Option<Arc<RouterManager>>,
     pub response_storage: SharedResponseStorage,
+    /// MCP servers for the Responses API tool loop (None = MCP tools disabled)
+    pub mcp_manager: Option<Arc<McpClientManager>>,
+    pub api_keys: Arc<ApiKeyStore>,
+    pub tenant_rate_limiter: Arc<TenantRateLimiter>,
+    pub model_aliases: Arc<ModelAliases>,
+    pub model_catalog: Arc<ModelCatalog>,
+    pub worker_metrics: Arc<WorkerMetrics>,
 }
 
 impl AppContext {
@@ -64,35 +83,42 @@ impl AppContext {
         let rate_limit_tokens = rate_limit_tokens_per_second.unwrap_or(max_concurrent_requests);
         let rate_limiter = Arc::new(TokenBucket::new(max_concurrent_requests, rate_limit_tokens));
 
+        // The tokenizer is needed in gRPC mode and by token-level cache trees
+        let tokenizer = if router_config.connection_mode == ConnectionMode::Grpc
+            || router_config.uses_token_tree()
+        {
+            let tokenizer_path = router_config
+                .tokenizer_path
+                .clone()
+                .or_else(|| router_config.model_path.clone())
+                .ok_or_else(|| {
+                    "gRPC mode and the token cache tree require either --tokenizer-path or --model-path to be specified"
+                        .to_string()
+                })?;
+
+            Some(
+                tokenizer_factory::create_tokenizer(&tokenizer_path)
+                    .map_err(|e| format!("Failed to create tokenizer: {e}"))?,
+            )
+        } else {
+            None
+        };
+
         // Initialize gRPC-specific components only when in gRPC mode
-        let (tokenizer, reasoning_parser_factory, tool_parser_registry) =
+        let (reasoning_parser_factory, tool_parser_registry) =
             if router_config.connection_mode == ConnectionMode::Grpc {
-                // Get tokenizer path (required for gRPC mode)
-                let tokenizer_path = router_config
-                    .tokenizer_path
-                    .clone()
-                    .or_else(|| router_config.model_path.clone())
-                    .ok_or_else(|| {
-                        "gRPC mode requires either --tokenizer-path or --model-path to be specified"
-                            .to_string()
-                    })?;
-
-                // Initialize all gRPC components
-                let tokenizer = Some(
-                    tokenizer_factory::create_tokenizer(&tokenizer_path)
-                        .map_err(|e| format!("Failed to create tokenizer: {e}"))?,
-                );
-                let reasoning_parser_factory = Some(ParserFactory::new());
-                let tool_parser_registry = Some(ParserRegistry::new());
-
-                (tokenizer, reasoning_parser_factory, tool_parser_registry)
+                (Some(ParserFactory::new()), Some(ParserRegistry::new()))
             } else {
                 // HTTP mode doesn't need these components
-                (None, None, None)
+                (None, None)
             };
 
         let worker_registry = Arc::new(WorkerRegistry::new());
-        let policy_registry = Arc::new(PolicyRegistry::new(router_config.policy.clone()));
+        let policy_registry = Arc::new(PolicyRegistry::with_options(
+            router_config.policy.clone(),
+            tokenizer.clone(),
+            router_config.load_unit,
+        ));
 
         let router_manager = None;
 
@@ -100,8 +126,35 @@ impl AppContext {
         let response_storage: SharedResponseStorage = match router_config.history_backend {
             HistoryBackend::Memory => Arc::new(MemoryResponseStorage::new()),
             HistoryBackend::None => Arc::new(NoOpResponseStorage::new()),
+            HistoryBackend::File => {
+                let storage = &router_config.history_storage;
+                let path = storage.path.as_deref().ok_or_else(|| {
+                    "history_storage.path is required for file backend".to_string()
+                })?;
+                Arc::new(
+                    FileResponseStorage::open(path, storage.ttl_secs.map(Duration::from_secs))
+                        .map_err(|e| format!("Failed to open response storage: {}", e))?,
+                )
+            }
         };
 
+        let api_keys = Arc::new(ApiKeyStore::from_config(&router_config.auth)?);
+        let tenant_rate_limiter =
+            Arc::new(TenantRateLimiter::new(&router_config.tenant_rate_limits));
+        let model_aliases = Arc::new(ModelAliases::new(router_config.model_aliases.clone()));
+        let model_catalog = Arc::new(ModelCatalog::new(
+            worker_registry.clone(),
+            client.clone(),
+            router_config.api_key.clone(),
+            Duration::from_secs(router_config.model_refresh_interval_secs),
+        ));
+        let worker_metrics = Arc::new(WorkerMetrics::new(
+            worker_registry.clone(),
+            policy_registry.clone(),
+            client.clone(),
+            router_config.api_key.clone(),
+        ));
+
         Ok(Self {
             client,
             router_config,
@@ -113,16 +166,43 @@ impl AppContext {
             policy_registry,
             router_manager,
             response_storage,
+            api_keys,
+            tenant_rate_limiter,
+            model_aliases,
+            model_catalog,
+            worker_metrics,
+            mcp_manager: None,
         })
     }
+
+    /// Connect to the MCP servers listed in `router_config.mcp.config_path`, if any.
+    pub async fn connect_mcp_servers(&mut self) -> Result<(), String> {
+        let Some(path) = self.router_config.mcp.config_path.clone() else {
+            return Ok(());
+        };
+        let mcp_config = McpConfig::from_file(&path)
+            .await
+            .map_err(|e| format!("Failed to load MCP config '{}': {}", path, e))?;
+        let manager = McpClientManager::new(mcp_config)
+            .await
+            .map_err(|e| format!("Failed to connect to MCP servers: {}", e))?;
+        info!(
+            "Connected to MCP servers {:?} ({} tools)",
+            manager.list_servers(),
+            manager.list_tools().len()
+        );
+        self.mcp_manager = Some(Arc::new(manager));
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub router: Arc<dyn RouterTrait>,
     pub context: Arc<AppContext>,
-    pub concurrency_queue_tx: Option<tokio::sync::mpsc::Sender<QueuedRequest>>,
+    pub admission_queue: Option<Arc<AdmissionQueue>>,
     pub router_manager: Option<Arc<RouterManager>>,
+    pub shutdown: Arc<ShutdownState>,
 }
 
 // Fallback handler for unmatched routes
@@ -136,6 +216,19 @@ async fn liveness(State(state): State<Arc<AppState>>) -> Response {
 }
 
 async fn readiness(State(state): State<Arc<AppState>>) -> Response {
+    if let Some(draining_for) = state.shutdown.draining_for() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({
+                "status": "draining",
+                "reason": "router is shutting down",
+                "in_flight_requests": state.shutdown.in_flight(),
+                "draining_secs": draining_for.as_secs(),
+                "grace_period_secs": state.context.router_config.drain.shutdown_grace_period_secs,
+            })),
+        )
+            .into_response();
+    }
     state.router.readiness()
 }
 
@@ -151,8 +244,49 @@ async fn get_server_info(State(state): State<Arc<AppState>>, req: Request) -> Re
     state.router.get_server_info(req).await
 }
 
-async fn v1_models(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    state.router.get_models(req).await
+pub(crate) async fn v1_models(State(state): State<Arc<AppState>>, req: Request) -> Response {
+    let response = state.router.get_models(req).await;
+    state.context.model_aliases.list_models(response).await
+}
+
+async fn v1_model(
+    State(state): State<Arc<AppState>>,
+    Path(model_id): Path<String>,
+    req: Request,
+) -> Response {
+    let response = v1_models(State(state), req).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    let models: serde_json::Value = match to_bytes(response.into_body(), usize::MAX)
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+    {
+        Ok(models) => models,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read models response: {}", e),
+            )
+                .into_response()
+        }
+    };
+
+    let model = models["data"]
+        .as_array()
+        .and_then(|data| data.iter().find(|model| model["id"] == model_id.as_str()));
+    match model {
+        Some(model) => Json(model).into_response(),
+        None => {
+            let error = ErrorResponse::new(
+                format!("The model '{}' does not exist", model_id),
+                "invalid_request_error",
+                Some("model_not_found"),
+            );
+            (StatusCode::NOT_FOUND, Json(error)).into_response()
+        }
+    }
 }
 
 async fn get_model_info(State(state): State<Arc<AppState>>, req: Request) -> Response {
@@ -177,7 +311,14 @@ async fn v1_chat_completions(
     headers: http::HeaderMap,
     Json(body): Json<ChatCompletionRequest>,
 ) -> Response {
-    state.router.route_chat(Some(&headers), &body, None).await
+    let router = &state.router;
+    model_alias::route_with_alias(
+        &state.context.model_aliases,
+        headers,
+        body,
+        |headers, body| async move { router.route_chat(Some(&headers), &body, None).await },
+    )
+    .await
 }
 
 async fn v1_completions(
@@ -185,10 +326,14 @@ async fn v1_completions(
     headers: http::HeaderMap,
     Json(body): Json<CompletionRequest>,
 ) -> Response {
-    state
-        .router
-        .route_completion(Some(&headers), &body, None)
-        .await
+    let router = &state.router;
+    model_alias::route_with_alias(
+        &state.context.model_aliases,
+        headers,
+        body,
+        |headers, body| async move { router.route_completion(Some(&headers), &body, None).await },
+    )
+    .await
 }
 
 async fn rerank(
@@ -221,6 +366,20 @@ async fn v1_responses(
         .await
 }
 
+async fn v1_messages(
+    State(state): State<Arc<AppState>>,
+    headers: http::HeaderMap,
+    Json(body): Json<MessagesRequest>,
+) -> Response {
+    anthropic::route_messages(
+        state.router.as_ref(),
+        &state.context.model_aliases,
+        headers,
+        &body,
+    )
+    .await
+}
+
 async fn v1_embeddings(
     State(state): State<Arc<AppState>>,
     headers: http::HeaderMap,
@@ -304,7 +463,13 @@ async fn remove_worker(
     State(state): State<Arc<AppState>>,
     Query(UrlQuery { url }): Query<UrlQuery>,
 ) -> Response {
-    state.router.remove_worker(&url);
+    if remove_worker_after_drain(&state, &url).await {
+        return (
+            StatusCode::OK,
+            format!("Draining worker: {url}, it will be removed once its requests finish"),
+        )
+            .into_response();
+    }
     (
         StatusCode::OK,
         format!("Successfully removed worker: {url}"),
@@ -312,6 +477,22 @@ async fn remove_worker(
         .into_response()
 }
 
+/// Remove a worker once its in-flight requests finish or the drain timeout passes.
+///
+/// Returns whether the removal was deferred because the worker is still busy.
+async fn remove_worker_after_drain(state: &Arc<AppState>, url: &str) -> bool {
+    let timeout = Duration::from_secs(state.context.router_config.drain.worker_timeout_secs);
+    let router = state.router.clone();
+    let worker_url = url.to_string();
+    state
+        .context
+        .worker_registry
+        .remove_after_drain(url, timeout, async move {
+            router.remove_worker(&worker_url);
+        })
+        .await
+}
+
 async fn flush_cache(State(state): State<Arc<AppState>>, _req: Request) -> Response {
     state.router.flush_cache().await
 }
@@ -335,8 +516,8 @@ async fn create_worker(
             Err(error) => (StatusCode::BAD_REQUEST, Json(error)).into_response(),
         }
     } else {
-        // In single router mode, use the router's add_worker with basic config
-        match state.router.add_worker(&config.url).await {
+        // In single router mode, the router picks the settings it supports
+        match state.router.add_worker_with_config(&config).await {
             Ok(message) => {
                 let response = WorkerApiResponse {
                     success: true,
@@ -357,7 +538,7 @@ async fn create_worker(
 }
 
 /// GET /workers - List all workers with details
-async fn list_workers_rest(State(state): State<Arc<AppState>>) -> Response {
+pub(crate) async fn list_workers_rest(State(state): State<Arc<AppState>>) -> Response {
     if let Some(router_manager) = &state.router_manager {
         let response = router_manager.list_workers();
         Json(response).into_response()
@@ -375,6 +556,7 @@ async fn list_workers_rest(State(state): State<Arc<AppState>>) -> Response {
                         WorkerType::Decode => "decode",
                     },
                     "is_healthy": worker.is_healthy(),
+                    "is_draining": worker.is_draining(),
                     "load": worker.load(),
                     "connection_mode": format!("{:?}", worker.connection_mode()),
                     "priority": worker.priority(),
@@ -430,8 +612,62 @@ async fn get_worker(State(state): State<Arc<AppState>>, Path(url): Path<String>)
     }
 }
 
+/// PATCH /workers/{url} - Update worker priority, cost, labels or draining state in place
+async fn update_worker(
+    State(state): State<Arc<AppState>>,
+    Path(url): Path<String>,
+    Json(update): Json<WorkerUpdateRequest>,
+) -> Response {
+    let result = if let Some(router_manager) = &state.router_manager {
+        router_manager.update_worker(&url, &update)
+    } else {
+        // In single router mode, update the shared registry directly
+        apply_worker_update(
+            &state.context.worker_registry,
+            &state.context.policy_registry,
+            &url,
+            &update,
+        )
+        .map(|_| WorkerApiResponse {
+            success: true,
+            message: format!("Worker {url} updated successfully"),
+            worker: None,
+        })
+    };
+
+    match result {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(error) if error.code == "WORKER_NOT_FOUND" => {
+            (StatusCode::NOT_FOUND, Json(error)).into_response()
+        }
+        Err(error) => (StatusCode::BAD_REQUEST, Json(error)).into_response(),
+    }
+}
+
 /// DELETE /workers/{url} - Remove a worker
 async fn delete_worker(State(state): State<Arc<AppState>>, Path(url): Path<String>) -> Response {
+    let busy = state
+        .context
+        .worker_registry
+        .get_by_url(&url)
+        .is_some_and(|worker| worker.load() > 0);
+    if busy {
+        let (status, message) = if remove_worker_after_drain(&state, &url).await {
+            (
+                StatusCode::ACCEPTED,
+                format!("Worker {url} is draining and will be removed once its requests finish"),
+            )
+        } else {
+            (StatusCode::OK, format!("Worker {url} removed successfully"))
+        };
+        let response = WorkerApiResponse {
+            success: true,
+            message,
+            worker: None,
+        };
+        return (status, Json(response)).into_response();
+    }
+
     if let Some(router_manager) = &state.router_manager {
         match router_manager.remove_worker_from_registry(&url) {
             Ok(response) => (StatusCode::OK, Json(response)).into_response(),
@@ -462,6 +698,31 @@ pub struct ServerConfig {
     pub request_id_headers: Option<Vec<String>>,
 }
 
+/// Wrap inference routes in draining, authentication, tenant rate limiting and
+/// admission control, outermost first
+fn with_inference_layers(
+    routes: Router<Arc<AppState>>,
+    app_state: &Arc<AppState>,
+) -> Router<Arc<AppState>> {
+    routes
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::concurrency_limit_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::tenant_rate_limit_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth::inference_auth_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::shutdown_drain_middleware,
+        ))
+}
+
 /// Build the Axum application with all routes and middleware
 pub fn build_app(
     app_state: Arc<AppState>,
@@ -478,6 +739,7 @@ pub fn build_app(
         .route("/v1/rerank", post(v1_rerank))
         .route("/v1/responses", post(v1_responses))
         .route("/v1/embeddings", post(v1_embeddings))
+        .route("/v1/messages", post(v1_messages))
         .route("/v1/responses/{response_id}", get(v1_responses_get))
         .route(
             "/v1/responses/{response_id}/cancel",
@@ -487,41 +749,68 @@ pub fn build_app(
         .route(
             "/v1/responses/{response_id}/input",
             get(v1_responses_list_input_items),
-        )
+        );
+    let protected_routes = with_inference_layers(protected_routes, &app_state);
+
+    // Model listings and info reveal what the router serves, so they need an inference key too
+    let model_routes = Router::new()
+        .route("/v1/models", get(v1_models))
+        .route("/v1/models/{*model_id}", get(v1_model))
+        .route("/get_model_info", get(get_model_info))
+        .route("/get_server_info", get(get_server_info))
         .route_layer(axum::middleware::from_fn_with_state(
             app_state.clone(),
-            middleware::concurrency_limit_middleware,
+            auth::inference_auth_middleware,
         ));
 
     let public_routes = Router::new()
         .route("/liveness", get(liveness))
         .route("/readiness", get(readiness))
         .route("/health", get(health))
-        .route("/health_generate", get(health_generate))
-        .route("/v1/models", get(v1_models))
-        .route("/get_model_info", get(get_model_info))
-        .route("/get_server_info", get(get_server_info));
+        .route("/health_generate", get(health_generate));
 
     let admin_routes = Router::new()
         .route("/add_worker", post(add_worker))
         .route("/remove_worker", post(remove_worker))
         .route("/list_workers", get(list_workers))
         .route("/flush_cache", post(flush_cache))
-        .route("/get_loads", get(get_loads));
+        .route("/get_loads", get(get_loads))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth::admin_auth_middleware,
+        ));
 
     // Worker management routes
     let worker_routes = Router::new()
         .route("/workers", post(create_worker))
         .route("/workers", get(list_workers_rest))
         .route("/workers/{url}", get(get_worker))
-        .route("/workers/{url}", delete(delete_worker));
+        .route("/workers/{url}", patch(update_worker))
+        .route("/workers/{url}", delete(delete_worker))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth::admin_auth_middleware,
+        ));
+
+    // The router's own MCP server; its chat tool is subject to the same limits as the
+    // inference routes, and admin tools are gated inside the handler
+    let mcp_routes = if app_state.context.router_config.mcp_server.enabled {
+        with_inference_layers(
+            Router::new().nest_service("/mcp", RouterMcpServer::service(app_state.clone())),
+            &app_state,
+        )
+    } else {
+        Router::new()
+    };
 
     // Build app with all routes and middleware
     Router::new()
         .merge(protected_routes)
         .merge(public_routes)
+        .merge(model_routes)
         .merge(admin_routes)
         .merge(worker_routes)
+        .merge(mcp_routes)
         // Request body size limiting
         .layer(tower_http::limit::RequestBodyLimitLayer::new(
             max_payload_size,
@@ -594,16 +883,38 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
 
     // Create the application context with all dependencies
     println!("DEBUG: Creating AppContext");
-    let app_context = AppContext::new(
+    let mut app_context = AppContext::new(
         config.router_config.clone(),
         client.clone(),
         config.router_config.max_concurrent_requests,
         config.router_config.rate_limit_tokens_per_second,
     )?;
+    app_context.connect_mcp_servers().await?;
     println!("DEBUG: AppContext created");
 
     let app_context = Arc::new(app_context);
 
+    if app_context.api_keys.is_enabled() {
+        info!("API key authentication enabled");
+    }
+    if app_context.tenant_rate_limiter.is_enabled() {
+        info!(
+            "Tenant rate limiting enabled: {:?}",
+            app_context.tenant_rate_limiter
+        );
+    }
+    let _api_key_reloader = app_context.api_keys.start_reloader(Duration::from_secs(
+        config.router_config.auth.key_file_reload_interval_secs,
+    ));
+    let _model_catalog_refresher = app_context.model_catalog.start_refresher();
+    // Load-aware policies are fed the loads the workers report
+    let load_check_interval = app_context.router_config.load_check_interval_secs();
+    let _worker_metrics_scraper = load_check_interval.map(|secs| {
+        app_context
+            .worker_metrics
+            .start_scraper(Duration::from_secs(secs))
+    });
+
     // Create the appropriate router based on enable_igw flag
     let (router, router_manager): (Arc<dyn RouterTrait>, Option<Arc<RouterManager>>) =
         if config.router_config.enable_igw {
@@ -615,6 +926,7 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
                 client.clone(),
                 app_context.worker_registry.clone(),
                 app_context.policy_registry.clone(),
+                app_context.model_catalog.clone(),
             ));
 
             // 1. HTTP Regular Router
@@ -677,27 +989,33 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
         };
 
     // Start health checker for all workers in the registry
-    let _health_checker = app_context
-        .worker_registry
-        .start_health_checker(config.router_config.health_check.check_interval_secs);
+    let _health_checker = app_context.worker_registry.start_health_checker(
+        config.router_config.health_check.check_interval_secs,
+        config.router_config.api_key.clone(),
+        app_context.policy_registry.clone(),
+    );
     info!(
         "Started health checker for workers with {}s interval",
         config.router_config.health_check.check_interval_secs
     );
 
-    // Set up concurrency limiter with queue if configured
-    let (limiter, processor) = middleware::ConcurrencyLimiter::new(
+    // Set up the admission queue for requests over the concurrency limit, if configured
+    let admission_queue = AdmissionQueue::new(
         app_context.rate_limiter.clone(),
+        &config.router_config.admission,
         config.router_config.queue_size,
         Duration::from_secs(config.router_config.queue_timeout_secs),
-    );
+    )
+    .map(Arc::new);
 
-    // Start queue processor if enabled
-    if let Some(processor) = processor {
-        tokio::spawn(processor.run());
+    // Start the queue dispatcher if enabled
+    if let Some(queue) = &admission_queue {
+        tokio::spawn(queue.clone().run());
         info!(
-            "Started request queue with size: {}, timeout: {}s",
-            config.router_config.queue_size, config.router_config.queue_timeout_secs
+            "Started admission queue with size: {}, timeout: {}s, priority classes: {}",
+            config.router_config.queue_size,
+            config.router_config.queue_timeout_secs,
+            config.router_config.admission.classes.len()
         );
     }
 
@@ -705,15 +1023,24 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
     let app_state = Arc::new(AppState {
         router,
         context: app_context.clone(),
-        concurrency_queue_tx: limiter.queue_tx.clone(),
+        admission_queue,
         router_manager,
+        shutdown: Arc::new(ShutdownState::new()),
     });
     let router_arc = Arc::clone(&app_state.router);
+    let shutdown = Arc::clone(&app_state.shutdown);
 
     // Start the service discovery if enabled
     if let Some(service_discovery_config) = config.service_discovery_config {
         if service_discovery_config.enabled {
-            match start_service_discovery(service_discovery_config, router_arc).await {
+            match start_service_discovery(
+                service_discovery_config,
+                router_arc,
+                app_context.worker_registry.clone(),
+                Duration::from_secs(config.router_config.drain.worker_timeout_secs),
+            )
+            .await
+            {
                 Ok(handle) => {
                     info!("Service discovery started");
                     // Spawn a task to handle the service discovery thread
@@ -756,14 +1083,52 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
     let addr = format!("{}:{}", config.host, config.port);
     let listener = TcpListener::bind(&addr).await?;
     info!("Starting server on {}", addr);
-    serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
+    let grace_period = Duration::from_secs(config.router_config.drain.shutdown_grace_period_secs);
+    let drained = drain_on_shutdown_signal(shutdown, grace_period).shared();
+    let server = serve(listener, app)
+        .with_graceful_shutdown(drained.clone().map(|_| ()))
+        .into_future();
+
+    // Requests still in flight once the grace period is over are cut off
+    tokio::select! {
+        result = server => {
+            result.map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
+        }
+        _ = async {
+            if drained.await {
+                std::future::pending::<()>().await;
+            }
+        } => {}
+    }
 
     Ok(())
 }
 
+/// Wait for a shutdown signal, then drain in-flight requests for up to `grace_period`.
+///
+/// While draining, new inference requests are rejected and `/readiness` reports progress so
+/// load balancers stop sending traffic. Returns whether all requests finished in time.
+async fn drain_on_shutdown_signal(shutdown: Arc<ShutdownState>, grace_period: Duration) -> bool {
+    shutdown_signal().await;
+    shutdown.begin_drain();
+    info!(
+        "Draining {} in-flight requests (grace period {:?})",
+        shutdown.in_flight(),
+        grace_period
+    );
+
+    let idle = shutdown.wait_idle(grace_period).await;
+    if idle {
+        info!("All in-flight requests finished, shutting down");
+    } else {
+        warn!(
+            "{} requests still in flight after the grace period, shutting down",
+            shutdown.in_flight()
+        );
+    }
+    idle
+}
+
 // Graceful shutdown handler
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -813,8 +1178,17 @@ fn create_cors_layer(allowed_origins: Vec<String>) -> tower_http::cors::CorsLaye
 
         tower_http::cors::CorsLayer::new()
             .allow_origin(origins)
-            .allow_methods([http::Method::GET, http::Method::POST, http::Method::OPTIONS])
-            .allow_headers([http::header::CONTENT_TYPE, http::header::AUTHORIZATION])
+            .allow_methods([
+                http::Method::GET,
+                http::Method::POST,
+                http::Method::PATCH,
+                http::Method::OPTIONS,
+            ])
+            .allow_headers([
+                http::header::CONTENT_TYPE,
+                http::header::AUTHORIZATION,
+                http::header::HeaderName::from_static(auth::API_KEY_HEADER),
+            ])
             .expose_headers([http::header::HeaderName::from_static("x-request-id")])
     };
 
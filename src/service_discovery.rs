@@ -1,3 +1,4 @@
+use crate::core::WorkerRegistry;
 use crate::routers::RouterTrait;
 
 use futures::{StreamExt, TryStreamExt};
@@ -173,9 +174,15 @@ impl PodInfo {
     }
 }
 
+/// Start watching Kubernetes pods and add/remove their workers to/from the router.
+///
+/// Workers of deleted pods are drained first: they get no new requests but are only removed
+/// from the router once their in-flight requests finish or `drain_timeout` passes.
 pub async fn start_service_discovery(
     config: ServiceDiscoveryConfig,
     router: Arc<dyn RouterTrait>,
+    worker_registry: Arc<WorkerRegistry>,
+    drain_timeout: Duration,
 ) -> Result<task::JoinHandle<()>, kube::Error> {
     // Don't initialize anything if service discovery is disabled
     if !config.enabled {
@@ -278,12 +285,14 @@ pub async fn start_service_discovery(
             // Clone again for the next closure
             let tracked_pods_clone2 = Arc::clone(&tracked_pods_clone);
             let router_clone = Arc::clone(&router);
+            let registry_clone = Arc::clone(&worker_registry);
             let config_clone2 = Arc::clone(&config_arc);
 
             match filtered_stream
                 .try_for_each(move |pod| {
                     let tracked_pods_inner = Arc::clone(&tracked_pods_clone2);
                     let router_inner = Arc::clone(&router_clone);
+                    let registry_inner = Arc::clone(&registry_clone);
                     let config_inner = Arc::clone(&config_clone2);
 
                     async move {
@@ -295,6 +304,8 @@ pub async fn start_service_discovery(
                                     &pod_info,
                                     tracked_pods_inner,
                                     router_inner,
+                                    &registry_inner,
+                                    drain_timeout,
                                     port,
                                     config_inner.pd_mode,
                                 )
@@ -429,6 +440,8 @@ async fn handle_pod_deletion(
     pod_info: &PodInfo,
     tracked_pods: Arc<Mutex<HashSet<PodInfo>>>,
     router: Arc<dyn RouterTrait>,
+    worker_registry: &WorkerRegistry,
+    drain_timeout: Duration,
     port: u16,
     pd_mode: bool,
 ) {
@@ -451,43 +464,68 @@ async fn handle_pod_deletion(
             pod_info.name, pod_info.pod_type, worker_url
         );
 
-        // Handle PD mode removal
-        if pd_mode && pod_info.pod_type.is_some() {
-            use crate::routers::http::pd_router::PDRouter;
+        // Let the worker finish its in-flight requests before it leaves the router
+        let remove = remove_pod_worker(
+            router,
+            worker_url.clone(),
+            pod_info.pod_type.clone(),
+            pd_mode,
+        );
+        if worker_registry
+            .remove_after_drain(&worker_url, drain_timeout, remove)
+            .await
+        {
+            info!(
+                "Pod {} is draining, its worker will be removed once idle",
+                pod_info.name
+            );
+        }
+    } else {
+        // This case might occur if a pod is deleted before it was ever marked healthy and added.
+        // Or if the event is duplicated. No action needed on the router if it wasn't tracked (and thus not added).
+        debug!(
+            "Pod deletion event for untracked/already removed pod: {} (type: {:?}). Worker URL: {}",
+            pod_info.name, pod_info.pod_type, worker_url
+        );
+    }
+}
 
-            // Try to downcast to PDRouter for PD-specific removal
-            if let Some(pd_router) = router.as_any().downcast_ref::<PDRouter>() {
-                match &pod_info.pod_type {
-                    Some(PodType::Prefill) => {
-                        if let Err(e) = pd_router.remove_prefill_server(&worker_url).await {
-                            error!("Failed to remove prefill server {}: {}", worker_url, e);
-                        }
-                    }
-                    Some(PodType::Decode) => {
-                        if let Err(e) = pd_router.remove_decode_server(&worker_url).await {
-                            error!("Failed to remove decode server {}: {}", worker_url, e);
-                        }
+/// Remove the worker of a deleted pod from the router
+async fn remove_pod_worker(
+    router: Arc<dyn RouterTrait>,
+    worker_url: String,
+    pod_type: Option<PodType>,
+    pd_mode: bool,
+) {
+    // Handle PD mode removal
+    if pd_mode && pod_type.is_some() {
+        use crate::routers::http::pd_router::PDRouter;
+
+        // Try to downcast to PDRouter for PD-specific removal
+        if let Some(pd_router) = router.as_any().downcast_ref::<PDRouter>() {
+            match &pod_type {
+                Some(PodType::Prefill) => {
+                    if let Err(e) = pd_router.remove_prefill_server(&worker_url).await {
+                        error!("Failed to remove prefill server {}: {}", worker_url, e);
                     }
-                    Some(PodType::Regular) | None => {
-                        // Fall back to regular remove_worker
-                        router.remove_worker(&worker_url);
+                }
+                Some(PodType::Decode) => {
+                    if let Err(e) = pd_router.remove_decode_server(&worker_url).await {
+                        error!("Failed to remove decode server {}: {}", worker_url, e);
                     }
                 }
-            } else {
-                // PD mode but not a PDRouter, use generic removal
-                router.remove_worker(&worker_url);
+                Some(PodType::Regular) | None => {
+                    // Fall back to regular remove_worker
+                    router.remove_worker(&worker_url);
+                }
             }
         } else {
-            // Regular mode removal
+            // PD mode but not a PDRouter, use generic removal
             router.remove_worker(&worker_url);
         }
     } else {
-        // This case might occur if a pod is deleted before it was ever marked healthy and added.
-        // Or if the event is duplicated. No action needed on the router if it wasn't tracked (and thus not added).
-        debug!(
-            "Pod deletion event for untracked/already removed pod: {} (type: {:?}). Worker URL: {}",
-            pod_info.name, pod_info.pod_type, worker_url
-        );
+        // Regular mode removal
+        router.remove_worker(&worker_url);
     }
 }
 
@@ -591,19 +629,39 @@ mod tests {
         }; // Very short timeout for tests
 
         // Create AppContext with minimal components
+        let worker_registry = Arc::new(crate::core::WorkerRegistry::new());
+        let policy_registry = Arc::new(crate::policies::PolicyRegistry::new(
+            router_config.policy.clone(),
+        ));
         let app_context = Arc::new(AppContext {
             client: reqwest::Client::new(),
             router_config: router_config.clone(),
             rate_limiter: Arc::new(TokenBucket::new(1000, 1000)),
-            worker_registry: Arc::new(crate::core::WorkerRegistry::new()),
-            policy_registry: Arc::new(crate::policies::PolicyRegistry::new(
-                router_config.policy.clone(),
-            )),
+            worker_registry: worker_registry.clone(),
+            policy_registry: policy_registry.clone(),
             tokenizer: None,                // HTTP mode doesn't need tokenizer
             reasoning_parser_factory: None, // HTTP mode doesn't need reasoning parser
             tool_parser_registry: None,     // HTTP mode doesn't need tool parser
             router_manager: None,           // Test doesn't need router manager
             response_storage: Arc::new(crate::data_connector::MemoryResponseStorage::new()),
+            api_keys: Arc::new(crate::auth::ApiKeyStore::default()),
+            tenant_rate_limiter: Arc::new(crate::core::TenantRateLimiter::new(
+                &router_config.tenant_rate_limits,
+            )),
+            model_aliases: Arc::new(crate::routers::model_alias::ModelAliases::default()),
+            model_catalog: Arc::new(crate::core::ModelCatalog::new(
+                worker_registry.clone(),
+                reqwest::Client::new(),
+                None,
+                Duration::from_secs(30),
+            )),
+            worker_metrics: Arc::new(crate::core::WorkerMetrics::new(
+                worker_registry,
+                policy_registry.clone(),
+                reqwest::Client::new(),
+                None,
+            )),
+            mcp_manager: None,
         });
 
         let router = Router::new(vec![], &app_context).await.unwrap();
@@ -954,6 +1012,8 @@ mod tests {
             &pod_info,
             Arc::clone(&tracked_pods),
             Arc::clone(&router),
+            &WorkerRegistry::new(),
+            Duration::from_secs(1),
             port,
             false, // pd_mode = false
         )
@@ -1044,6 +1104,8 @@ mod tests {
             &pod_info,
             Arc::clone(&tracked_pods),
             Arc::clone(&router),
+            &WorkerRegistry::new(),
+            Duration::from_secs(1),
             port,
             false, // pd_mode = false
         )
@@ -1073,6 +1135,8 @@ mod tests {
             &pod_info,
             Arc::clone(&tracked_pods),
             Arc::clone(&router),
+            &WorkerRegistry::new(),
+            Duration::from_secs(1),
             port,
             true, // pd_mode = true
         )
@@ -1164,6 +1228,8 @@ mod tests {
             &pod_info,
             Arc::clone(&tracked_pods),
             Arc::clone(&router),
+            &WorkerRegistry::new(),
+            Duration::from_secs(1),
             port,
             true, // pd_mode = true
         )
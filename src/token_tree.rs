@@ -0,0 +1,453 @@
+//! Token-level radix tree for cache-aware routing
+//!
+//! [`crate::tree::Tree`] approximates the prefix caches of the workers on the characters
+//! of the request text. Workers cache KV blocks of tokens, though: vLLM only reuses full
+//! pages of `block_size` tokens, and texts sharing a character prefix may still tokenize
+//! differently. [`TokenTree`] stores token ids and matches whole blocks only, so a match
+//! length is the number of prompt tokens a worker can serve from its cache.
+
+use crate::tokenizer::traits::TokenIdType;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+type NodeId = usize;
+
+const ROOT: NodeId = 0;
+
+#[derive(Debug)]
+struct Node {
+    /// Edge label, a whole number of blocks (empty for the root)
+    tokens: Vec<TokenIdType>,
+    /// Children by the first block of their edge
+    children: HashMap<Vec<TokenIdType>, NodeId>,
+    parent: Option<NodeId>,
+    /// Logical time of each tenant's last access
+    tenant_last_access: HashMap<String, u64>,
+}
+
+impl Node {
+    fn new(tokens: Vec<TokenIdType>, parent: Option<NodeId>) -> Self {
+        Self {
+            tokens,
+            children: HashMap::new(),
+            parent,
+            tenant_last_access: HashMap::new(),
+        }
+    }
+}
+
+/// Multi-tenant radix tree over blocks of token ids
+///
+/// Unlike the char tree, nodes live in an arena and the tree is not synchronized
+/// internally; the cache-aware policy only accesses its trees under a lock. Sizes are
+/// counted in tokens.
+#[derive(Debug)]
+pub struct TokenTree {
+    block_size: usize,
+    nodes: HashMap<NodeId, Node>,
+    next_id: NodeId,
+    /// Logical clock ordering accesses for LRU eviction
+    clock: u64,
+    tenant_token_count: HashMap<String, usize>,
+}
+
+impl TokenTree {
+    pub fn new(block_size: usize) -> Self {
+        assert!(block_size > 0, "block_size must be > 0");
+        Self {
+            block_size,
+            nodes: HashMap::from([(ROOT, Node::new(Vec::new(), None))]),
+            next_id: ROOT + 1,
+            clock: 0,
+            tenant_token_count: HashMap::new(),
+        }
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Insert the full blocks of `tokens` for `tenant`; a trailing partial block is not
+    /// cached by the worker and is dropped
+    pub fn insert(&mut self, tokens: &[TokenIdType], tenant: &str) {
+        let now = self.tick();
+        let tokens = &tokens[..self.full_blocks(tokens.len())];
+        self.attach(ROOT, tenant, now);
+
+        let mut curr = ROOT;
+        let mut idx = 0;
+        while idx < tokens.len() {
+            let rest = &tokens[idx..];
+            let next = match self.child(curr, rest) {
+                None => self.add_node(curr, rest.to_vec()),
+                Some(child) => {
+                    let shared = self.shared_blocks(&self.nodes[&child].tokens, rest);
+                    if shared < self.nodes[&child].tokens.len() {
+                        self.split(child, shared)
+                    } else {
+                        child
+                    }
+                }
+            };
+            self.attach(next, tenant, now);
+            idx += self.nodes[&next].tokens.len();
+            curr = next;
+        }
+    }
+
+    /// Longest cached prefix of `tokens` in tokens, always a whole number of blocks, and
+    /// the tenant that used it most recently (`"empty"` if the tree has no tenant)
+    pub fn prefix_match(&mut self, tokens: &[TokenIdType]) -> (usize, String) {
+        let (matched, node) = self.walk(tokens, None);
+        let tenant = self.nodes[&node]
+            .tenant_last_access
+            .iter()
+            .max_by_key(|(_, time)| **time)
+            .map(|(tenant, _)| tenant.clone());
+
+        match tenant {
+            Some(tenant) => {
+                self.touch(node, &tenant);
+                (matched, tenant)
+            }
+            None => (matched, "empty".to_string()),
+        }
+    }
+
+    /// Longest prefix of `tokens` cached for `tenant`, in tokens
+    pub fn prefix_match_tenant(&mut self, tokens: &[TokenIdType], tenant: &str) -> usize {
+        let (matched, node) = self.walk(tokens, Some(tenant));
+        if self.nodes[&node].tenant_last_access.contains_key(tenant) {
+            self.touch(node, tenant);
+        }
+        matched
+    }
+
+    /// Evict the least recently used leaves of every tenant holding more than
+    /// `max_size` tokens until it fits
+    pub fn evict_tenant_by_size(&mut self, max_size: usize) {
+        let mut heap = BinaryHeap::new();
+        for (&id, node) in &self.nodes {
+            for (tenant, &time) in &node.tenant_last_access {
+                if self.exceeds(tenant, max_size) && self.is_leaf_of(id, tenant) {
+                    heap.push(Reverse((time, id, tenant.clone())));
+                }
+            }
+        }
+
+        while let Some(Reverse((_, id, tenant))) = heap.pop() {
+            if id == ROOT || !self.exceeds(&tenant, max_size) {
+                continue;
+            }
+            let Some(parent) = self.detach(id, &tenant) else {
+                continue;
+            };
+            if self.is_leaf_of(parent, &tenant) {
+                let time = self.nodes[&parent].tenant_last_access[&tenant];
+                heap.push(Reverse((time, parent, tenant)));
+            }
+        }
+    }
+
+    pub fn remove_tenant(&mut self, tenant: &str) {
+        for node in self.nodes.values_mut() {
+            node.tenant_last_access.remove(tenant);
+        }
+        // A node without tenants has no tenants below it either
+        let orphans: Vec<NodeId> = self
+            .nodes
+            .iter()
+            .filter(|(&id, node)| id != ROOT && node.tenant_last_access.is_empty())
+            .map(|(&id, _)| id)
+            .collect();
+        for id in orphans {
+            self.remove_node(id);
+        }
+        self.tenant_token_count.remove(tenant);
+    }
+
+    pub fn get_tenant_token_count(&self) -> HashMap<String, usize> {
+        self.tenant_token_count.clone()
+    }
+
+    pub fn get_smallest_tenant(&self) -> String {
+        self.tenant_token_count
+            .iter()
+            .min_by_key(|(_, count)| **count)
+            .map(|(tenant, _)| tenant.clone())
+            .unwrap_or_else(|| "empty".to_string())
+    }
+
+    pub fn get_used_size_per_tenant(&self) -> HashMap<String, usize> {
+        let mut used_size_per_tenant: HashMap<String, usize> = HashMap::new();
+        for node in self.nodes.values() {
+            for tenant in node.tenant_last_access.keys() {
+                *used_size_per_tenant.entry(tenant.clone()).or_insert(0) += node.tokens.len();
+            }
+        }
+        used_size_per_tenant
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn full_blocks(&self, len: usize) -> usize {
+        len - len % self.block_size
+    }
+
+    /// Number of leading tokens `a` and `b` share, rounded down to whole blocks
+    fn shared_blocks(&self, a: &[TokenIdType], b: &[TokenIdType]) -> usize {
+        let shared = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+        self.full_blocks(shared)
+    }
+
+    /// Child of `id` whose edge starts with the first block of `rest`
+    fn child(&self, id: NodeId, rest: &[TokenIdType]) -> Option<NodeId> {
+        if rest.len() < self.block_size {
+            return None;
+        }
+        self.nodes[&id]
+            .children
+            .get(&rest[..self.block_size])
+            .copied()
+    }
+
+    /// Follow `tokens` from the root, through nodes of `tenant` only if given; returns
+    /// the matched length and the deepest node reached
+    fn walk(&self, tokens: &[TokenIdType], tenant: Option<&str>) -> (usize, NodeId) {
+        let mut curr = ROOT;
+        let mut matched = 0;
+        while let Some(child) = self.child(curr, &tokens[matched..]) {
+            let node = &self.nodes[&child];
+            if tenant.is_some_and(|tenant| !node.tenant_last_access.contains_key(tenant)) {
+                break;
+            }
+            let shared = self.shared_blocks(&node.tokens, &tokens[matched..]);
+            matched += shared;
+            curr = child;
+            if shared < node.tokens.len() {
+                break;
+            }
+        }
+        (matched, curr)
+    }
+
+    /// Mark `tenant` as using `id` and its ancestors now
+    fn touch(&mut self, id: NodeId, tenant: &str) {
+        let now = self.tick();
+        let mut curr = Some(id);
+        while let Some(id) = curr {
+            let node = self.nodes.get_mut(&id).unwrap();
+            node.tenant_last_access.insert(tenant.to_string(), now);
+            curr = node.parent;
+        }
+    }
+
+    fn add_node(&mut self, parent: NodeId, tokens: Vec<TokenIdType>) -> NodeId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let key = tokens[..self.block_size].to_vec();
+        self.nodes.insert(id, Node::new(tokens, Some(parent)));
+        self.nodes
+            .get_mut(&parent)
+            .unwrap()
+            .children
+            .insert(key, id);
+        id
+    }
+
+    /// Split the edge of `id` after `at` tokens and return the new upper node
+    ///
+    /// [parent] -> [id]  becomes  [parent] -> [upper] -> [id]
+    fn split(&mut self, id: NodeId, at: usize) -> NodeId {
+        let upper = self.next_id;
+        self.next_id += 1;
+
+        let node = self.nodes.get_mut(&id).unwrap();
+        let tail = node.tokens.split_off(at);
+        let head = std::mem::replace(&mut node.tokens, tail);
+        let parent = node
+            .parent
+            .replace(upper)
+            .expect("only the root has no parent");
+        let tenant_last_access = node.tenant_last_access.clone();
+        let lower_key = node.tokens[..self.block_size].to_vec();
+        let upper_key = head[..self.block_size].to_vec();
+
+        self.nodes.insert(
+            upper,
+            Node {
+                tokens: head,
+                children: HashMap::from([(lower_key, id)]),
+                parent: Some(parent),
+                tenant_last_access,
+            },
+        );
+        self.nodes
+            .get_mut(&parent)
+            .unwrap()
+            .children
+            .insert(upper_key, upper);
+        upper
+    }
+
+    /// Add `tenant` to `id`, counting the node's tokens if the tenant is new to it
+    fn attach(&mut self, id: NodeId, tenant: &str, now: u64) {
+        let node = self.nodes.get_mut(&id).unwrap();
+        let added = node
+            .tenant_last_access
+            .insert(tenant.to_string(), now)
+            .is_none();
+        let count = self
+            .tenant_token_count
+            .entry(tenant.to_string())
+            .or_insert(0);
+        if added {
+            *count += node.tokens.len();
+        }
+    }
+
+    /// Remove `tenant` from `id`, dropping the node once no tenant uses it; returns the
+    /// parent
+    fn detach(&mut self, id: NodeId, tenant: &str) -> Option<NodeId> {
+        let node = self.nodes.get_mut(&id)?;
+        node.tenant_last_access.remove(tenant)?;
+        let len = node.tokens.len();
+        let parent = node.parent;
+        let unused = node.children.is_empty() && node.tenant_last_access.is_empty();
+
+        if let Some(count) = self.tenant_token_count.get_mut(tenant) {
+            *count = count.saturating_sub(len);
+        }
+        if unused {
+            self.remove_node(id);
+        }
+        parent
+    }
+
+    fn remove_node(&mut self, id: NodeId) {
+        let Some(node) = self.nodes.remove(&id) else {
+            return;
+        };
+        if let Some(parent) = node.parent.and_then(|parent| self.nodes.get_mut(&parent)) {
+            parent.children.remove(&node.tokens[..self.block_size]);
+        }
+    }
+
+    fn exceeds(&self, tenant: &str, max_size: usize) -> bool {
+        self.tenant_token_count
+            .get(tenant)
+            .is_some_and(|count| *count > max_size)
+    }
+
+    /// Whether `id` holds `tenant` and none of its children does
+    fn is_leaf_of(&self, id: NodeId, tenant: &str) -> bool {
+        let node = &self.nodes[&id];
+        node.tenant_last_access.contains_key(tenant)
+            && node
+                .children
+                .values()
+                .all(|child| !self.nodes[child].tenant_last_access.contains_key(tenant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(range: std::ops::Range<u32>) -> Vec<TokenIdType> {
+        range.collect()
+    }
+
+    #[test]
+    fn test_match_whole_blocks() {
+        let mut tree = TokenTree::new(4);
+        tree.insert(&tokens(0..10), "w1");
+
+        // The trailing partial block is not cached
+        assert_eq!(tree.get_tenant_token_count()["w1"], 8);
+        assert_eq!(tree.prefix_match(&tokens(0..10)), (8, "w1".to_string()));
+        assert_eq!(tree.prefix_match(&tokens(0..7)), (4, "w1".to_string()));
+
+        // A mismatch inside the second block only matches the first
+        let mut diverging = tokens(0..8);
+        diverging[6] = 99;
+        assert_eq!(tree.prefix_match(&diverging).0, 4);
+
+        // Shorter than a block never matches
+        assert_eq!(tree.prefix_match(&tokens(0..3)).0, 0);
+    }
+
+    #[test]
+    fn test_split_and_tenants() {
+        let mut tree = TokenTree::new(2);
+        tree.insert(&tokens(0..8), "w1");
+        let mut branch = tokens(0..4);
+        branch.extend([50, 51, 52, 53]);
+        tree.insert(&branch, "w2");
+
+        assert_eq!(tree.prefix_match(&branch), (8, "w2".to_string()));
+        assert_eq!(tree.prefix_match(&tokens(0..8)), (8, "w1".to_string()));
+        assert_eq!(tree.prefix_match_tenant(&tokens(0..8), "w2"), 4);
+        assert_eq!(tree.prefix_match_tenant(&branch, "w1"), 4);
+
+        // The shared prefix was split off but is still counted once per tenant
+        assert_eq!(
+            tree.get_tenant_token_count(),
+            tree.get_used_size_per_tenant()
+        );
+        assert_eq!(tree.get_tenant_token_count()["w1"], 8);
+        assert_eq!(tree.get_tenant_token_count()["w2"], 8);
+    }
+
+    #[test]
+    fn test_most_recent_tenant_wins() {
+        let mut tree = TokenTree::new(2);
+        tree.insert(&tokens(0..4), "w1");
+        tree.insert(&tokens(0..4), "w2");
+        assert_eq!(tree.prefix_match(&tokens(0..4)).1, "w2");
+        tree.insert(&tokens(0..4), "w1");
+        assert_eq!(tree.prefix_match(&tokens(0..4)).1, "w1");
+    }
+
+    #[test]
+    fn test_evict_lru_leaves() {
+        let mut tree = TokenTree::new(2);
+        tree.insert(&tokens(0..4), "w1");
+        tree.insert(&tokens(10..14), "w1");
+        tree.insert(&tokens(20..24), "w2");
+        // Refresh the first prefix so the second is the least recently used
+        tree.prefix_match_tenant(&tokens(0..4), "w1");
+
+        tree.evict_tenant_by_size(4);
+
+        assert_eq!(tree.get_tenant_token_count()["w1"], 4);
+        assert_eq!(tree.get_tenant_token_count()["w2"], 4);
+        assert_eq!(
+            tree.get_tenant_token_count(),
+            tree.get_used_size_per_tenant()
+        );
+        assert_eq!(tree.prefix_match_tenant(&tokens(0..4), "w1"), 4);
+        assert_eq!(tree.prefix_match_tenant(&tokens(10..14), "w1"), 0);
+    }
+
+    #[test]
+    fn test_remove_tenant() {
+        let mut tree = TokenTree::new(2);
+        tree.insert(&tokens(0..6), "w1");
+        tree.insert(&tokens(0..4), "w2");
+
+        tree.remove_tenant("w1");
+
+        assert_eq!(tree.get_smallest_tenant(), "w2");
+        assert_eq!(tree.prefix_match(&tokens(0..6)), (4, "w2".to_string()));
+        assert!(!tree.get_used_size_per_tenant().contains_key("w1"));
+
+        tree.remove_tenant("w2");
+        assert_eq!(tree.get_smallest_tenant(), "empty");
+        assert_eq!(tree.prefix_match(&tokens(0..6)), (0, "empty".to_string()));
+        assert_eq!(tree.nodes.len(), 1);
+    }
+}
@@ -210,6 +210,14 @@ impl TokenizerTrait for HuggingFaceTokenizer {
     fn id_to_token(&self, id: TokenIdType) -> Option<String> {
         self.reverse_vocab.get(&id).cloned()
     }
+
+    fn apply_chat_template(
+        &self,
+        messages: &[ChatMessage],
+        add_generation_prompt: bool,
+    ) -> Result<String> {
+        HuggingFaceTokenizer::apply_chat_template(self, messages, add_generation_prompt)
+    }
 }
 
 #[cfg(test)]
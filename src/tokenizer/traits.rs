@@ -1,3 +1,4 @@
+use super::chat_template::ChatMessage;
 use anyhow::Result;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
@@ -22,6 +23,25 @@ pub trait Tokenizer: Encoder + Decoder {
     fn get_special_tokens(&self) -> &SpecialTokens;
     fn token_to_id(&self, token: &str) -> Option<TokenIdType>;
     fn id_to_token(&self, id: TokenIdType) -> Option<String>;
+
+    /// Render chat messages into a prompt string.
+    ///
+    /// Tokenizers without a chat template fall back to a simple
+    /// "role: content" layout.
+    fn apply_chat_template(
+        &self,
+        messages: &[ChatMessage],
+        add_generation_prompt: bool,
+    ) -> Result<String> {
+        let mut result = String::new();
+        for msg in messages {
+            result.push_str(&format!("{}: {}\n", msg.role, msg.content));
+        }
+        if add_generation_prompt {
+            result.push_str("assistant: ");
+        }
+        Ok(result)
+    }
 }
 
 /// Contains the results of tokenizing text: token IDs, string tokens, and their spans
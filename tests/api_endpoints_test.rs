@@ -9,9 +9,11 @@ use common::mock_worker::{HealthStatus, MockWorker, MockWorkerConfig, WorkerType
 use reqwest::Client;
 use serde_json::json;
 use vllm_router_rs::config::{
-    CircuitBreakerConfig, ConnectionMode, PolicyConfig, RetryConfig, RouterConfig, RoutingMode,
+    CircuitBreakerConfig, ConnectionMode, LoadUnit, PolicyConfig, RetryConfig, RouterConfig,
+    RoutingMode,
 };
 use vllm_router_rs::routers::{RouterFactory, RouterTrait};
+use vllm_router_rs::server::AppContext;
 use std::sync::Arc;
 use tower::ServiceExt;
 
@@ -21,6 +23,7 @@ struct TestContext {
     router: Arc<dyn RouterTrait>,
     client: Client,
     config: RouterConfig,
+    app_context: Arc<AppContext>,
 }
 
 impl TestContext {
@@ -40,6 +43,7 @@ impl TestContext {
             discovery: None,
             dp_aware: false,
             api_key: None,
+            auth: vllm_router_rs::config::AuthConfig::default(),
             metrics: None,
             log_dir: None,
             log_level: None,
@@ -47,7 +51,10 @@ impl TestContext {
             max_concurrent_requests: 64,
             queue_size: 0,
             queue_timeout_secs: 60,
+            admission: vllm_router_rs::config::AdmissionConfig::default(),
+            drain: vllm_router_rs::config::DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: vllm_router_rs::config::TenantRateLimitConfig::default(),
             cors_allowed_origins: vec![],
             retry: RetryConfig::default(),
             circuit_breaker: CircuitBreakerConfig::default(),
@@ -59,6 +66,14 @@ impl TestContext {
             model_path: None,
             tokenizer_path: None,
             history_backend: vllm_router_rs::config::HistoryBackend::Memory,
+            history_storage: vllm_router_rs::config::HistoryStorageConfig::default(),
+            mcp: vllm_router_rs::config::McpToolsConfig::default(),
+            mcp_server: vllm_router_rs::config::McpEndpointConfig::default(),
+            backend: vllm_router_rs::config::WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         Self::new_with_config(config, worker_configs).await
@@ -98,8 +113,15 @@ impl TestContext {
             .build()
             .unwrap();
 
-        // Create app context
-        let app_context = common::create_test_context(config.clone());
+        // Create app context, connecting to the MCP servers if configured
+        let mut app_context = common::create_test_context(config.clone());
+        if config.mcp.config_path.is_some() {
+            Arc::get_mut(&mut app_context)
+                .unwrap()
+                .connect_mcp_servers()
+                .await
+                .unwrap();
+        }
 
         // Create router
         let router = RouterFactory::create_router(&app_context).await.unwrap();
@@ -115,6 +137,7 @@ impl TestContext {
             router,
             client,
             config,
+            app_context,
         }
     }
 
@@ -126,6 +149,15 @@ impl TestContext {
         )
     }
 
+    /// Create an app that shares the router's context, for endpoints acting on its registries
+    async fn create_app_with_shared_context(&self) -> axum::Router {
+        common::test_app::create_test_app_with_context(
+            Arc::clone(&self.router),
+            Arc::clone(&self.app_context),
+            &self.config,
+        )
+    }
+
     async fn shutdown(mut self) {
         for worker in &mut self.workers {
             worker.stop().await;
@@ -198,6 +230,74 @@ mod health_tests {
         ctx.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn test_shutdown_drain_rejects_requests_and_reports_readiness() {
+        use vllm_router_rs::middleware::ShutdownState;
+        use vllm_router_rs::server::AppState;
+
+        let ctx = TestContext::new(vec![MockWorkerConfig {
+            port: 18002,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms: 0,
+            fail_rate: 0.0,
+        }])
+        .await;
+
+        let shutdown = Arc::new(ShutdownState::new());
+        let app = common::test_app::create_test_app_with_state(
+            Arc::new(AppState {
+                router: Arc::clone(&ctx.router),
+                context: Arc::clone(&ctx.app_context),
+                admission_queue: None,
+                router_manager: None,
+                shutdown: Arc::clone(&shutdown),
+            }),
+            &ctx.config,
+        );
+        let readiness = || {
+            Request::builder()
+                .method("GET")
+                .uri("/readiness")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let generate = || {
+            Request::builder()
+                .method("POST")
+                .uri("/generate")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&json!({"text": "Hello", "stream": false})).unwrap(),
+                ))
+                .unwrap()
+        };
+
+        let resp = app.clone().oneshot(generate()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(shutdown.in_flight(), 0);
+
+        shutdown.begin_drain();
+
+        let resp = app.clone().oneshot(readiness()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "draining");
+        assert_eq!(body["in_flight_requests"], 0);
+
+        let resp = app.clone().oneshot(generate()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let idle = shutdown
+            .wait_idle(std::time::Duration::from_millis(10))
+            .await;
+        assert!(idle);
+
+        ctx.shutdown().await;
+    }
+
     #[tokio::test]
     async fn test_health_endpoint_details() {
         let ctx = TestContext::new(vec![
@@ -511,6 +611,65 @@ mod model_info_tests {
         ctx.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn test_trtllm_backend_profile() {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            backend: vllm_router_rs::config::WorkerBackend::Trtllm,
+            ..Default::default()
+        };
+        let ctx = TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port: 18966,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await;
+
+        // trtllm-serve has no /get_model_info; the router answers from /v1/models
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("GET")
+            .uri("/get_model_info")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body_json["object"], "list");
+
+        // Requests carrying vLLM-only fields still reach the worker
+        let app = ctx.create_app().await;
+        let payload = json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "Hello!"}],
+            "regex": "[a-z]+",
+            "separate_reasoning": true
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        ctx.shutdown().await;
+    }
+
     #[tokio::test]
     async fn test_v1_models() {
         let ctx = TestContext::new(vec![MockWorkerConfig {
@@ -702,12 +861,122 @@ mod model_info_tests {
 
         ctx.shutdown().await;
     }
+
+    #[tokio::test]
+    async fn test_v1_models_aggregates_workers() {
+        let ctx = TestContext::new(vec![
+            MockWorkerConfig {
+                port: 18968,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            },
+            MockWorkerConfig {
+                port: 18969,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            },
+        ])
+        .await;
+
+        // Both workers report the model; it is listed once
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("GET")
+            .uri("/v1/models")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let models: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let data = models["data"].as_array().unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0]["id"], "mock-model");
+        assert_eq!(data[0]["owned_by"], "organization-owner");
+        assert_eq!(data[0]["workers"], 2);
+        assert_eq!(data[0]["available_workers"], 2);
+
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("GET")
+            .uri("/v1/models/mock-model")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let model: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(model["id"], "mock-model");
+        assert_eq!(model["workers"], 2);
+
+        // Model ids may contain slashes
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("GET")
+            .uri("/v1/models/meta-llama/unknown-model")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"]["code"], "model_not_found");
+
+        ctx.shutdown().await;
+    }
 }
 
 #[cfg(test)]
 mod worker_management_tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_workers_registered_under_served_models() {
+        let ctx = TestContext::new(vec![MockWorkerConfig {
+            port: 18970,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms: 0,
+            fail_rate: 0.0,
+        }])
+        .await;
+
+        // The worker was asked for its models when it was registered
+        let registry = &ctx.app_context.worker_registry;
+        assert_eq!(registry.get_by_model_fast("mock-model").len(), 1);
+        assert!(registry.get_by_model_fast("unknown").is_empty());
+
+        // Requests naming a model only reach workers serving it
+        let request: vllm_router_rs::protocols::spec::ChatCompletionRequest =
+            serde_json::from_value(json!({
+                "model": "mock-model",
+                "messages": [{"role": "user", "content": "Hello!"}]
+            }))
+            .unwrap();
+        let resp = ctx
+            .router
+            .route_chat(None, &request, Some("mock-model"))
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = ctx
+            .router
+            .route_chat(None, &request, Some("other-model"))
+            .await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        ctx.shutdown().await;
+    }
+
     #[tokio::test]
     async fn test_add_new_worker() {
         let ctx = TestContext::new(vec![]).await;
@@ -918,6 +1187,91 @@ mod worker_management_tests {
         worker.stop().await;
         ctx.shutdown().await;
     }
+    #[tokio::test]
+    async fn test_update_and_drain_worker() {
+        let ctx = TestContext::new(vec![MockWorkerConfig {
+            port: 19301,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms: 0,
+            fail_rate: 0.0,
+        }])
+        .await;
+
+        let app = ctx.create_app_with_shared_context().await;
+        let worker_url = "http://127.0.0.1:19301";
+        let path = format!(
+            "/workers/{}",
+            worker_url.replace(':', "%3A").replace('/', "%2F")
+        );
+
+        let patch = |uri: &str, body: serde_json::Value| {
+            Request::builder()
+                .method("PATCH")
+                .uri(uri)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_string(&body).unwrap()))
+                .unwrap()
+        };
+        let generate = || {
+            Request::builder()
+                .method("POST")
+                .uri("/generate")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&json!({"text": "Hello", "stream": false})).unwrap(),
+                ))
+                .unwrap()
+        };
+
+        // Update metadata in place
+        let resp = app
+            .clone()
+            .oneshot(patch(&path, json!({"priority": 80, "cost": 0.5})))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let worker = ctx
+            .app_context
+            .worker_registry
+            .get_by_url(worker_url)
+            .unwrap();
+        assert_eq!(worker.priority(), 80);
+        assert_eq!(worker.cost(), 0.5);
+
+        // A draining worker is no longer selected
+        let resp = app
+            .clone()
+            .oneshot(patch(&path, json!({"drain": true})))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = app.clone().oneshot(generate()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Undraining restores it
+        let resp = app
+            .clone()
+            .oneshot(patch(&path, json!({"drain": false})))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = app.clone().oneshot(generate()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Unknown workers are reported as missing
+        let resp = app
+            .oneshot(patch(
+                "/workers/http%3A%2F%2F127.0.0.1%3A1",
+                json!({"drain": true}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        ctx.shutdown().await;
+    }
 }
 
 #[cfg(test)]
@@ -995,7 +1349,9 @@ mod router_policy_tests {
 #[cfg(test)]
 mod responses_endpoint_tests {
     use super::*;
+    use common::mock_mcp_server::MockMCPServer;
     use reqwest::Client as HttpClient;
+    use vllm_router_rs::config::McpToolsConfig;
 
     #[tokio::test]
     async fn test_v1_responses_non_streaming() {
@@ -1132,7 +1488,7 @@ mod responses_endpoint_tests {
             port: 18953,
             worker_type: WorkerType::Regular,
             health_status: HealthStatus::Healthy,
-            response_delay_ms: 0,
+            response_delay_ms: 2000,
             fail_rate: 0.0,
         }])
         .await;
@@ -1176,7 +1532,7 @@ mod responses_endpoint_tests {
     }
 
     #[tokio::test]
-    async fn test_v1_responses_delete_and_list_not_implemented() {
+    async fn test_v1_responses_served_from_router_storage() {
         let ctx = TestContext::new(vec![MockWorkerConfig {
             port: 18954,
             worker_type: WorkerType::Regular,
@@ -1188,61 +1544,10 @@ mod responses_endpoint_tests {
 
         let app = ctx.create_app().await;
 
-        // Use an arbitrary id for delete/list
-        let resp_id = "resp-test-123";
-
-        let req = Request::builder()
-            .method("DELETE")
-            .uri(format!("/v1/responses/{}", resp_id))
-            .body(Body::empty())
-            .unwrap();
-        let resp = app.clone().oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
-
-        let req = Request::builder()
-            .method("GET")
-            .uri(format!("/v1/responses/{}/input", resp_id))
-            .body(Body::empty())
-            .unwrap();
-        let resp = app.clone().oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
-
-        ctx.shutdown().await;
-    }
-
-    #[tokio::test]
-    async fn test_v1_responses_get_multi_worker_fanout() {
-        // Start two mock workers
-        let ctx = TestContext::new(vec![
-            MockWorkerConfig {
-                port: 18960,
-                worker_type: WorkerType::Regular,
-                health_status: HealthStatus::Healthy,
-                response_delay_ms: 0,
-                fail_rate: 0.0,
-            },
-            MockWorkerConfig {
-                port: 18961,
-                worker_type: WorkerType::Regular,
-                health_status: HealthStatus::Healthy,
-                response_delay_ms: 0,
-                fail_rate: 0.0,
-            },
-        ])
-        .await;
-
-        let app = ctx.create_app().await;
-
-        // Create a background response with a known id
-        let rid = format!("resp_{}", 18960); // arbitrary unique id
         let payload = json!({
             "input": "Hello Responses API",
-            "model": "mock-model",
-            "background": true,
-            "store": true,
-            "request_id": rid,
+            "model": "mock-model"
         });
-
         let req = Request::builder()
             .method("POST")
             .uri("/v1/responses")
@@ -1251,29 +1556,289 @@ mod responses_endpoint_tests {
             .unwrap();
         let resp = app.clone().oneshot(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let resp_id = created["id"].as_str().unwrap().to_string();
 
-        // Using the router, GET should succeed by fanning out across workers
+        // The worker keeps no state; the router serves the response from its storage
         let req = Request::builder()
             .method("GET")
-            .uri(format!("/v1/responses/{}", rid))
+            .uri(format!("/v1/responses/{}", resp_id))
             .body(Body::empty())
             .unwrap();
         let resp = app.clone().oneshot(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let get_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(get_json["id"], resp_id.as_str());
 
-        // Validate only one worker holds the metadata: direct calls
+        let req = Request::builder()
+            .method("GET")
+            .uri(format!("/v1/responses/{}/input", resp_id))
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let items: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(items["object"], "list");
+        assert_eq!(items["data"][0]["role"], "user");
+
+        // Follow-up requests continue the stored conversation
+        let payload = json!({
+            "input": "Tell me more",
+            "model": "mock-model",
+            "previous_response_id": resp_id
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/responses")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/v1/responses/{}", resp_id))
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = Request::builder()
+            .method("GET")
+            .uri(format!("/v1/responses/{}/input", resp_id))
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // Unknown previous responses are rejected before reaching a worker
+        let payload = json!({
+            "input": "Tell me more",
+            "previous_response_id": resp_id
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/responses")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_v1_responses_background_runs_on_router() {
+        // Start two mock workers
+        let ctx = TestContext::new(vec![
+            MockWorkerConfig {
+                port: 18960,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            },
+            MockWorkerConfig {
+                port: 18961,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            },
+        ])
+        .await;
+
+        let app = ctx.create_app().await;
+
+        // Create a background response with a known id
+        let rid = format!("resp_{}", 18960); // arbitrary unique id
+        let payload = json!({
+            "input": "Hello Responses API",
+            "model": "mock-model",
+            "background": true,
+            "store": true,
+            "request_id": rid,
+        });
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/responses")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let queued: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(queued["id"], rid.as_str());
+        assert_eq!(queued["status"], "queued");
+
+        // Poll the router until the background task has finished
+        let mut status = String::new();
+        for _ in 0..50 {
+            let req = Request::builder()
+                .method("GET")
+                .uri(format!("/v1/responses/{}", rid))
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let polled: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            status = polled["status"].as_str().unwrap_or_default().to_string();
+            if status == "completed" {
+                assert_eq!(polled["id"], rid.as_str());
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(status, "completed");
+
+        // Workers stay stateless: none of them holds the response
         let client = HttpClient::new();
-        let mut ok_count = 0usize;
         for url in ctx.router.get_worker_urls() {
             let get_url = format!("{}/v1/responses/{}", url, rid);
             let res = client.get(get_url).send().await.unwrap();
-            if res.status() == StatusCode::OK {
-                ok_count += 1;
-            }
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        }
+
+        ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_v1_responses_mcp_tool_loop() {
+        let mut mcp_server = MockMCPServer::start().await.unwrap();
+        let mcp_config = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            mcp_config.path(),
+            format!(
+                "servers:\n  - name: search\n    protocol: streamable\n    url: {}\n",
+                mcp_server.url()
+            ),
+        )
+        .unwrap();
+
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            mcp: McpToolsConfig {
+                config_path: Some(mcp_config.path().to_string_lossy().into_owned()),
+                max_iterations: 3,
+            },
+            ..Default::default()
+        };
+        let ctx = TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port: 18962,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await;
+        let app = ctx.create_app().await;
+
+        let payload = json!({
+            "input": "Search the web",
+            "model": "mock-model",
+            "tools": [{
+                "type": "mcp",
+                "server_label": "search",
+                "allowed_tools": ["brave_web_search"]
+            }]
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/responses")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["status"], "completed");
+        let output = response["output"].as_array().unwrap();
+        assert_eq!(output.len(), 2);
+        assert_eq!(output[0]["type"], "mcp_call");
+        assert_eq!(output[0]["server_label"], "search");
+        assert_eq!(output[0]["name"], "brave_web_search");
+        assert_eq!(output[0]["status"], "completed");
+        assert_eq!(output[0]["output"], "Mock search results for: vllm router");
+        assert_eq!(output[1]["type"], "message");
+        assert_eq!(
+            output[1]["content"][0]["text"],
+            "Based on the tool: Mock search results for: vllm router"
+        );
+
+        // Streaming reports each step as events
+        let mut payload = payload;
+        payload["stream"] = json!(true);
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/responses")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let events: Vec<String> = String::from_utf8_lossy(&body)
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .map(str::to_string)
+            .collect();
+        for expected in [
+            "response.created",
+            "response.mcp_call.in_progress",
+            "response.mcp_call.completed",
+            "response.output_text.delta",
+            "response.completed",
+        ] {
+            assert!(events.iter().any(|e| e == expected), "missing {}", expected);
         }
-        assert_eq!(ok_count, 1, "exactly one worker should store the response");
+
+        // Unknown MCP servers are rejected
+        let payload = json!({
+            "input": "Search the web",
+            "tools": [{"type": "mcp", "server_label": "missing"}]
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/responses")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 
         ctx.shutdown().await;
+        mcp_server.stop().await;
     }
 }
 
@@ -1374,6 +1939,7 @@ mod error_tests {
             worker_startup_check_interval_secs: 1,
             dp_aware: false,
             api_key: None,
+            auth: vllm_router_rs::config::AuthConfig::default(),
             discovery: None,
             metrics: None,
             log_dir: None,
@@ -1382,7 +1948,10 @@ mod error_tests {
             max_concurrent_requests: 64,
             queue_size: 0,
             queue_timeout_secs: 60,
+            admission: vllm_router_rs::config::AdmissionConfig::default(),
+            drain: vllm_router_rs::config::DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: vllm_router_rs::config::TenantRateLimitConfig::default(),
             cors_allowed_origins: vec![],
             retry: RetryConfig::default(),
             circuit_breaker: CircuitBreakerConfig::default(),
@@ -1394,6 +1963,14 @@ mod error_tests {
             model_path: None,
             tokenizer_path: None,
             history_backend: vllm_router_rs::config::HistoryBackend::Memory,
+            history_storage: vllm_router_rs::config::HistoryStorageConfig::default(),
+            mcp: vllm_router_rs::config::McpToolsConfig::default(),
+            mcp_server: vllm_router_rs::config::McpEndpointConfig::default(),
+            backend: vllm_router_rs::config::WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         let ctx = TestContext::new_with_config(
@@ -1736,12 +2313,16 @@ mod pd_mode_tests {
             log_dir: None,
             dp_aware: false,
             api_key: None,
+            auth: vllm_router_rs::config::AuthConfig::default(),
             log_level: None,
             request_id_headers: None,
             max_concurrent_requests: 64,
             queue_size: 0,
             queue_timeout_secs: 60,
+            admission: vllm_router_rs::config::AdmissionConfig::default(),
+            drain: vllm_router_rs::config::DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: vllm_router_rs::config::TenantRateLimitConfig::default(),
             cors_allowed_origins: vec![],
             retry: RetryConfig::default(),
             circuit_breaker: CircuitBreakerConfig::default(),
@@ -1753,6 +2334,14 @@ mod pd_mode_tests {
             model_path: None,
             tokenizer_path: None,
             history_backend: vllm_router_rs::config::HistoryBackend::Memory,
+            history_storage: vllm_router_rs::config::HistoryStorageConfig::default(),
+            mcp: vllm_router_rs::config::McpToolsConfig::default(),
+            mcp_server: vllm_router_rs::config::McpEndpointConfig::default(),
+            backend: vllm_router_rs::config::WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         // Create app context
@@ -1898,13 +2487,17 @@ mod request_id_tests {
             metrics: None,
             dp_aware: false,
             api_key: None,
+            auth: vllm_router_rs::config::AuthConfig::default(),
             log_dir: None,
             log_level: None,
             request_id_headers: Some(vec!["custom-id".to_string(), "trace-id".to_string()]),
             max_concurrent_requests: 64,
             queue_size: 0,
             queue_timeout_secs: 60,
+            admission: vllm_router_rs::config::AdmissionConfig::default(),
+            drain: vllm_router_rs::config::DrainConfig::default(),
             rate_limit_tokens_per_second: None,
+            tenant_rate_limits: vllm_router_rs::config::TenantRateLimitConfig::default(),
             cors_allowed_origins: vec![],
             retry: RetryConfig::default(),
             circuit_breaker: CircuitBreakerConfig::default(),
@@ -1916,6 +2509,14 @@ mod request_id_tests {
             model_path: None,
             tokenizer_path: None,
             history_backend: vllm_router_rs::config::HistoryBackend::Memory,
+            history_storage: vllm_router_rs::config::HistoryStorageConfig::default(),
+            mcp: vllm_router_rs::config::McpToolsConfig::default(),
+            mcp_server: vllm_router_rs::config::McpEndpointConfig::default(),
+            backend: vllm_router_rs::config::WorkerBackend::default(),
+            openai_upstreams: vec![],
+            model_aliases: vec![],
+            model_refresh_interval_secs: 30,
+            load_unit: LoadUnit::Requests,
         };
 
         let ctx = TestContext::new_with_config(
@@ -2285,3 +2886,945 @@ mod rerank_tests {
         ctx.shutdown().await;
     }
 }
+
+#[cfg(test)]
+mod auth_tests {
+    use super::*;
+    use axum::http::header::AUTHORIZATION;
+    use vllm_router_rs::config::AuthConfig;
+
+    async fn create_auth_context(port: u16) -> TestContext {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            auth: AuthConfig {
+                inference_keys: vec!["sk-user".to_string()],
+                admin_keys: vec!["sk-admin".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await
+    }
+
+    fn generate_request(api_key: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/generate")
+            .header(CONTENT_TYPE, "application/json");
+        if let Some(key) = api_key {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {}", key));
+        }
+        builder
+            .body(Body::from(
+                serde_json::to_string(&json!({"text": "Hello", "stream": false})).unwrap(),
+            ))
+            .unwrap()
+    }
+
+    fn get_request(uri: &str, api_key: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().method("GET").uri(uri);
+        if let Some(key) = api_key {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {}", key));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    async fn error_body(resp: axum::response::Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_inference_route_requires_api_key() {
+        let ctx = create_auth_context(19101).await;
+        let app = ctx.create_app().await;
+
+        let resp = app.clone().oneshot(generate_request(None)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let body = error_body(resp).await;
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+
+        let resp = app
+            .clone()
+            .oneshot(generate_request(Some("sk-wrong")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let body = error_body(resp).await;
+        assert_eq!(body["error"]["code"], "invalid_api_key");
+
+        let resp = app
+            .clone()
+            .oneshot(generate_request(Some("sk-user")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Admin keys are valid everywhere
+        let resp = app
+            .clone()
+            .oneshot(generate_request(Some("sk-admin")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Model listings and info need a key, health probes stay public
+        for uri in ["/v1/models", "/get_model_info", "/get_server_info"] {
+            let resp = app.clone().oneshot(get_request(uri, None)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+        let resp = app
+            .clone()
+            .oneshot(get_request("/v1/models", Some("sk-user")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = app.oneshot(get_request("/health", None)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_require_admin_scope() {
+        let ctx = create_auth_context(19102).await;
+        let app = ctx.create_app().await;
+
+        for uri in ["/workers", "/list_workers"] {
+            let resp = app.clone().oneshot(get_request(uri, None)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+            let resp = app
+                .clone()
+                .oneshot(get_request(uri, Some("sk-user")))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+            let body = error_body(resp).await;
+            assert_eq!(body["error"]["type"], "permission_error");
+
+            let resp = app
+                .clone()
+                .oneshot(get_request(uri, Some("sk-admin")))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        ctx.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tenant_rate_limit_tests {
+    use super::*;
+    use axum::http::header::AUTHORIZATION;
+    use vllm_router_rs::config::{TenantRateLimit, TenantRateLimitConfig, TenantSource};
+
+    async fn create_tenant_context(
+        port: u16,
+        tenant_rate_limits: TenantRateLimitConfig,
+    ) -> TestContext {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            tenant_rate_limits,
+            ..Default::default()
+        };
+
+        TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await
+    }
+
+    fn generate_request(api_key: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/generate")
+            .header(CONTENT_TYPE, "application/json")
+            .header(AUTHORIZATION, format!("Bearer {}", api_key))
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap()
+    }
+
+    fn header<'a>(resp: &'a axum::response::Response, name: &str) -> Option<&'a str> {
+        resp.headers().get(name).map(|v| v.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_requests_per_minute_limit() {
+        let ctx = create_tenant_context(
+            19201,
+            TenantRateLimitConfig {
+                default_requests_per_minute: Some(2),
+                ..Default::default()
+            },
+        )
+        .await;
+        let app = ctx.create_app().await;
+        let body = json!({"text": "Hello", "stream": false});
+
+        let resp = app
+            .clone()
+            .oneshot(generate_request("sk-a", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(header(&resp, "x-ratelimit-limit-requests"), Some("2"));
+        assert_eq!(header(&resp, "x-ratelimit-remaining-requests"), Some("1"));
+        assert!(header(&resp, "x-ratelimit-reset-requests").is_some());
+        assert!(header(&resp, "x-ratelimit-limit-tokens").is_none());
+
+        let resp = app
+            .clone()
+            .oneshot(generate_request("sk-a", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = app
+            .clone()
+            .oneshot(generate_request("sk-a", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(header(&resp, "x-ratelimit-remaining-requests"), Some("0"));
+        assert!(header(&resp, "retry-after").is_some());
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(error["error"]["code"], "rate_limit_exceeded");
+        assert_eq!(error["error"]["type"], "requests");
+
+        // Other keys have their own budget
+        let resp = app.oneshot(generate_request("sk-b", body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_tokens_per_minute_limit_by_user() {
+        let ctx = create_tenant_context(
+            19202,
+            TenantRateLimitConfig {
+                tenant_source: TenantSource::User,
+                tenants: vec![TenantRateLimit {
+                    name: "acme".to_string(),
+                    ids: vec!["alice".to_string(), "bob".to_string()],
+                    requests_per_minute: None,
+                    tokens_per_minute: Some(100),
+                }],
+                ..Default::default()
+            },
+        )
+        .await;
+        let app = ctx.create_app().await;
+        let request = |user: &str| {
+            generate_request(
+                "unused",
+                json!({
+                    "text": "Hello",
+                    "user": user,
+                    "stream": false,
+                    "sampling_params": {"max_new_tokens": 60}
+                }),
+            )
+        };
+
+        // "Hello" is estimated at 2 tokens on top of max_new_tokens
+        let resp = app.clone().oneshot(request("alice")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(header(&resp, "x-ratelimit-limit-tokens"), Some("100"));
+        assert_eq!(header(&resp, "x-ratelimit-remaining-tokens"), Some("38"));
+
+        // Tenants share their budget across ids
+        let resp = app.clone().oneshot(request("bob")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Requests above the whole per-minute budget are rejected outright
+        let resp = app
+            .clone()
+            .oneshot(generate_request(
+                "unused",
+                json!({
+                    "text": "Hello",
+                    "user": "alice",
+                    "stream": false,
+                    "sampling_params": {"max_new_tokens": 200}
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(resp.headers().get("retry-after").is_none());
+
+        // Users without explicit limits are not limited
+        let resp = app.oneshot(request("carol")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        ctx.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod mcp_server_tests {
+    use super::*;
+    use vllm_router_rs::config::{AuthConfig, McpEndpointConfig, TenantRateLimitConfig};
+    use vllm_router_rs::mcp::{McpClientManager, McpConfig, McpServerConfig, McpTransport};
+
+    /// Serve `app` on an ephemeral port and return the URL of its MCP endpoint
+    async fn serve(app: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    async fn connect(url: &str, api_key: &str) -> McpClientManager {
+        McpClientManager::new(McpConfig {
+            servers: vec![McpServerConfig {
+                name: "router".to_string(),
+                transport: McpTransport::Streamable {
+                    url: url.to_string(),
+                    token: Some(api_key.to_string()),
+                },
+            }],
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_router_mcp_server_tools() {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            auth: AuthConfig {
+                inference_keys: vec!["sk-user".to_string()],
+                admin_keys: vec!["sk-admin".to_string()],
+                ..Default::default()
+            },
+            mcp_server: McpEndpointConfig {
+                enabled: true,
+                admin_key: None,
+            },
+            ..Default::default()
+        };
+        let ctx = TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port: 18963,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await;
+        let url = serve(ctx.create_app_with_shared_context().await).await;
+
+        // Inference keys see the inference tools only
+        let mut user = connect(&url, "sk-user").await;
+        let mut tools: Vec<String> = user.list_tools().into_iter().map(|t| t.name).collect();
+        tools.sort();
+        assert_eq!(tools, vec!["chat", "list_models"]);
+
+        let arguments = json!({
+            "model": "mock-model",
+            "messages": [{"role": "user", "content": "Hello"}]
+        });
+        let result = user
+            .call_tool("chat", arguments.as_object().cloned())
+            .await
+            .unwrap();
+        assert_ne!(result.is_error, Some(true));
+        assert_eq!(
+            result.structured_content.unwrap()["choices"][0]["message"]["content"],
+            "This is a mock chat response."
+        );
+        assert!(user.call_tool("list_workers", None).await.is_err());
+        user.shutdown().await;
+
+        // Admin keys unlock the worker tools
+        let mut admin = connect(&url, "sk-admin").await;
+        assert!(admin.has_tool("list_workers"));
+        assert!(admin.has_tool("get_worker_loads"));
+        let result = admin.call_tool("list_workers", None).await.unwrap();
+        let workers = result.structured_content.unwrap();
+        assert_eq!(workers["workers"].as_array().unwrap().len(), 1);
+        admin.shutdown().await;
+
+        ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_router_mcp_chat_charges_tenant_tokens() {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            tenant_rate_limits: TenantRateLimitConfig {
+                default_tokens_per_minute: Some(20),
+                ..Default::default()
+            },
+            mcp_server: McpEndpointConfig {
+                enabled: true,
+                admin_key: None,
+            },
+            ..Default::default()
+        };
+        let ctx = TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port: 18967,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await;
+        let url = serve(ctx.create_app_with_shared_context().await).await;
+        let mut client = connect(&url, "sk-a").await;
+
+        let chat = |max_tokens: u64| {
+            json!({
+                "model": "mock-model",
+                "messages": [{"role": "user", "content": "Hi"}],
+                "max_tokens": max_tokens
+            })
+            .as_object()
+            .cloned()
+        };
+
+        // The chat arguments are charged, not just the JSON-RPC envelope
+        let result = client.call_tool("chat", chat(50)).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(
+            result.structured_content.unwrap()["error"]["code"],
+            "request_too_large"
+        );
+
+        let result = client.call_tool("chat", chat(5)).await.unwrap();
+        assert_ne!(result.is_error, Some(true));
+
+        client.shutdown().await;
+        ctx.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod anthropic_messages_tests {
+    use super::*;
+
+    fn messages_request(payload: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/messages")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_v1_messages_round_trip() {
+        let ctx = TestContext::new(vec![MockWorkerConfig {
+            port: 18964,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms: 0,
+            fail_rate: 0.0,
+        }])
+        .await;
+
+        // Plain text reply
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(messages_request(json!({
+                "model": "test-model",
+                "max_tokens": 64,
+                "system": "You are terse.",
+                "messages": [{"role": "user", "content": [{"type": "text", "text": "Hello!"}]}]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(message["type"], "message");
+        assert_eq!(message["role"], "assistant");
+        assert!(message["id"].as_str().unwrap().starts_with("msg_"));
+        assert_eq!(message["content"][0]["type"], "text");
+        assert_eq!(
+            message["content"][0]["text"],
+            "This is a mock chat response."
+        );
+        assert_eq!(message["stop_reason"], "end_turn");
+        assert_eq!(message["usage"]["input_tokens"], 10);
+        assert_eq!(message["usage"]["output_tokens"], 5);
+
+        // Tools offered to the model come back as tool_use blocks
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(messages_request(json!({
+                "model": "test-model",
+                "max_tokens": 64,
+                "tools": [{
+                    "name": "search",
+                    "description": "Search the web",
+                    "input_schema": {"type": "object", "properties": {"query": {"type": "string"}}}
+                }],
+                "messages": [{"role": "user", "content": "Look up the router"}]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(message["stop_reason"], "tool_use");
+        let tool_use = &message["content"][0];
+        assert_eq!(tool_use["type"], "tool_use");
+        assert_eq!(tool_use["name"], "search");
+        assert_eq!(tool_use["input"]["query"], "vllm router");
+
+        // Feeding the tool result back produces the final answer
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(messages_request(json!({
+                "model": "test-model",
+                "max_tokens": 64,
+                "tools": [{"name": "search", "input_schema": {"type": "object"}}],
+                "messages": [
+                    {"role": "user", "content": "Look up the router"},
+                    {"role": "assistant", "content": [tool_use.clone()]},
+                    {"role": "user", "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": tool_use["id"],
+                        "content": "a fast router"
+                    }]}
+                ]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(message["stop_reason"], "end_turn");
+        assert_eq!(
+            message["content"][0]["text"],
+            "Based on the tool: a fast router"
+        );
+
+        ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_v1_messages_streaming() {
+        let ctx = TestContext::new(vec![MockWorkerConfig {
+            port: 18965,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms: 0,
+            fail_rate: 0.0,
+        }])
+        .await;
+
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(messages_request(json!({
+                "model": "test-model",
+                "max_tokens": 64,
+                "stream": true,
+                "messages": [{"role": "user", "content": "Hello!"}]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[CONTENT_TYPE], "text/event-stream");
+
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert!(body.contains(r#""text":"This is a mock chat response.""#));
+        assert!(body.contains(r#""stop_reason":"end_turn""#));
+
+        ctx.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod model_alias_tests {
+    use super::*;
+    use std::collections::HashMap;
+    use vllm_router_rs::config::{ModelAlias, ParameterPolicy};
+
+    fn chat_request(payload: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&payload).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_model_alias_round_trip() {
+        let aliases = vec![
+            ModelAlias {
+                name: "gpt-4o-mini".to_string(),
+                model_id: Some("mock-model".to_string()),
+                worker_selector: HashMap::new(),
+                parameters: ParameterPolicy {
+                    max: HashMap::from([("max_tokens".to_string(), serde_json::Number::from(64))]),
+                    ..Default::default()
+                },
+            },
+            // No worker carries this label
+            ModelAlias {
+                name: "gpt-4o".to_string(),
+                model_id: None,
+                worker_selector: HashMap::from([("tier".to_string(), "large".to_string())]),
+                parameters: ParameterPolicy::default(),
+            },
+        ];
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            model_aliases: aliases,
+            ..Default::default()
+        };
+        let ctx = TestContext::new_with_config(
+            config,
+            vec![MockWorkerConfig {
+                port: 18967,
+                worker_type: WorkerType::Regular,
+                health_status: HealthStatus::Healthy,
+                response_delay_ms: 0,
+                fail_rate: 0.0,
+            }],
+        )
+        .await;
+
+        // Responses report the alias instead of the backend model
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(chat_request(json!({
+                "model": "gpt-4o-mini",
+                "messages": [{"role": "user", "content": "Hello!"}],
+                "max_tokens": 4096
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let completion: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(completion["model"], "gpt-4o-mini");
+
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(chat_request(json!({
+                "model": "gpt-4o-mini",
+                "messages": [{"role": "user", "content": "Hello!"}],
+                "stream": true
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("\"model\":\"gpt-4o-mini\""));
+        assert!(!text.contains("mock-model"));
+        assert!(text.contains("data: [DONE]"));
+
+        // The selector leaves no worker for this alias
+        let app = ctx.create_app().await;
+        let resp = app
+            .oneshot(chat_request(json!({
+                "model": "gpt-4o",
+                "messages": [{"role": "user", "content": "Hello!"}]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Aliases are listed next to the workers' models
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("GET")
+            .uri("/v1/models")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let models: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let ids: Vec<_> = models["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["mock-model", "gpt-4o-mini", "gpt-4o"]);
+
+        ctx.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod worker_metrics_tests {
+    use super::*;
+    use vllm_router_rs::core::WorkerLoad;
+    use vllm_router_rs::policies::{KvPressurePolicy, LoadBalancingPolicy};
+
+    fn vllm_metrics(running: u64, waiting: u64, kv_cache_usage: f64) -> String {
+        format!(
+            "# TYPE vllm:num_requests_running gauge\n\
+             vllm:num_requests_running{{model_name=\"mock-model\"}} {running}.0\n\
+             # TYPE vllm:num_requests_waiting gauge\n\
+             vllm:num_requests_waiting{{model_name=\"mock-model\"}} {waiting}.0\n\
+             # TYPE vllm:gpu_cache_usage_perc gauge\n\
+             vllm:gpu_cache_usage_perc{{model_name=\"mock-model\"}} {kv_cache_usage}\n"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_kv_pressure_routing_from_scraped_metrics() {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            policy: PolicyConfig::KvPressure {
+                queue_weight: 2.0,
+                kv_cache_weight: 16.0,
+                max_kv_cache_usage: 0.95,
+                load_check_interval_secs: 1,
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            ..Default::default()
+        };
+        let worker_config = |port| MockWorkerConfig {
+            port,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms: 0,
+            fail_rate: 0.0,
+        };
+        let ctx =
+            TestContext::new_with_config(config, vec![worker_config(18971), worker_config(18972)])
+                .await;
+
+        // The first worker runs fewer requests, but its queue grows and its KV cache is full
+        ctx.workers[0].set_metrics(&vllm_metrics(1, 6, 0.97)).await;
+        ctx.workers[1].set_metrics(&vllm_metrics(3, 0, 0.4)).await;
+        ctx.app_context.worker_metrics.scrape().await;
+
+        let busy = "http://127.0.0.1:18971";
+        let idle = "http://127.0.0.1:18972";
+        let loads = ctx.app_context.worker_metrics.loads();
+        assert_eq!(
+            loads.get(busy),
+            Some(&WorkerLoad {
+                running: 1,
+                waiting: 6,
+                kv_cache_usage: Some(0.97),
+            })
+        );
+        assert_eq!(loads.get(idle).map(|load| load.requests()), Some(3));
+
+        // The snapshot reached the policy, which avoids the saturated worker
+        let policy = ctx.app_context.policy_registry.get_default_policy();
+        let kv_pressure = policy.as_any().downcast_ref::<KvPressurePolicy>().unwrap();
+        assert_eq!(kv_pressure.worker_load(busy), loads.get(busy).copied());
+        let workers = ctx.app_context.worker_registry.get_all();
+        for _ in 0..5 {
+            let selected = kv_pressure.select_worker(&workers, None).unwrap();
+            assert_eq!(workers[selected].url(), idle);
+        }
+
+        // /get_loads reports the workers' running and waiting requests
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("GET")
+            .uri("/get_loads")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let mut reported: Vec<_> = body["workers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| {
+                (
+                    w["worker"].as_str().unwrap().to_string(),
+                    w["load"].as_i64().unwrap(),
+                )
+            })
+            .collect();
+        reported.sort();
+        assert_eq!(reported, vec![(busy.to_string(), 7), (idle.to_string(), 3)]);
+
+        // Requests are still served while the policy routes on the scraped loads
+        let app = ctx.create_app().await;
+        let req = Request::builder()
+            .method("POST")
+            .uri("/generate")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::to_string(&json!({"text": "Hello", "stream": false})).unwrap(),
+            ))
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        ctx.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod latency_routing_tests {
+    use super::*;
+    use std::time::Duration;
+    use vllm_router_rs::policies::{LatencyPolicy, LoadBalancingPolicy};
+
+    #[tokio::test]
+    async fn test_latency_routing_from_streamed_responses() {
+        let config = RouterConfig {
+            mode: RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            policy: PolicyConfig::Latency {
+                ewma_alpha: 0.5,
+                decay_half_life_secs: 60,
+                expected_output_tokens: 256,
+            },
+            worker_startup_timeout_secs: 1,
+            worker_startup_check_interval_secs: 1,
+            queue_size: 0,
+            ..Default::default()
+        };
+        let worker_config = |port, response_delay_ms| MockWorkerConfig {
+            port,
+            worker_type: WorkerType::Regular,
+            health_status: HealthStatus::Healthy,
+            response_delay_ms,
+            fail_rate: 0.0,
+        };
+        let ctx = TestContext::new_with_config(
+            config,
+            vec![worker_config(18973, 300), worker_config(18974, 0)],
+        )
+        .await;
+        let slow = "http://127.0.0.1:18973";
+        let fast = "http://127.0.0.1:18974";
+
+        // Each worker gets measured once, then the fast one takes the traffic
+        let app = ctx.create_app().await;
+        for _ in 0..4 {
+            let req = Request::builder()
+                .method("POST")
+                .uri("/generate")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&json!({"text": "Hello", "stream": true})).unwrap(),
+                ))
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+        }
+
+        let policy = ctx.app_context.policy_registry.get_default_policy();
+        let latency = policy.as_any().downcast_ref::<LatencyPolicy>().unwrap();
+        let slow_ttft = latency.time_to_first_token(slow).unwrap();
+        let fast_ttft = latency.time_to_first_token(fast).unwrap();
+        assert!(slow_ttft >= Duration::from_millis(300));
+        assert!(fast_ttft < slow_ttft);
+
+        let workers = ctx.app_context.worker_registry.get_all();
+        let selected = latency.select_worker(&workers, None).unwrap();
+        assert_eq!(workers[selected].url(), fast);
+
+        ctx.shutdown().await;
+    }
+}
@@ -47,6 +47,7 @@ fn default_chat_completion_request() -> ChatCompletionRequest {
         logprobs: false,
         top_logprobs: None,
         user: None,
+        prompt_cache_key: None,
         response_format: None,
         seed: None,
         tools: None,
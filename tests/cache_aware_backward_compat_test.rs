@@ -1,3 +1,4 @@
+use vllm_router_rs::config::{CacheTreeType, LoadUnit};
 use vllm_router_rs::core::{BasicWorker, Worker, WorkerType};
 use vllm_router_rs::policies::{CacheAwareConfig, CacheAwarePolicy, LoadBalancingPolicy};
 use std::collections::HashMap;
@@ -11,6 +12,9 @@ fn test_backward_compatibility_with_empty_model_id() {
         balance_rel_threshold: 1.5,
         eviction_interval_secs: 0, // Disable background eviction for testing
         max_tree_size: 100,
+        tree_type: CacheTreeType::Char,
+        block_size: 16,
+        load_unit: LoadUnit::Requests,
     };
 
     let policy = CacheAwarePolicy::with_config(config);
@@ -48,6 +52,9 @@ fn test_mixed_model_ids() {
         balance_rel_threshold: 1.5,
         eviction_interval_secs: 0,
         max_tree_size: 100,
+        tree_type: CacheTreeType::Char,
+        block_size: 16,
+        load_unit: LoadUnit::Requests,
     };
 
     let policy = CacheAwarePolicy::with_config(config);
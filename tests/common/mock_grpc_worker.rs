@@ -0,0 +1,224 @@
+//! In-process mock of the VllmScheduler gRPC service
+
+#![allow(dead_code)]
+
+use futures_util::{stream, StreamExt};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tonic::{Request, Response, Status};
+use vllm_router_rs::grpc::proto::{
+    self,
+    vllm_scheduler_server::{VllmScheduler, VllmSchedulerServer},
+};
+
+/// Scripted behavior of the mock scheduler
+#[derive(Clone)]
+pub struct MockGrpcWorkerConfig {
+    /// Token ids streamed back as individual chunks
+    pub output_ids: Vec<i32>,
+    /// Finish reason reported in the final message
+    pub finish_reason: proto::generate_complete::FinishReason,
+    /// If set, the worker answers every Generate call with this (http status, message) error
+    pub error: Option<(String, String)>,
+    /// If set, every chunk reports this logprob for its token when logprobs are requested
+    pub logprob: Option<f32>,
+    /// Delay before each streamed message, to keep a request in flight
+    pub chunk_delay: Option<Duration>,
+}
+
+impl Default for MockGrpcWorkerConfig {
+    fn default() -> Self {
+        Self {
+            // "Hello world" in the mock tokenizer vocabulary
+            output_ids: vec![1, 2],
+            finish_reason: proto::generate_complete::FinishReason::Length,
+            error: None,
+            logprob: None,
+            chunk_delay: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct MockScheduler {
+    config: MockGrpcWorkerConfig,
+    requests: Arc<Mutex<Vec<proto::GenerateRequest>>>,
+    aborts: Arc<Mutex<Vec<String>>>,
+}
+
+type GenerateStream =
+    Pin<Box<dyn futures_util::Stream<Item = Result<proto::GenerateResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl VllmScheduler for MockScheduler {
+    type GenerateStream = GenerateStream;
+
+    async fn generate(
+        &self,
+        request: Request<proto::GenerateRequest>,
+    ) -> Result<Response<Self::GenerateStream>, Status> {
+        let request = request.into_inner();
+        let request_id = request.request_id.clone();
+        let return_logprob = request.return_logprob;
+        let prompt_tokens = request
+            .tokenized
+            .as_ref()
+            .map(|t| t.input_ids.len() as i32)
+            .unwrap_or(0);
+        self.requests.lock().unwrap().push(request);
+
+        let mut messages = Vec::new();
+        if let Some((http_status_code, message)) = &self.config.error {
+            messages.push(Ok(proto::GenerateResponse {
+                request_id,
+                response: Some(proto::generate_response::Response::Error(
+                    proto::GenerateError {
+                        message: message.clone(),
+                        http_status_code: http_status_code.clone(),
+                        details: String::new(),
+                    },
+                )),
+            }));
+        } else {
+            for (i, &token_id) in self.config.output_ids.iter().enumerate() {
+                messages.push(Ok(proto::GenerateResponse {
+                    request_id: request_id.clone(),
+                    response: Some(proto::generate_response::Response::Chunk(
+                        proto::GenerateStreamChunk {
+                            token_id,
+                            prompt_tokens,
+                            completion_tokens: i as i32 + 1,
+                            logprobs: self.config.logprob.filter(|_| return_logprob).map(
+                                |logprob| proto::LogProbs {
+                                    token_logprobs: vec![logprob],
+                                    token_ids: vec![token_id],
+                                    ..Default::default()
+                                },
+                            ),
+                            ..Default::default()
+                        },
+                    )),
+                }));
+            }
+            messages.push(Ok(proto::GenerateResponse {
+                request_id,
+                response: Some(proto::generate_response::Response::Complete(
+                    proto::GenerateComplete {
+                        output_ids: self.config.output_ids.clone(),
+                        finish_reason: self.config.finish_reason as i32,
+                        ..Default::default()
+                    },
+                )),
+            }));
+        }
+
+        let chunk_delay = self.config.chunk_delay;
+        let stream = stream::iter(messages).then(move |message| async move {
+            if let Some(delay) = chunk_delay {
+                tokio::time::sleep(delay).await;
+            }
+            message
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn embed(
+        &self,
+        _request: Request<proto::EmbedRequest>,
+    ) -> Result<Response<proto::EmbedResponse>, Status> {
+        Err(Status::unimplemented("embed is not supported by the mock"))
+    }
+
+    async fn health_check(
+        &self,
+        _request: Request<proto::HealthCheckRequest>,
+    ) -> Result<Response<proto::HealthCheckResponse>, Status> {
+        Ok(Response::new(proto::HealthCheckResponse {
+            healthy: true,
+            message: "OK".to_string(),
+        }))
+    }
+
+    async fn abort(
+        &self,
+        request: Request<proto::AbortRequest>,
+    ) -> Result<Response<proto::AbortResponse>, Status> {
+        self.aborts
+            .lock()
+            .unwrap()
+            .push(request.into_inner().request_id);
+        Ok(Response::new(proto::AbortResponse {
+            success: true,
+            message: String::new(),
+        }))
+    }
+}
+
+/// Mock gRPC worker served on a local port
+pub struct MockGrpcWorker {
+    url: String,
+    requests: Arc<Mutex<Vec<proto::GenerateRequest>>>,
+    aborts: Arc<Mutex<Vec<String>>>,
+    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl MockGrpcWorker {
+    /// Start the mock scheduler and return once it accepts connections
+    pub async fn start(config: MockGrpcWorkerConfig) -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let aborts = Arc::new(Mutex::new(Vec::new()));
+        let service = MockScheduler {
+            config,
+            requests: requests.clone(),
+            aborts: aborts.clone(),
+        };
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(VllmSchedulerServer::new(service))
+                .serve_with_shutdown(addr, async {
+                    let _ = shutdown_rx.await;
+                })
+                .await
+                .unwrap();
+        });
+
+        // Give the server a moment to start listening
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        Self {
+            url: format!("grpc://{}", addr),
+            requests,
+            aborts,
+            shutdown_tx: Some(shutdown_tx),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Generate requests received so far
+    pub fn requests(&self) -> Vec<proto::GenerateRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Request ids of Abort calls received so far
+    pub fn aborts(&self) -> Vec<String> {
+        self.aborts.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockGrpcWorker {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
@@ -42,9 +42,20 @@ pub enum HealthStatus {
     Degraded,
 }
 
+/// Prometheus metrics of an idle vLLM worker
+const IDLE_METRICS: &str = "# TYPE vllm:num_requests_running gauge
+vllm:num_requests_running{model_name=\"mock-model\"} 0.0
+# TYPE vllm:num_requests_waiting gauge
+vllm:num_requests_waiting{model_name=\"mock-model\"} 0.0
+# TYPE vllm:gpu_cache_usage_perc gauge
+vllm:gpu_cache_usage_perc{model_name=\"mock-model\"} 0.0
+";
+
 /// Mock worker server for testing
 pub struct MockWorker {
     config: Arc<RwLock<MockWorkerConfig>>,
+    /// Body served on `/metrics`
+    metrics: Arc<RwLock<String>>,
     shutdown_handle: Option<tokio::task::JoinHandle<()>>,
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
 }
@@ -53,6 +64,7 @@ impl MockWorker {
     pub fn new(config: MockWorkerConfig) -> Self {
         Self {
             config: Arc::new(RwLock::new(config)),
+            metrics: Arc::new(RwLock::new(IDLE_METRICS.to_string())),
             shutdown_handle: None,
             shutdown_tx: None,
         }
@@ -74,6 +86,7 @@ impl MockWorker {
             port
         };
 
+        let metrics = self.metrics.clone();
         let app = Router::new()
             .route("/health", get(health_handler))
             .route("/health_generate", get(health_generate_handler))
@@ -91,6 +104,10 @@ impl MockWorker {
             )
             .route("/flush_cache", post(flush_cache_handler))
             .route("/v1/models", get(v1_models_handler))
+            .route(
+                "/metrics",
+                get(move || async move { metrics.read().await.clone() }),
+            )
             .with_state(config);
 
         let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
@@ -124,6 +141,11 @@ impl MockWorker {
         Ok(url)
     }
 
+    /// Replace the Prometheus text served on `/metrics`
+    pub async fn set_metrics(&self, metrics: &str) {
+        *self.metrics.write().await = metrics.to_string();
+    }
+
     /// Stop the mock worker server
     pub async fn stop(&mut self) {
         if let Some(shutdown_tx) = self.shutdown_tx.take() {
@@ -453,6 +475,56 @@ async fn chat_completions_handler(
             .keep_alive(KeepAlive::default())
             .into_response()
     } else {
+        // With tools offered, call the first one and then answer with its result
+        let tool_name = payload
+            .get("tools")
+            .and_then(|v| v.as_array())
+            .and_then(|tools| tools.first())
+            .and_then(|tool| tool.pointer("/function/name"))
+            .and_then(|v| v.as_str());
+        let tool_result = payload
+            .get("messages")
+            .and_then(|v| v.as_array())
+            .and_then(|messages| {
+                messages
+                    .iter()
+                    .rev()
+                    .find(|m| m.get("role").and_then(|r| r.as_str()) == Some("tool"))
+            })
+            .and_then(|m| m.get("content"))
+            .and_then(|v| v.as_str());
+        let (message, finish_reason) = match (tool_name, tool_result) {
+            (Some(name), None) => (
+                json!({
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": format!("call_{}", Uuid::new_v4().simple()),
+                        "type": "function",
+                        "function": {
+                            "name": name,
+                            "arguments": "{\"query\":\"vllm router\"}"
+                        }
+                    }]
+                }),
+                "tool_calls",
+            ),
+            (_, Some(result)) => (
+                json!({
+                    "role": "assistant",
+                    "content": format!("Based on the tool: {}", result)
+                }),
+                "stop",
+            ),
+            (None, None) => (
+                json!({
+                    "role": "assistant",
+                    "content": "This is a mock chat response."
+                }),
+                "stop",
+            ),
+        };
+
         Json(json!({
             "id": format!("chatcmpl-{}", Uuid::new_v4()),
             "object": "chat.completion",
@@ -460,11 +532,8 @@ async fn chat_completions_handler(
             "model": "mock-model",
             "choices": [{
                 "index": 0,
-                "message": {
-                    "role": "assistant",
-                    "content": "This is a mock chat response."
-                },
-                "finish_reason": "stop"
+                "message": message,
+                "finish_reason": finish_reason
             }],
             "usage": {
                 "prompt_tokens": 10,
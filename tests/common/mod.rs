@@ -1,6 +1,7 @@
 // These modules are used by tests and benchmarks
 #![allow(dead_code)]
 
+pub mod mock_grpc_worker;
 pub mod mock_mcp_server;
 pub mod mock_openai_server;
 pub mod mock_worker;
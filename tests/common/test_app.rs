@@ -2,6 +2,7 @@ use axum::Router;
 use reqwest::Client;
 use vllm_router_rs::{
     config::RouterConfig,
+    middleware::ShutdownState,
     routers::RouterTrait,
     server::{build_app, AppContext, AppState},
 };
@@ -25,14 +26,34 @@ pub fn create_test_app(
         .expect("Failed to create AppContext in test"),
     );
 
+    create_test_app_with_context(router, app_context, router_config)
+}
+
+/// Create a test Axum application sharing the context (and worker registry) of the router
+#[allow(dead_code)]
+pub fn create_test_app_with_context(
+    router: Arc<dyn RouterTrait>,
+    app_context: Arc<AppContext>,
+    router_config: &RouterConfig,
+) -> Router {
     // Create AppState with the test router and context
     let app_state = Arc::new(AppState {
         router,
         context: app_context,
-        concurrency_queue_tx: None,
+        admission_queue: None,
         router_manager: None,
+        shutdown: Arc::new(ShutdownState::new()),
     });
 
+    create_test_app_with_state(app_state, router_config)
+}
+
+/// Create a test Axum application from a prepared AppState
+#[allow(dead_code)]
+pub fn create_test_app_with_state(
+    app_state: Arc<AppState>,
+    router_config: &RouterConfig,
+) -> Router {
     // Configure request ID headers (use defaults if not specified)
     let request_id_headers = router_config.request_id_headers.clone().unwrap_or_else(|| {
         vec![
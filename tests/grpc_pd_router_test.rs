@@ -0,0 +1,226 @@
+mod common;
+
+use axum::body::to_bytes;
+use axum::http::StatusCode;
+use common::mock_grpc_worker::{MockGrpcWorker, MockGrpcWorkerConfig};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use vllm_router_rs::config::{ConnectionMode, RouterConfig, RoutingMode};
+use vllm_router_rs::policies::RoundRobinPolicy;
+use vllm_router_rs::protocols::spec::ChatCompletionRequest;
+use vllm_router_rs::reasoning_parser::ParserFactory;
+use vllm_router_rs::routers::grpc::pd_router::GrpcPDRouter;
+use vllm_router_rs::routers::RouterTrait;
+use vllm_router_rs::server::AppContext;
+use vllm_router_rs::tokenizer::mock::MockTokenizer;
+use vllm_router_rs::tool_parser::ParserRegistry;
+
+const BOOTSTRAP_PORT: u16 = 8998;
+
+/// Build a gRPC PD router in front of one mock prefill and one mock decode worker
+async fn create_grpc_pd_router(prefill: &MockGrpcWorker, decode: &MockGrpcWorker) -> GrpcPDRouter {
+    let prefill_urls = vec![(prefill.url(), Some(BOOTSTRAP_PORT))];
+    let decode_urls = vec![decode.url()];
+    let config = RouterConfig {
+        mode: RoutingMode::PrefillDecode {
+            prefill_urls: prefill_urls.clone(),
+            decode_urls: decode_urls.clone(),
+            prefill_policy: None,
+            decode_policy: None,
+        },
+        connection_mode: ConnectionMode::Http,
+        worker_startup_timeout_secs: 1,
+        worker_startup_check_interval_secs: 1,
+        ..Default::default()
+    };
+
+    let mut ctx = AppContext::new(
+        config.clone(),
+        reqwest::Client::new(),
+        config.max_concurrent_requests,
+        config.rate_limit_tokens_per_second,
+    )
+    .unwrap();
+    ctx.tokenizer = Some(Arc::new(MockTokenizer::new()));
+    ctx.reasoning_parser_factory = Some(ParserFactory::new());
+    ctx.tool_parser_registry = Some(ParserRegistry::new());
+    ctx.policy_registry
+        .set_prefill_policy(Arc::new(RoundRobinPolicy::new()));
+    ctx.policy_registry
+        .set_decode_policy(Arc::new(RoundRobinPolicy::new()));
+
+    GrpcPDRouter::new(prefill_urls, decode_urls, &Arc::new(ctx))
+        .await
+        .unwrap()
+}
+
+fn chat_request(body: Value) -> ChatCompletionRequest {
+    serde_json::from_value(body).unwrap()
+}
+
+async fn body_json(response: axum::response::Response) -> Value {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_pd_chat_sends_matching_bootstrap_params() {
+    let prefill = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        output_ids: vec![3],
+        ..Default::default()
+    })
+    .await;
+    let decode = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_pd_router(&prefill, &decode).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello world"}]
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Only the decode output reaches the client
+    let body = body_json(response).await;
+    assert_eq!(body["choices"][0]["message"]["content"], "Hello world");
+
+    // The prefill stream is drained in the background
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    let prefill_requests = prefill.requests();
+    let decode_requests = decode.requests();
+    assert_eq!(prefill_requests.len(), 1);
+    assert_eq!(decode_requests.len(), 1);
+
+    let prefill_params = prefill_requests[0].disaggregated_params.clone().unwrap();
+    let decode_params = decode_requests[0].disaggregated_params.clone().unwrap();
+    assert_eq!(prefill_params, decode_params);
+    assert_eq!(decode_params.bootstrap_host, "127.0.0.1");
+    assert_eq!(decode_params.bootstrap_port, BOOTSTRAP_PORT as i32);
+    assert!(decode_params.bootstrap_room >= 0);
+    assert_eq!(
+        prefill_requests[0].request_id,
+        decode_requests[0].request_id
+    );
+}
+
+#[tokio::test]
+async fn test_grpc_pd_merges_prefill_logprobs() {
+    let prefill = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        output_ids: vec![3],
+        logprob: Some(-0.25),
+        ..Default::default()
+    })
+    .await;
+    let decode = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        logprob: Some(-0.5),
+        ..Default::default()
+    })
+    .await;
+    let router = create_grpc_pd_router(&prefill, &decode).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello world"}],
+        "logprobs": true
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = body_json(response).await;
+    let content = body["choices"][0]["logprobs"]["content"]
+        .as_array()
+        .unwrap();
+    let tokens: Vec<&str> = content
+        .iter()
+        .map(|entry| entry["token"].as_str().unwrap())
+        .collect();
+    assert_eq!(tokens, vec!["test", "Hello", "world"]);
+    assert_eq!(content[0]["logprob"], json!(-0.25));
+    assert_eq!(content[1]["logprob"], json!(-0.5));
+}
+
+#[tokio::test]
+async fn test_grpc_pd_prefill_error_is_returned() {
+    let prefill = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        error: Some(("400".to_string(), "prompt too long".to_string())),
+        ..Default::default()
+    })
+    .await;
+    let decode = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_pd_router(&prefill, &decode).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello world"}],
+        "logprobs": true
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(
+        String::from_utf8(bytes.to_vec()).unwrap(),
+        "Prefill server error: prompt too long"
+    );
+}
+
+#[tokio::test]
+async fn test_grpc_pd_releases_load_after_streaming() {
+    let prefill = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let decode = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_pd_router(&prefill, &decode).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello world"}],
+        "stream": true
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8(bytes.to_vec())
+        .unwrap()
+        .ends_with("data: [DONE]\n\n"));
+
+    let loads = body_json(router.get_worker_loads().await).await;
+    assert_eq!(loads["prefill"][0]["load"], 0);
+    assert_eq!(loads["decode"][0]["load"], 0);
+}
+
+#[tokio::test]
+async fn test_grpc_pd_client_disconnect_aborts_both_workers() {
+    let prefill = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        output_ids: vec![3],
+        ..Default::default()
+    })
+    .await;
+    let decode = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        output_ids: vec![1, 2, 3, 4, 1, 2, 3, 4],
+        chunk_delay: Some(Duration::from_millis(50)),
+        ..Default::default()
+    })
+    .await;
+    let router = create_grpc_pd_router(&prefill, &decode).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello world"}],
+        "stream": true
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut body = response.into_body().into_data_stream();
+    assert!(body.next().await.unwrap().is_ok());
+    drop(body);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let request_id = decode.requests()[0].request_id.clone();
+    assert_eq!(prefill.aborts(), vec![request_id.clone()]);
+    assert_eq!(decode.aborts(), vec![request_id]);
+
+    let loads = body_json(router.get_worker_loads().await).await;
+    assert_eq!(loads["prefill"][0]["load"], 0);
+    assert_eq!(loads["decode"][0]["load"], 0);
+}
@@ -0,0 +1,280 @@
+mod common;
+
+use axum::body::to_bytes;
+use axum::http::StatusCode;
+use common::mock_grpc_worker::{MockGrpcWorker, MockGrpcWorkerConfig};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use vllm_router_rs::config::{ConnectionMode, RouterConfig, RoutingMode};
+use vllm_router_rs::grpc::proto;
+use vllm_router_rs::policies::RoundRobinPolicy;
+use vllm_router_rs::protocols::spec::{ChatCompletionRequest, CompletionRequest, GenerateRequest};
+use vllm_router_rs::reasoning_parser::ParserFactory;
+use vllm_router_rs::routers::grpc::router::GrpcRouter;
+use vllm_router_rs::routers::RouterTrait;
+use vllm_router_rs::server::AppContext;
+use vllm_router_rs::tokenizer::mock::MockTokenizer;
+use vllm_router_rs::tool_parser::ParserRegistry;
+
+/// Build a gRPC router in front of the given mock workers, using the mock tokenizer
+async fn create_grpc_router(workers: &[&MockGrpcWorker]) -> GrpcRouter {
+    let worker_urls: Vec<String> = workers.iter().map(|w| w.url()).collect();
+    let config = RouterConfig {
+        mode: RoutingMode::Regular {
+            worker_urls: worker_urls.clone(),
+        },
+        connection_mode: ConnectionMode::Http,
+        worker_startup_timeout_secs: 1,
+        worker_startup_check_interval_secs: 1,
+        ..Default::default()
+    };
+
+    let mut ctx = AppContext::new(
+        config.clone(),
+        reqwest::Client::new(),
+        config.max_concurrent_requests,
+        config.rate_limit_tokens_per_second,
+    )
+    .unwrap();
+    ctx.tokenizer = Some(Arc::new(MockTokenizer::new()));
+    ctx.reasoning_parser_factory = Some(ParserFactory::new());
+    ctx.tool_parser_registry = Some(ParserRegistry::new());
+
+    GrpcRouter::new(
+        worker_urls,
+        Arc::new(RoundRobinPolicy::new()),
+        &Arc::new(ctx),
+    )
+    .await
+    .unwrap()
+}
+
+fn chat_request(body: Value) -> ChatCompletionRequest {
+    serde_json::from_value(body).unwrap()
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Parse the `data:` payloads of an SSE body, excluding `[DONE]`
+fn sse_payloads(body: &str) -> Vec<Value> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter(|data| *data != "[DONE]")
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_grpc_chat_completion_non_streaming() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello world"}],
+        "temperature": 0.5,
+        "max_tokens": 8
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["object"], "chat.completion");
+    assert_eq!(body["model"], "test-model");
+    assert_eq!(body["choices"][0]["message"]["role"], "assistant");
+    assert_eq!(body["choices"][0]["message"]["content"], "Hello world");
+    assert_eq!(body["choices"][0]["finish_reason"], "length");
+    assert_eq!(body["usage"]["completion_tokens"], 2);
+
+    let requests = worker.requests();
+    assert_eq!(requests.len(), 1);
+    let sent = &requests[0];
+    assert!(sent.request_id.starts_with("chatcmpl-"));
+    assert_eq!(sent.tokenized.as_ref().unwrap().input_ids, vec![1, 2]);
+    let params = sent.sampling_params.as_ref().unwrap();
+    assert_eq!(params.temperature, 0.5);
+    assert_eq!(params.max_new_tokens, 8);
+}
+
+#[tokio::test]
+async fn test_grpc_chat_completion_streaming() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        finish_reason: proto::generate_complete::FinishReason::Stop,
+        ..Default::default()
+    })
+    .await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": true,
+        "stream_options": {"include_usage": true}
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    let body = body_string(response).await;
+    assert!(body.trim_end().ends_with("data: [DONE]"));
+
+    let chunks = sse_payloads(&body);
+    assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+    let content: String = chunks
+        .iter()
+        .filter_map(|c| c["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "Hello world");
+
+    let finish = chunks
+        .iter()
+        .find_map(|c| c["choices"][0]["finish_reason"].as_str())
+        .unwrap();
+    assert_eq!(finish, "stop");
+
+    let usage = chunks.last().unwrap();
+    assert_eq!(usage["choices"].as_array().unwrap().len(), 0);
+    assert_eq!(usage["usage"]["completion_tokens"], 2);
+}
+
+#[tokio::test]
+async fn test_grpc_completion_applies_stop_sequence() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        output_ids: vec![1, 2, 3],
+        ..Default::default()
+    })
+    .await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request: CompletionRequest = serde_json::from_value(json!({
+        "model": "test-model",
+        "prompt": "Hello",
+        "stop": ["world"]
+    }))
+    .unwrap();
+    let response = router.route_completion(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["object"], "text_completion");
+    assert_eq!(body["choices"][0]["text"], "Hello ");
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+    assert_eq!(
+        worker.requests()[0].sampling_params.as_ref().unwrap().stop,
+        vec!["world".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_grpc_generate_with_input_ids() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request: GenerateRequest = serde_json::from_value(json!({
+        "input_ids": [3, 4],
+        "sampling_params": {"max_new_tokens": 4},
+        "rid": "gen-1"
+    }))
+    .unwrap();
+    let response = router.route_generate(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["text"], "Hello world");
+    assert_eq!(body["meta_info"]["id"], "gen-1");
+    assert_eq!(body["meta_info"]["finish_reason"]["type"], "length");
+
+    let sent = &worker.requests()[0];
+    assert_eq!(sent.request_id, "gen-1");
+    assert_eq!(sent.tokenized.as_ref().unwrap().input_ids, vec![3, 4]);
+}
+
+#[tokio::test]
+async fn test_grpc_worker_error_maps_to_http_status() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        error: Some(("400".to_string(), "prompt too long".to_string())),
+        ..Default::default()
+    })
+    .await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello"}]
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(body_string(response).await, "prompt too long");
+}
+
+#[tokio::test]
+async fn test_grpc_chat_rejects_multiple_choices() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "n": 2
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(worker.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_grpc_client_disconnect_aborts_worker_request() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig {
+        output_ids: vec![1, 2, 3, 4, 1, 2, 3, 4],
+        chunk_delay: Some(Duration::from_millis(50)),
+        ..Default::default()
+    })
+    .await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": true
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Read the first chunk, then go away
+    let mut body = response.into_body().into_data_stream();
+    assert!(body.next().await.unwrap().is_ok());
+    drop(body);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let request_id = worker.requests()[0].request_id.clone();
+    assert_eq!(worker.aborts(), vec![request_id]);
+
+    let loads: Value =
+        serde_json::from_str(&body_string(router.get_worker_loads().await).await).unwrap();
+    assert_eq!(loads["workers"][0]["load"], 0);
+}
+
+#[tokio::test]
+async fn test_grpc_completed_stream_is_not_aborted() {
+    let worker = MockGrpcWorker::start(MockGrpcWorkerConfig::default()).await;
+    let router = create_grpc_router(&[&worker]).await;
+
+    let request = chat_request(json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": true
+    }));
+    let response = router.route_chat(None, &request, None).await;
+    assert!(body_string(response).await.ends_with("data: [DONE]\n\n"));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(worker.aborts().is_empty());
+}
@@ -1,12 +1,13 @@
 //! Integration tests for PolicyRegistry with RouterManager
 
 use vllm_router_rs::config::{PolicyConfig, RouterConfig};
-use vllm_router_rs::core::WorkerRegistry;
+use vllm_router_rs::core::{ModelCatalog, WorkerRegistry};
 use vllm_router_rs::policies::PolicyRegistry;
 use vllm_router_rs::protocols::worker_spec::WorkerConfigRequest;
 use vllm_router_rs::routers::router_manager::RouterManager;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_policy_registry_with_router_manager() {
@@ -23,6 +24,12 @@ async fn test_policy_registry_with_router_manager() {
     // Create shared registries
     let worker_registry = Arc::new(WorkerRegistry::new());
     let policy_registry = Arc::new(PolicyRegistry::new(PolicyConfig::RoundRobin));
+    let model_catalog = Arc::new(ModelCatalog::new(
+        worker_registry.clone(),
+        client.clone(),
+        None,
+        Duration::from_secs(30),
+    ));
 
     // Create RouterManager with shared registries
     let _router_manager = RouterManager::new(
@@ -30,6 +37,7 @@ async fn test_policy_registry_with_router_manager() {
         client,
         worker_registry.clone(),
         policy_registry.clone(),
+        model_catalog,
     );
 
     // Test adding workers with different models and policies
@@ -46,6 +54,10 @@ async fn test_policy_registry_with_router_manager() {
         cost: None,
         labels: labels1,
         bootstrap_port: None,
+        backend: None,
+        api_key: None,
+        model_map: HashMap::new(),
+        weight: None,
         tokenizer_path: None,
         reasoning_parser: None,
         tool_parser: None,
@@ -71,6 +83,10 @@ async fn test_policy_registry_with_router_manager() {
         cost: None,
         labels: labels2,
         bootstrap_port: None,
+        backend: None,
+        api_key: None,
+        model_map: HashMap::new(),
+        weight: None,
         tokenizer_path: None,
         reasoning_parser: None,
         tool_parser: None,
@@ -91,6 +107,10 @@ async fn test_policy_registry_with_router_manager() {
         cost: None,
         labels: labels3,
         bootstrap_port: None,
+        backend: None,
+        api_key: None,
+        model_map: HashMap::new(),
+        weight: None,
         tokenizer_path: None,
         reasoning_parser: None,
         tool_parser: None,
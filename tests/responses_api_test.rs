@@ -29,11 +29,14 @@ fn test_responses_request_creation() {
         tool_choice: ToolChoice::Value(ToolChoiceValue::Auto),
         tools: vec![ResponseTool {
             r#type: ResponseToolType::WebSearchPreview,
+            server_label: None,
+            allowed_tools: None,
         }],
         top_logprobs: 5,
         top_p: Some(0.9),
         truncation: Truncation::Disabled,
         user: Some("test-user".to_string()),
+        prompt_cache_key: None,
         request_id: "resp_test123".to_string(),
         priority: 0,
         frequency_penalty: 0.0,
@@ -75,6 +78,7 @@ fn test_sampling_params_conversion() {
         top_p: Some(0.95),
         truncation: Truncation::Auto,
         user: None,
+        prompt_cache_key: None,
         request_id: "resp_test456".to_string(),
         priority: 0,
         frequency_penalty: 0.1,
@@ -182,11 +186,14 @@ fn test_json_serialization() {
         tool_choice: ToolChoice::Value(ToolChoiceValue::Required),
         tools: vec![ResponseTool {
             r#type: ResponseToolType::CodeInterpreter,
+            server_label: None,
+            allowed_tools: None,
         }],
         top_logprobs: 10,
         top_p: Some(0.8),
         truncation: Truncation::Auto,
         user: Some("test_user".to_string()),
+        prompt_cache_key: None,
         request_id: "resp_comprehensive_test".to_string(),
         priority: 1,
         frequency_penalty: 0.3,
@@ -1,25 +1,30 @@
 #[cfg(test)]
 mod consistent_hash_policy_tests {
     use std::collections::HashMap;
+    use std::sync::Arc;
 
+    use axum::http::HeaderMap;
+    use serde_json::json;
+    use vllm_router_rs::config::HashKeySource;
     use vllm_router_rs::core::BasicWorker;
     use vllm_router_rs::core::Worker;
     use vllm_router_rs::core::WorkerType;
+    use vllm_router_rs::policies::ConsistentHashConfig;
     use vllm_router_rs::policies::ConsistentHashPolicy;
     use vllm_router_rs::policies::LoadBalancingPolicy;
 
     /// Helper function to create test workers
-    fn create_test_workers() -> Vec<Box<dyn Worker>> {
+    fn create_test_workers() -> Vec<Arc<dyn Worker>> {
         vec![
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker1:8000".to_string(),
                 WorkerType::Regular,
             )),
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker2:8000".to_string(),
                 WorkerType::Regular,
             )),
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker3:8000".to_string(),
                 WorkerType::Regular,
             )),
@@ -27,21 +32,21 @@ mod consistent_hash_policy_tests {
     }
 
     /// Helper function to create DP-aware test workers
-    fn create_dp_test_workers() -> Vec<Box<dyn Worker>> {
+    fn create_dp_test_workers() -> Vec<Arc<dyn Worker>> {
         vec![
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker1:8000@0".to_string(), // DP rank 0
                 WorkerType::Regular,
             )),
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker2:8000@1".to_string(), // DP rank 1
                 WorkerType::Regular,
             )),
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker3:8000@2".to_string(), // DP rank 2
                 WorkerType::Regular,
             )),
-            Box::new(BasicWorker::new(
+            Arc::new(BasicWorker::new(
                 "http://worker4:8000@3".to_string(), // DP rank 3
                 WorkerType::Regular,
             )),
@@ -208,7 +213,7 @@ mod consistent_hash_policy_tests {
     #[test]
     fn test_no_workers_available() {
         let policy = ConsistentHashPolicy::new();
-        let empty_workers: Vec<Box<dyn Worker>> = vec![];
+        let empty_workers: Vec<Arc<dyn Worker>> = vec![];
 
         // Should return None when no workers are available
         let result = policy.select_worker(&empty_workers, Some(r#"{"session_id": "test"}"#));
@@ -229,7 +234,7 @@ mod consistent_hash_policy_tests {
         // Mark first worker as unhealthy
         worker1.set_healthy(false);
 
-        let workers: Vec<Box<dyn Worker>> = vec![Box::new(worker1), Box::new(worker2)];
+        let workers: Vec<Arc<dyn Worker>> = vec![Arc::new(worker1), Arc::new(worker2)];
 
         // Should still work and select healthy workers
         let result = policy.select_worker(&workers, Some(r#"{"session_id": "test"}"#));
@@ -449,4 +454,37 @@ mod consistent_hash_policy_tests {
             );
         }
     }
+
+    #[test]
+    fn test_header_affinity_key_routing() {
+        let policy = ConsistentHashPolicy::with_config(ConsistentHashConfig {
+            hash_key_sources: vec![
+                HashKeySource::Header("x-session-id".to_string()),
+                HashKeySource::PromptCacheKey,
+            ],
+            ..Default::default()
+        });
+        let workers = create_test_workers();
+
+        // The header decides the worker regardless of the body
+        let mut headers = HeaderMap::new();
+        headers.insert("x-session-id", "header-session".parse().unwrap());
+        let mut selected = Vec::new();
+        for body in [
+            json!({"user": "alice", "prompt_cache_key": "k1"}),
+            json!({"user": "bob", "prompt_cache_key": "k2"}),
+        ] {
+            let key = policy.affinity_key(Some(&headers), &body).unwrap();
+            assert_eq!(key, "header:x-session-id:header-session");
+            selected.push(policy.select_worker(&workers, Some(&key)).unwrap());
+        }
+        assert_eq!(selected[0], selected[1]);
+
+        // Without the header the prompt cache key is used
+        let body = json!({"user": "alice", "prompt_cache_key": "k1"});
+        assert_eq!(
+            policy.affinity_key(None, &body),
+            Some("prompt_cache_key:k1".to_string())
+        );
+    }
 }
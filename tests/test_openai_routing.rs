@@ -3,19 +3,27 @@
 use axum::{
     body::Body,
     extract::Request,
-    http::{Method, StatusCode},
+    http::{HeaderMap, Method, StatusCode},
     routing::post,
-    Router,
+    Json, Router,
 };
 use serde_json::json;
 use vllm_router_rs::{
-    config::{RouterConfig, RoutingMode},
-    protocols::spec::{
-        ChatCompletionRequest, ChatMessage, CompletionRequest, GenerateRequest, UserMessageContent,
+    config::{OpenAIUpstream, RouterConfig, RoutingMode},
+    protocols::{
+        spec::{
+            ChatCompletionRequest, ChatMessage, CompletionRequest, GenerateRequest,
+            UserMessageContent,
+        },
+        worker_spec::WorkerConfigRequest,
     },
-    routers::{openai_router::OpenAIRouter, RouterTrait},
+    routers::{openai_router::OpenAIRouter, RouterTrait, WorkerManagement},
+};
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
 };
-use std::sync::Arc;
 use tower::ServiceExt;
 
 mod common;
@@ -72,6 +80,50 @@ fn create_minimal_completion_request() -> CompletionRequest {
     }
 }
 
+/// Start an upstream answering chat completions with `status`, echoing the model and
+/// Authorization header it received; returns its URL and request counter
+async fn spawn_echo_upstream(status: StatusCode) -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().route(
+        "/v1/chat/completions",
+        post(
+            move |headers: HeaderMap, Json(body): Json<serde_json::Value>| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let auth = headers
+                        .get("authorization")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string();
+                    let response = json!({
+                        "object": "chat.completion",
+                        "model": body["model"],
+                        "authorization": auth,
+                        "choices": []
+                    });
+                    (status, Json(response))
+                }
+            },
+        ),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), hits)
+}
+
+async fn response_json(response: axum::response::Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
 // ============= Basic Unit Tests =============
 
 /// Test basic OpenAI router creation and configuration
@@ -421,3 +473,137 @@ async fn test_openai_router_models_auth_forwarding() {
     let models: serde_json::Value = serde_json::from_str(&body_str).unwrap();
     assert_eq!(models["object"], "list");
 }
+
+/// Test failover to an overflow provider with its own key and model name
+#[tokio::test]
+async fn test_openai_router_failover_to_overflow_upstream() {
+    let (primary_url, primary_hits) = spawn_echo_upstream(StatusCode::SERVICE_UNAVAILABLE).await;
+    let (overflow_url, overflow_hits) = spawn_echo_upstream(StatusCode::OK).await;
+
+    // Weight 0 only receives requests the primary fails
+    let mut overflow = OpenAIUpstream::new(overflow_url);
+    overflow.weight = 0;
+    overflow.api_key = Some("sk-overflow".to_string());
+    overflow.model_map = HashMap::from([("gpt-3.5-turbo".to_string(), "hosted-model".to_string())]);
+    let router =
+        OpenAIRouter::with_upstreams(vec![OpenAIUpstream::new(primary_url), overflow], None)
+            .await
+            .unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", "Bearer client-token".parse().unwrap());
+    let response = router
+        .route_chat(Some(&headers), &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response_json(response).await;
+    assert_eq!(body["model"], "hosted-model");
+    assert_eq!(body["authorization"], "Bearer sk-overflow");
+    assert_eq!(primary_hits.load(Ordering::SeqCst), 1);
+    assert_eq!(overflow_hits.load(Ordering::SeqCst), 1);
+}
+
+/// Test that the client's Authorization is withheld from providers under router auth
+#[tokio::test]
+async fn test_openai_router_withholds_client_auth() {
+    let (url, _) = spawn_echo_upstream(StatusCode::OK).await;
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", "Bearer router-key".parse().unwrap());
+
+    let router = OpenAIRouter::new(url.clone(), None).await.unwrap();
+    let response = router
+        .route_chat(Some(&headers), &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(
+        response_json(response).await["authorization"],
+        "Bearer router-key"
+    );
+
+    let router = OpenAIRouter::new(url, None)
+        .await
+        .unwrap()
+        .with_client_auth_forwarding(false);
+    let response = router
+        .route_chat(Some(&headers), &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response_json(response).await["authorization"], "");
+}
+
+/// Test which upstream statuses fail over and what is returned when all providers fail
+#[tokio::test]
+async fn test_openai_router_failover_statuses() {
+    // Client errors are relayed without trying other providers
+    let (bad_request_url, _) = spawn_echo_upstream(StatusCode::BAD_REQUEST).await;
+    let (spare_url, spare_hits) = spawn_echo_upstream(StatusCode::OK).await;
+    let mut spare = OpenAIUpstream::new(spare_url);
+    spare.weight = 0;
+    let router =
+        OpenAIRouter::with_upstreams(vec![OpenAIUpstream::new(bad_request_url), spare], None)
+            .await
+            .unwrap();
+    let response = router
+        .route_chat(None, &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(spare_hits.load(Ordering::SeqCst), 0);
+
+    // Rate-limited everywhere: the last provider's response is relayed
+    let (first_url, first_hits) = spawn_echo_upstream(StatusCode::TOO_MANY_REQUESTS).await;
+    let (second_url, second_hits) = spawn_echo_upstream(StatusCode::TOO_MANY_REQUESTS).await;
+    let router = OpenAIRouter::with_upstreams(
+        vec![
+            OpenAIUpstream::new(first_url),
+            OpenAIUpstream::new(second_url),
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    let response = router
+        .route_chat(None, &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(first_hits.load(Ordering::SeqCst), 1);
+    assert_eq!(second_hits.load(Ordering::SeqCst), 1);
+}
+
+/// Test adding and removing upstreams through the worker management API
+#[tokio::test]
+async fn test_openai_router_manage_upstreams() {
+    let (primary_url, _) = spawn_echo_upstream(StatusCode::INTERNAL_SERVER_ERROR).await;
+    let (hosted_url, hosted_hits) = spawn_echo_upstream(StatusCode::OK).await;
+    let router = OpenAIRouter::new(primary_url.clone(), None).await.unwrap();
+
+    let config: WorkerConfigRequest = serde_json::from_value(json!({
+        "url": format!("{}/", hosted_url),
+        "api_key": "sk-hosted",
+        "model_map": {"gpt-3.5-turbo": "hosted-model"},
+        "weight": 0
+    }))
+    .unwrap();
+    assert!(router.add_worker_with_config(&config).await.is_ok());
+    assert!(router.add_worker(&hosted_url).await.is_err());
+    assert_eq!(
+        router.get_worker_urls(),
+        vec![primary_url.clone(), hosted_url.clone()]
+    );
+
+    let response = router
+        .route_chat(None, &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response_json(response).await;
+    assert_eq!(body["model"], "hosted-model");
+    assert_eq!(body["authorization"], "Bearer sk-hosted");
+    assert_eq!(hosted_hits.load(Ordering::SeqCst), 1);
+
+    // Without the hosted provider only the failing primary is left
+    router.remove_worker(&hosted_url);
+    assert_eq!(router.get_worker_urls(), vec![primary_url]);
+    let response = router
+        .route_chat(None, &create_minimal_chat_request(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -2,7 +2,8 @@
 mod test_pd_routing {
     use serde_json::json;
     use vllm_router_rs::config::{
-        CircuitBreakerConfig, ConnectionMode, PolicyConfig, RetryConfig, RouterConfig, RoutingMode,
+        CacheTreeType, CircuitBreakerConfig, ConnectionMode, PolicyConfig, RetryConfig,
+        RouterConfig, RoutingMode,
     };
     use vllm_router_rs::core::{WorkerFactory, WorkerType};
     use vllm_router_rs::routers::http::pd_types::get_hostname;
@@ -156,6 +157,8 @@ mod test_pd_routing {
                     balance_rel_threshold: 1.2,
                     eviction_interval_secs: 60,
                     max_tree_size: 1000000,
+                    tree_type: CacheTreeType::Char,
+                    block_size: 16,
                 },
             ),
         ];
@@ -172,6 +175,7 @@ mod test_pd_routing {
                 worker_startup_check_interval_secs: 1,
                 dp_aware: false,
                 api_key: None,
+                auth: vllm_router_rs::config::AuthConfig::default(),
                 discovery: None,
                 metrics: None,
                 log_dir: None,
@@ -180,6 +184,8 @@ mod test_pd_routing {
                 max_concurrent_requests: 64,
                 queue_size: 0,
                 queue_timeout_secs: 60,
+                admission: vllm_router_rs::config::AdmissionConfig::default(),
+                drain: vllm_router_rs::config::DrainConfig::default(),
                 cors_allowed_origins: vec![],
                 retry: RetryConfig::default(),
                 circuit_breaker: CircuitBreakerConfig::default(),
@@ -188,10 +194,19 @@ mod test_pd_routing {
                 health_check: vllm_router_rs::config::HealthCheckConfig::default(),
                 enable_igw: false,
                 rate_limit_tokens_per_second: None,
+                tenant_rate_limits: vllm_router_rs::config::TenantRateLimitConfig::default(),
                 connection_mode: ConnectionMode::Http,
                 model_path: None,
                 tokenizer_path: None,
                 history_backend: vllm_router_rs::config::HistoryBackend::Memory,
+                history_storage: vllm_router_rs::config::HistoryStorageConfig::default(),
+                mcp: vllm_router_rs::config::McpToolsConfig::default(),
+                mcp_server: vllm_router_rs::config::McpEndpointConfig::default(),
+                backend: vllm_router_rs::config::WorkerBackend::default(),
+                openai_upstreams: vec![],
+                model_aliases: vec![],
+                model_refresh_interval_secs: 30,
+                load_unit: vllm_router_rs::config::LoadUnit::Requests,
             };
 
             // Router creation will fail due to health checks, but config should be valid